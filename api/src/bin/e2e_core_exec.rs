@@ -1,10 +1,12 @@
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 use uuid::Uuid;
 
-use el_core::event::{Event, EventPayload, EventType, Exchange};
+use el_core::event::{Event, EventPayload, EventType, Exchange, SCHEMA_VERSION};
+use el_core::exec::{OrderFlags, OrderType, Side, TimeInForce};
 use el_core::time::{Timestamp, TimeSource};
+use el_core::clock::Clock;
+use el_core::num::{Price, Qty};
 
 use exec::order::bridge::to_exec_event;
 use exec::order::snapshot::build_snapshot;
@@ -12,18 +14,8 @@ use exec::events::ExecEvent;
 
 use eventlog::EventLogWriter;
 
-fn now_ns_i64() -> i64 {
-    let d = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    (d.as_secs() * 1_000_000_000 + d.subsec_nanos() as u64) as i64
-}
-
-fn now_ns_u64() -> u64 {
-    let d = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    d.as_secs() * 1_000_000_000 + d.subsec_nanos() as u64
-}
-
-fn mk_event(event_type: EventType, payload: EventPayload) -> Event {
-    let t = now_ns_i64();
+fn mk_event(clock: &dyn Clock, event_type: EventType, payload: EventPayload) -> Event {
+    let t = clock.now_ns();
     Event {
         id: Uuid::new_v4(),
         event_type,
@@ -34,7 +26,7 @@ fn mk_event(event_type: EventType, payload: EventPayload) -> Event {
         ts_recv: Timestamp::new(t, TimeSource::Receive),
         ts_proc: Timestamp::new(t, TimeSource::Process),
         seq: None,
-        schema_version: 1,
+        schema_version: SCHEMA_VERSION,
         integrity_flags: vec![],
         payload,
         meta: HashMap::new(),
@@ -52,36 +44,45 @@ fn main() -> anyhow::Result<()> {
 
     let core_events = vec![
         mk_event(
+            w.clock(),
             EventType::OrderSubmit,
             EventPayload::OrderSubmit {
                 order_id: order_id.clone(),
-                side: "buy".to_string(),
-                price: 100.0,
-                qty: 1.0,
+                client_order_id: None,
+                side: Side::Buy,
+                order_type: OrderType::Limit,
+                time_in_force: TimeInForce::Gtc,
+                price: Some(Price::from_int(100)),
+                qty: Qty::from_int(1),
+                flags: OrderFlags::default(),
             },
         ),
         mk_event(
+            w.clock(),
             EventType::OrderAck,
             EventPayload::OrderAck {
                 order_id: order_id.clone(),
             },
         ),
         mk_event(
+            w.clock(),
             EventType::Fill,
             EventPayload::Fill {
                 order_id: order_id.clone(),
                 fill_id: "F1".to_string(),
-                price: 100.0,
-                qty: 1.0,
+                price: Price::from_int(100),
+                qty: Qty::from_int(1),
             },
         ),
         mk_event(
+            w.clock(),
             EventType::CancelRequest,
             EventPayload::CancelRequest {
                 order_id: order_id.clone(),
             },
         ),
         mk_event(
+            w.clock(),
             EventType::CancelAck,
             EventPayload::CancelAck {
                 order_id: order_id.clone(),
@@ -92,13 +93,13 @@ fn main() -> anyhow::Result<()> {
     let mut exec_events: Vec<ExecEvent> = Vec::new();
     for ev in &core_events {
         if let Some(x) = to_exec_event(ev)? {
-            w.append_bytes("event", now_ns_u64(), &serde_json::to_vec(&x)?)?;
+            w.append_bytes("event", w.now_ns(), &serde_json::to_vec(&x)?)?;
             exec_events.push(x);
         }
     }
 
     let (_store, h) = build_snapshot(&exec_events).map_err(|e| anyhow::anyhow!(e.to_string()))?;
-    w.append_bytes("snapshot_hash", now_ns_u64(), &h.to_le_bytes())?;
+    w.append_bytes("snapshot_hash", w.now_ns(), &h.to_le_bytes())?;
     w.flush()?;
 
     println!("E2E OK: wrote exec events + snapshot_hash={}", h);
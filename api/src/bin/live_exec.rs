@@ -1,17 +1,10 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
 use adapters::{SeqTracker, AdapterSignal};
 use eventlog::EventLogWriter;
 use exec::events::{ExecEvent, OrderId};
 use exec::order::snapshot::build_snapshot;
-use exec::util::instrument::InstrumentKey;
+use el_core::instrument::InstrumentKey;
 use replay::ReplayGuard;
 
-fn now_ns() -> u64 {
-    let d = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    d.as_secs() * 1_000_000_000 + d.subsec_nanos() as u64
-}
-
 fn main() -> anyhow::Result<()> {
     let path = "var/live_exec.log";
     std::fs::create_dir_all("var")?;
@@ -29,8 +22,8 @@ fn main() -> anyhow::Result<()> {
     // --- event #1 (ok) ---
     seq.observe(1).unwrap();
     if guard.allow_event() {
-        let ev = ExecEvent::OrderCreated { instrument: instrument.clone(), id: OrderId(1) };
-        w.append_bytes("event", now_ns(), &serde_json::to_vec(&ev)?)?;
+        let ev = ExecEvent::OrderCreated { instrument: instrument.clone(), id: OrderId(1), params: None };
+        w.append_bytes("event", w.now_ns(), &serde_json::to_vec(&ev)?)?;
         live_events.push(ev);
     }
 
@@ -40,7 +33,7 @@ fn main() -> anyhow::Result<()> {
     guard.on_adapter_signal();
 
     // --- SNAPSHOT BARRIER (unblock + reset seq) ---
-    w.append_bytes("snapshot", now_ns(), &0u64.to_le_bytes())?;
+    w.append_bytes("snapshot", w.now_ns(), &0u64.to_le_bytes())?;
     guard.on_snapshot();
     seq.reset(10);
 
@@ -48,13 +41,13 @@ fn main() -> anyhow::Result<()> {
     seq.observe(11).unwrap();
     if guard.allow_event() {
         let ev = ExecEvent::OrderAcked { instrument: instrument.clone(), id: OrderId(1) };
-        w.append_bytes("event", now_ns(), &serde_json::to_vec(&ev)?)?;
+        w.append_bytes("event", w.now_ns(), &serde_json::to_vec(&ev)?)?;
         live_events.push(ev);
     }
 
     // --- FINAL COMMIT SNAPSHOT (hash) ---
     let (_store, live_hash) = build_snapshot(&live_events).map_err(|e| anyhow::anyhow!(e.to_string()))?;
-    w.append_bytes("snapshot_hash", now_ns(), &live_hash.to_le_bytes())?;
+    w.append_bytes("snapshot_hash", w.now_ns(), &live_hash.to_le_bytes())?;
 
     w.flush()?;
 
@@ -1,20 +1,13 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
 use adapters::{SeqTracker, AdapterSignal};
 use eventlog::{EventLogReader, EventLogWriter};
 use eventlog::hash::stable_hash;
 use eventlog::snapshot::Snapshot;
 use replay::ReplayGuard;
 
-fn now_ns() -> u64 {
-    let d = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    d.as_secs() * 1_000_000_000 + d.subsec_nanos() as u64
-}
-
 // Дет. редьюсер состояния (placeholder reducer).
 // Важно: одинаково для live и replay.
 fn reduce(mut state: u64, payload: &[u8]) -> u64 {
-    let h = stable_hash(&payload.to_vec());
+    let h = stable_hash(payload);
     state = state.wrapping_mul(1_000_000_003) ^ h;
     state
 }
@@ -37,7 +30,7 @@ fn main() -> anyhow::Result<()> {
     seq.observe(1).unwrap();
     if guard.allow_event() {
         let p = br#"{"ev":"a","v":1}"#;
-        w.append_bytes("event", now_ns(), p)?;
+        w.append_bytes("event", w.now_ns(), p)?;
         live_state = reduce(live_state, p);
     }
 
@@ -50,7 +43,7 @@ fn main() -> anyhow::Result<()> {
     // SNAPSHOT BARRIER: пишем snapshot payload = live_state (как bytes)
     // и UNBLOCK + reset seq tracker
     let snap_bytes = live_state.to_le_bytes();
-    w.append_bytes("snapshot", now_ns(), &snap_bytes)?;
+    w.append_bytes("snapshot", w.now_ns(), &snap_bytes)?;
     guard.on_snapshot();
     seq.reset(10);
 
@@ -58,7 +51,7 @@ fn main() -> anyhow::Result<()> {
     seq.observe(11).unwrap();
     if guard.allow_event() {
         let p = br#"{"ev":"b","v":2}"#;
-        w.append_bytes("event", now_ns(), p)?;
+        w.append_bytes("event", w.now_ns(), p)?;
         live_state = reduce(live_state, p);
     }
 
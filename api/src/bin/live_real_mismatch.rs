@@ -1,16 +1,9 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
 use eventlog::{EventLogReader, EventLogWriter};
 use eventlog::hash::stable_hash;
 use eventlog::snapshot::Snapshot;
 
-fn now_ns() -> u64 {
-    let d = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    d.as_secs() * 1_000_000_000 + d.subsec_nanos() as u64
-}
-
 fn reduce(mut state: u64, payload: &[u8]) -> u64 {
-    let h = stable_hash(&payload.to_vec());
+    let h = stable_hash(payload);
     state = state.wrapping_mul(1_000_000_003) ^ h;
     state
 }
@@ -25,7 +18,7 @@ fn main() -> anyhow::Result<()> {
     let mut live_state: u64 = 0;
 
     let p = br#"{"ev":"x","v":9}"#;
-    w.append_bytes("event", now_ns(), p)?;
+    w.append_bytes("event", w.now_ns(), p)?;
     live_state = reduce(live_state, p);
     w.flush()?;
 
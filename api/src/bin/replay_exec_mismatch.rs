@@ -37,7 +37,7 @@ fn main() -> anyhow::Result<()> {
     }
 
     // ЛОМАЕМ replay: добавляем лишнее событие
-    events.push(ExecEvent::OrderRejected { instrument: exec::util::instrument::InstrumentKey::new("binance","BTCUSDT"), id: OrderId(1), reason: "boom".to_string() });
+    events.push(ExecEvent::OrderRejected { instrument: el_core::instrument::InstrumentKey::new("binance","BTCUSDT"), id: OrderId(1), reason: "boom".to_string() });
 
     let (_store, replay_hash) = build_snapshot(&events).map_err(|e| anyhow::anyhow!(e.to_string()))?;
     let snap_hash = snap_hash.expect("snapshot_hash must exist");
@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use columnar::ExportOptions;
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let usage = "usage: export_parquet <log|dir> <out dir> [--stream NAME]... [--batch-rows N]";
+
+    let mut paths = Vec::new();
+    let mut opts = ExportOptions::default();
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            // only this stream (repeatable)
+            "--stream" => opts.streams.push(args.next().context("--stream needs a name")?),
+            // rows per Parquet row group
+            "--batch-rows" => opts.batch_rows = args.next().context("--batch-rows needs N")?.parse()?,
+            _ => paths.push(a),
+        }
+    }
+    let [log, out] = <[String; 2]>::try_from(paths).map_err(|_| anyhow::anyhow!(usage))?;
+
+    let r = columnar::export(&log, &out, &opts)?;
+    eprintln!(
+        "EXPORTED: {} -> {} records={} book={} trades={} bbo={} derivatives={} gaps={} executions={} skipped={} undecoded={}",
+        log,
+        out,
+        r.records,
+        r.book_rows,
+        r.trade_rows,
+        r.bbo_rows,
+        r.derivative_rows,
+        r.gap_rows,
+        r.execution_rows,
+        r.skipped,
+        r.undecoded
+    );
+    Ok(())
+}
@@ -0,0 +1,300 @@
+use anyhow::{Context, Result};
+use el_core::event::{Event, EventPayload};
+use eventlog::{EventEnvelope, LogSource, SchemaRegistry};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use crate::tables::{self, Bbo, BookLevel, Common, Derivative, Execution, Gap, Row, Trade};
+
+/// Rows buffered per table before they are written out as one row group.
+pub const DEFAULT_BATCH_ROWS: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub batch_rows: usize,
+    /// only export these streams (empty: all)
+    pub streams: Vec<String>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self { batch_rows: DEFAULT_BATCH_ROWS, streams: Vec::new() }
+    }
+}
+
+/// Rows written to each table, and what was left out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportReport {
+    pub records: u64,
+    /// records of other kinds, and "event" payloads that are not
+    /// `el_core::event::Event`s (exec events share the kind)
+    pub undecoded: u64,
+    /// events without a table (connectivity, resync, risk)
+    pub skipped: u64,
+    pub book_rows: u64,
+    pub trade_rows: u64,
+    pub bbo_rows: u64,
+    pub derivative_rows: u64,
+    pub gap_rows: u64,
+    pub execution_rows: u64,
+}
+
+/// One Parquet file being written.
+struct Table<R: Row> {
+    rows: Vec<(Common, R)>,
+    writer: ArrowWriter<File>,
+    written: u64,
+}
+
+impl<R: Row> Table<R> {
+    fn create(dir: &Path) -> Result<Self> {
+        let path = table_path::<R>(dir);
+        let file = File::create(&path).with_context(|| format!("create {:?}", path))?;
+        let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let writer = ArrowWriter::try_new(file, tables::schema::<R>(), Some(props))?;
+        Ok(Self { rows: Vec::new(), writer, written: 0 })
+    }
+
+    fn push(&mut self, common: Common, row: R, batch_rows: usize) -> Result<()> {
+        self.rows.push((common, row));
+        if self.rows.len() >= batch_rows {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        self.writer.write(&tables::record_batch(&self.rows)?)?;
+        // close the row group here rather than at the writer's own size limit
+        self.writer.flush()?;
+        self.written += self.rows.len() as u64;
+        self.rows.clear();
+        Ok(())
+    }
+
+    fn close(mut self) -> Result<u64> {
+        self.flush()?;
+        self.writer.close()?;
+        Ok(self.written)
+    }
+}
+
+/// `<dir>/<table>.parquet`
+pub fn table_path<R: Row>(dir: impl AsRef<Path>) -> PathBuf {
+    dir.as_ref().join(format!("{}.parquet", R::NAME))
+}
+
+/// Decodes core events and appends them to the tables in a directory:
+/// `book` (a row per level), `trades`, `bbo`, `derivatives`, `gaps` and
+/// `executions`.
+/// Existing tables there are replaced. Kinds and versions the schema
+/// registry (`SchemaRegistry::core` by default) does not know fail the
+/// export; older events are upcast first.
+pub struct Exporter {
+    book: Table<BookLevel>,
+    trades: Table<Trade>,
+    bbo: Table<Bbo>,
+    derivatives: Table<Derivative>,
+    gaps: Table<Gap>,
+    executions: Table<Execution>,
+    batch_rows: usize,
+    schemas: SchemaRegistry,
+    report: ExportReport,
+}
+
+impl Exporter {
+    pub fn create(dir: impl AsRef<Path>, batch_rows: usize) -> Result<Self> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir).with_context(|| format!("create_dir_all {:?}", dir))?;
+        Ok(Self {
+            book: Table::create(dir)?,
+            trades: Table::create(dir)?,
+            bbo: Table::create(dir)?,
+            derivatives: Table::create(dir)?,
+            gaps: Table::create(dir)?,
+            executions: Table::create(dir)?,
+            batch_rows: batch_rows.max(1),
+            schemas: SchemaRegistry::core(),
+            report: ExportReport::default(),
+        })
+    }
+
+    /// Check and upcast records with `schemas` instead of the core registry.
+    pub fn with_schemas(mut self, schemas: SchemaRegistry) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
+    /// Add one log record; payloads that are not events are counted and skipped.
+    pub fn push(&mut self, env: &EventEnvelope, payload: &[u8]) -> Result<()> {
+        self.report.records += 1;
+        let payload = self.schemas.upcast(env, payload.to_vec())?;
+        if env.kind != "event" {
+            self.report.undecoded += 1;
+            return Ok(());
+        }
+        match serde_json::from_slice::<Event>(&payload) {
+            Ok(ev) => self.push_event(env.seq, &env.stream, &ev),
+            Err(_) => {
+                self.report.undecoded += 1;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn push_event(&mut self, seq: u64, stream: &str, ev: &Event) -> Result<()> {
+        let n = self.batch_rows;
+        let common = Common::of(seq, stream, ev);
+        let exec = |event_type, order_id: &str| Execution {
+            event_type,
+            order_id: order_id.to_string(),
+            side: None,
+            order_type: None,
+            time_in_force: None,
+            price: None,
+            qty: None,
+            fill_id: None,
+            reason: None,
+        };
+        let deriv = |event_type| Derivative {
+            event_type,
+            price: None,
+            qty: None,
+            notional: None,
+            rate: None,
+            next_funding_ts: None,
+            side: None,
+        };
+        match &ev.payload {
+            EventPayload::BookSnapshot { bids, asks } | EventPayload::BookDelta { bids, asks } => {
+                let is_snapshot = matches!(ev.payload, EventPayload::BookSnapshot { .. });
+                for (side, levels) in [("bid", bids), ("ask", asks)] {
+                    for (i, (price, qty)) in levels.iter().enumerate() {
+                        let row = BookLevel { is_snapshot, side, level: i as u32, price: price.to_f64(), qty: qty.to_f64() };
+                        self.book.push(common.clone(), row, n)?;
+                    }
+                }
+            }
+            EventPayload::Trade { price, qty, is_maker, trade_id, aggressor } => {
+                let row = Trade {
+                    price: price.to_f64(),
+                    qty: qty.to_f64(),
+                    is_maker: *is_maker,
+                    trade_id: trade_id.clone(),
+                    aggressor: aggressor.map(|s| s.to_string()),
+                };
+                self.trades.push(common, row, n)?
+            }
+            EventPayload::TickerBbo { bid, ask, bid_qty, ask_qty } => {
+                let row = Bbo {
+                    bid: bid.to_f64(),
+                    ask: ask.to_f64(),
+                    bid_qty: bid_qty.map(|q| q.to_f64()),
+                    ask_qty: ask_qty.map(|q| q.to_f64()),
+                };
+                self.bbo.push(common, row, n)?
+            }
+            EventPayload::MarkPrice { price } => {
+                let row = Derivative { price: Some(price.to_f64()), ..deriv("MarkPrice") };
+                self.derivatives.push(common, row, n)?
+            }
+            EventPayload::IndexPrice { price } => {
+                let row = Derivative { price: Some(price.to_f64()), ..deriv("IndexPrice") };
+                self.derivatives.push(common, row, n)?
+            }
+            EventPayload::FundingRate { rate, next_funding_ts } => {
+                let row = Derivative {
+                    rate: Some(rate.to_f64()),
+                    next_funding_ts: next_funding_ts.map(|t| t.nanos),
+                    ..deriv("FundingRate")
+                };
+                self.derivatives.push(common, row, n)?
+            }
+            EventPayload::Liquidation { side, price, qty } => {
+                let row = Derivative {
+                    side: Some(side.to_string()),
+                    price: Some(price.to_f64()),
+                    qty: Some(qty.to_f64()),
+                    ..deriv("Liquidation")
+                };
+                self.derivatives.push(common, row, n)?
+            }
+            EventPayload::OpenInterest { qty, notional } => {
+                let row = Derivative {
+                    qty: Some(qty.to_f64()),
+                    notional: notional.map(|v| v.to_f64()),
+                    ..deriv("OpenInterest")
+                };
+                self.derivatives.push(common, row, n)?
+            }
+            EventPayload::GapDetected { from, to } => self.gaps.push(common, Gap { from: *from, to: *to }, n)?,
+            EventPayload::OrderSubmit { order_id, side, order_type, time_in_force, price, qty, .. } => {
+                let row = Execution {
+                    side: Some(side.to_string()),
+                    order_type: Some(format!("{:?}", order_type)),
+                    time_in_force: Some(format!("{:?}", time_in_force)),
+                    price: price.map(|p| p.to_f64()),
+                    qty: Some(qty.to_f64()),
+                    ..exec("OrderSubmit", order_id)
+                };
+                self.executions.push(common, row, n)?
+            }
+            EventPayload::OrderAck { order_id } => self.executions.push(common, exec("OrderAck", order_id), n)?,
+            EventPayload::OrderReject { order_id, reason } => {
+                let row = Execution { reason: Some(reason.clone()), ..exec("OrderReject", order_id) };
+                self.executions.push(common, row, n)?
+            }
+            EventPayload::Fill { order_id, fill_id, price, qty } => {
+                let row = Execution {
+                    fill_id: Some(fill_id.clone()),
+                    price: Some(price.to_f64()),
+                    qty: Some(qty.to_f64()),
+                    ..exec("Fill", order_id)
+                };
+                self.executions.push(common, row, n)?
+            }
+            EventPayload::CancelRequest { order_id } => {
+                self.executions.push(common, exec("CancelRequest", order_id), n)?
+            }
+            EventPayload::CancelAck { order_id } => self.executions.push(common, exec("CancelAck", order_id), n)?,
+            EventPayload::Connectivity { .. }
+            | EventPayload::ResyncStarted
+            | EventPayload::ResyncFinished
+            | EventPayload::Risk { .. }
+            | EventPayload::KillSwitch { .. } => self.report.skipped += 1,
+        }
+        Ok(())
+    }
+
+    /// Write out what is buffered and close the files.
+    pub fn finish(self) -> Result<ExportReport> {
+        Ok(ExportReport {
+            book_rows: self.book.close()?,
+            trade_rows: self.trades.close()?,
+            bbo_rows: self.bbo.close()?,
+            derivative_rows: self.derivatives.close()?,
+            gap_rows: self.gaps.close()?,
+            execution_rows: self.executions.close()?,
+            ..self.report
+        })
+    }
+}
+
+/// Export a log file or segment directory into Parquet tables in `dir`.
+pub fn export(log: impl AsRef<Path>, dir: impl AsRef<Path>, opts: &ExportOptions) -> Result<ExportReport> {
+    let mut r = LogSource::open(log)?;
+    if !opts.streams.is_empty() {
+        r = r.with_streams(opts.streams.iter().cloned());
+    }
+    let mut out = Exporter::create(dir, opts.batch_rows)?;
+    while let Some((env, payload)) = r.next()? {
+        out.push(&env, &payload)?;
+    }
+    out.finish()
+}
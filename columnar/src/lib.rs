@@ -0,0 +1,4 @@
+pub mod tables;
+pub mod export;
+
+pub use export::{export, ExportOptions, ExportReport, Exporter};
@@ -0,0 +1,319 @@
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, TimestampNanosecondArray, UInt32Array, UInt64Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use el_core::event::{Event, Exchange};
+use std::sync::Arc;
+
+/// Columns every table starts with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Common {
+    /// seq of the record in the log
+    pub seq: u64,
+    pub stream: String,
+    /// exchange sequence / update id of the event
+    pub event_seq: Option<u64>,
+    pub ts_exchange: Option<i64>,
+    pub ts_recv: i64,
+    pub ts_proc: i64,
+    pub exchange: String,
+    pub symbol: String,
+    /// `EXCHANGE:SYMBOL`
+    pub instrument: String,
+}
+
+pub fn exchange_name(e: &Exchange) -> String {
+    e.name().to_string()
+}
+
+impl Common {
+    pub fn of(seq: u64, stream: &str, ev: &Event) -> Self {
+        let exchange = exchange_name(&ev.instrument.exchange);
+        Self {
+            seq,
+            stream: stream.to_string(),
+            event_seq: ev.seq,
+            ts_exchange: ev.ts_exchange.map(|t| t.nanos),
+            ts_recv: ev.ts_recv.nanos,
+            ts_proc: ev.ts_proc.nanos,
+            instrument: format!("{}:{}", exchange, ev.instrument.symbol.0),
+            exchange,
+            symbol: ev.symbol.clone(),
+        }
+    }
+}
+
+fn ts_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into()))
+}
+
+fn common_fields() -> Vec<Field> {
+    vec![
+        Field::new("seq", DataType::UInt64, false),
+        Field::new("stream", DataType::Utf8, false),
+        Field::new("event_seq", DataType::UInt64, true),
+        Field::new("ts_exchange", ts_type(), true),
+        Field::new("ts_recv", ts_type(), false),
+        Field::new("ts_proc", ts_type(), false),
+        Field::new("exchange", DataType::Utf8, false),
+        Field::new("symbol", DataType::Utf8, false),
+        Field::new("instrument", DataType::Utf8, false),
+    ]
+}
+
+fn common_columns<R>(rows: &[(Common, R)]) -> Vec<ArrayRef> {
+    let ts = |f: fn(&Common) -> Option<i64>| -> ArrayRef {
+        Arc::new(TimestampNanosecondArray::from_iter(rows.iter().map(|(c, _)| f(c))).with_timezone("UTC"))
+    };
+    vec![
+        Arc::new(UInt64Array::from_iter_values(rows.iter().map(|(c, _)| c.seq))),
+        Arc::new(StringArray::from_iter_values(rows.iter().map(|(c, _)| &c.stream))),
+        Arc::new(UInt64Array::from_iter(rows.iter().map(|(c, _)| c.event_seq))),
+        ts(|c| c.ts_exchange),
+        ts(|c| Some(c.ts_recv)),
+        ts(|c| Some(c.ts_proc)),
+        Arc::new(StringArray::from_iter_values(rows.iter().map(|(c, _)| &c.exchange))),
+        Arc::new(StringArray::from_iter_values(rows.iter().map(|(c, _)| &c.symbol))),
+        Arc::new(StringArray::from_iter_values(rows.iter().map(|(c, _)| &c.instrument))),
+    ]
+}
+
+/// Row type of one table: its own columns after the common ones.
+pub trait Row: Sized {
+    /// file stem of the table
+    const NAME: &'static str;
+    fn fields() -> Vec<Field>;
+    fn columns(rows: &[(Common, Self)]) -> Vec<ArrayRef>;
+}
+
+pub fn schema<R: Row>() -> SchemaRef {
+    let mut fields = common_fields();
+    fields.extend(R::fields());
+    Arc::new(Schema::new(fields))
+}
+
+pub fn record_batch<R: Row>(rows: &[(Common, R)]) -> Result<RecordBatch, ArrowError> {
+    let mut columns = common_columns(rows);
+    columns.extend(R::columns(rows));
+    RecordBatch::try_new(schema::<R>(), columns)
+}
+
+fn f64s<R>(rows: &[(Common, R)], f: impl Fn(&R) -> f64) -> ArrayRef {
+    Arc::new(Float64Array::from_iter_values(rows.iter().map(|(_, r)| f(r))))
+}
+
+fn opt_f64s<R>(rows: &[(Common, R)], f: impl Fn(&R) -> Option<f64>) -> ArrayRef {
+    Arc::new(Float64Array::from_iter(rows.iter().map(|(_, r)| f(r))))
+}
+
+fn strs<'a, R: 'a>(rows: &'a [(Common, R)], f: impl Fn(&'a R) -> Option<&'a str>) -> ArrayRef {
+    Arc::new(StringArray::from_iter(rows.iter().map(|(_, r)| f(r))))
+}
+
+/// One price level of a book snapshot or delta; an event has a row per level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookLevel {
+    pub is_snapshot: bool,
+    /// `bid` or `ask`
+    pub side: &'static str,
+    /// position of the level within its side of the event
+    pub level: u32,
+    pub price: f64,
+    /// 0 removes the level (deltas)
+    pub qty: f64,
+}
+
+impl Row for BookLevel {
+    const NAME: &'static str = "book";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("is_snapshot", DataType::Boolean, false),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("level", DataType::UInt32, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("qty", DataType::Float64, false),
+        ]
+    }
+
+    fn columns(rows: &[(Common, Self)]) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(BooleanArray::from_iter(rows.iter().map(|(_, r)| Some(r.is_snapshot)))),
+            strs(rows, |r| Some(r.side)),
+            Arc::new(UInt32Array::from_iter_values(rows.iter().map(|(_, r)| r.level))),
+            f64s(rows, |r| r.price),
+            f64s(rows, |r| r.qty),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub price: f64,
+    pub qty: f64,
+    pub is_maker: bool,
+    pub trade_id: Option<String>,
+    /// `Buy` or `Sell`
+    pub aggressor: Option<String>,
+}
+
+impl Row for Trade {
+    const NAME: &'static str = "trades";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("price", DataType::Float64, false),
+            Field::new("qty", DataType::Float64, false),
+            Field::new("is_maker", DataType::Boolean, false),
+            Field::new("trade_id", DataType::Utf8, true),
+            Field::new("aggressor", DataType::Utf8, true),
+        ]
+    }
+
+    fn columns(rows: &[(Common, Self)]) -> Vec<ArrayRef> {
+        vec![
+            f64s(rows, |r| r.price),
+            f64s(rows, |r| r.qty),
+            Arc::new(BooleanArray::from_iter(rows.iter().map(|(_, r)| Some(r.is_maker)))),
+            strs(rows, |r| r.trade_id.as_deref()),
+            strs(rows, |r| r.aggressor.as_deref()),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bbo {
+    pub bid: f64,
+    pub ask: f64,
+    pub bid_qty: Option<f64>,
+    pub ask_qty: Option<f64>,
+}
+
+impl Row for Bbo {
+    const NAME: &'static str = "bbo";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("bid", DataType::Float64, false),
+            Field::new("ask", DataType::Float64, false),
+            Field::new("bid_qty", DataType::Float64, true),
+            Field::new("ask_qty", DataType::Float64, true),
+        ]
+    }
+
+    fn columns(rows: &[(Common, Self)]) -> Vec<ArrayRef> {
+        vec![f64s(rows, |r| r.bid), f64s(rows, |r| r.ask), opt_f64s(rows, |r| r.bid_qty), opt_f64s(rows, |r| r.ask_qty)]
+    }
+}
+
+/// Any derivatives market data event; columns an event type does not have are null.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Derivative {
+    /// `MarkPrice`, `IndexPrice`, `FundingRate`, `Liquidation` or `OpenInterest`
+    pub event_type: &'static str,
+    pub price: Option<f64>,
+    pub qty: Option<f64>,
+    pub notional: Option<f64>,
+    /// funding rate per period
+    pub rate: Option<f64>,
+    pub next_funding_ts: Option<i64>,
+    /// side of the liquidation order
+    pub side: Option<String>,
+}
+
+impl Row for Derivative {
+    const NAME: &'static str = "derivatives";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("event_type", DataType::Utf8, false),
+            Field::new("price", DataType::Float64, true),
+            Field::new("qty", DataType::Float64, true),
+            Field::new("notional", DataType::Float64, true),
+            Field::new("rate", DataType::Float64, true),
+            Field::new("next_funding_ts", ts_type(), true),
+            Field::new("side", DataType::Utf8, true),
+        ]
+    }
+
+    fn columns(rows: &[(Common, Self)]) -> Vec<ArrayRef> {
+        vec![
+            strs(rows, |r| Some(r.event_type)),
+            opt_f64s(rows, |r| r.price),
+            opt_f64s(rows, |r| r.qty),
+            opt_f64s(rows, |r| r.notional),
+            opt_f64s(rows, |r| r.rate),
+            Arc::new(TimestampNanosecondArray::from_iter(rows.iter().map(|(_, r)| r.next_funding_ts)).with_timezone("UTC")),
+            strs(rows, |r| r.side.as_deref()),
+        ]
+    }
+}
+
+/// Missing exchange seqs `from..=to` reported by a connector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gap {
+    pub from: u64,
+    pub to: u64,
+}
+
+impl Row for Gap {
+    const NAME: &'static str = "gaps";
+
+    fn fields() -> Vec<Field> {
+        vec![Field::new("from", DataType::UInt64, false), Field::new("to", DataType::UInt64, false)]
+    }
+
+    fn columns(rows: &[(Common, Self)]) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(|(_, r)| r.from))),
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(|(_, r)| r.to))),
+        ]
+    }
+}
+
+/// Any order lifecycle event; columns an event type does not have are null.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Execution {
+    /// `OrderSubmit`, `OrderAck`, `OrderReject`, `Fill`, `CancelRequest` or `CancelAck`
+    pub event_type: &'static str,
+    pub order_id: String,
+    pub side: Option<String>,
+    /// `Limit` or `Market`
+    pub order_type: Option<String>,
+    /// `Gtc`, `Ioc` or `Fok`
+    pub time_in_force: Option<String>,
+    pub price: Option<f64>,
+    pub qty: Option<f64>,
+    pub fill_id: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl Row for Execution {
+    const NAME: &'static str = "executions";
+
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new("event_type", DataType::Utf8, false),
+            Field::new("order_id", DataType::Utf8, false),
+            Field::new("side", DataType::Utf8, true),
+            Field::new("order_type", DataType::Utf8, true),
+            Field::new("time_in_force", DataType::Utf8, true),
+            Field::new("price", DataType::Float64, true),
+            Field::new("qty", DataType::Float64, true),
+            Field::new("fill_id", DataType::Utf8, true),
+            Field::new("reason", DataType::Utf8, true),
+        ]
+    }
+
+    fn columns(rows: &[(Common, Self)]) -> Vec<ArrayRef> {
+        vec![
+            strs(rows, |r| Some(r.event_type)),
+            strs(rows, |r| Some(&r.order_id)),
+            strs(rows, |r| r.side.as_deref()),
+            strs(rows, |r| r.order_type.as_deref()),
+            strs(rows, |r| r.time_in_force.as_deref()),
+            opt_f64s(rows, |r| r.price),
+            opt_f64s(rows, |r| r.qty),
+            strs(rows, |r| r.fill_id.as_deref()),
+            strs(rows, |r| r.reason.as_deref()),
+        ]
+    }
+}
@@ -0,0 +1,231 @@
+use anyhow::Result;
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, TimestampNanosecondType, UInt32Type, UInt64Type};
+use arrow_array::{Array, RecordBatch};
+use columnar::export::table_path;
+use columnar::tables::{BookLevel, Derivative, Execution, Gap, Row, Trade};
+use columnar::{export, ExportOptions};
+use el_core::event::{Event, EventId, EventPayload, EventType, Exchange};
+use el_core::exec::{OrderType, Side, TimeInForce};
+use el_core::instrument::InstrumentKey;
+use el_core::num::{Decimal, Price, Qty};
+use el_core::time::{TimeSource, Timestamp};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogWriter, LogFormat};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::path::{Path, PathBuf};
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&p);
+    std::fs::create_dir_all(&p).unwrap();
+    p
+}
+
+fn event(event_type: EventType, ts: i64, payload: EventPayload) -> Event {
+    Event {
+        id: EventId::nil(),
+        event_type,
+        exchange: Exchange::Binance,
+        symbol: "BTCUSDT".to_string(),
+        instrument: InstrumentKey::new(Exchange::Binance, "BTCUSDT"),
+        ts_exchange: (ts % 20 == 0).then(|| Timestamp::new(ts - 5, TimeSource::Exchange)),
+        ts_recv: Timestamp::new(ts, TimeSource::Receive),
+        ts_proc: Timestamp::new(ts + 1, TimeSource::Process),
+        seq: Some(ts as u64 / 10),
+        schema_version: 1,
+        integrity_flags: vec![],
+        payload,
+        meta: Default::default(),
+    }
+}
+
+fn px(v: f64) -> Price {
+    Price::from_f64(v).unwrap()
+}
+
+fn qty(v: f64) -> Qty {
+    Qty::from_f64(v).unwrap()
+}
+
+fn lvl(p: f64, q: f64) -> (Price, Qty) {
+    (px(p), qty(q))
+}
+
+fn capture(path: &Path) -> Result<()> {
+    let events = [
+        event(EventType::BookSnapshot, 10, EventPayload::BookSnapshot {
+            bids: vec![lvl(100.0, 1.0), lvl(99.0, 2.0)],
+            asks: vec![lvl(101.0, 3.0)],
+        }),
+        event(EventType::BookDelta, 20, EventPayload::BookDelta { bids: vec![lvl(100.0, 0.0)], asks: vec![] }),
+        event(EventType::Trade, 30, EventPayload::Trade {
+            price: px(100.5),
+            qty: qty(0.25),
+            is_maker: true,
+            trade_id: Some("17".to_string()),
+            aggressor: Some(Side::Sell),
+        }),
+        event(EventType::Connectivity, 40, EventPayload::Connectivity { status: "up".to_string() }),
+        event(EventType::GapDetected, 50, EventPayload::GapDetected { from: 7, to: 9 }),
+        event(EventType::OrderSubmit, 60, EventPayload::OrderSubmit {
+            order_id: "o1".to_string(),
+            client_order_id: None,
+            side: Side::Buy,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::Ioc,
+            price: Some(px(100.0)),
+            qty: qty(1.0),
+            flags: Default::default(),
+        }),
+        event(EventType::Fill, 70, EventPayload::Fill {
+            order_id: "o1".to_string(),
+            fill_id: "f1".to_string(),
+            price: px(100.0),
+            qty: qty(0.5),
+        }),
+        event(EventType::FundingRate, 75, EventPayload::FundingRate {
+            rate: "0.0001".parse::<Decimal>().unwrap(),
+            next_funding_ts: Some(Timestamp::new(1_000, TimeSource::Exchange)),
+        }),
+    ];
+    let opts = WriterOptions { format: LogFormat::Binary, ..Default::default() };
+    let mut w = EventLogWriter::open_with(path, "md", opts)?;
+    for ev in &events {
+        w.append_bytes("event", ev.ts_recv.nanos as u64, &serde_json::to_vec(ev)?)?;
+    }
+    w.append_stream("exec", "snapshot_hash", 80, &7u64.to_le_bytes())?;
+    Ok(w.flush()?)
+}
+
+fn read_table<R: Row>(dir: &Path) -> Result<(RecordBatch, usize)> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(table_path::<R>(dir))?)?;
+    let row_groups = reader.metadata().num_row_groups();
+    let batches = reader.build()?.collect::<Result<Vec<_>, _>>()?;
+    let schema = batches.first().map(|b| b.schema()).unwrap_or_else(|| columnar::tables::schema::<R>());
+    Ok((arrow_select::concat::concat_batches(&schema, &batches)?, row_groups))
+}
+
+#[test]
+fn exports_typed_tables() -> Result<()> {
+    let dir = tmp_dir("export_tables");
+    let log = dir.join("capture.log");
+    capture(&log)?;
+
+    let out = dir.join("tables");
+    let report = export(&log, &out, &ExportOptions { batch_rows: 2, ..Default::default() })?;
+    assert_eq!((report.records, report.undecoded, report.skipped), (9, 1, 1));
+    assert_eq!((report.book_rows, report.trade_rows, report.bbo_rows, report.derivative_rows), (4, 1, 0, 1));
+    assert_eq!((report.gap_rows, report.execution_rows), (1, 2));
+
+    let (book, row_groups) = read_table::<BookLevel>(&out)?;
+    assert_eq!(row_groups, 2, "batch_rows rows per group");
+    let seq = book.column_by_name("seq").unwrap().as_primitive::<UInt64Type>();
+    assert_eq!(seq.values().to_vec(), vec![1, 1, 1, 2]);
+    let side = book.column_by_name("side").unwrap().as_string::<i32>();
+    assert_eq!(side.iter().flatten().collect::<Vec<_>>(), vec!["bid", "bid", "ask", "bid"]);
+    let level = book.column_by_name("level").unwrap().as_primitive::<UInt32Type>();
+    assert_eq!(level.values().to_vec(), vec![0, 1, 0, 0]);
+    let qty = book.column_by_name("qty").unwrap().as_primitive::<Float64Type>();
+    assert_eq!(qty.values().to_vec(), vec![1.0, 2.0, 3.0, 0.0]);
+    let snapshot = book.column_by_name("is_snapshot").unwrap().as_boolean();
+    assert_eq!(snapshot.iter().flatten().collect::<Vec<_>>(), vec![true, true, true, false]);
+
+    let (trades, _) = read_table::<Trade>(&out)?;
+    let recv = trades.column_by_name("ts_recv").unwrap().as_primitive::<TimestampNanosecondType>();
+    assert_eq!(recv.value(0), 30);
+    assert!(trades.column_by_name("ts_exchange").unwrap().is_null(0));
+    let event_seq = trades.column_by_name("event_seq").unwrap().as_primitive::<UInt64Type>();
+    assert_eq!(event_seq.value(0), 3);
+    let instrument = trades.column_by_name("instrument").unwrap().as_string::<i32>();
+    assert_eq!(instrument.value(0), "Binance:BTCUSDT");
+    let trade_id = trades.column_by_name("trade_id").unwrap().as_string::<i32>();
+    let aggressor = trades.column_by_name("aggressor").unwrap().as_string::<i32>();
+    assert_eq!((trade_id.value(0), aggressor.value(0)), ("17", "Sell"));
+
+    let (derivs, _) = read_table::<Derivative>(&out)?;
+    let kind = derivs.column_by_name("event_type").unwrap().as_string::<i32>();
+    assert_eq!(kind.value(0), "FundingRate");
+    let rate = derivs.column_by_name("rate").unwrap().as_primitive::<Float64Type>();
+    assert_eq!(rate.value(0), 0.0001);
+    let next = derivs.column_by_name("next_funding_ts").unwrap().as_primitive::<TimestampNanosecondType>();
+    assert_eq!(next.value(0), 1_000);
+    assert!(derivs.column_by_name("price").unwrap().is_null(0));
+
+    let (gaps, _) = read_table::<Gap>(&out)?;
+    let to = gaps.column_by_name("to").unwrap().as_primitive::<UInt64Type>();
+    assert_eq!(to.value(0), 9);
+
+    let (execs, _) = read_table::<Execution>(&out)?;
+    let kind = execs.column_by_name("event_type").unwrap().as_string::<i32>();
+    assert_eq!(kind.iter().flatten().collect::<Vec<_>>(), vec!["OrderSubmit", "Fill"]);
+    let exch = execs.column_by_name("ts_exchange").unwrap().as_primitive::<TimestampNanosecondType>();
+    assert_eq!((exch.value(0), exch.is_null(1)), (55, true));
+    let tif = execs.column_by_name("time_in_force").unwrap().as_string::<i32>();
+    assert_eq!(tif.iter().collect::<Vec<_>>(), vec![Some("Ioc"), None]);
+    let fill_id = execs.column_by_name("fill_id").unwrap().as_string::<i32>();
+    assert_eq!(fill_id.iter().collect::<Vec<_>>(), vec![None, Some("f1")]);
+    Ok(())
+}
+
+#[test]
+fn unknown_kinds_and_versions_fail_the_export() -> Result<()> {
+    let dir = tmp_dir("export_schemas");
+    let trade = event(EventType::Trade, 10, EventPayload::Trade {
+        price: px(100.0),
+        qty: qty(1.0),
+        is_maker: false,
+        trade_id: None,
+        aggressor: None,
+    });
+    let newer = Event { schema_version: 99, ..trade.clone() };
+
+    for (name, kind, ev, expected) in [("kind", "note", &trade, "unknown kind \"note\""), ("version", "event", &newer, "version 99")] {
+        let log = dir.join(format!("{}.log", name));
+        let mut w = EventLogWriter::open_with(&log, "md", WriterOptions::default())?;
+        w.append_bytes("event", 1, &serde_json::to_vec(&trade)?)?;
+        w.append_bytes(kind, 2, &serde_json::to_vec(ev)?)?;
+        w.flush()?;
+        drop(w);
+
+        let err = export(&log, dir.join(name), &ExportOptions::default()).unwrap_err();
+        assert!(format!("{:#}", err).contains(expected), "{:#}", err);
+    }
+    Ok(())
+}
+
+#[test]
+fn v1_order_submits_are_upcast() -> Result<()> {
+    let dir = tmp_dir("export_v1_submit");
+    let submit = event(EventType::OrderSubmit, 10, EventPayload::OrderSubmit {
+        order_id: "o1".to_string(),
+        client_order_id: None,
+        side: Side::Sell,
+        order_type: OrderType::Limit,
+        time_in_force: TimeInForce::Gtc,
+        price: Some(px(100.0)),
+        qty: qty(1.0),
+        flags: Default::default(),
+    });
+    // as written under v1: a free-form side, no order type or time in force
+    let mut v1 = serde_json::to_value(&submit)?;
+    let fields = v1.pointer_mut("/payload/OrderSubmit").and_then(|p| p.as_object_mut()).unwrap();
+    fields.insert("side".into(), "SELL".into());
+    fields.remove("order_type");
+    fields.remove("time_in_force");
+
+    let log = dir.join("v1.log");
+    let mut w = EventLogWriter::open_with(&log, "md", WriterOptions::default())?;
+    w.append_bytes("event", 1, &serde_json::to_vec(&v1)?)?;
+    w.flush()?;
+    drop(w);
+
+    let out = dir.join("tables");
+    let report = export(&log, &out, &ExportOptions::default())?;
+    assert_eq!((report.records, report.undecoded, report.execution_rows), (1, 0, 1));
+    let (execs, _) = read_table::<Execution>(&out)?;
+    let side = execs.column_by_name("side").unwrap().as_string::<i32>();
+    let tif = execs.column_by_name("time_in_force").unwrap().as_string::<i32>();
+    assert_eq!((side.value(0), tif.value(0)), ("Sell", "Gtc"));
+    Ok(())
+}
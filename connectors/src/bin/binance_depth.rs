@@ -1,4 +1,8 @@
 use anyhow::Result;
+use el_core::clock::MonotonicClock;
+use eventlog::compress::{Compression, DictId};
+use eventlog::segment::Rotation;
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -6,5 +10,44 @@ async fn main() -> Result<()> {
     let symbol = args.next().unwrap_or_else(|| "BTCUSDT".to_string());
     let log_path = args.next().unwrap_or_else(|| "/tmp/binance_depth.ndjson".to_string());
 
-    connectors::binance::run_depth_reconstructed(&symbol, &log_path).await
+    // any rotation flag turns log_path into a segment directory
+    let mut rotation = Rotation::default();
+    let mut segmented = false;
+    let mut zstd_level: Option<i32> = None;
+    let mut zstd_dict: Option<DictId> = None;
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--segment-mb" => {
+                let mb: u64 = args.next().and_then(|x| x.parse().ok()).expect("--segment-mb must be u64");
+                rotation.max_bytes = Some(mb * 1024 * 1024);
+                segmented = true;
+            }
+            "--segment-secs" => {
+                let secs: u64 = args.next().and_then(|x| x.parse().ok()).expect("--segment-secs must be u64");
+                rotation.interval_ns = Some(secs * 1_000_000_000);
+                segmented = true;
+            }
+            "--zstd-level" => {
+                zstd_level = Some(args.next().and_then(|x| x.parse().ok()).expect("--zstd-level must be i32"));
+            }
+            // dictionary id printed by eventlog's train_dict, installed next to the log
+            "--zstd-dict" => {
+                zstd_dict = Some(args.next().and_then(|x| x.parse().ok()).expect("--zstd-dict must be a hex id"));
+            }
+            _ => {}
+        }
+    }
+
+    let compression = match (zstd_level, zstd_dict) {
+        (None, None) => Compression::None,
+        (level, dict) => Compression::Zstd { level: level.unwrap_or(3), dict },
+    };
+
+    // wall time at start, then monotonic: record ts never step back on NTP corrections
+    let clock = Arc::new(MonotonicClock::new());
+    if segmented {
+        connectors::binance::run_depth_recorder(&symbol, &log_path, rotation, compression, clock).await
+    } else {
+        connectors::binance::run_depth_to_file(&symbol, &log_path, compression, clock).await
+    }
 }
@@ -1,28 +1,34 @@
-use el_core::event::{Event, EventPayload, EventType, Exchange};
+use el_core::event::{Event, EventPayload, EventType, Exchange, SCHEMA_VERSION};
 use el_core::time::{Timestamp, TimeSource};
-use el_core::instrument::InstrumentKey;
-use eventlog::writer::EventLogWriter;
+use el_core::instrument::{ContractKind, InstrumentKey, InstrumentRegistry, InstrumentSpec};
+use el_core::num::{Decimal, Notional, Price, Qty};
+use anyhow::Context;
+use el_core::clock::{MonotonicClock, SharedClock};
+use eventlog::segment::Rotation;
+use eventlog::compress::Compression;
+use eventlog::index;
+use eventlog::group::{GroupCommitOptions, GroupCommitWriter};
+use eventlog::writer::WriterOptions;
+use eventlog::EventSink;
 use futures_util::StreamExt;
 use orderbook::OrderBook;
 use serde::Deserialize;
 use std::collections::HashMap;
-use time::OffsetDateTime;
+use std::sync::Arc;
 use tokio_tungstenite::connect_async;
 use url::Url;
 use uuid::Uuid;
 
-fn now_nanos() -> i64 {
-    OffsetDateTime::now_utc().unix_timestamp_nanos() as i64
-}
-
 fn ts(now: i64, src: TimeSource) -> Timestamp {
     Timestamp::new(now, src)
 }
 
-fn parse_levels(levels: Vec<[String; 2]>) -> Vec<(f64, f64)> {
+/// Binance `[price, qty]` decimal strings, exactly; a malformed level fails
+/// the whole snapshot or diff (see `run_depth` for what happens then).
+fn parse_levels(levels: Vec<[String; 2]>) -> anyhow::Result<Vec<(Price, Qty)>> {
     levels
         .into_iter()
-        .map(|x| (x[0].parse().unwrap_or(0.0), x[1].parse().unwrap_or(0.0)))
+        .map(|[p, q]| Ok((p.parse().with_context(|| format!("price {:?}", p))?, q.parse().with_context(|| format!("qty {:?}", q))?)))
         .collect()
 }
 
@@ -50,6 +56,78 @@ struct DepthDiff {
     asks: Vec<[String; 2]>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ExchangeInfo {
+    symbols: Vec<SymbolInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SymbolInfo {
+    symbol: String,
+    base_asset: String,
+    quote_asset: String,
+    /// futures only: PERPETUAL, CURRENT_QUARTER, ...
+    #[serde(default)]
+    contract_type: Option<String>,
+    /// coin-margined futures only
+    #[serde(default)]
+    contract_size: Option<Decimal>,
+    filters: Vec<serde_json::Value>,
+}
+
+fn filter_field<'a>(info: &'a SymbolInfo, filter_type: &str, field: &str) -> Option<&'a str> {
+    info.filters
+        .iter()
+        .find(|f| f["filterType"] == filter_type)
+        .and_then(|f| f[field].as_str())
+}
+
+fn spec_of(info: SymbolInfo) -> anyhow::Result<InstrumentSpec> {
+    let field = |filter: &str, name: &str| -> anyhow::Result<Decimal> {
+        let v = filter_field(&info, filter, name).with_context(|| format!("{}: no {}.{}", info.symbol, filter, name))?;
+        v.parse().with_context(|| format!("{}: {}.{} {:?}", info.symbol, filter, name, v))
+    };
+    // spot moved MIN_NOTIONAL.minNotional to NOTIONAL; USD-M futures use MIN_NOTIONAL.notional.
+    // Only a symbol with none of them has no minimum; a present one must parse.
+    let min_notional = [("NOTIONAL", "minNotional"), ("MIN_NOTIONAL", "minNotional"), ("MIN_NOTIONAL", "notional")]
+        .into_iter()
+        .find(|(filter, name)| filter_field(&info, filter, name).is_some())
+        .map(|(filter, name)| field(filter, name))
+        .transpose()?
+        .unwrap_or(Decimal::ZERO);
+    let kind = match info.contract_type.as_deref() {
+        None | Some("") => ContractKind::Spot,
+        Some("PERPETUAL") => ContractKind::Perp,
+        Some(_) => ContractKind::Future,
+    };
+    Ok(InstrumentSpec {
+        exchange: Exchange::Binance,
+        tick_size: Price(field("PRICE_FILTER", "tickSize")?),
+        lot_step: Qty(field("LOT_SIZE", "stepSize")?),
+        min_qty: Qty(field("LOT_SIZE", "minQty")?),
+        min_notional: Notional(min_notional),
+        contract_multiplier: info.contract_size.unwrap_or(Decimal::from_int(1)),
+        kind,
+        symbol: info.symbol,
+        base: info.base_asset,
+        quote: info.quote_asset,
+    })
+}
+
+/// Specs from a Binance `exchangeInfo` response (spot, USD-M or coin-M), recorded or live.
+pub fn parse_exchange_info(body: &str) -> anyhow::Result<InstrumentRegistry> {
+    let info: ExchangeInfo = serde_json::from_str(body).context("exchangeInfo")?;
+    let specs = info.symbols.into_iter().map(spec_of).collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(InstrumentRegistry::from_specs(specs)?)
+}
+
+pub async fn fetch_exchange_info(symbol: &str) -> anyhow::Result<InstrumentRegistry> {
+    let url = format!("https://api.binance.com/api/v3/exchangeInfo?symbol={}", symbol.to_uppercase());
+    let body = reqwest::Client::new().get(url).send().await?.error_for_status()?.text().await?;
+    parse_exchange_info(&body)
+}
+
 async fn fetch_snapshot(symbol: &str, limit: u32) -> anyhow::Result<DepthSnapshot> {
     let url = format!(
         "https://api.binance.com/api/v3/depth?symbol={}&limit={}",
@@ -61,12 +139,11 @@ async fn fetch_snapshot(symbol: &str, limit: u32) -> anyhow::Result<DepthSnapsho
     Ok(snap)
 }
 
-fn emit_snapshot(writer: &mut EventLogWriter, symbol: &str, book: &OrderBook, last_u: u64) -> anyhow::Result<()> {
-    let now = now_nanos();
-    let bids: Vec<(f64, f64)> = book.bids.iter().map(|(p,q)| (p.0, *q)).collect();
-    let asks: Vec<(f64, f64)> = book.asks.iter().map(|(p,q)| (p.0, *q)).collect();
+fn snapshot_event(now: i64, symbol: &str, book: &OrderBook, last_u: u64) -> Event {
+    let bids: Vec<(Price, Qty)> = book.bids.iter().map(|(p,q)| (*p, *q)).collect();
+    let asks: Vec<(Price, Qty)> = book.asks.iter().map(|(p,q)| (*p, *q)).collect();
 
-    let ev = Event {
+    Event {
         id: Uuid::new_v4(),
         event_type: EventType::BookSnapshot,
         exchange: Exchange::Binance,
@@ -76,19 +153,15 @@ fn emit_snapshot(writer: &mut EventLogWriter, symbol: &str, book: &OrderBook, la
         ts_recv: ts(now, TimeSource::Receive),
         ts_proc: ts(now, TimeSource::Process),
         seq: Some(last_u),
-        schema_version: 1,
+        schema_version: SCHEMA_VERSION,
         integrity_flags: vec![],
         payload: EventPayload::BookSnapshot { bids, asks },
         meta: HashMap::new(),
-    };
-
-    writer.write(&ev)?;
-    Ok(())
+    }
 }
 
-fn emit_gap(writer: &mut EventLogWriter, symbol: &str, from: u64, to: u64, current_u: u64) -> anyhow::Result<()> {
-    let now = now_nanos();
-    let ev = Event {
+fn gap_event(now: i64, symbol: &str, from: u64, to: u64, current_u: u64, flag: &str) -> Event {
+    Event {
         id: Uuid::new_v4(),
         event_type: EventType::GapDetected,
         exchange: Exchange::Binance,
@@ -98,18 +171,15 @@ fn emit_gap(writer: &mut EventLogWriter, symbol: &str, from: u64, to: u64, curre
         ts_recv: ts(now, TimeSource::Receive),
         ts_proc: ts(now, TimeSource::Process),
         seq: Some(current_u),
-        schema_version: 1,
-        integrity_flags: vec!["depth_gap".to_string()],
+        schema_version: SCHEMA_VERSION,
+        integrity_flags: vec![flag.to_string()],
         payload: EventPayload::GapDetected { from, to },
         meta: HashMap::new(),
-    };
-    writer.write(&ev)?;
-    Ok(())
+    }
 }
 
-fn emit_resync_started(writer: &mut EventLogWriter, symbol: &str, current_u: u64) -> anyhow::Result<()> {
-    let now = now_nanos();
-    let ev = Event {
+fn resync_started_event(now: i64, symbol: &str, current_u: u64) -> Event {
+    Event {
         id: Uuid::new_v4(),
         event_type: EventType::ResyncStarted,
         exchange: Exchange::Binance,
@@ -119,27 +189,91 @@ fn emit_resync_started(writer: &mut EventLogWriter, symbol: &str, current_u: u64
         ts_recv: ts(now, TimeSource::Receive),
         ts_proc: ts(now, TimeSource::Process),
         seq: Some(current_u),
-        schema_version: 1,
+        schema_version: SCHEMA_VERSION,
         integrity_flags: vec!["need_snapshot".to_string()],
         payload: EventPayload::ResyncStarted,
         meta: HashMap::new(),
-    };
-    writer.write(&ev)?;
-    Ok(())
+    }
+}
+
+/// Recorded logs keep a sparse index so replays can seek into them, and a
+/// hash chain so `elog verify` can show they were not edited afterwards.
+fn recorder_options(compression: Compression) -> WriterOptions {
+    WriterOptions {
+        index_stride: Some(index::DEFAULT_STRIDE),
+        hash_chain: true,
+        compression,
+        ..Default::default()
+    }
 }
 
 pub async fn run_depth_reconstructed(symbol: &str, log_path: &str) -> anyhow::Result<()> {
+    run_depth_to_file(symbol, log_path, Compression::None, Arc::new(MonotonicClock::new())).await
+}
+
+/// Records go through a group-commit writer thread, so fsyncs never stall
+/// the websocket loop; while its queue is full the loop waits for room
+/// without blocking the runtime worker.
+/// Event and record timestamps both come from `clock`.
+pub async fn run_depth_to_file(
+    symbol: &str,
+    log_path: &str,
+    compression: Compression,
+    clock: SharedClock,
+) -> anyhow::Result<()> {
+    let opts = recorder_options(compression);
+    let mut writer =
+        GroupCommitWriter::open(log_path, "el:eventlog", opts, GroupCommitOptions::default())?.with_clock(clock);
+    run_depth(symbol, &mut writer).await
+}
+
+/// 24/7 recorder: same stream as `run_depth_reconstructed`, written to a
+/// rolling segment directory.
+pub async fn run_depth_recorder(
+    symbol: &str,
+    log_dir: &str,
+    rotation: Rotation,
+    compression: Compression,
+    clock: SharedClock,
+) -> anyhow::Result<()> {
+    let opts = recorder_options(compression);
+    let group = GroupCommitOptions::default();
+    let mut writer =
+        GroupCommitWriter::open_segmented(log_dir, "el:eventlog", opts, rotation, group)?.with_clock(clock);
+    run_depth(symbol, &mut writer).await
+}
+
+/// Record `gap` and a resync, then rebuild `book` from a fresh snapshot;
+/// returns the snapshot's last update id.
+async fn resync(symbol: &str, writer: &mut GroupCommitWriter, book: &mut OrderBook, gap: Event) -> anyhow::Result<u64> {
+    let current_u = gap.seq.unwrap_or_default();
+    writer.write_async(&gap).await?;
+    writer.write_async(&resync_started_event(gap.ts_recv.nanos, &gap.symbol, current_u)).await?;
+
+    let snap = fetch_snapshot(symbol, 1000).await?;
+    *book = OrderBook::new();
+    book.apply_levels(&parse_levels(snap.bids)?, &parse_levels(snap.asks)?);
+
+    let now = writer.now_ns() as i64;
+    writer.write_async(&snapshot_event(now, &symbol.to_uppercase(), book, snap.last_update_id)).await?;
+    Ok(snap.last_update_id)
+}
+
+/// A diff that is out of sequence or has a malformed level is recorded as a
+/// gap and the book is rebuilt from a new snapshot; the recorder keeps going.
+/// A malformed snapshot is fatal: there is no book to continue from.
+async fn run_depth(symbol: &str, writer: &mut GroupCommitWriter) -> anyhow::Result<()> {
     // 1) snapshot
     let snap = fetch_snapshot(symbol, 1000).await?;
     let mut book = OrderBook::new();
-    let bids = snap.bids.into_iter().map(|x| (x[0].parse().unwrap_or(0.0), x[1].parse().unwrap_or(0.0))).collect::<Vec<_>>();
-    let asks = snap.asks.into_iter().map(|x| (x[0].parse().unwrap_or(0.0), x[1].parse().unwrap_or(0.0))).collect::<Vec<_>>();
+    let bids = parse_levels(snap.bids)?;
+    let asks = parse_levels(snap.asks)?;
     book.apply_levels(&bids, &asks);
 
     let mut last_u = snap.last_update_id;
 
-    let mut writer = EventLogWriter::open(log_path)?;
-    emit_snapshot(&mut writer, &symbol.to_uppercase(), &book, last_u)?;
+    let now = writer.now_ns() as i64;
+    writer.write_async(&snapshot_event(now, &symbol.to_uppercase(), &book, last_u)).await?;
 
     // 2) diff stream
     let url = Url::parse(&format!(
@@ -151,7 +285,7 @@ pub async fn run_depth_reconstructed(symbol: &str, log_path: &str) -> anyhow::Re
     let (_, mut read) = ws.split();
 
     let mut in_sync = false;
-    let mut last_checkpoint_ns: i64 = now_nanos();
+    let mut last_checkpoint_ns: i64 = writer.now_ns() as i64;
 
     while let Some(msg) = read.next().await {
         let msg = msg?;
@@ -172,32 +306,33 @@ pub async fn run_depth_reconstructed(symbol: &str, log_path: &str) -> anyhow::Re
             // After sync: expect U == last_u + 1
             if d.first_update_id != last_u + 1 {
                 // gap/resync
-                emit_gap(&mut writer, &d.symbol, last_u + 1, d.first_update_id.saturating_sub(1), d.final_update_id)?;
-                emit_resync_started(&mut writer, &d.symbol, d.final_update_id)?;
-
-                // re-snapshot
-                let snap = fetch_snapshot(symbol, 1000).await?;
-                book = OrderBook::new();
-                let bids = snap.bids.into_iter().map(|x| (x[0].parse().unwrap_or(0.0), x[1].parse().unwrap_or(0.0))).collect::<Vec<_>>();
-                let asks = snap.asks.into_iter().map(|x| (x[0].parse().unwrap_or(0.0), x[1].parse().unwrap_or(0.0))).collect::<Vec<_>>();
-                book.apply_levels(&bids, &asks);
-                last_u = snap.last_update_id;
-
-                emit_snapshot(&mut writer, &symbol.to_uppercase(), &book, last_u)?;
+                let now = writer.now_ns() as i64;
+                let to = d.first_update_id.saturating_sub(1);
+                let gap = gap_event(now, &d.symbol, last_u + 1, to, d.final_update_id, "depth_gap");
+                last_u = resync(symbol, writer, &mut book, gap).await?;
                 in_sync = false;
                 continue;
             }
         }
 
-        // Apply deltas
-        let bids = parse_levels(d.bids);
-        let asks = parse_levels(d.asks);
+        // Apply deltas; a malformed level loses the whole diff, which is a gap too
+        let (bids, asks) = match parse_levels(d.bids).and_then(|bids| Ok((bids, parse_levels(d.asks)?))) {
+            Ok(levels) => levels,
+            Err(e) => {
+                eprintln!("{}: dropping depth diff u={}: {:#}", d.symbol, d.final_update_id, e);
+                let now = writer.now_ns() as i64;
+                let gap = gap_event(now, &d.symbol, last_u + 1, d.final_update_id, d.final_update_id, "malformed_depth");
+                last_u = resync(symbol, writer, &mut book, gap).await?;
+                in_sync = false;
+                continue;
+            }
+        };
         book.apply_levels(&bids, &asks);
 
         last_u = d.final_update_id;
 
         // Emit BookDelta event (keeps raw deltas for replay)
-        let now = now_nanos();
+        let now = writer.now_ns() as i64;
         let ev = Event {
             id: Uuid::new_v4(),
             event_type: EventType::BookDelta,
@@ -210,16 +345,16 @@ pub async fn run_depth_reconstructed(symbol: &str, log_path: &str) -> anyhow::Re
             ts_proc: ts(now, TimeSource::Process),
 
             seq: Some(d.final_update_id),
-            schema_version: 1,
+            schema_version: SCHEMA_VERSION,
             integrity_flags: vec![],
             payload: EventPayload::BookDelta { bids, asks },
             meta: HashMap::new(),
         };
-        writer.write(&ev)?;
+        writer.write_async(&ev).await?;
 
         // Periodic checkpoint snapshot (every ~5s)
         if now - last_checkpoint_ns >= 5_000_000_000 {
-            emit_snapshot(&mut writer, &symbol.to_uppercase(), &book, last_u)?;
+            writer.write_async(&snapshot_event(now, &symbol.to_uppercase(), &book, last_u)).await?;
             last_checkpoint_ns = now;
         }
     }
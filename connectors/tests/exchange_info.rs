@@ -0,0 +1,66 @@
+use connectors::binance::parse_exchange_info;
+use el_core::event::Exchange;
+use el_core::instrument::{ContractKind, InstrumentKey};
+
+// trimmed from recorded spot, USD-M and coin-M exchangeInfo responses
+const EXCHANGE_INFO: &str = r#"{
+  "timezone": "UTC",
+  "symbols": [
+    {"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT",
+     "filters": [
+       {"filterType": "PRICE_FILTER", "minPrice": "0.01000000", "maxPrice": "1000000.00000000", "tickSize": "0.01000000"},
+       {"filterType": "LOT_SIZE", "minQty": "0.00001000", "maxQty": "9000.00000000", "stepSize": "0.00001000"},
+       {"filterType": "NOTIONAL", "minNotional": "5.00000000", "applyMinToMarket": true}
+     ]},
+    {"symbol": "ETHUSDT", "contractType": "PERPETUAL", "baseAsset": "ETH", "quoteAsset": "USDT",
+     "filters": [
+       {"filterType": "PRICE_FILTER", "tickSize": "0.01"},
+       {"filterType": "LOT_SIZE", "minQty": "0.001", "stepSize": "0.001"},
+       {"filterType": "MIN_NOTIONAL", "notional": "20"}
+     ]},
+    {"symbol": "BTCUSD_250926", "contractType": "CURRENT_QUARTER", "contractSize": 100, "baseAsset": "BTC", "quoteAsset": "USD",
+     "filters": [
+       {"filterType": "PRICE_FILTER", "tickSize": "0.1"},
+       {"filterType": "LOT_SIZE", "minQty": "1", "stepSize": "1"}
+     ]}
+  ]
+}"#;
+
+#[test]
+fn specs_from_recorded_exchange_info() {
+    let reg = parse_exchange_info(EXCHANGE_INFO).unwrap();
+    assert_eq!(reg.len(), 3);
+
+    let spot = reg.get(&InstrumentKey::new(Exchange::Binance, "BTCUSDT")).unwrap();
+    assert_eq!((spot.kind, spot.base.as_str(), spot.quote.as_str()), (ContractKind::Spot, "BTC", "USDT"));
+    assert_eq!(spot.tick_size.to_string(), "0.01");
+    assert_eq!(spot.lot_step.to_string(), "0.00001");
+    assert_eq!(spot.min_notional.to_string(), "5");
+
+    let perp = reg.get(&InstrumentKey::new(Exchange::Binance, "ETHUSDT")).unwrap();
+    assert_eq!(perp.kind, ContractKind::Perp);
+    assert_eq!(perp.min_notional.to_string(), "20");
+
+    let future = reg.get(&InstrumentKey::new(Exchange::Binance, "BTCUSD_250926")).unwrap();
+    assert_eq!(future.kind, ContractKind::Future);
+    assert_eq!(future.contract_multiplier.to_string(), "100");
+    assert!(future.min_notional.is_zero());
+}
+
+#[test]
+fn missing_filters_fail() {
+    let body = r#"{"symbols": [{"symbol": "X", "baseAsset": "A", "quoteAsset": "B", "filters": []}]}"#;
+    let err = parse_exchange_info(body).unwrap_err();
+    assert!(err.to_string().contains("X: no PRICE_FILTER.tickSize"), "{}", err);
+}
+
+#[test]
+fn unparseable_min_notional_fails() {
+    let body = r#"{"symbols": [{"symbol": "X", "baseAsset": "A", "quoteAsset": "B", "filters": [
+        {"filterType": "PRICE_FILTER", "tickSize": "0.01"},
+        {"filterType": "LOT_SIZE", "minQty": "1", "stepSize": "1"},
+        {"filterType": "NOTIONAL", "minNotional": "5,0"}
+    ]}]}"#;
+    let err = parse_exchange_info(body).unwrap_err();
+    assert!(format!("{:#}", err).contains("X: NOTIONAL.minNotional \"5,0\""), "{:#}", err);
+}
@@ -0,0 +1,95 @@
+use crate::time::UnixNanos;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Источник текущего времени для писателей лога и коннекторов
+pub trait Clock: Send + Sync {
+    fn now_ns(&self) -> UnixNanos;
+}
+
+/// Часы, разделяемые между писателем и коннектором
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Системное время (может прыгать назад при коррекции NTP)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WallClock;
+
+impl Clock for WallClock {
+    fn now_ns(&self) -> UnixNanos {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as UnixNanos)
+            .unwrap_or(0)
+    }
+}
+
+/// Системное время в момент создания плюс монотонно прошедшее:
+/// не идёт назад, но может разойтись с системным на долгих сессиях
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicClock {
+    anchor_ns: UnixNanos,
+    start: Instant,
+}
+
+impl MonotonicClock {
+    pub fn new() -> Self {
+        Self::anchored_at(WallClock.now_ns())
+    }
+
+    pub fn anchored_at(anchor_ns: UnixNanos) -> Self {
+        Self { anchor_ns, start: Instant::now() }
+    }
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now_ns(&self) -> UnixNanos {
+        self.anchor_ns + self.start.elapsed().as_nanos() as UnixNanos
+    }
+}
+
+/// Ручные / симулированные часы для тестов и реплея: время двигают
+/// `set` / `advance`, либо каждое чтение сдвигает его на `step`
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: AtomicI64,
+    step: UnixNanos,
+}
+
+impl ManualClock {
+    pub fn new(start_ns: UnixNanos) -> Self {
+        Self { now: AtomicI64::new(start_ns), step: 0 }
+    }
+
+    /// Каждое `now_ns` возвращает текущее время и сдвигает его на `step_ns`
+    pub fn with_step(mut self, step_ns: UnixNanos) -> Self {
+        self.step = step_ns;
+        self
+    }
+
+    pub fn set(&self, ns: UnixNanos) {
+        self.now.store(ns, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, ns: UnixNanos) {
+        self.now.fetch_add(ns, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ns(&self) -> UnixNanos {
+        self.now.fetch_add(self.step, Ordering::SeqCst)
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now_ns(&self) -> UnixNanos {
+        (**self).now_ns()
+    }
+}
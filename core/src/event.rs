@@ -1,14 +1,22 @@
 use crate::time::Timestamp;
+use crate::exec::{OrderFlags, OrderType, Side, TimeInForce};
 use crate::instrument::InstrumentKey;
+use crate::num::{Decimal, Notional, Price, Qty};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use uuid::Uuid;
 
+/// Версия схемы `Event`, которую пишет текущий код
+/// (поле `schema_version`; старые версии поднимает `eventlog::schema`)
+pub const SCHEMA_VERSION: u16 = 2;
+
 /// Уникальный идентификатор события
 pub type EventId = Uuid;
 
 /// Биржа-источник
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "ExchangeRepr")]
 pub enum Exchange {
     Binance,
     Okx,
@@ -16,6 +24,67 @@ pub enum Exchange {
     Other(String),
 }
 
+impl Exchange {
+    /// Известные биржи без учёта регистра, остальное — `Other`
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "binance" => Exchange::Binance,
+            "okx" => Exchange::Okx,
+            "bybit" => Exchange::Bybit,
+            _ => Exchange::Other(name.to_string()),
+        }
+    }
+
+    /// Имя как в wire-формате: `Binance`, `Okx`, `Bybit` или строка `Other`
+    pub fn name(&self) -> &str {
+        match self {
+            Exchange::Binance => "Binance",
+            Exchange::Okx => "Okx",
+            Exchange::Bybit => "Bybit",
+            Exchange::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for Exchange {
+    fn from(name: &str) -> Self {
+        Self::from_name(name)
+    }
+}
+
+impl From<String> for Exchange {
+    fn from(name: String) -> Self {
+        Self::from_name(&name)
+    }
+}
+
+impl fmt::Display for Exchange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Чтение `Exchange`: `"Binance"`, `{"Other": "x"}` и строки старых
+/// exec-логов (`"binance"`)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExchangeRepr {
+    Name(String),
+    Other {
+        #[serde(rename = "Other")]
+        other: String,
+    },
+}
+
+impl From<ExchangeRepr> for Exchange {
+    fn from(r: ExchangeRepr) -> Self {
+        match r {
+            ExchangeRepr::Name(name) => Self::from_name(&name),
+            ExchangeRepr::Other { other } => Exchange::Other(other),
+        }
+    }
+}
+
 /// Тип события (строгий контракт)
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EventType {
@@ -25,6 +94,13 @@ pub enum EventType {
     Trade,
     TickerBbo,
 
+    // Derivatives market data
+    MarkPrice,
+    IndexPrice,
+    FundingRate,
+    Liquidation,
+    OpenInterest,
+
     // Infra / data quality
     Connectivity,
     GapDetected,
@@ -44,6 +120,8 @@ pub enum EventType {
     KillSwitch,
 }
 
+/// Грубые виды событий ордера для старого `exec::order_fsm`
+#[deprecated(note = "use el_core::exec::ExecEvent")]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ExecEvent {
     OrderPlaced,
@@ -85,25 +163,63 @@ pub struct Event {
     pub meta: HashMap<String, String>,
 }
 
-/// Полезная нагрузка события (строго типизирована)
+/// Полезная нагрузка события (строго типизирована).
+/// Цены и количества — точные десятичные (`crate::num`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EventPayload {
     BookSnapshot {
-        bids: Vec<(f64, f64)>,
-        asks: Vec<(f64, f64)>,
+        bids: Vec<(Price, Qty)>,
+        asks: Vec<(Price, Qty)>,
     },
     BookDelta {
-        bids: Vec<(f64, f64)>,
-        asks: Vec<(f64, f64)>,
+        bids: Vec<(Price, Qty)>,
+        asks: Vec<(Price, Qty)>,
     },
     Trade {
-        price: f64,
-        qty: f64,
+        price: Price,
+        qty: Qty,
         is_maker: bool,
+        /// Идентификатор сделки на бирже
+        #[serde(default)]
+        trade_id: Option<String>,
+        /// Сторона тейкера
+        #[serde(default)]
+        aggressor: Option<Side>,
     },
     TickerBbo {
-        bid: f64,
-        ask: f64,
+        bid: Price,
+        ask: Price,
+        #[serde(default)]
+        bid_qty: Option<Qty>,
+        #[serde(default)]
+        ask_qty: Option<Qty>,
+    },
+    // Derivatives market data
+    MarkPrice {
+        price: Price,
+    },
+    IndexPrice {
+        price: Price,
+    },
+    /// Ставка за период (0.0001 = 0.01%)
+    FundingRate {
+        rate: Decimal,
+        /// Время следующего списания по часам биржи
+        #[serde(default)]
+        next_funding_ts: Option<Timestamp>,
+    },
+    /// Принудительная ликвидация; `side` — сторона ордера ликвидации
+    Liquidation {
+        side: Side,
+        price: Price,
+        qty: Qty,
+    },
+    /// Открытый интерес в контрактах
+    OpenInterest {
+        qty: Qty,
+        /// В котируемом активе, если биржа его даёт
+        #[serde(default)]
+        notional: Option<Notional>,
     },
     Connectivity {
         status: String,
@@ -115,11 +231,22 @@ pub enum EventPayload {
     ResyncStarted,
     ResyncFinished, 
     // Execution (strict)
+    /// С v2 сторона, тип и срок действия типизированы; v1 (`side` строкой)
+    /// поднимает `eventlog::schema`
     OrderSubmit {
         order_id: String,
-        side: String,
-        price: f64,
-        qty: f64,
+        #[serde(default)]
+        client_order_id: Option<String>,
+        side: Side,
+        #[serde(default)]
+        order_type: OrderType,
+        #[serde(default)]
+        time_in_force: TimeInForce,
+        /// `None` у рыночного ордера
+        price: Option<Price>,
+        qty: Qty,
+        #[serde(default)]
+        flags: OrderFlags,
     },
     OrderAck {
         order_id: String,
@@ -131,8 +258,8 @@ pub enum EventPayload {
     Fill {
         order_id: String,
         fill_id: String,
-        price: f64,
-        qty: f64,
+        price: Price,
+        qty: Qty,
     },
     CancelRequest {
         order_id: String,
@@ -0,0 +1,220 @@
+//! Каноническая модель исполнения: ордер, его параметры, события и
+//! состояния.
+//!
+//! Одни и те же типы идут от коннектора через eventlog в exec; старые
+//! пути (`exec::events`, `exec::util::instrument`, `exec::order::OrderState`)
+//! — реэкспорты или устаревшие псевдонимы этих типов.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::hash::{StateHash, StateHasher};
+use crate::instrument::InstrumentKey;
+use crate::num::{Price, Qty};
+
+/// Идентификатор ордера внутри exec
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OrderId(pub u64);
+
+/// Сторона ордера
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    /// `buy` / `sell` без учёта регистра
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "buy" => Some(Side::Buy),
+            "sell" => Some(Side::Sell),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderType {
+    #[default]
+    Limit,
+    Market,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TimeInForce {
+    /// Стоит в книге до отмены
+    #[default]
+    Gtc,
+    /// Исполняется сразу, остаток истекает
+    Ioc,
+    /// Исполняется сразу целиком или истекает
+    Fok,
+}
+
+/// Флаги ордера; по умолчанию все выключены
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OrderFlags {
+    /// Только мейкер: ордер, который исполнился бы сразу, отклоняется
+    #[serde(default)]
+    pub post_only: bool,
+    /// Только уменьшение позиции. Позиций здесь не ведётся, поэтому флаг
+    /// не проверяется: он передаётся бирже и сохраняется в `OrderParams`
+    #[serde(default)]
+    pub reduce_only: bool,
+}
+
+/// Параметры ордера, с которыми он был отправлен
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrderParams {
+    pub side: Side,
+    pub order_type: OrderType,
+    pub time_in_force: TimeInForce,
+    /// `None` у рыночного ордера
+    pub price: Option<Price>,
+    pub qty: Qty,
+    #[serde(default)]
+    pub flags: OrderFlags,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+}
+
+impl OrderParams {
+    /// Остаток не встаёт в книгу: биржа отменяет его по завершении матчинга
+    pub fn is_immediate(&self) -> bool {
+        is_immediate(self.order_type, self.time_in_force)
+    }
+
+    /// Согласованность параметров (не биржевых фильтров, см. `InstrumentSpec`)
+    pub fn check(&self) -> Result<(), String> {
+        match (self.order_type, self.price) {
+            (OrderType::Limit, None) => return Err("limit order without a price".into()),
+            (OrderType::Market, Some(_)) => return Err("market order with a price".into()),
+            _ => {}
+        }
+        if self.flags.post_only && self.is_immediate() {
+            return Err(format!("post-only {:?} {:?} order can never rest", self.order_type, self.time_in_force));
+        }
+        if !self.qty.is_positive() {
+            return Err(format!("qty {} must be positive", self.qty));
+        }
+        Ok(())
+    }
+}
+
+/// Рыночные, IOC и FOK ордера не встают в книгу
+pub fn is_immediate(order_type: OrderType, time_in_force: TimeInForce) -> bool {
+    order_type == OrderType::Market || time_in_force != TimeInForce::Gtc
+}
+
+/// Событие жизненного цикла ордера
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ExecEvent {
+    /// `params` нет у событий, записанных до типизированного `OrderSubmit`
+    OrderCreated {
+        instrument: InstrumentKey,
+        id: OrderId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        params: Option<OrderParams>,
+    },
+    OrderValidated { instrument: InstrumentKey, id: OrderId },
+    OrderSent { instrument: InstrumentKey, id: OrderId },
+    OrderAcked { instrument: InstrumentKey, id: OrderId },
+
+    /// Исполнение увеличивает `filled_qty`; `avg_px` — средняя цена этого
+    /// исполнения по данным биржи
+    OrderFill { instrument: InstrumentKey, id: OrderId, filled_qty: Qty, avg_px: Price },
+
+    OrderCancelRequested { instrument: InstrumentKey, id: OrderId },
+    OrderCancelled { instrument: InstrumentKey, id: OrderId },
+
+    OrderRejected { instrument: InstrumentKey, id: OrderId, reason: String },
+    OrderExpired { instrument: InstrumentKey, id: OrderId },
+}
+
+impl ExecEvent {
+    pub fn instrument(&self) -> &InstrumentKey {
+        match self {
+            ExecEvent::OrderCreated { instrument, .. }
+            | ExecEvent::OrderValidated { instrument, .. }
+            | ExecEvent::OrderSent { instrument, .. }
+            | ExecEvent::OrderAcked { instrument, .. }
+            | ExecEvent::OrderFill { instrument, .. }
+            | ExecEvent::OrderCancelRequested { instrument, .. }
+            | ExecEvent::OrderCancelled { instrument, .. }
+            | ExecEvent::OrderRejected { instrument, .. }
+            | ExecEvent::OrderExpired { instrument, .. } => instrument,
+        }
+    }
+}
+
+/// Состояние ордера
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderState {
+    Created,
+    Validated,
+    Sent,
+    Acknowledged,
+    PartiallyFilled,
+    Filled,
+    CancelRequested,
+    Cancelled,
+    Rejected,
+    Expired,
+}
+
+impl StateHash for OrderState {
+    /// Теги фиксированы; новые состояния получают новые теги
+    fn hash_state(&self, h: &mut StateHasher) {
+        h.write_u8(match self {
+            OrderState::Created => 0,
+            OrderState::Validated => 1,
+            OrderState::Sent => 2,
+            OrderState::Acknowledged => 3,
+            OrderState::PartiallyFilled => 4,
+            OrderState::Filled => 5,
+            OrderState::CancelRequested => 6,
+            OrderState::Cancelled => 7,
+            OrderState::Rejected => 8,
+            OrderState::Expired => 9,
+        });
+    }
+}
+
+macro_rules! impl_state_hash_tags {
+    ($($t:ty { $($v:path => $tag:expr),* $(,)? })*) => {
+        $(impl StateHash for $t {
+            fn hash_state(&self, h: &mut StateHasher) {
+                h.write_u8(match self {
+                    $($v => $tag),*
+                });
+            }
+        })*
+    };
+}
+
+// теги фиксированы, как и у `OrderState`
+impl_state_hash_tags! {
+    Side { Side::Buy => 0, Side::Sell => 1 }
+    OrderType { OrderType::Limit => 0, OrderType::Market => 1 }
+    TimeInForce { TimeInForce::Gtc => 0, TimeInForce::Ioc => 1, TimeInForce::Fok => 2 }
+}
+
+impl StateHash for OrderParams {
+    fn hash_state(&self, h: &mut StateHasher) {
+        self.side.hash_state(h);
+        self.order_type.hash_state(h);
+        self.time_in_force.hash_state(h);
+        self.price.hash_state(h);
+        self.qty.hash_state(h);
+        h.write_bool(self.flags.post_only);
+        h.write_bool(self.flags.reduce_only);
+        self.client_order_id.hash_state(h);
+    }
+}
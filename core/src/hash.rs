@@ -0,0 +1,230 @@
+//! Канонический хеш состояния для суждений "live ≡ replay" и golden-тестов.
+//!
+//! Значение не зависит от версии Rust и платформы: типы пишут себя в
+//! `StateHasher` явным little-endian кодированием, коллекции — с длиной,
+//! а сам хеш — blake3 в режиме derive_key с контекстом
+//! `execution-lab state-hash v<VERSION> <domain>`. Разные домены (книга,
+//! ордера, цепочка реплея) не пересекаются даже на одинаковых байтах.
+//!
+//! Любое изменение кодирования поднимает `STATE_HASH_VERSION`: golden-хеши
+//! тогда перегенерируются, а не "плывут" молча.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Версия схемы кодирования; входит в контекст каждого хеша.
+///
+/// - v1: первая версия, цены и количества как `f64`
+/// - v2: цены и количества как точные `Decimal` (`crate::num`)
+pub const STATE_HASH_VERSION: u32 = 2;
+
+/// Полный 256-битный хеш состояния
+pub type StateDigest = [u8; 32];
+
+/// Принимает каноническое кодирование состояния
+pub struct StateHasher {
+    inner: blake3::Hasher,
+}
+
+impl StateHasher {
+    /// Хешер домена `domain` (например "orderbook"); домен — часть ключа
+    pub fn new(domain: &str) -> Self {
+        let context = format!("execution-lab state-hash v{} {}", STATE_HASH_VERSION, domain);
+        Self { inner: blake3::Hasher::new_derive_key(&context) }
+    }
+
+    pub fn write_u8(&mut self, v: u8) {
+        self.inner.update(&[v]);
+    }
+
+    pub fn write_bool(&mut self, v: bool) {
+        self.write_u8(v as u8);
+    }
+
+    pub fn write_u32(&mut self, v: u32) {
+        self.inner.update(&v.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, v: u64) {
+        self.inner.update(&v.to_le_bytes());
+    }
+
+    pub fn write_i64(&mut self, v: i64) {
+        self.inner.update(&v.to_le_bytes());
+    }
+
+    pub fn write_i128(&mut self, v: i128) {
+        self.inner.update(&v.to_le_bytes());
+    }
+
+    /// `-0.0` пишется как `0.0`, любой NaN — как один канонический NaN
+    pub fn write_f64(&mut self, v: f64) {
+        let v = if v == 0.0 {
+            0.0
+        } else if v.is_nan() {
+            f64::NAN
+        } else {
+            v
+        };
+        self.write_u64(v.to_bits());
+    }
+
+    /// Длина коллекции (всегда 8 байт, независимо от ширины `usize`)
+    pub fn write_len(&mut self, len: usize) {
+        self.write_u64(len as u64);
+    }
+
+    /// Байты с префиксом длины
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_len(bytes.len());
+        self.inner.update(bytes);
+    }
+
+    pub fn write_str(&mut self, s: &str) {
+        self.write_bytes(s.as_bytes());
+    }
+
+    pub fn finish(&self) -> StateDigest {
+        *self.inner.finalize().as_bytes()
+    }
+
+    /// Первые 8 байт хеша как little-endian `u64`
+    pub fn finish64(&self) -> u64 {
+        let d = self.finish();
+        u64::from_le_bytes(d[..8].try_into().expect("8 bytes"))
+    }
+}
+
+/// Тип, который умеет записать своё состояние канонически.
+///
+/// Реализации пишут только содержимое, влияющее на поведение, в
+/// детерминированном порядке (`HashMap` сначала сортируют) и никогда
+/// не полагаются на `std::hash::Hash`.
+pub trait StateHash {
+    fn hash_state(&self, h: &mut StateHasher);
+}
+
+/// Хеш `value` в домене `domain`
+pub fn state_digest<T: StateHash + ?Sized>(domain: &str, value: &T) -> StateDigest {
+    let mut h = StateHasher::new(domain);
+    value.hash_state(&mut h);
+    h.finish()
+}
+
+/// 64-битный хеш `value` в домене `domain`
+pub fn state_hash64<T: StateHash + ?Sized>(domain: &str, value: &T) -> u64 {
+    let mut h = StateHasher::new(domain);
+    value.hash_state(&mut h);
+    h.finish64()
+}
+
+/// Hex-запись хеша (нижний регистр)
+pub fn to_hex(d: &StateDigest) -> String {
+    d.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+macro_rules! impl_state_hash_via {
+    ($($t:ty => $write:ident as $as:ty),* $(,)?) => {
+        $(impl StateHash for $t {
+            fn hash_state(&self, h: &mut StateHasher) {
+                h.$write(*self as $as);
+            }
+        })*
+    };
+}
+
+impl_state_hash_via! {
+    u8 => write_u8 as u8,
+    bool => write_bool as bool,
+    u16 => write_u32 as u32,
+    u32 => write_u32 as u32,
+    u64 => write_u64 as u64,
+    usize => write_u64 as u64,
+    i32 => write_i64 as i64,
+    i64 => write_i64 as i64,
+    f64 => write_f64 as f64,
+}
+
+impl StateHash for str {
+    fn hash_state(&self, h: &mut StateHasher) {
+        h.write_str(self);
+    }
+}
+
+impl StateHash for String {
+    fn hash_state(&self, h: &mut StateHasher) {
+        h.write_str(self);
+    }
+}
+
+impl<T: StateHash> StateHash for [T] {
+    fn hash_state(&self, h: &mut StateHasher) {
+        h.write_len(self.len());
+        for v in self {
+            v.hash_state(h);
+        }
+    }
+}
+
+impl<T: StateHash> StateHash for Vec<T> {
+    fn hash_state(&self, h: &mut StateHasher) {
+        self.as_slice().hash_state(h);
+    }
+}
+
+impl<T: StateHash + ?Sized> StateHash for &T {
+    fn hash_state(&self, h: &mut StateHasher) {
+        (**self).hash_state(h);
+    }
+}
+
+impl<T: StateHash + ?Sized> StateHash for Box<T> {
+    fn hash_state(&self, h: &mut StateHasher) {
+        (**self).hash_state(h);
+    }
+}
+
+impl<T: StateHash> StateHash for Option<T> {
+    fn hash_state(&self, h: &mut StateHasher) {
+        match self {
+            None => h.write_u8(0),
+            Some(v) => {
+                h.write_u8(1);
+                v.hash_state(h);
+            }
+        }
+    }
+}
+
+impl<A: StateHash, B: StateHash> StateHash for (A, B) {
+    fn hash_state(&self, h: &mut StateHasher) {
+        self.0.hash_state(h);
+        self.1.hash_state(h);
+    }
+}
+
+impl<A: StateHash, B: StateHash, C: StateHash> StateHash for (A, B, C) {
+    fn hash_state(&self, h: &mut StateHasher) {
+        self.0.hash_state(h);
+        self.1.hash_state(h);
+        self.2.hash_state(h);
+    }
+}
+
+impl<K: StateHash, V: StateHash> StateHash for BTreeMap<K, V> {
+    fn hash_state(&self, h: &mut StateHasher) {
+        h.write_len(self.len());
+        for (k, v) in self {
+            k.hash_state(h);
+            v.hash_state(h);
+        }
+    }
+}
+
+impl<T: StateHash> StateHash for BTreeSet<T> {
+    fn hash_state(&self, h: &mut StateHasher) {
+        h.write_len(self.len());
+        for v in self {
+            v.hash_state(h);
+        }
+    }
+}
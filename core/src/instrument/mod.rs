@@ -1,25 +1,54 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 
 use crate::event::Exchange;
+use crate::hash::{StateHash, StateHasher};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+mod registry;
+mod spec;
+
+pub use registry::{InstrumentRegistry, RegistryError, SpecFile};
+pub use spec::{ContractKind, InstrumentSpec, SpecError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Symbol(pub String);
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Биржа + символ; единственный ключ инструмента от коннектора до exec
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct InstrumentKey {
     pub exchange: Exchange,
     pub symbol: Symbol,
 }
 
 impl InstrumentKey {
-    pub fn new(exchange: Exchange, symbol: impl Into<String>) -> Self {
-        Self { exchange, symbol: Symbol(symbol.into()) }
+    /// `exchange` — `Exchange` или имя биржи (`"binance"`, `"Okx"`, ...)
+    pub fn new(exchange: impl Into<Exchange>, symbol: impl Into<String>) -> Self {
+        Self { exchange: exchange.into(), symbol: Symbol(symbol.into()) }
     }
 }
 
 impl fmt::Display for InstrumentKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}:{}", self.exchange, self.symbol.0)
+        write!(f, "{}:{}", self.exchange, self.symbol.0)
+    }
+}
+
+/// `EXCHANGE:SYMBOL`, обратное к `Display`
+impl FromStr for InstrumentKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((exchange, symbol)) if !exchange.is_empty() && !symbol.is_empty() => Ok(Self::new(exchange, symbol)),
+            _ => Err(format!("instrument must be EXCHANGE:SYMBOL, got {:?}", s)),
+        }
+    }
+}
+
+impl StateHash for InstrumentKey {
+    fn hash_state(&self, h: &mut StateHasher) {
+        h.write_str(self.exchange.name());
+        h.write_str(&self.symbol.0);
     }
 }
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use super::spec::{InstrumentSpec, SpecError};
+use super::InstrumentKey;
+use crate::num::{Notional, Price, Qty};
+
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("read {path}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+
+    #[error("parse instrument specs: {0}")]
+    Parse(String),
+
+    #[error("duplicate instrument {0}")]
+    Duplicate(String),
+
+    #[error("{symbol}: {reason}")]
+    Invalid { symbol: String, reason: String },
+}
+
+/// Файл справочника: `{"instruments": [...]}` в JSON или `[[instruments]]` в TOML
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SpecFile {
+    pub instruments: Vec<InstrumentSpec>,
+}
+
+/// Справочник инструментов по `InstrumentKey`
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    specs: HashMap<InstrumentKey, InstrumentSpec>,
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Справочник из списка; повтор ключа или нулевой шаг — ошибка
+    pub fn from_specs(specs: impl IntoIterator<Item = InstrumentSpec>) -> Result<Self, RegistryError> {
+        let mut reg = Self::new();
+        for spec in specs {
+            validate(&spec)?;
+            let key = spec.key();
+            if reg.specs.contains_key(&key) {
+                return Err(RegistryError::Duplicate(key.to_string()));
+            }
+            reg.specs.insert(key, spec);
+        }
+        Ok(reg)
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self, RegistryError> {
+        let file: SpecFile = serde_json::from_str(s).map_err(|e| RegistryError::Parse(e.to_string()))?;
+        Self::from_specs(file.instruments)
+    }
+
+    pub fn from_toml_str(s: &str) -> Result<Self, RegistryError> {
+        let file: SpecFile = toml::from_str(s).map_err(|e| RegistryError::Parse(e.to_string()))?;
+        Self::from_specs(file.instruments)
+    }
+
+    /// Файл `.toml` читается как TOML, остальные — как JSON
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RegistryError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|source| RegistryError::Io { path: path.to_path_buf(), source })?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml_str(&text),
+            _ => Self::from_json_str(&text),
+        }
+    }
+
+    /// Добавить или заменить спецификацию; возвращает прежнюю
+    pub fn insert(&mut self, spec: InstrumentSpec) -> Result<Option<InstrumentSpec>, RegistryError> {
+        validate(&spec)?;
+        Ok(self.specs.insert(spec.key(), spec))
+    }
+
+    pub fn get(&self, key: &InstrumentKey) -> Option<&InstrumentSpec> {
+        self.specs.get(key)
+    }
+
+    pub fn require(&self, key: &InstrumentKey) -> Result<&InstrumentSpec, SpecError> {
+        self.get(key).ok_or_else(|| SpecError::UnknownInstrument(key.to_string()))
+    }
+
+    /// `InstrumentSpec::check_order` по ключу
+    pub fn check_order(&self, key: &InstrumentKey, price: Price, qty: Qty) -> Result<Notional, SpecError> {
+        self.require(key)?.check_order(price, qty)
+    }
+
+    pub fn len(&self) -> usize {
+        self.specs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &InstrumentSpec> {
+        self.specs.values()
+    }
+
+    /// Содержимое в формате файла, по ключу (детерминированно)
+    pub fn to_spec_file(&self) -> SpecFile {
+        let mut instruments: Vec<InstrumentSpec> = self.specs.values().cloned().collect();
+        instruments.sort_by_key(|s| s.key().to_string());
+        SpecFile { instruments }
+    }
+}
+
+fn validate(spec: &InstrumentSpec) -> Result<(), RegistryError> {
+    let invalid = |reason: &str| RegistryError::Invalid { symbol: spec.symbol.clone(), reason: reason.to_string() };
+    if !spec.tick_size.is_positive() {
+        return Err(invalid("tick_size must be positive"));
+    }
+    if !spec.lot_step.is_positive() {
+        return Err(invalid("lot_step must be positive"));
+    }
+    if spec.min_qty.is_negative() || spec.min_notional.is_negative() {
+        return Err(invalid("minimums must not be negative"));
+    }
+    if !spec.contract_multiplier.is_positive() {
+        return Err(invalid("contract_multiplier must be positive"));
+    }
+    Ok(())
+}
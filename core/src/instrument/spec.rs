@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::InstrumentKey;
+use crate::event::Exchange;
+use crate::num::{Decimal, Notional, Price, Qty};
+
+/// Тип контракта
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractKind {
+    Spot,
+    Perp,
+    Future,
+}
+
+/// Справочные данные инструмента: шаги цены и лота, минимумы, активы
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstrumentSpec {
+    pub exchange: Exchange,
+    pub symbol: String,
+    pub base: String,
+    pub quote: String,
+    pub kind: ContractKind,
+    /// Шаг цены
+    pub tick_size: Price,
+    /// Шаг количества
+    pub lot_step: Qty,
+    #[serde(default)]
+    pub min_qty: Qty,
+    /// Минимум `price * qty * contract_multiplier` в котируемом активе
+    #[serde(default)]
+    pub min_notional: Notional,
+    /// Базовый актив на один контракт (1 для спота)
+    #[serde(default = "one")]
+    pub contract_multiplier: Decimal,
+}
+
+fn one() -> Decimal {
+    Decimal::from_int(1)
+}
+
+/// Почему цена/количество не подходят инструменту
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SpecError {
+    #[error("unknown instrument {0}")]
+    UnknownInstrument(String),
+
+    #[error("{symbol}: price {price} is not a positive multiple of tick {tick}")]
+    PriceOffTick { symbol: String, price: Price, tick: Price },
+
+    #[error("{symbol}: qty {qty} is not a positive multiple of lot {step}")]
+    QtyOffLot { symbol: String, qty: Qty, step: Qty },
+
+    #[error("{symbol}: qty {qty} below min {min}")]
+    QtyBelowMin { symbol: String, qty: Qty, min: Qty },
+
+    #[error("{symbol}: notional {notional} below min {min}")]
+    NotionalBelowMin { symbol: String, notional: Notional, min: Notional },
+
+    #[error("{symbol}: notional overflows")]
+    Overflow { symbol: String },
+}
+
+impl InstrumentSpec {
+    pub fn key(&self) -> InstrumentKey {
+        InstrumentKey::new(self.exchange.clone(), self.symbol.clone())
+    }
+
+    /// Стоимость `qty` по цене `price` в котируемом активе
+    pub fn notional(&self, price: Price, qty: Qty) -> Option<Notional> {
+        price.checked_mul(qty)?.0.checked_mul(self.contract_multiplier).map(Notional)
+    }
+
+    pub fn check_price(&self, price: Price) -> Result<(), SpecError> {
+        if !price.is_positive() || !price.0.is_multiple_of(self.tick_size.0) {
+            return Err(SpecError::PriceOffTick { symbol: self.symbol.clone(), price, tick: self.tick_size });
+        }
+        Ok(())
+    }
+
+    pub fn check_qty(&self, qty: Qty) -> Result<(), SpecError> {
+        if !qty.is_positive() || !qty.0.is_multiple_of(self.lot_step.0) {
+            return Err(SpecError::QtyOffLot { symbol: self.symbol.clone(), qty, step: self.lot_step });
+        }
+        if qty < self.min_qty {
+            return Err(SpecError::QtyBelowMin { symbol: self.symbol.clone(), qty, min: self.min_qty });
+        }
+        Ok(())
+    }
+
+    /// Проверка лимитного ордера: шаг цены, лот, минимумы.
+    /// Возвращает номинал ордера в котируемом активе
+    pub fn check_order(&self, price: Price, qty: Qty) -> Result<Notional, SpecError> {
+        self.check_price(price)?;
+        self.check_qty(qty)?;
+        let notional = self
+            .notional(price, qty)
+            .ok_or_else(|| SpecError::Overflow { symbol: self.symbol.clone() })?;
+        if notional < self.min_notional {
+            return Err(SpecError::NotionalBelowMin { symbol: self.symbol.clone(), notional, min: self.min_notional });
+        }
+        Ok(notional)
+    }
+
+    /// Цена на ближайшем шаге (half-even)
+    pub fn round_price(&self, price: Price) -> Option<Price> {
+        price.0.round_to_step(self.tick_size.0).map(Price)
+    }
+
+    /// Количество, урезанное вниз до лота
+    pub fn trunc_qty(&self, qty: Qty) -> Option<Qty> {
+        qty.0.trunc_to_step(self.lot_step.0).map(Qty)
+    }
+
+    /// Уровень стакана на сетке инструмента. Цена вне шага — ошибка
+    /// (битые данные не подгоняются под сетку); количество урезается вниз
+    /// до лота, но ненулевое никогда не становится нулём (удалением уровня)
+    pub fn normalize_level(&self, price: Price, qty: Qty) -> Result<(Price, Qty), SpecError> {
+        self.check_price(price)?;
+        if qty.is_zero() {
+            return Ok((price, qty));
+        }
+        let trunc = Some(qty).filter(|q| q.is_positive()).and_then(|q| self.trunc_qty(q)).filter(|q| !q.is_zero());
+        let qty = trunc.ok_or_else(|| SpecError::QtyOffLot { symbol: self.symbol.clone(), qty, step: self.lot_step })?;
+        Ok((price, qty))
+    }
+}
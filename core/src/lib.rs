@@ -1,4 +1,8 @@
 pub mod instrument;
 pub mod event;
+pub mod exec;
 pub mod time;
+pub mod clock;
 pub mod error;
+pub mod hash;
+pub mod num;
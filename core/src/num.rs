@@ -0,0 +1,457 @@
+//! Точные десятичные цены и количества.
+//!
+//! `Decimal` — целая мантисса и число знаков после точки, всегда в
+//! нормальной форме (без хвостовых нулей), поэтому `1.50 == 1.5` и хеш у
+//! них один. Арифметика только checked; деление округляет явно.
+//!
+//! Масштаб инструмента (число знаков шага цены / лота) задаётся при
+//! переходе к целым единицам: `to_units(scale)` / `from_units(units, scale)`.
+//!
+//! В JSON значения пишутся строкой (`"100.5"`), читаются и из строки, и из
+//! числа — старые логи с `f64` остаются читаемыми.
+
+use crate::hash::{StateHash, StateHasher};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Максимум знаков после точки
+pub const MAX_SCALE: u32 = 28;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DecimalError {
+    #[error("invalid decimal: {0:?}")]
+    Invalid(String),
+
+    #[error("decimal out of range: {0:?}")]
+    OutOfRange(String),
+}
+
+fn pow10(n: u32) -> Option<i128> {
+    10i128.checked_pow(n)
+}
+
+/// Десятичное число `mantissa * 10^-scale`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal { mantissa: 0, scale: 0 };
+
+    /// `mantissa * 10^-scale`; `None` при `scale > MAX_SCALE`
+    pub fn new(mut mantissa: i128, mut scale: u32) -> Option<Self> {
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        (scale <= MAX_SCALE).then_some(Self { mantissa, scale })
+    }
+
+    pub fn from_int(v: i64) -> Self {
+        Self { mantissa: v as i128, scale: 0 }
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// Знаков после точки в нормальной форме
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.mantissa > 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    /// Мантиссы обоих чисел при общем масштабе
+    fn aligned(self, other: Self) -> Option<(i128, i128, u32)> {
+        let scale = self.scale.max(other.scale);
+        let a = self.mantissa.checked_mul(pow10(scale - self.scale)?)?;
+        let b = other.mantissa.checked_mul(pow10(scale - other.scale)?)?;
+        Some((a, b, scale))
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.aligned(other)?;
+        Self::new(a.checked_add(b)?, scale)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.aligned(other)?;
+        Self::new(a.checked_sub(b)?, scale)
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        Self::new(self.mantissa.checked_mul(other.mantissa)?, self.scale + other.scale)
+    }
+
+    /// `self / other`, округлённое (half-even) до `scale` знаков
+    pub fn checked_div(self, other: Self, scale: u32) -> Option<Self> {
+        if other.mantissa == 0 || scale > MAX_SCALE {
+            return None;
+        }
+        // self / other * 10^scale = m1 * 10^(s2 + scale - s1) / m2
+        let e = (other.scale + scale) as i64 - self.scale as i64;
+        let (num, den) = match e >= 0 {
+            true => (self.mantissa.checked_mul(pow10(e as u32)?)?, other.mantissa),
+            false => (self.mantissa, other.mantissa.checked_mul(pow10((-e) as u32)?)?),
+        };
+        Self::new(div_half_even(num, den), scale)
+    }
+
+    /// Округление (half-even) до `scale` знаков
+    pub fn round_dp(self, scale: u32) -> Self {
+        if scale >= self.scale {
+            return self;
+        }
+        let den = pow10(self.scale - scale).expect("scale <= MAX_SCALE");
+        Self::new(div_half_even(self.mantissa, den), scale).expect("smaller scale")
+    }
+
+    /// Кратно ли значение шагу `step` (шаг цены, лот); нулевой шаг — любое
+    pub fn is_multiple_of(self, step: Self) -> bool {
+        if step.is_zero() {
+            return true;
+        }
+        match self.aligned(step) {
+            Some((a, b, _)) => a % b == 0,
+            None => false,
+        }
+    }
+
+    /// Ближайшее кратное `step` (half-even); `None` при переполнении
+    pub fn round_to_step(self, step: Self) -> Option<Self> {
+        self.snap_to_step(step, div_half_even)
+    }
+
+    /// Кратное `step`, ближайшее к нулю (лот вниз)
+    pub fn trunc_to_step(self, step: Self) -> Option<Self> {
+        self.snap_to_step(step, |num, den| num / den)
+    }
+
+    fn snap_to_step(self, step: Self, div: fn(i128, i128) -> i128) -> Option<Self> {
+        if step.is_zero() {
+            return Some(self);
+        }
+        let (a, b, scale) = self.aligned(step)?;
+        Self::new(div(a, b).checked_mul(b)?, scale)
+    }
+
+    /// Целое число единиц `10^-scale`; `None`, если значение не кратно
+    /// единице или не помещается
+    pub fn to_units(self, scale: u32) -> Option<i128> {
+        if self.scale > scale {
+            return None;
+        }
+        self.mantissa.checked_mul(pow10(scale - self.scale)?)
+    }
+
+    pub fn from_units(units: i128, scale: u32) -> Option<Self> {
+        Self::new(units, scale)
+    }
+
+    /// Ближайший `f64` (для статистики и экспорта, не для учёта)
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().expect("decimal text is a valid f64")
+    }
+
+    /// Кратчайшая десятичная запись `v`, как её печатает Rust; `None` для
+    /// NaN и бесконечностей
+    pub fn from_f64(v: f64) -> Option<Self> {
+        if !v.is_finite() {
+            return None;
+        }
+        format!("{}", v).parse().ok()
+    }
+}
+
+/// `num / den`, округлённое к ближайшему, при равенстве — к чётному
+fn div_half_even(num: i128, den: i128) -> i128 {
+    let q = num / den;
+    let r = num % den;
+    if r == 0 {
+        return q;
+    }
+    let away = if (num < 0) == (den < 0) { 1 } else { -1 };
+    let twice = r.unsigned_abs().saturating_mul(2);
+    match twice.cmp(&den.unsigned_abs()) {
+        Ordering::Less => q,
+        Ordering::Greater => q + away,
+        Ordering::Equal if q % 2 == 0 => q,
+        Ordering::Equal => q + away,
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.aligned(*other) {
+            Some((a, b, _)) => a.cmp(&b),
+            // выравнивание переполнилось: больше по модулю то число, у
+            // которого масштаб меньше, его знак и решает
+            None if self.scale < other.scale => self.mantissa.signum().cmp(&0),
+            None => 0.cmp(&other.mantissa.signum()),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = DecimalError;
+
+    /// `[+-]digits[.digits][e[+-]digits]`, без потери точности
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DecimalError::Invalid(s.to_string());
+        let out_of_range = || DecimalError::OutOfRange(s.to_string());
+
+        let (neg, body) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (num, exp) = match body.find(['e', 'E']) {
+            Some(i) => (&body[..i], body[i + 1..].parse::<i32>().map_err(|_| invalid())?),
+            None => (body, 0),
+        };
+        let (int, frac) = num.split_once('.').unwrap_or((num, ""));
+        if int.is_empty() && frac.is_empty() {
+            return Err(invalid());
+        }
+        if !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let frac = frac.trim_end_matches('0');
+        let mut mantissa: i128 = 0;
+        for b in int.bytes().chain(frac.bytes()) {
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add((b - b'0') as i128))
+                .ok_or_else(out_of_range)?;
+        }
+        if neg {
+            mantissa = -mantissa;
+        }
+
+        let scale = frac.len() as i64 - exp as i64;
+        if scale < 0 {
+            let mul = u32::try_from(-scale).ok().and_then(pow10).ok_or_else(out_of_range)?;
+            mantissa = mantissa.checked_mul(mul).ok_or_else(out_of_range)?;
+            return Ok(Self { mantissa, scale: 0 });
+        }
+        let scale = u32::try_from(scale).map_err(|_| out_of_range())?;
+        match mantissa {
+            0 => Ok(Self::ZERO),
+            _ => Self::new(mantissa, scale).ok_or_else(out_of_range),
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        if digits.len() > scale {
+            let (int, frac) = digits.split_at(digits.len() - scale);
+            write!(f, "{}{}.{}", sign, int, frac)
+        } else {
+            write!(f, "{}0.{}{}", sign, "0".repeat(scale - digits.len()), digits)
+        }
+    }
+}
+
+impl fmt::Debug for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl StateHash for Decimal {
+    fn hash_state(&self, h: &mut StateHasher) {
+        h.write_i128(self.mantissa);
+        h.write_u32(self.scale);
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct DecimalVisitor;
+
+        impl Visitor<'_> for DecimalVisitor {
+            type Value = Decimal;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a decimal string or number")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Decimal, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Decimal, E> {
+                Ok(Decimal::from_int(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Decimal, E> {
+                Ok(Decimal { mantissa: v as i128, scale: 0 })
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Decimal, E> {
+                Decimal::from_f64(v).ok_or_else(|| E::custom(format!("not a finite decimal: {}", v)))
+            }
+        }
+
+        d.deserialize_any(DecimalVisitor)
+    }
+}
+
+macro_rules! decimal_newtype {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(pub Decimal);
+
+        impl $name {
+            pub const ZERO: $name = $name(Decimal::ZERO);
+
+            pub fn new(mantissa: i128, scale: u32) -> Option<Self> {
+                Decimal::new(mantissa, scale).map(Self)
+            }
+
+            pub fn from_int(v: i64) -> Self {
+                Self(Decimal::from_int(v))
+            }
+
+            pub fn from_units(units: i128, scale: u32) -> Option<Self> {
+                Decimal::from_units(units, scale).map(Self)
+            }
+
+            pub fn to_units(self, scale: u32) -> Option<i128> {
+                self.0.to_units(scale)
+            }
+
+            pub fn from_f64(v: f64) -> Option<Self> {
+                Decimal::from_f64(v).map(Self)
+            }
+
+            pub fn to_f64(self) -> f64 {
+                self.0.to_f64()
+            }
+
+            pub fn is_zero(&self) -> bool {
+                self.0.is_zero()
+            }
+
+            pub fn is_positive(&self) -> bool {
+                self.0.is_positive()
+            }
+
+            pub fn is_negative(&self) -> bool {
+                self.0.is_negative()
+            }
+
+            pub fn checked_add(self, other: Self) -> Option<Self> {
+                self.0.checked_add(other.0).map(Self)
+            }
+
+            pub fn checked_sub(self, other: Self) -> Option<Self> {
+                self.0.checked_sub(other.0).map(Self)
+            }
+
+            pub fn round_dp(self, scale: u32) -> Self {
+                Self(self.0.round_dp(scale))
+            }
+        }
+
+        impl From<Decimal> for $name {
+            fn from(d: Decimal) -> Self {
+                Self(d)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = DecimalError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map(Self)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({})", stringify!($name), self.0)
+            }
+        }
+
+        impl StateHash for $name {
+            fn hash_state(&self, h: &mut StateHasher) {
+                self.0.hash_state(h);
+            }
+        }
+    };
+}
+
+decimal_newtype! {
+    /// Цена
+    Price
+}
+
+decimal_newtype! {
+    /// Количество (базовый актив или контракты)
+    Qty
+}
+
+decimal_newtype! {
+    /// Цена × количество
+    Notional
+}
+
+impl Price {
+    /// `self * qty`
+    pub fn checked_mul(self, qty: Qty) -> Option<Notional> {
+        self.0.checked_mul(qty.0).map(Notional)
+    }
+}
+
+impl Notional {
+    /// Средняя цена `self / qty`, округлённая (half-even) до `scale` знаков
+    pub fn checked_div(self, qty: Qty, scale: u32) -> Option<Price> {
+        self.0.checked_div(qty.0, scale).map(Price)
+    }
+}
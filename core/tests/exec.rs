@@ -0,0 +1,48 @@
+use el_core::event::Exchange;
+use el_core::exec::{ExecEvent, OrderId, OrderState};
+use el_core::hash::state_hash64;
+use el_core::instrument::InstrumentKey;
+
+#[test]
+fn exchange_names_round_trip() {
+    for (name, ex) in [("binance", Exchange::Binance), ("OKX", Exchange::Okx), ("Bybit", Exchange::Bybit)] {
+        assert_eq!(Exchange::from_name(name), ex);
+        assert_eq!(Exchange::from_name(ex.name()), ex);
+    }
+    assert_eq!(Exchange::from_name("deribit"), Exchange::Other("deribit".into()));
+
+    // serialized form is unchanged; lowercase names from old exec logs are accepted
+    assert_eq!(serde_json::to_string(&Exchange::Binance).unwrap(), "\"Binance\"");
+    assert_eq!(serde_json::to_string(&Exchange::Other("x".into())).unwrap(), "{\"Other\":\"x\"}");
+    for json in ["\"Binance\"", "\"binance\""] {
+        assert_eq!(serde_json::from_str::<Exchange>(json).unwrap(), Exchange::Binance);
+    }
+    assert_eq!(serde_json::from_str::<Exchange>("{\"Other\":\"x\"}").unwrap(), Exchange::Other("x".into()));
+}
+
+#[test]
+fn instrument_keys_parse_and_order() {
+    let k: InstrumentKey = "binance:BTCUSDT".parse().unwrap();
+    assert_eq!(k, InstrumentKey::new(Exchange::Binance, "BTCUSDT"));
+    assert_eq!(k.to_string(), "Binance:BTCUSDT");
+    assert_eq!(k.to_string().parse::<InstrumentKey>().unwrap(), k);
+    assert!("BTCUSDT".parse::<InstrumentKey>().is_err());
+    assert!(":BTCUSDT".parse::<InstrumentKey>().is_err());
+
+    let mut keys = [InstrumentKey::new("binance", "ETHUSDT"), InstrumentKey::new("binance", "BTCUSDT")];
+    keys.sort();
+    assert_eq!(keys[0].symbol.0, "BTCUSDT");
+}
+
+#[test]
+fn exec_model_hashes_and_serializes() {
+    let instrument = InstrumentKey::new(Exchange::Binance, "BTCUSDT");
+    let ev = ExecEvent::OrderExpired { instrument: instrument.clone(), id: OrderId(9) };
+    assert_eq!(ev.instrument(), &instrument);
+    let json = serde_json::to_string(&ev).unwrap();
+    assert_eq!(json, r#"{"OrderExpired":{"instrument":{"exchange":"Binance","symbol":"BTCUSDT"},"id":9}}"#);
+    assert_eq!(serde_json::from_str::<ExecEvent>(&json).unwrap(), ev);
+
+    assert_ne!(state_hash64("t", &OrderState::Filled), state_hash64("t", &OrderState::Cancelled));
+    assert_ne!(state_hash64("t", &instrument), state_hash64("t", &InstrumentKey::new(Exchange::Okx, "BTCUSDT")));
+}
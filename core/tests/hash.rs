@@ -0,0 +1,56 @@
+use el_core::hash::{state_digest, state_hash64, to_hex, StateHash, StateHasher, STATE_HASH_VERSION};
+use std::collections::BTreeMap;
+
+struct Level {
+    price: f64,
+    qty: f64,
+}
+
+impl StateHash for Level {
+    fn hash_state(&self, h: &mut StateHasher) {
+        h.write_f64(self.price);
+        h.write_f64(self.qty);
+    }
+}
+
+fn sample() -> BTreeMap<String, Vec<Level>> {
+    let mut m = BTreeMap::new();
+    m.insert("BTCUSDT".to_string(), vec![Level { price: 100.5, qty: 2.0 }, Level { price: 101.0, qty: 0.25 }]);
+    m.insert("ETHUSDT".to_string(), vec![]);
+    m
+}
+
+// computed once under v2; they must never change within a version
+const PINNED_U64: u64 = 3458609391433028195;
+const PINNED_SAMPLE: u64 = 18164406497682211953;
+const PINNED_STR_HEX: &str = "d2a9a4a9f6e8fb891c48abc2f68804f62e5c7234244aff7b38fe57c8b4bf2dcd";
+
+#[test]
+fn values_are_pinned() {
+    // any change here breaks every golden hash: bump STATE_HASH_VERSION instead
+    assert_eq!(STATE_HASH_VERSION, 2);
+    assert_eq!(state_hash64("test", &42u64), PINNED_U64);
+    assert_eq!(state_hash64("test", &sample()), PINNED_SAMPLE);
+    assert_eq!(to_hex(&state_digest("test", "abc")), PINNED_STR_HEX);
+}
+
+#[test]
+fn encoding_is_canonical_and_unambiguous() {
+    assert_eq!(state_hash64("test", &0.0f64), state_hash64("test", &-0.0f64));
+    assert_eq!(state_hash64("test", &f64::NAN), state_hash64("test", &-f64::NAN));
+    assert_eq!(state_hash64("test", &7usize), state_hash64("test", &7u64));
+
+    let a: Vec<Vec<u8>> = vec![vec![1], vec![]];
+    let b: Vec<Vec<u8>> = vec![vec![], vec![1]];
+    assert_ne!(state_hash64("test", &a), state_hash64("test", &b));
+    assert_ne!(state_hash64("test", &(String::from("ab"), String::from("c"))), state_hash64("test", &(String::from("a"), String::from("bc"))));
+    assert_ne!(state_hash64("test", &None::<u8>), state_hash64("test", &Some(0u8)));
+}
+
+#[test]
+fn domains_are_separate() {
+    assert_ne!(state_hash64("orderbook", &sample()), state_hash64("exec.order_store", &sample()));
+    let mut h = StateHasher::new("test");
+    42u64.hash_state(&mut h);
+    assert_eq!(h.finish64(), state_hash64("test", &42u64));
+}
@@ -0,0 +1,99 @@
+use el_core::event::Exchange;
+use el_core::instrument::{ContractKind, InstrumentKey, InstrumentRegistry, RegistryError, SpecError};
+use el_core::num::{Notional, Price, Qty};
+use std::path::PathBuf;
+
+const TOML: &str = r#"
+[[instruments]]
+exchange = "Binance"
+symbol = "BTCUSDT"
+base = "BTC"
+quote = "USDT"
+kind = "spot"
+tick_size = "0.01"
+lot_step = "0.00001"
+min_qty = "0.00001"
+min_notional = "5"
+
+[[instruments]]
+exchange = { Other = "deribit" }
+symbol = "BTC-PERP"
+base = "BTC"
+quote = "USD"
+kind = "perp"
+tick_size = "0.5"
+lot_step = "1"
+contract_multiplier = "10"
+"#;
+
+fn px(s: &str) -> Price {
+    s.parse().unwrap()
+}
+
+fn qty(s: &str) -> Qty {
+    s.parse().unwrap()
+}
+
+fn btc() -> InstrumentKey {
+    InstrumentKey::new(Exchange::Binance, "BTCUSDT")
+}
+
+#[test]
+fn checks_orders_against_the_spec() {
+    let reg = InstrumentRegistry::from_toml_str(TOML).unwrap();
+    assert_eq!(reg.len(), 2);
+    let spec = reg.get(&btc()).unwrap();
+    assert_eq!(spec.kind, ContractKind::Spot);
+
+    assert_eq!(reg.check_order(&btc(), px("65000.01"), qty("0.001")), Ok("65.00001".parse::<Notional>().unwrap()));
+    assert!(matches!(reg.check_order(&btc(), px("65000.005"), qty("0.001")), Err(SpecError::PriceOffTick { .. })));
+    assert!(matches!(reg.check_order(&btc(), px("0"), qty("0.001")), Err(SpecError::PriceOffTick { .. })));
+    assert!(matches!(reg.check_order(&btc(), px("65000"), qty("0.000015")), Err(SpecError::QtyOffLot { .. })));
+    let err = reg.check_order(&btc(), px("100"), qty("0.01")).unwrap_err();
+    assert_eq!(err.to_string(), "BTCUSDT: notional 1 below min 5");
+    let unknown = InstrumentKey::new(Exchange::Okx, "BTCUSDT");
+    assert!(matches!(reg.check_order(&unknown, px("1"), qty("1")), Err(SpecError::UnknownInstrument(_))));
+
+    // contracts: 3 x 10 BTC at 100.5
+    let perp = reg.require(&InstrumentKey::new(Exchange::Other("deribit".into()), "BTC-PERP")).unwrap();
+    assert_eq!(perp.notional(px("100.5"), qty("3")), Some("3015".parse::<Notional>().unwrap()));
+    assert!(matches!(perp.check_order(px("100.5"), qty("0.5")), Err(SpecError::QtyOffLot { .. })));
+}
+
+#[test]
+fn normalizes_onto_the_grid() {
+    let reg = InstrumentRegistry::from_toml_str(TOML).unwrap();
+    let spec = reg.get(&btc()).unwrap();
+    assert_eq!(spec.round_price(px("100.005")), Some(px("100")));
+    assert_eq!(spec.round_price(px("100.015")), Some(px("100.02")));
+    assert_eq!(spec.trunc_qty(qty("0.123456789")), Some(qty("0.12345")));
+    assert_eq!(spec.normalize_level(px("99.99"), qty("0.000016")), Ok((px("99.99"), qty("0.00001"))));
+    assert_eq!(spec.normalize_level(px("99.99"), qty("0")), Ok((px("99.99"), qty("0"))));
+    assert!(matches!(spec.normalize_level(px("99.999"), qty("1")), Err(SpecError::PriceOffTick { .. })));
+    assert!(matches!(spec.normalize_level(px("99.99"), qty("0.000009")), Err(SpecError::QtyOffLot { .. })));
+}
+
+#[test]
+fn json_and_toml_files_load_the_same() {
+    let from_toml = InstrumentRegistry::from_toml_str(TOML).unwrap();
+    let json = serde_json::to_string_pretty(&from_toml.to_spec_file()).unwrap();
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(dir.join("instruments.json"), &json).unwrap();
+    std::fs::write(dir.join("instruments.toml"), TOML).unwrap();
+
+    let a = InstrumentRegistry::load(dir.join("instruments.json")).unwrap();
+    let b = InstrumentRegistry::load(dir.join("instruments.toml")).unwrap();
+    assert_eq!(serde_json::to_value(a.to_spec_file()).unwrap(), serde_json::to_value(b.to_spec_file()).unwrap());
+    let spec = a.get(&btc()).unwrap();
+    assert_eq!(spec.contract_multiplier.to_string(), "1", "defaults to 1");
+    assert!(matches!(InstrumentRegistry::load(dir.join("missing.toml")), Err(RegistryError::Io { .. })));
+}
+
+#[test]
+fn rejects_bad_files() {
+    let twice = format!("{}\n{}", TOML, &TOML[..TOML.find("[[instruments]]\nexchange = {").unwrap()]);
+    assert!(matches!(InstrumentRegistry::from_toml_str(&twice), Err(RegistryError::Duplicate(_))));
+    let zero_tick = TOML.replace("tick_size = \"0.5\"", "tick_size = \"0\"");
+    assert!(matches!(InstrumentRegistry::from_toml_str(&zero_tick), Err(RegistryError::Invalid { .. })));
+    assert!(matches!(InstrumentRegistry::from_json_str("{\"instruments\": [{}]}"), Err(RegistryError::Parse(_))));
+}
@@ -0,0 +1,100 @@
+use el_core::num::{Decimal, DecimalError, Notional, Price, Qty, MAX_SCALE};
+use std::collections::BTreeMap;
+
+fn d(s: &str) -> Decimal {
+    s.parse().unwrap()
+}
+
+#[test]
+fn parses_exchange_strings_exactly() {
+    assert_eq!(d("0.01000000").to_string(), "0.01");
+    assert_eq!(d("0.01000000"), d("0.01"));
+    assert_eq!(d("65432.10").scale(), 1);
+    assert_eq!(d("-0.5").to_string(), "-0.5");
+    assert_eq!(d("+3").to_string(), "3");
+    assert_eq!(d("-0.000").to_string(), "0");
+    assert_eq!(d(".5").to_string(), "0.5");
+    assert_eq!(d("1.5e3").to_string(), "1500");
+    assert_eq!(d("15E-4").to_string(), "0.0015");
+    assert_eq!(d("0.1").checked_add(d("0.2")), Some(d("0.3")));
+
+    for bad in ["", "-", ".", "1.2.3", "abc", "1,5", " 1", "0x10", "1e"] {
+        assert!(matches!(bad.parse::<Decimal>(), Err(DecimalError::Invalid(_))), "{:?}", bad);
+    }
+    let tiny = format!("0.{}1", "0".repeat(MAX_SCALE as usize));
+    assert!(matches!(tiny.parse::<Decimal>(), Err(DecimalError::OutOfRange(_))));
+    assert!(matches!("1e40".parse::<Decimal>(), Err(DecimalError::OutOfRange(_))));
+}
+
+#[test]
+fn orders_by_value_across_scales() {
+    let mut book: BTreeMap<Price, Qty> = BTreeMap::new();
+    for (p, q) in [("100.50", "1"), ("100.5", "2"), ("99.999", "3"), ("101", "4")] {
+        book.insert(p.parse().unwrap(), q.parse().unwrap());
+    }
+    let prices: Vec<String> = book.keys().map(|p| p.to_string()).collect();
+    assert_eq!(prices, ["99.999", "100.5", "101"]);
+    assert_eq!(book[&"100.500".parse().unwrap()], Qty::from_int(2));
+
+    // alignment overflows; the magnitudes still decide
+    let huge = Decimal::new(i128::MAX / 10, 0).unwrap();
+    let fine = Decimal::new(1, MAX_SCALE).unwrap();
+    assert!(huge > fine && fine > huge.checked_sub(huge.checked_add(huge).unwrap()).unwrap());
+    assert!(Decimal::new(-(i128::MAX / 10), 0).unwrap() < fine);
+}
+
+#[test]
+fn arithmetic_is_checked_and_division_rounds_half_even() {
+    let px: Price = "100.25".parse().unwrap();
+    let qty: Qty = "0.004".parse().unwrap();
+    assert_eq!(px.checked_mul(qty), Some("0.401".parse::<Notional>().unwrap()));
+    assert_eq!(Decimal::new(i128::MAX, 0).unwrap().checked_add(d("1")), None);
+    assert_eq!(Decimal::new(i128::MAX, 0).unwrap().checked_mul(d("2")), None);
+
+    // 320 / 3 = 106.666...
+    let n: Notional = "320".parse().unwrap();
+    assert_eq!(n.checked_div(Qty::from_int(3), 4).unwrap().to_string(), "106.6667");
+    assert_eq!(n.checked_div(Qty::ZERO, 4), None);
+    assert_eq!(d("0.125").checked_div(d("1"), 2).unwrap(), d("0.12"));
+    assert_eq!(d("0.135").checked_div(d("1"), 2).unwrap(), d("0.14"));
+    assert_eq!(d("-0.125").round_dp(2), d("-0.12"));
+    assert_eq!(d("2.5").round_dp(0), d("2"));
+    assert_eq!(d("-2.51").round_dp(0), d("-3"));
+}
+
+#[test]
+fn units_at_instrument_scale() {
+    let px: Price = "65432.1".parse().unwrap();
+    assert_eq!(px.to_units(2), Some(6_543_210));
+    assert_eq!(px.to_units(0), None, "not a whole number of units");
+    assert_eq!(Price::from_units(6_543_210, 2), Some(px));
+    assert_eq!(Qty::from_units(15, 1).unwrap().to_string(), "1.5");
+}
+
+#[test]
+fn serde_writes_strings_and_reads_legacy_numbers() {
+    let px: Price = "100.10".parse().unwrap();
+    assert_eq!(serde_json::to_string(&px).unwrap(), "\"100.1\"");
+    assert_eq!(serde_json::from_str::<Price>("\"100.1\"").unwrap(), px);
+    assert_eq!(serde_json::from_str::<Price>("100.1").unwrap(), px);
+    assert_eq!(serde_json::from_str::<Qty>("3").unwrap(), Qty::from_int(3));
+    assert_eq!(serde_json::from_str::<Qty>("0.30000000000000004").unwrap().to_string(), "0.30000000000000004");
+    assert!(serde_json::from_str::<Qty>("\"x\"").is_err());
+
+    let levels: Vec<(Price, Qty)> = serde_json::from_str("[[100.5, 1.0], [\"99\", \"0.25\"]]").unwrap();
+    assert_eq!(format!("{:?}", levels), "[(Price(100.5), Qty(1)), (Price(99), Qty(0.25))]");
+    assert_eq!(px.to_f64(), 100.1);
+    assert_eq!(Price::from_f64(f64::NAN), None);
+}
+
+#[test]
+fn snaps_to_steps() {
+    let step = d("0.25");
+    assert!(d("1.75").is_multiple_of(step));
+    assert!(!d("1.8").is_multiple_of(step));
+    assert!(d("1.8").is_multiple_of(Decimal::ZERO));
+    assert_eq!(d("1.875").round_to_step(step), Some(d("2")));
+    assert_eq!(d("1.625").round_to_step(step), Some(d("1.5")), "ties to even multiple");
+    assert_eq!(d("1.99").trunc_to_step(step), Some(d("1.75")));
+    assert_eq!(d("-1.99").trunc_to_step(step), Some(d("-1.75")));
+}
@@ -0,0 +1,121 @@
+use el_core::num::Decimal;
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    /// substring of the field's text
+    Contains,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// `PATH OP VALUE` over a printed record (see `record::record_json`), e.g.
+/// `payload.symbol=BTCUSDT`, `payload.payload.Trade.qty>=1.5`, `kind!=event`.
+/// `PATH` is dot-separated; numeric parts index arrays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldFilter {
+    pub path: Vec<String>,
+    pub op: Op,
+    pub value: String,
+}
+
+// longest operators first, so `>=` is not read as `>`
+const OPS: [(&str, Op); 7] = [
+    ("!=", Op::Ne),
+    ("<=", Op::Le),
+    (">=", Op::Ge),
+    ("=", Op::Eq),
+    ("~", Op::Contains),
+    ("<", Op::Lt),
+    (">", Op::Gt),
+];
+
+impl FromStr for FieldFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (at, token, op) = OPS
+            .iter()
+            .filter_map(|(token, op)| s.find(token).map(|at| (at, *token, *op)))
+            .min_by_key(|(at, token, _)| (*at, std::cmp::Reverse(token.len())))
+            .ok_or_else(|| format!("{:?}: expected PATH=VALUE, PATH!=VALUE, PATH~TEXT or a comparison", s))?;
+        let path = &s[..at];
+        if path.is_empty() {
+            return Err(format!("{:?}: empty field path", s));
+        }
+        Ok(Self {
+            path: path.split('.').map(str::to_string).collect(),
+            op,
+            value: s[at + token.len()..].to_string(),
+        })
+    }
+}
+
+impl fmt::Display for FieldFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let token = OPS.iter().find(|(_, op)| *op == self.op).map(|(t, _)| *t).unwrap_or("?");
+        write!(f, "{}{}{}", self.path.join("."), token, self.value)
+    }
+}
+
+fn lookup<'a>(v: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(v, |v, part| match v {
+        Value::Object(m) => m.get(part),
+        Value::Array(a) => a.get(part.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+/// Text of a scalar as it would be typed in a filter.
+fn text(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+/// A number, or a decimal string such as a price.
+fn decimal(v: &Value) -> Option<Decimal> {
+    match v {
+        Value::Number(n) => n.to_string().parse().ok(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+impl FieldFilter {
+    /// A missing field only matches `!=`.
+    pub fn matches(&self, record: &Value) -> bool {
+        let Some(v) = lookup(record, &self.path) else {
+            return self.op == Op::Ne;
+        };
+        // exactly when both sides are decimals, so `price=100` matches 100.0
+        // and "100.00"; a number never orders against text
+        let wanted = self.value.parse::<Decimal>().ok();
+        let num = decimal(v).zip(wanted);
+        let eq = || match num {
+            Some((a, b)) => a == b,
+            None => text(v) == self.value,
+        };
+        let ord = || match num {
+            Some((a, b)) => Some(a.cmp(&b)),
+            None if wanted.is_some() => None,
+            None => Some(text(v).as_str().cmp(self.value.as_str())),
+        };
+        match self.op {
+            Op::Eq => eq(),
+            Op::Ne => !eq(),
+            Op::Contains => text(v).contains(&self.value),
+            Op::Lt => ord().is_some_and(|o| o.is_lt()),
+            Op::Le => ord().is_some_and(|o| o.is_le()),
+            Op::Gt => ord().is_some_and(|o| o.is_gt()),
+            Op::Ge => ord().is_some_and(|o| o.is_ge()),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod record;
+pub mod filter;
+pub mod stats;
+pub mod verify;
+pub mod repair;
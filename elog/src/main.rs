@@ -0,0 +1,332 @@
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand};
+use elog::filter::FieldFilter;
+use elog::record::{format_ts, record_json};
+use elog::repair::{RepairAction, RepairOptions};
+use elog::stats::{Counter, Stats};
+use elog::verify::VerifyOptions;
+use eventlog::chain;
+use eventlog::crypto::{FileKeys, KeyId};
+use eventlog::{EventEnvelope, LogSource};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::io::{self, BufWriter, StdoutLock, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Inspect, verify and repair eventlogs (a log file or a segment directory).
+#[derive(Parser)]
+#[command(name = "elog")]
+struct Cli {
+    #[command(subcommand)]
+    cmd: Cmd,
+}
+
+#[derive(Subcommand)]
+enum Cmd {
+    /// Print records as JSON, payloads decoded
+    Cat(Select),
+    /// Print the first records
+    Head {
+        #[command(flatten)]
+        sel: Select,
+        #[arg(short = 'n', long, default_value_t = 10)]
+        lines: usize,
+    },
+    /// Print the last records
+    Tail {
+        #[command(flatten)]
+        sel: Select,
+        #[arg(short = 'n', long, default_value_t = 10)]
+        lines: usize,
+    },
+    /// Print records matching every filter; exits 1 when none does
+    Grep {
+        #[command(flatten)]
+        sel: Select,
+        /// PATH=VALUE, PATH!=VALUE, PATH~TEXT, PATH<N, PATH<=N, PATH>N or PATH>=N
+        /// over the printed record, e.g. payload.symbol=BTCUSDT
+        filters: Vec<FieldFilter>,
+        /// text anywhere in the record
+        #[arg(long)]
+        text: Option<String>,
+        /// stop after this many matches
+        #[arg(short = 'm', long)]
+        max_count: Option<usize>,
+        /// print the number of matches only
+        #[arg(long)]
+        count: bool,
+    },
+    /// Counts, bytes, ts ranges and rates per kind and stream
+    Stats {
+        log: PathBuf,
+        /// only this stream (repeatable)
+        #[arg(long)]
+        stream: Vec<String>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check checksums, seq continuity (global and per stream) and the hash chain
+    Verify {
+        log: PathBuf,
+        /// accept records the hash chain does not cover (by default every
+        /// record must be chained)
+        #[arg(long)]
+        allow_unchained: bool,
+        /// only this stream (repeatable); the hash chain is then not checked,
+        /// so this needs --allow-unchained
+        #[arg(long)]
+        stream: Vec<String>,
+        /// also reject kinds and payload versions the core schemas do not know
+        #[arg(long)]
+        schemas: bool,
+        /// decrypt encrypted payloads with the keys (<id>.key, hex) in this directory
+        #[arg(long)]
+        keys: Option<PathBuf>,
+    },
+    /// Cut a torn tail in place, or copy a log damaged mid-file to --out
+    Repair {
+        log: PathBuf,
+        /// write the readable records to this new log
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// report what would be done
+        #[arg(long)]
+        dry_run: bool,
+        /// decrypt encrypted payloads with the keys (<id>.key, hex) in this directory
+        #[arg(long)]
+        keys: Option<PathBuf>,
+        /// key the salvaged copy of an encrypted log is sealed with
+        #[arg(long)]
+        key_id: Option<u32>,
+    },
+}
+
+/// Which records to print.
+#[derive(Args)]
+struct Select {
+    log: PathBuf,
+    /// only this stream (repeatable)
+    #[arg(long)]
+    stream: Vec<String>,
+    /// only this kind (repeatable)
+    #[arg(long)]
+    kind: Vec<String>,
+    /// start at this seq
+    #[arg(long)]
+    from_seq: Option<u64>,
+    /// stop after this seq
+    #[arg(long)]
+    to_seq: Option<u64>,
+    /// one record per line instead of pretty-printed
+    #[arg(short, long)]
+    compact: bool,
+    /// decrypt encrypted payloads with the keys (<id>.key, hex) in this directory
+    #[arg(long)]
+    keys: Option<PathBuf>,
+}
+
+impl Select {
+    /// Call `f` on every selected record until it returns false.
+    fn each(&self, mut f: impl FnMut(&EventEnvelope, &[u8]) -> Result<bool>) -> Result<()> {
+        let mut r = LogSource::open(&self.log)?;
+        if !self.stream.is_empty() {
+            r = r.with_streams(self.stream.iter().cloned());
+        }
+        if let Some(dir) = &self.keys {
+            r = r.with_keys(Arc::new(FileKeys::new(dir)));
+        }
+        if let Some(seq) = self.from_seq {
+            r.seek_seq(seq)?;
+        }
+        while let Some((env, payload)) = r.next()? {
+            if self.to_seq.is_some_and(|to| env.seq > to) {
+                break;
+            }
+            if !self.kind.is_empty() && !self.kind.contains(&env.kind) {
+                continue;
+            }
+            if !f(&env, &payload)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn printer(&self) -> Printer {
+        Printer { out: BufWriter::new(io::stdout().lock()), compact: self.compact }
+    }
+}
+
+struct Printer {
+    out: BufWriter<StdoutLock<'static>>,
+    compact: bool,
+}
+
+impl Printer {
+    fn print(&mut self, v: &Value) -> Result<()> {
+        match self.compact {
+            true => serde_json::to_writer(&mut self.out, v)?,
+            false => serde_json::to_writer_pretty(&mut self.out, v)?,
+        }
+        writeln!(self.out)?;
+        Ok(())
+    }
+}
+
+fn main() -> Result<()> {
+    match run(Cli::parse()) {
+        // `elog cat log | head`
+        Err(e) if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) => Ok(()),
+        r => r,
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match cli.cmd {
+        Cmd::Cat(sel) => {
+            let mut p = sel.printer();
+            sel.each(|env, payload| p.print(&record_json(env, payload)).map(|_| true))?;
+            p.out.flush()?;
+        }
+        Cmd::Head { sel, lines } => {
+            let mut p = sel.printer();
+            let mut left = lines;
+            if left > 0 {
+                sel.each(|env, payload| {
+                    p.print(&record_json(env, payload))?;
+                    left -= 1;
+                    Ok(left > 0)
+                })?;
+            }
+            p.out.flush()?;
+        }
+        Cmd::Tail { sel, lines } => {
+            let mut last = VecDeque::with_capacity(lines);
+            sel.each(|env, payload| {
+                if last.len() == lines {
+                    last.pop_front();
+                }
+                if lines > 0 {
+                    last.push_back(record_json(env, payload));
+                }
+                Ok(true)
+            })?;
+            let mut p = sel.printer();
+            for rec in &last {
+                p.print(rec)?;
+            }
+            p.out.flush()?;
+        }
+        Cmd::Grep { sel, filters, text, max_count, count } => {
+            let mut p = sel.printer();
+            let mut matched = 0usize;
+            sel.each(|env, payload| {
+                let rec = record_json(env, payload);
+                let hit = filters.iter().all(|f| f.matches(&rec))
+                    && text.as_ref().is_none_or(|t| rec.to_string().contains(t.as_str()));
+                if hit {
+                    matched += 1;
+                    if !count {
+                        p.print(&rec)?;
+                    }
+                }
+                Ok(max_count.is_none_or(|m| matched < m))
+            })?;
+            if count {
+                writeln!(p.out, "{}", matched)?;
+            }
+            p.out.flush()?;
+            if matched == 0 {
+                std::process::exit(1);
+            }
+        }
+        Cmd::Stats { log, stream, json } => {
+            // encrypted payloads count at their sealed size; no keys needed
+            let mut r = LogSource::open(&log)?.with_sealed_payloads();
+            if !stream.is_empty() {
+                r = r.with_streams(stream);
+            }
+            let mut stats = Stats::default();
+            while let Some((env, payload)) = r.next()? {
+                stats.observe(&env, &payload);
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else {
+                print_stats(&stats);
+            }
+        }
+        Cmd::Verify { log, allow_unchained, stream, schemas, keys } => {
+            let opts = VerifyOptions { allow_unchained, streams: stream, schemas, keys };
+            let report = elog::verify::verify(&log, &opts)?;
+            for (name, s) in &report.streams {
+                eprintln!("stream {:?}: records={} stream_seq={}..={}", name, s.records, s.first_seq, s.last_seq);
+            }
+            eprintln!(
+                "OK: records={} last_seq={:?} chained={} chained_from={:?} sealed={} head={}",
+                report.records,
+                report.last_seq,
+                report.chained,
+                report.chained_from,
+                report.sealed,
+                report.head.map(|d| chain::to_hex(&d)).unwrap_or_else(|| "-".to_string())
+            );
+        }
+        Cmd::Repair { log, out, dry_run, keys, key_id } => {
+            let opts = RepairOptions { out, dry_run, keys, key_id: key_id.map(KeyId) };
+            let report = elog::repair::repair(&log, &opts)?;
+            let s = &report.salvage;
+            for skip in &s.skipped {
+                eprintln!("DAMAGED: offset={} bytes={} seq={:?}: {}", skip.offset, skip.bytes, skip.seq, skip.error);
+            }
+            match &report.action {
+                RepairAction::None => eprintln!("CLEAN: records={}", s.records),
+                RepairAction::Truncated { from, to } => eprintln!(
+                    "TRUNCATED: {} -> {} bytes, records={} reindexed={}",
+                    from, to, s.records, report.reindexed
+                ),
+                RepairAction::WouldTruncate { from, to } => {
+                    eprintln!("WOULD TRUNCATE: {} -> {} bytes, records={}", from, to, s.records)
+                }
+                RepairAction::Salvaged { out } => eprintln!(
+                    "SALVAGED: records={} -> {:?} missing_seqs={} bytes_lost={}",
+                    s.records,
+                    out,
+                    s.missing_seqs,
+                    s.bytes_lost()
+                ),
+                RepairAction::WouldSalvage => eprintln!(
+                    "WOULD SALVAGE: records={} missing_seqs={} bytes_lost={}",
+                    s.records,
+                    s.missing_seqs,
+                    s.bytes_lost()
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn counter_line(c: &Counter) -> String {
+    let rate = c.rate_per_sec().map(|r| format!("{:.1}/s", r)).unwrap_or_else(|| "-".to_string());
+    format!("records={} bytes={} span={:.3}s rate={}", c.records, c.bytes, c.span_ns() as f64 / 1e9, rate)
+}
+
+fn print_stats(s: &Stats) {
+    let ts = |t: Option<u64>| t.map(format_ts).unwrap_or_else(|| "-".to_string());
+    println!("{}", counter_line(&s.total));
+    println!(
+        "seq={:?}..={:?} ts={}..{}",
+        s.first_seq,
+        s.last_seq,
+        ts(s.total.first_ts_ns),
+        ts(s.total.last_ts_ns)
+    );
+    for (name, c) in &s.kinds {
+        println!("kind {:?}: {}", name, counter_line(c));
+    }
+    for (name, c) in &s.streams {
+        println!("stream {:?}: {}", name, counter_line(c));
+    }
+}
@@ -0,0 +1,82 @@
+use base64::Engine;
+use el_core::event::Event;
+use eventlog::EventEnvelope;
+use exec::events::ExecEvent;
+use serde_json::{json, Value};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// What a payload decoded as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadType {
+    /// `el_core::event::Event`
+    CoreEvent,
+    /// `exec::events::ExecEvent`
+    ExecEvent,
+    /// any other JSON document
+    Json,
+    /// `snapshot_hash`: u64 LE
+    U64,
+    /// anything else, shown as base64
+    Bytes,
+}
+
+impl PayloadType {
+    pub fn name(self) -> &'static str {
+        match self {
+            PayloadType::CoreEvent => "core_event",
+            PayloadType::ExecEvent => "exec_event",
+            PayloadType::Json => "json",
+            PayloadType::U64 => "u64",
+            PayloadType::Bytes => "bytes",
+        }
+    }
+}
+
+pub fn decode_payload(kind: &str, payload: &[u8]) -> (PayloadType, Value) {
+    if kind == "snapshot_hash" {
+        if let Ok(b) = <[u8; 8]>::try_from(payload) {
+            return (PayloadType::U64, json!(u64::from_le_bytes(b)));
+        }
+    }
+    let Ok(doc) = serde_json::from_slice::<Value>(payload) else {
+        return (PayloadType::Bytes, json!(base64::engine::general_purpose::STANDARD.encode(payload)));
+    };
+    if kind == "event" {
+        if let Ok(ev) = serde_json::from_value::<Event>(doc.clone()) {
+            return (PayloadType::CoreEvent, serde_json::to_value(ev).unwrap_or(doc));
+        }
+        if let Ok(ev) = serde_json::from_value::<ExecEvent>(doc.clone()) {
+            return (PayloadType::ExecEvent, serde_json::to_value(ev).unwrap_or(doc));
+        }
+    }
+    (PayloadType::Json, doc)
+}
+
+/// RFC 3339 UTC time of a record `ts_ns`.
+pub fn format_ts(ts_ns: u64) -> String {
+    OffsetDateTime::from_unix_timestamp_nanos(ts_ns as i128)
+        .ok()
+        .and_then(|t| t.format(&Rfc3339).ok())
+        .unwrap_or_else(|| ts_ns.to_string())
+}
+
+/// A record as `elog` prints it: envelope fields, then the decoded payload.
+pub fn record_json(env: &EventEnvelope, payload: &[u8]) -> Value {
+    let (ty, decoded) = decode_payload(&env.kind, payload);
+    let mut rec = json!({
+        "seq": env.seq,
+        "stream": env.stream,
+        "stream_seq": env.seq_in_stream(),
+        "kind": env.kind,
+        "ts_ns": env.ts_ns,
+        "ts": format_ts(env.ts_ns),
+        "bytes": payload.len(),
+        "type": ty.name(),
+        "payload": decoded,
+    });
+    if let Some(origin) = env.origin_seq {
+        rec["origin_seq"] = json!(origin);
+    }
+    rec
+}
@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use eventlog::crypto::{FileKeys, KeyId};
+use eventlog::index::{self, DEFAULT_STRIDE};
+use eventlog::salvage::{SalvageReader, SalvageReport};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter};
+use fs2::FileExt;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default)]
+pub struct RepairOptions {
+    /// write what is recoverable to this new log (renumbered, old seq kept
+    /// as origin_seq); needed when records are damaged mid-log
+    pub out: Option<PathBuf>,
+    /// report only, change nothing
+    pub dry_run: bool,
+    /// key directory (see `FileKeys`) to decrypt sealed records with; the
+    /// salvaged copy of a sealed log needs it, sealed again with `key_id`
+    pub keys: Option<PathBuf>,
+    pub key_id: Option<KeyId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepairAction {
+    /// the log reads clean
+    None,
+    /// a torn last record was cut off in place
+    Truncated { from: u64, to: u64 },
+    /// damaged records were skipped while copying to a new log
+    Salvaged { out: PathBuf },
+    /// what would be done, with `dry_run`
+    WouldTruncate { from: u64, to: u64 },
+    WouldSalvage,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepairReport {
+    pub salvage: SalvageReport,
+    pub action: RepairAction,
+    pub reindexed: bool,
+}
+
+/// Fix a single log file.
+///
+/// A torn tail (the usual crash damage) is truncated in place, which is what
+/// a writer would do on reopen. Records damaged mid-log are never dropped in
+/// place: with `out` the readable records are copied to a new log, without it
+/// this fails.
+pub fn repair(path: impl AsRef<Path>, opts: &RepairOptions) -> Result<RepairReport> {
+    let path = path.as_ref();
+    if path.is_dir() {
+        anyhow::bail!("{:?} is a segment directory; repair its segment files one at a time", path);
+    }
+
+    // without keys a sealed record is checked for framing only
+    let mut s = SalvageReader::open(path)?.with_sealed_payloads();
+    if let Some(dir) = &opts.keys {
+        s = s.with_keys(Arc::new(FileKeys::new(dir)));
+    }
+    while s.next()?.is_some() {}
+    let mut report = RepairReport { salvage: s.finish(), action: RepairAction::None, reindexed: false };
+    if report.salvage.is_clean() {
+        return Ok(report);
+    }
+
+    if !report.salvage.skipped.is_empty() {
+        let Some(out) = &opts.out else {
+            anyhow::bail!(
+                "{} damaged records mid-log ({} bytes); pass --out <log> to write a salvaged copy",
+                report.salvage.skipped.len(),
+                report.salvage.bytes_lost()
+            );
+        };
+        if opts.dry_run {
+            report.action = RepairAction::WouldSalvage;
+        } else {
+            salvage_to(path, out, opts)?;
+            report.action = RepairAction::Salvaged { out: out.clone() };
+        }
+        return Ok(report);
+    }
+
+    // only the tail is torn
+    let to = report.salvage.truncated_tail.expect("not clean");
+    let from = std::fs::metadata(path)?.len();
+    if opts.dry_run {
+        report.action = RepairAction::WouldTruncate { from, to };
+        return Ok(report);
+    }
+
+    let file = OpenOptions::new().write(true).open(path).with_context(|| format!("open {:?}", path))?;
+    file.try_lock_exclusive().with_context(|| format!("{:?} is open by a writer", path))?;
+    file.set_len(to).context("set_len")?;
+    file.sync_all()?;
+    drop(file);
+    report.action = RepairAction::Truncated { from, to };
+
+    // the index may point at the torn record
+    if index::index_path(path).exists() {
+        index::rebuild(path, DEFAULT_STRIDE)?;
+        report.reindexed = true;
+    }
+    Ok(report)
+}
+
+fn salvage_to(path: &Path, out: &Path, opts: &RepairOptions) -> Result<()> {
+    if std::fs::metadata(out).is_ok_and(|m| m.len() > 0) {
+        anyhow::bail!("output {:?} already exists", out);
+    }
+    let format = EventLogReader::open(path)?.format();
+    let mut w = EventLogWriter::open_with(out, "el:salvage", WriterOptions { format, ..Default::default() })?;
+    let mut s = SalvageReader::open(path)?;
+    // sealed records are refused by the copy without keys
+    if let Some(dir) = &opts.keys {
+        let keys = FileKeys::new(dir);
+        w = w.with_encryption(&keys, opts.key_id.context("--keys needs --key-id to seal the salvaged copy")?)?;
+        s = s.with_keys(Arc::new(keys));
+    }
+    while let Some((env, payload)) = s.next()? {
+        w.append_copy(&env, &payload)?;
+    }
+    Ok(w.flush()?)
+}
@@ -0,0 +1,53 @@
+use eventlog::EventEnvelope;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Records, payload bytes and ts span of one kind, one stream or the whole log.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Counter {
+    pub records: u64,
+    pub bytes: u64,
+    pub first_ts_ns: Option<u64>,
+    pub last_ts_ns: Option<u64>,
+}
+
+impl Counter {
+    fn observe(&mut self, ts_ns: u64, bytes: usize) {
+        self.records += 1;
+        self.bytes += bytes as u64;
+        self.first_ts_ns = Some(self.first_ts_ns.map_or(ts_ns, |t| t.min(ts_ns)));
+        self.last_ts_ns = Some(self.last_ts_ns.map_or(ts_ns, |t| t.max(ts_ns)));
+    }
+
+    pub fn span_ns(&self) -> u64 {
+        match (self.first_ts_ns, self.last_ts_ns) {
+            (Some(a), Some(b)) => b - a,
+            _ => 0,
+        }
+    }
+
+    /// Records per second over the ts span; `None` for a single instant.
+    pub fn rate_per_sec(&self) -> Option<f64> {
+        let span = self.span_ns();
+        (span > 0).then(|| self.records as f64 * 1e9 / span as f64)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Stats {
+    pub total: Counter,
+    pub first_seq: Option<u64>,
+    pub last_seq: Option<u64>,
+    pub kinds: BTreeMap<String, Counter>,
+    pub streams: BTreeMap<String, Counter>,
+}
+
+impl Stats {
+    pub fn observe(&mut self, env: &EventEnvelope, payload: &[u8]) {
+        self.total.observe(env.ts_ns, payload.len());
+        self.first_seq.get_or_insert(env.seq);
+        self.last_seq = Some(env.seq);
+        self.kinds.entry(env.kind.clone()).or_default().observe(env.ts_ns, payload.len());
+        self.streams.entry(env.stream.clone()).or_default().observe(env.ts_ns, payload.len());
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use eventlog::chain::{ChainVerifier, Digest};
+use eventlog::compact::Anchor;
+use eventlog::crypto::FileKeys;
+use eventlog::streams::{StreamSeqAudit, StreamSpan};
+use eventlog::{LogSource, SchemaRegistry};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default)]
+pub struct VerifyOptions {
+    /// accept records the hash chain does not cover (a log written without
+    /// chaining, sealed payloads without keys); by default they fail
+    pub allow_unchained: bool,
+    /// only these streams (empty: all); global seqs and the chain are then
+    /// not checked, so this needs `allow_unchained`
+    pub streams: Vec<String>,
+    /// reject kinds and versions `SchemaRegistry::core` does not know
+    pub schemas: bool,
+    /// key directory (see `FileKeys`); without it encrypted payloads stay
+    /// sealed and the chain is checked only up to the first of them
+    pub keys: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    pub records: u64,
+    pub last_seq: Option<u64>,
+    pub chained: u64,
+    pub chained_from: Option<u64>,
+    /// encrypted records read without their key
+    pub sealed: u64,
+    pub head: Option<Digest>,
+    pub streams: Vec<(String, StreamSpan)>,
+}
+
+/// Read the whole log, checking checksums (the reader does), global and
+/// per-stream seq continuity and the hash chain from the log's anchor.
+/// Every record must be chained unless `opts.allow_unchained`: stripping
+/// the digests must not turn a tampered log into an unchained one that
+/// passes. Stops at the first problem. Encrypted payloads are decrypted (and so
+/// authenticated) with `opts.keys`; without keys only their framing and seqs
+/// are checked.
+pub fn verify(path: impl AsRef<Path>, opts: &VerifyOptions) -> Result<VerifyReport> {
+    let path = path.as_ref();
+    let filtered = !opts.streams.is_empty();
+    if filtered && !opts.allow_unchained {
+        anyhow::bail!("--stream skips the hash chain; pass --allow-unchained");
+    }
+
+    let mut r = LogSource::open(path)?;
+    if filtered {
+        r = r.with_streams(opts.streams.iter().cloned());
+    }
+    if opts.schemas {
+        r = r.with_schemas(SchemaRegistry::core());
+    }
+    r = match &opts.keys {
+        Some(dir) => r.with_keys(Arc::new(FileKeys::new(dir))),
+        None => r.with_sealed_payloads(),
+    };
+    // a compacted log continues the chain of the records it dropped
+    let anchor = Anchor::load(path)?;
+    let mut chain = ChainVerifier::new();
+    let mut per_stream = StreamSeqAudit::new();
+    let mut n = 0u64;
+    let mut last_seq: Option<u64> = None;
+    let mut sealed = 0u64;
+
+    while let Some((env, payload)) = r.next()? {
+        if n == 0 {
+            if let Some(prev) = anchor.as_ref().and_then(|a| a.chain_before(env.seq)) {
+                chain = ChainVerifier::anchored(prev);
+            }
+        }
+        if let Some(prev) = last_seq.filter(|_| !filtered) {
+            if env.seq != prev + 1 {
+                anyhow::bail!("seq gap: prev={} current={} (record={})", prev, env.seq, n + 1);
+            }
+        }
+        if let Err(gap) = per_stream.check(&env) {
+            anyhow::bail!("{} (record={})", gap, n + 1);
+        }
+        // the chain covers plaintext, which a sealed record does not give
+        if env.key_id.is_some() && opts.keys.is_none() {
+            sealed += 1;
+        }
+        if !filtered && sealed == 0 {
+            if let Err(b) = chain.check(&env, &payload) {
+                anyhow::bail!("BROKEN: {} (record={}, last good seq={:?})", b, n + 1, last_seq);
+            }
+        }
+        last_seq = Some(env.seq);
+        n += 1;
+    }
+
+    if !opts.allow_unchained && sealed > 0 {
+        anyhow::bail!(
+            "the chain covers encrypted payloads ({} records); pass --keys to check it, or --allow-unchained",
+            sealed
+        );
+    }
+    if !opts.allow_unchained && (chain.chained() != n || n == 0) {
+        anyhow::bail!(
+            "chain does not cover the log (pass --allow-unchained for a log written without it): records={} chained={} chained_from={:?}",
+            n,
+            chain.chained(),
+            chain.first_chained_seq()
+        );
+    }
+
+    Ok(VerifyReport {
+        records: n,
+        last_seq,
+        chained: chain.chained(),
+        chained_from: chain.first_chained_seq(),
+        sealed,
+        head: chain.head(),
+        streams: per_stream.streams().map(|(name, s)| (name.to_string(), *s)).collect(),
+    })
+}
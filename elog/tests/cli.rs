@@ -0,0 +1,251 @@
+use anyhow::Result;
+use el_core::event::{Event, EventId, EventPayload, EventType, Exchange, SCHEMA_VERSION};
+use el_core::instrument::InstrumentKey;
+use el_core::num::{Price, Qty};
+use el_core::time::{TimeSource, Timestamp};
+use elog::filter::FieldFilter;
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogWriter, LogFormat};
+use exec::events::{ExecEvent, OrderId};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+fn trade(symbol: &str, qty: f64) -> Event {
+    Event {
+        id: EventId::nil(),
+        event_type: EventType::Trade,
+        exchange: Exchange::Binance,
+        symbol: symbol.to_string(),
+        instrument: InstrumentKey::new(Exchange::Binance, symbol),
+        ts_exchange: None,
+        ts_recv: Timestamp::new(1, TimeSource::Receive),
+        ts_proc: Timestamp::new(1, TimeSource::Process),
+        seq: None,
+        schema_version: SCHEMA_VERSION,
+        integrity_flags: vec![],
+        payload: EventPayload::Trade {
+            price: Price::from_int(100),
+            qty: Qty::from_f64(qty).unwrap(),
+            is_maker: false,
+            trade_id: None,
+            aggressor: None,
+        },
+        meta: Default::default(),
+    }
+}
+
+/// seq 1-2 trades on "md", 3 an exec event and 4 a snapshot hash on "exec",
+/// 5 opaque bytes; ts 1s apart.
+fn write(path: &Path) -> Result<()> {
+    let opts = WriterOptions { format: LogFormat::Binary, hash_chain: true, index_stride: Some(2), ..Default::default() };
+    let mut w = EventLogWriter::open_with(path, "md", opts)?;
+    let s = 1_000_000_000;
+    w.append_stream("md", "event", s, &serde_json::to_vec(&trade("BTCUSDT", 0.5))?)?;
+    w.append_stream("md", "event", 2 * s, &serde_json::to_vec(&trade("ETHUSDT", 2.0))?)?;
+    let exec = ExecEvent::OrderCreated { instrument: InstrumentKey::new(Exchange::Binance, "BTCUSDT"), id: OrderId(7), params: None };
+    w.append_stream("exec", "event", 3 * s, &serde_json::to_vec(&exec)?)?;
+    w.append_stream("exec", "snapshot_hash", 4 * s, &42u64.to_le_bytes())?;
+    w.append_stream("exec", "blob", 5 * s, &[0xff, 0x00])?;
+    Ok(w.flush()?)
+}
+
+fn elog(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_elog")).args(args).output().expect("run elog")
+}
+
+fn lines(out: &Output) -> Vec<Value> {
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    String::from_utf8_lossy(&out.stdout).lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+}
+
+#[test]
+fn cat_head_tail_decode_payloads() -> Result<()> {
+    let path = tmp_log("elog_cat.bin");
+    write(&path)?;
+    let log = path.to_str().unwrap();
+
+    let all = lines(&elog(&["cat", log, "-c"]));
+    let types: Vec<_> = all.iter().map(|r| r["type"].as_str().unwrap()).collect();
+    assert_eq!(types, vec!["core_event", "core_event", "exec_event", "u64", "bytes"]);
+    assert_eq!(all[0]["payload"]["payload"]["Trade"]["qty"], json!("0.5"));
+    assert_eq!(all[2]["payload"]["OrderCreated"]["id"], json!(7));
+    assert_eq!(all[3]["payload"], json!(42));
+    assert_eq!(all[4]["payload"], json!("/wA="));
+    assert_eq!((all[2]["stream"].clone(), all[2]["stream_seq"].clone()), (json!("exec"), json!(1)));
+    assert_eq!(all[0]["ts"], json!("1970-01-01T00:00:01Z"));
+
+    // pretty by default
+    let pretty = elog(&["cat", log, "--kind", "snapshot_hash"]);
+    let text = String::from_utf8(pretty.stdout)?;
+    assert!(text.contains("\n  \"seq\": 4,"), "{}", text);
+
+    let seqs = |out: Vec<Value>| out.iter().map(|r| r["seq"].as_u64().unwrap()).collect::<Vec<_>>();
+    assert_eq!(seqs(lines(&elog(&["head", log, "-n", "2", "-c"]))), vec![1, 2]);
+    assert_eq!(seqs(lines(&elog(&["tail", log, "-n", "2", "-c"]))), vec![4, 5]);
+    assert_eq!(seqs(lines(&elog(&["tail", log, "-n", "1", "-c", "--stream", "md"]))), vec![2]);
+    assert_eq!(seqs(lines(&elog(&["cat", log, "-c", "--from-seq", "2", "--to-seq", "3"]))), vec![2, 3]);
+    Ok(())
+}
+
+#[test]
+fn grep_filters_on_decoded_fields() -> Result<()> {
+    let path = tmp_log("elog_grep.bin");
+    write(&path)?;
+    let log = path.to_str().unwrap();
+
+    let hits = lines(&elog(&["grep", log, "-c", "payload.symbol=ETHUSDT"]));
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["seq"], json!(2));
+    let hits = lines(&elog(&["grep", log, "-c", "payload.payload.Trade.qty>=0.5", "payload.payload.Trade.qty<2"]));
+    assert_eq!(hits.iter().map(|r| r["seq"].clone()).collect::<Vec<_>>(), vec![json!(1)]);
+    let hits = lines(&elog(&["grep", log, "-c", "type!=core_event", "--text", "OrderCreated"]));
+    assert_eq!(hits.len(), 1);
+
+    let count = elog(&["grep", log, "--count", "stream=exec"]);
+    assert_eq!(String::from_utf8(count.stdout)?.trim(), "3");
+    assert_eq!(elog(&["grep", log, "kind=nothing"]).status.code(), Some(1));
+    assert!(!elog(&["grep", log, "no operator"]).status.success());
+
+    let f: FieldFilter = "a.b>=3".parse().map_err(anyhow::Error::msg)?;
+    assert_eq!(f.to_string(), "a.b>=3");
+    assert!(f.matches(&json!({ "a": { "b": 3.0 } })));
+    assert!(!f.matches(&json!({ "a": { "b": "x" } })));
+    let f: FieldFilter = "a.0=100".parse().map_err(anyhow::Error::msg)?;
+    assert!(f.matches(&json!({ "a": [100.0] })));
+    Ok(())
+}
+
+#[test]
+fn stats_counts_kinds_streams_and_rates() -> Result<()> {
+    let path = tmp_log("elog_stats.bin");
+    write(&path)?;
+    let out = elog(&["stats", path.to_str().unwrap(), "--json"]);
+    assert!(out.status.success());
+    let stats: Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(stats["total"]["records"], json!(5));
+    assert_eq!((stats["first_seq"].clone(), stats["last_seq"].clone()), (json!(1), json!(5)));
+    assert_eq!(stats["kinds"]["event"]["records"], json!(3));
+    assert_eq!(stats["streams"]["exec"]["first_ts_ns"], json!(3_000_000_000u64));
+    assert_eq!(stats["streams"]["md"]["records"], json!(2));
+    assert_eq!(stats["kinds"]["snapshot_hash"]["bytes"], json!(8));
+
+    let text = String::from_utf8(elog(&["stats", path.to_str().unwrap()]).stdout)?;
+    assert!(text.starts_with("records=5 bytes="), "{}", text);
+    assert!(text.contains("span=4.000s rate=1.2/s"), "{}", text);
+    assert!(text.contains("stream \"md\": records=2"), "{}", text);
+    Ok(())
+}
+
+#[test]
+fn verify_and_repair() -> Result<()> {
+    let path = tmp_log("elog_verify.bin");
+    write(&path)?;
+    let log = path.to_str().unwrap();
+    let ok = elog(&["verify", log]);
+    assert!(ok.status.success(), "{}", String::from_utf8_lossy(&ok.stderr));
+    assert!(String::from_utf8_lossy(&ok.stderr).contains("OK: records=5 last_seq=Some(5) chained=5"));
+    assert!(!elog(&["verify", log, "--schemas"]).status.success(), "kind \"blob\" is unknown");
+
+    // a torn tail is cut in place and the index rebuilt
+    let full = std::fs::read(&path)?;
+    std::fs::write(&path, &full[..full.len() - 1])?;
+    assert!(!elog(&["verify", log]).status.success());
+    let dry = elog(&["repair", log, "--dry-run"]);
+    assert!(String::from_utf8_lossy(&dry.stderr).contains("WOULD TRUNCATE"));
+    assert_eq!(std::fs::metadata(&path)?.len() as usize, full.len() - 1);
+    let fixed = elog(&["repair", log]);
+    assert!(String::from_utf8_lossy(&fixed.stderr).contains("records=4 reindexed=true"), "{}", String::from_utf8_lossy(&fixed.stderr));
+    assert!(elog(&["verify", log]).status.success());
+    assert_eq!(lines(&elog(&["tail", log, "-n", "1", "-c"]))[0]["seq"], json!(4));
+    assert!(String::from_utf8_lossy(&elog(&["repair", log]).stderr).contains("CLEAN: records=4"));
+
+    // damage mid-log is only ever copied out
+    let mut bytes = std::fs::read(&path)?;
+    let at = bytes.windows(7).position(|w| w == b"ETHUSDT").unwrap();
+    bytes[at] = b'X';
+    std::fs::write(&path, &bytes)?;
+    let refused = elog(&["repair", log]);
+    assert!(!refused.status.success());
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("pass --out"));
+    let out = tmp_log("elog_verify_salvaged.bin");
+    let salvaged = elog(&["repair", log, "--out", out.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&salvaged.stderr).contains("SALVAGED: records=3"), "{}", String::from_utf8_lossy(&salvaged.stderr));
+    let copied = lines(&elog(&["cat", out.to_str().unwrap(), "-c"]));
+    assert_eq!(copied.iter().map(|r| r["origin_seq"].clone()).collect::<Vec<_>>(), vec![json!(1), json!(3), json!(4)]);
+    Ok(())
+}
+
+#[test]
+fn verify_requires_the_chain_unless_allowed() -> Result<()> {
+    // the same records with every digest stripped
+    let path = tmp_log("elog_unchained.bin");
+    let opts = WriterOptions { format: LogFormat::Binary, hash_chain: false, ..Default::default() };
+    let mut w = EventLogWriter::open_with(&path, "md", opts)?;
+    w.append_stream("md", "event", 1, &serde_json::to_vec(&trade("BTCUSDT", 0.5))?)?;
+    w.append_stream("md", "event", 2, &serde_json::to_vec(&trade("ETHUSDT", 2.0))?)?;
+    w.flush()?;
+    drop(w);
+    let log = path.to_str().unwrap();
+
+    let refused = elog(&["verify", log]);
+    assert!(!refused.status.success());
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("chain does not cover the log"));
+    let allowed = elog(&["verify", log, "--allow-unchained"]);
+    assert!(String::from_utf8_lossy(&allowed.stderr).contains("OK: records=2 last_seq=Some(2) chained=0"));
+
+    assert!(!elog(&["verify", log, "--stream", "md"]).status.success());
+    assert!(elog(&["verify", log, "--stream", "md", "--allow-unchained"]).status.success());
+    Ok(())
+}
+
+#[test]
+fn verify_encrypted_log_with_and_without_keys() -> Result<()> {
+    let keys = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("elog_keys");
+    let _ = std::fs::remove_dir_all(&keys);
+    std::fs::create_dir_all(&keys)?;
+    eventlog::crypto::generate_key_file(&keys, eventlog::crypto::KeyId(4))?;
+    let path = tmp_log("elog_encrypted.bin");
+    let opts = WriterOptions { format: LogFormat::Binary, hash_chain: true, ..Default::default() };
+    let mut w = EventLogWriter::open_with(&path, "md", opts)?
+        .with_encryption(&eventlog::crypto::FileKeys::new(&keys), eventlog::crypto::KeyId(4))?;
+    w.append_stream("md", "event", 1, &serde_json::to_vec(&trade("BTCUSDT", 0.5))?)?;
+    w.append_stream("md", "event", 2, &serde_json::to_vec(&trade("ETHUSDT", 2.0))?)?;
+    w.flush()?;
+    drop(w);
+    let (log, keys) = (path.to_str().unwrap(), keys.to_str().unwrap());
+
+    let sealed = elog(&["verify", log, "--allow-unchained"]);
+    assert!(String::from_utf8_lossy(&sealed.stderr).contains("OK: records=2 last_seq=Some(2) chained=0 chained_from=None sealed=2"));
+    let refused = elog(&["verify", log]);
+    assert!(!refused.status.success());
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("pass --keys"));
+    let full = elog(&["verify", log, "--keys", keys]);
+    assert!(String::from_utf8_lossy(&full.stderr).contains("chained=2 chained_from=Some(1) sealed=0"), "{}", String::from_utf8_lossy(&full.stderr));
+
+    assert!(!elog(&["cat", log]).status.success());
+    let recs = lines(&elog(&["cat", log, "-c", "--keys", keys]));
+    assert_eq!(recs[1]["payload"]["symbol"], json!("ETHUSDT"));
+
+    // repair finds framing damage without keys, and only copies sealed records with them
+    assert!(String::from_utf8_lossy(&elog(&["repair", log]).stderr).contains("CLEAN: records=2"));
+    let mut bytes = std::fs::read(&path)?;
+    let last = bytes.len() - 3;
+    bytes[last] ^= 1;
+    std::fs::write(&path, &bytes)?;
+    assert!(String::from_utf8_lossy(&elog(&["repair", log]).stderr).contains("CLEAN: records=2"));
+    let out = tmp_log("elog_encrypted_salvaged.bin");
+    let out_s = out.to_str().unwrap();
+    let salvaged = elog(&["repair", log, "--out", out_s, "--keys", keys, "--key-id", "4"]);
+    assert!(String::from_utf8_lossy(&salvaged.stderr).contains("SALVAGED: records=1"), "{}", String::from_utf8_lossy(&salvaged.stderr));
+    assert!(!std::fs::read(&out)?.windows(7).any(|w| w == b"BTCUSDT"), "plaintext in the salvaged copy");
+    let recs = lines(&elog(&["cat", out_s, "-c", "--keys", keys]));
+    assert_eq!(recs[0]["payload"]["symbol"], json!("BTCUSDT"));
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use eventlog::compact::{self, Retention};
+use eventlog::crypto::{FileKeys, SharedKeys};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const NS_PER_DAY: f64 = 86_400e9;
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let usage = "usage: compact <log|dir> (--days N | --keep-snapshots K | --before SEQ) [--archive DIR] [--keys DIR]";
+
+    let mut path = None::<String>;
+    let mut retention = None::<Retention>;
+    let mut before = None::<u64>;
+    let mut archive = None::<PathBuf>;
+    let mut keys = None::<SharedKeys>;
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--days" => {
+                let days: f64 = args.next().context("--days needs N")?.parse().context("--days N")?;
+                // `as u64` would turn these into 0 days and drop everything
+                if !days.is_finite() || days < 0.0 {
+                    anyhow::bail!("--days must be a non-negative number, got {}", days);
+                }
+                retention = Some(Retention::MaxAge { ns: (days * NS_PER_DAY) as u64 });
+            }
+            "--keep-snapshots" => {
+                let keep = args.next().context("--keep-snapshots needs K")?.parse().context("--keep-snapshots K")?;
+                retention = Some(Retention::Snapshots { keep });
+            }
+            // cut at an exact seq, without looking for snapshots
+            "--before" => before = Some(args.next().context("--before needs SEQ")?.parse().context("--before SEQ")?),
+            // move dropped records here instead of deleting them
+            "--archive" => archive = Some(args.next().context("--archive needs DIR")?.into()),
+            // open sealed payloads to find snapshots in them; without keys they are skipped
+            "--keys" => keys = Some(Arc::new(FileKeys::new(args.next().context("--keys needs DIR")?))),
+            _ => path = Some(a),
+        }
+    }
+    let path = path.context(usage)?;
+
+    let report = match (retention, before) {
+        (Some(r), None) => compact::compact(&path, &r, archive.as_deref(), keys)?,
+        (None, Some(seq)) => compact::compact_before(&path, seq, archive.as_deref())?,
+        _ => anyhow::bail!(usage),
+    };
+    for p in &report.archived {
+        eprintln!("ARCHIVED: {:?}", p);
+    }
+    eprintln!(
+        "COMPACTED: {} first_seq={:?} dropped_records={} dropped_bytes={}",
+        path, report.first_seq, report.dropped_records, report.dropped_bytes
+    );
+    Ok(())
+}
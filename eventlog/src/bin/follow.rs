@@ -0,0 +1,41 @@
+use anyhow::Result;
+use eventlog::crypto::FileKeys;
+use eventlog::LogFollower;
+use std::sync::Arc;
+
+fn main() -> Result<()> {
+    let mut path = None::<String>;
+    let mut from_seq = None::<u64>;
+    let mut keys = None::<FileKeys>;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            // decrypt with the keys in DIR; without it sealed payloads print at their sealed size
+            "--keys" => keys = args.next().map(FileKeys::new),
+            _ if path.is_none() => path = Some(a),
+            _ => from_seq = Some(a.parse::<u64>().expect("from seq must be u64")),
+        }
+    }
+    let path = path.unwrap_or_else(|| "/tmp/binance_depth.ndjson".to_string());
+
+    let mut f = match from_seq {
+        Some(seq) => LogFollower::from_seq(&path, seq),
+        None => LogFollower::open(&path),
+    };
+    f = match keys {
+        Some(keys) => f.with_keys(Arc::new(keys)),
+        None => f.with_sealed_payloads(),
+    };
+    loop {
+        let (env, payload) = f.next_blocking()?;
+        println!(
+            "seq={} ts_ns={} kind={} stream={} payload_bytes={}",
+            env.seq,
+            env.ts_ns,
+            env.kind,
+            env.stream,
+            payload.len()
+        );
+    }
+}
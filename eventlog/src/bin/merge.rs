@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use eventlog::crypto::{CopyKeys, FileKeys, KeyId};
+use eventlog::index::DEFAULT_STRIDE;
+use eventlog::merge::merge;
+use eventlog::writer::WriterOptions;
+use eventlog::LogFormat;
+use std::sync::Arc;
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+
+    let mut out = None::<String>;
+    let mut inputs: Vec<String> = Vec::new();
+    let mut opts = WriterOptions { index_stride: Some(DEFAULT_STRIDE), ..Default::default() };
+    let mut keys = None::<String>;
+    let mut key_id = None::<u32>;
+
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--out" => out = args.next(),
+            "--binary" => opts.format = LogFormat::Binary,
+            "--chain" => opts.hash_chain = true,
+            // decrypt sealed inputs with the keys in DIR and seal the output with key N
+            "--keys" => keys = args.next(),
+            "--key-id" => key_id = Some(args.next().context("--key-id needs N")?.parse().context("--key-id N")?),
+            _ => inputs.push(a),
+        }
+    }
+    let out = out.context("usage: merge --out <log> [--binary] [--chain] [--keys DIR --key-id N] <log or segment dir>...")?;
+    if inputs.is_empty() {
+        anyhow::bail!("no input logs given");
+    }
+
+    let keys = match keys {
+        Some(dir) => Some(CopyKeys::new(Arc::new(FileKeys::new(dir)), KeyId(key_id.context("--keys needs --key-id")?))),
+        None => None,
+    };
+
+    let report = merge(&inputs, &out, opts, keys.as_ref())?;
+    for input in &report.inputs {
+        eprintln!(
+            "INPUT: {:?} records={} out_of_order={}",
+            input.path, input.records, input.out_of_order
+        );
+    }
+    let r = report.range;
+    eprintln!(
+        "MERGED: {} records={} seq={}..={} ts_ns={}..={}",
+        out, r.records, r.first_seq, r.last_seq, r.first_ts_ns, r.last_ts_ns
+    );
+    Ok(())
+}
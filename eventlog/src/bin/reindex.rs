@@ -0,0 +1,22 @@
+use anyhow::{Context, Result};
+use eventlog::index::{self, DEFAULT_STRIDE};
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().context("usage: reindex <log> [stride]")?;
+    let stride = match args.next() {
+        Some(s) => s.parse::<u64>().context("stride must be u64")?,
+        None => DEFAULT_STRIDE,
+    };
+
+    let idx = index::rebuild(&path, stride)?;
+    eprintln!(
+        "INDEXED: {} points={} kinds={} streams={} -> {:?}",
+        path,
+        idx.points.len(),
+        idx.kinds.len(),
+        idx.streams.len(),
+        index::index_path(&path)
+    );
+    Ok(())
+}
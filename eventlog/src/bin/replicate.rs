@@ -0,0 +1,43 @@
+use anyhow::{Context, Result};
+use eventlog::replicate::{Endpoint, Primary, Replica, ReplicaOptions};
+use std::time::Duration;
+
+/// Wait before reconnecting to a primary that went away.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let usage = "usage: replicate serve <log> <host:port|unix:path> | replicate follow <log> <host:port|unix:path>";
+    let mode = args.next().context(usage)?;
+    let log = args.next().context(usage)?;
+    let endpoint: Endpoint = args.next().context(usage)?.parse()?;
+
+    match mode.as_str() {
+        "serve" => {
+            let listener = endpoint.listen()?;
+            eprintln!("SERVING: {} on {:?}", log, listener.endpoint()?);
+            let primary = Primary::new(&log);
+            loop {
+                let conn = listener.accept()?;
+                // one replica at a time; a dropped one may come back
+                if let Err(e) = primary.serve(conn) {
+                    eprintln!("REPLICA LOST: {:#}", e);
+                }
+                eprintln!("ACKED: seq={}", primary.acked_seq());
+            }
+        }
+        "follow" => {
+            let mut replica = Replica::open(&log, ReplicaOptions::default())?;
+            loop {
+                eprintln!("CONNECTING: {:?} from seq={}", endpoint, replica.next_seq()?);
+                let res = endpoint.connect().and_then(|conn| replica.run(conn));
+                if let Err(e) = res {
+                    eprintln!("PRIMARY LOST: {:#}", e);
+                }
+                eprintln!("DURABLE: seq={}", replica.durable_seq());
+                std::thread::sleep(RECONNECT_DELAY);
+            }
+        }
+        _ => anyhow::bail!(usage),
+    }
+}
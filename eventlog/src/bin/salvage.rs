@@ -0,0 +1,72 @@
+use anyhow::{Context, Result};
+use eventlog::crypto::{FileKeys, KeyId};
+use eventlog::salvage::SalvageReader;
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter};
+use std::sync::Arc;
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+
+    let mut out = None::<String>;
+    let mut input = None::<String>;
+    let mut keys = None::<FileKeys>;
+    let mut key_id = None::<u32>;
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            // copy what is recovered into a new log (renumbered, old seq kept as origin_seq)
+            "--out" => out = args.next(),
+            // decrypt sealed records with the keys in DIR; a copy is sealed again with key N
+            "--keys" => keys = args.next().map(FileKeys::new),
+            "--key-id" => key_id = Some(args.next().context("--key-id needs N")?.parse().context("--key-id N")?),
+            _ => input = Some(a),
+        }
+    }
+    let input = input.context("usage: salvage <log> [--out <log>] [--keys DIR [--key-id N]]")?;
+
+    let mut w = match &out {
+        Some(path) => {
+            if std::fs::metadata(path).is_ok_and(|m| m.len() > 0) {
+                anyhow::bail!("output {:?} already exists", path);
+            }
+            let format = EventLogReader::open(&input)?.format();
+            let w = EventLogWriter::open_with(path, "el:salvage", WriterOptions { format, ..Default::default() })?;
+            Some(match &keys {
+                Some(keys) => w.with_encryption(keys, KeyId(key_id.context("--out with --keys needs --key-id")?))?,
+                None => w,
+            })
+        }
+        None => None,
+    };
+
+    // without keys sealed records are still scanned, but not copied
+    let mut s = SalvageReader::open(&input)?.with_sealed_payloads();
+    if let Some(keys) = keys {
+        s = s.with_keys(Arc::new(keys));
+    }
+    while let Some((env, payload)) = s.next()? {
+        if let Some(w) = &mut w {
+            w.append_copy(&env, &payload)?;
+        }
+    }
+    if let Some(w) = &mut w {
+        w.flush()?;
+    }
+
+    let report = s.finish();
+    for skip in &report.skipped {
+        eprintln!("SKIPPED: offset={} bytes={} seq={:?}: {}", skip.offset, skip.bytes, skip.seq, skip.error);
+    }
+    if let Some(at) = report.truncated_tail {
+        eprintln!("TRUNCATED: offset={} bytes={}", at, report.tail_bytes);
+    }
+    eprintln!(
+        "{}: records={} skipped={} missing_seqs={} bytes_lost={}",
+        if report.is_clean() { "CLEAN" } else { "SALVAGED" },
+        report.records,
+        report.skipped.len(),
+        report.missing_seqs,
+        report.bytes_lost()
+    );
+    Ok(())
+}
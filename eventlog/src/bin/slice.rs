@@ -0,0 +1,61 @@
+use anyhow::{Context, Result};
+use eventlog::crypto::{CopyKeys, FileKeys, KeyId};
+use eventlog::index::DEFAULT_STRIDE;
+use eventlog::slice::{slice, SliceFilter};
+use eventlog::writer::WriterOptions;
+use eventlog::LogFormat;
+use std::sync::Arc;
+
+const USAGE: &str = "usage: slice --out <log> [--stream S]... [--kind K]... [--instrument EXCHANGE:SYMBOL]... \
+[--seq FROM..TO] [--ts FROM..TO] [--binary] [--chain] [--keys DIR --key-id N] <log or segment dir>";
+
+/// `FROM..TO`; either side may be left out.
+fn parse_range(s: &str, flag: &str) -> Result<(u64, u64)> {
+    let (from, to) = s.split_once("..").with_context(|| format!("{} must be FROM..TO", flag))?;
+    let from = if from.is_empty() { 0 } else { from.parse().with_context(|| format!("{} FROM must be u64", flag))? };
+    let to = if to.is_empty() { u64::MAX } else { to.parse().with_context(|| format!("{} TO must be u64", flag))? };
+    Ok((from, to))
+}
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+
+    let mut out = None::<String>;
+    let mut input = None::<String>;
+    let mut filter = SliceFilter::default();
+    let mut opts = WriterOptions { index_stride: Some(DEFAULT_STRIDE), ..Default::default() };
+    let mut keys = None::<String>;
+    let mut key_id = None::<u32>;
+
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--out" => out = args.next(),
+            "--stream" => filter.streams.push(args.next().context(USAGE)?),
+            "--kind" => filter.kinds.push(args.next().context(USAGE)?),
+            "--instrument" => filter.instruments.push(args.next().context(USAGE)?.parse().map_err(anyhow::Error::msg)?),
+            "--seq" => filter.seq_range = Some(parse_range(&args.next().context(USAGE)?, "--seq")?),
+            "--ts" => filter.ts_range_ns = Some(parse_range(&args.next().context(USAGE)?, "--ts")?),
+            "--binary" => opts.format = LogFormat::Binary,
+            "--chain" => opts.hash_chain = true,
+            // decrypt a sealed input with the keys in DIR and seal the output with key N
+            "--keys" => keys = Some(args.next().context(USAGE)?),
+            "--key-id" => key_id = Some(args.next().context(USAGE)?.parse().context("--key-id N")?),
+            _ => input = Some(a),
+        }
+    }
+    let out = out.context(USAGE)?;
+    let input = input.context(USAGE)?;
+
+    let keys = match keys {
+        Some(dir) => Some(CopyKeys::new(Arc::new(FileKeys::new(dir)), KeyId(key_id.context("--keys needs --key-id")?))),
+        None => None,
+    };
+
+    let report = slice(&input, &out, &filter, opts, keys.as_ref())?;
+    let r = report.range;
+    eprintln!(
+        "SLICED: {} -> {} records={} scanned={} undecoded={} ts_ns={}..={}",
+        input, out, r.records, report.scanned, report.undecoded, r.first_ts_ns, r.last_ts_ns
+    );
+    Ok(())
+}
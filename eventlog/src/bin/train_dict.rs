@@ -0,0 +1,44 @@
+use anyhow::{Context, Result};
+use eventlog::compress::{install_dictionary, train_dictionary};
+use eventlog::reader::EventLogReader;
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+
+    let mut out_dir = None::<String>;
+    let mut logs: Vec<String> = Vec::new();
+    let mut max_kb: usize = 112;
+    let mut max_samples: usize = 100_000;
+
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--out" => out_dir = args.next(),
+            "--max-kb" => max_kb = args.next().and_then(|x| x.parse().ok()).context("--max-kb must be usize")?,
+            "--samples" => {
+                max_samples = args.next().and_then(|x| x.parse().ok()).context("--samples must be usize")?
+            }
+            _ => logs.push(a),
+        }
+    }
+    let out_dir = out_dir.context("usage: train_dict --out <log dir> <log>... [--max-kb N] [--samples N]")?;
+    if logs.is_empty() {
+        anyhow::bail!("no sample logs given");
+    }
+
+    let mut samples: Vec<Vec<u8>> = Vec::new();
+    'logs: for path in &logs {
+        let mut r = EventLogReader::open(path)?;
+        while let Some((_, payload)) = r.next()? {
+            samples.push(payload);
+            if samples.len() >= max_samples {
+                break 'logs;
+            }
+        }
+    }
+
+    let dict = train_dictionary(&samples, max_kb * 1024)?;
+    let id = install_dictionary(&out_dir, &dict)?;
+    eprintln!("TRAINED: samples={} dict_bytes={} id={}", samples.len(), dict.len(), id);
+    println!("{}", id);
+    Ok(())
+}
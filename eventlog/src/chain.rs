@@ -0,0 +1,152 @@
+use crate::envelope::EventEnvelope;
+
+/// blake3 digest linking a record to everything before it.
+pub type Digest = [u8; 32];
+
+/// `prev` of the first chained record of a log.
+pub const GENESIS: Digest = [0u8; 32];
+
+/// Fields of one record covered by its chain digest.
+#[derive(Debug, Clone, Copy)]
+pub struct Linked<'a> {
+    pub seq: u64,
+    pub stream_seq: u64,
+    pub origin_seq: Option<u64>,
+    pub ts_ns: u64,
+    pub stream: &'a str,
+    pub kind: &'a str,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Linked<'a> {
+    pub fn of(env: &'a EventEnvelope, payload: &'a [u8]) -> Self {
+        Self {
+            seq: env.seq,
+            stream_seq: env.seq_in_stream(),
+            origin_seq: env.origin_seq,
+            ts_ns: env.ts_ns,
+            stream: &env.stream,
+            kind: &env.kind,
+            payload,
+        }
+    }
+}
+
+/// Digest of one record: covers the previous digest and every field of the
+/// record, so the value is the same whether the log is JSON lines or binary.
+///
+/// A stream seq that differs from `seq` and an origin seq are hashed under
+/// their own domain tag; plain single-stream records keep the v1 digest.
+pub fn link(prev: &Digest, rec: &Linked) -> Digest {
+    let mut h = blake3::Hasher::new();
+    if rec.stream_seq == rec.seq && rec.origin_seq.is_none() {
+        h.update(b"elog-chain:v1|");
+        h.update(prev);
+        h.update(&rec.seq.to_le_bytes());
+    } else {
+        h.update(b"elog-chain:v2|");
+        h.update(prev);
+        h.update(&rec.seq.to_le_bytes());
+        h.update(&rec.stream_seq.to_le_bytes());
+        match rec.origin_seq {
+            Some(o) => {
+                h.update(&[1]);
+                h.update(&o.to_le_bytes());
+            }
+            None => {
+                h.update(&[0]);
+            }
+        }
+    }
+    h.update(&rec.ts_ns.to_le_bytes());
+    for field in [rec.stream.as_bytes(), rec.kind.as_bytes(), rec.payload] {
+        h.update(&(field.len() as u64).to_le_bytes());
+        h.update(field);
+    }
+    *h.finalize().as_bytes()
+}
+
+pub fn to_hex(d: &Digest) -> String {
+    blake3::Hash::from(*d).to_hex().to_string()
+}
+
+pub fn from_hex(s: &str) -> Option<Digest> {
+    blake3::Hash::from_hex(s).ok().map(|h| *h.as_bytes())
+}
+
+/// First record where the chain does not hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainBreak {
+    /// Digest does not follow from the previous record (edited, removed or reordered records).
+    Mismatch { seq: u64 },
+    /// Record without a digest after the chain started.
+    Missing { seq: u64 },
+    /// Digest field is not 32 bytes of hex.
+    Malformed { seq: u64 },
+}
+
+impl std::fmt::Display for ChainBreak {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainBreak::Mismatch { seq } => write!(f, "chain mismatch at seq={}", seq),
+            ChainBreak::Missing { seq } => write!(f, "chain digest missing at seq={}", seq),
+            ChainBreak::Malformed { seq } => write!(f, "malformed chain digest at seq={}", seq),
+        }
+    }
+}
+
+/// Checks records in log order against their chain digests.
+///
+/// Records before the first chained one (a log that enabled chaining later)
+/// are counted but not covered.
+#[derive(Debug, Clone, Default)]
+pub struct ChainVerifier {
+    head: Option<Digest>,
+    first_chained_seq: Option<u64>,
+    chained: u64,
+}
+
+impl ChainVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify a log that continues a chain ending in `prev`
+    /// (e.g. a segment following another one).
+    pub fn anchored(prev: Digest) -> Self {
+        Self { head: Some(prev), ..Self::default() }
+    }
+
+    pub fn check(&mut self, env: &EventEnvelope, payload: &[u8]) -> Result<(), ChainBreak> {
+        let Some(hex) = &env.chain else {
+            return match self.head {
+                Some(_) => Err(ChainBreak::Missing { seq: env.seq }),
+                None => Ok(()),
+            };
+        };
+        let got = from_hex(hex).ok_or(ChainBreak::Malformed { seq: env.seq })?;
+        let prev = self.head.unwrap_or(GENESIS);
+        if link(&prev, &Linked::of(env, payload)) != got {
+            return Err(ChainBreak::Mismatch { seq: env.seq });
+        }
+        self.head = Some(got);
+        self.first_chained_seq.get_or_insert(env.seq);
+        self.chained += 1;
+        Ok(())
+    }
+
+    /// Digest of the last verified record. Publishing it out of band also
+    /// makes truncation of the log detectable.
+    pub fn head(&self) -> Option<Digest> {
+        self.head
+    }
+
+    pub fn first_chained_seq(&self) -> Option<u64> {
+        self.first_chained_seq
+    }
+
+    /// Number of records verified.
+    pub fn chained(&self) -> u64 {
+        self.chained
+    }
+}
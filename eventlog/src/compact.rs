@@ -0,0 +1,390 @@
+use anyhow::{Context, Result};
+use el_core::event::EventType;
+use el_core::instrument::InstrumentKey;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::chain::{self, Digest};
+use crate::compress;
+use crate::crypto::SharedKeys;
+use crate::error::EventLogError;
+use crate::frame::{self, FrameDict, LogFormat};
+use crate::index::{self, SparseIndex};
+use crate::reader::EventLogReader;
+use crate::segment::{self, Manifest};
+use crate::source::LogSource;
+use crate::writer::Continuation;
+
+/// How much of a log to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retention {
+    /// Records within `ns` of the newest record's ts.
+    MaxAge { ns: u64 },
+    /// Everything from the `keep`-th most recent `BookSnapshot` of every
+    /// instrument (from its oldest one if it has fewer).
+    Snapshots { keep: usize },
+}
+
+/// What precedes the first record of a compacted log: the last dropped seq,
+/// its chain digest and the stream seqs at that point. Lets the chain be
+/// verified and the stream seqs continue without the dropped records.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub seq: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stream_seqs: BTreeMap<String, u64>,
+}
+
+impl Anchor {
+    /// Digest to anchor a `ChainVerifier` to, if the log continues right
+    /// after this anchor at `first_seq`.
+    pub fn chain_before(&self, first_seq: u64) -> Option<Digest> {
+        if first_seq != self.seq + 1 {
+            return None;
+        }
+        self.chain.as_deref().and_then(chain::from_hex)
+    }
+
+    pub(crate) fn continuation(&self) -> Continuation {
+        Continuation {
+            seq: self.seq,
+            chain: self.chain.as_deref().and_then(chain::from_hex),
+            stream_seqs: self.stream_seqs.clone(),
+        }
+    }
+
+    /// Sidecar of a single-file log: `<log>.anchor`.
+    pub fn path(log: impl AsRef<Path>) -> PathBuf {
+        let mut s = log.as_ref().as_os_str().to_owned();
+        s.push(".anchor");
+        PathBuf::from(s)
+    }
+
+    /// Anchor of a log file or segment directory, if it was ever compacted.
+    pub fn load(log: impl AsRef<Path>) -> Result<Option<Self>, EventLogError> {
+        let log = log.as_ref();
+        if log.is_dir() {
+            return Ok(Manifest::load(log)?.anchor);
+        }
+        let path = Self::path(log);
+        match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map(Some).map_err(|e| EventLogError::layout(&path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(EventLogError::file(path)(e)),
+        }
+    }
+
+    pub(crate) fn store(&self, log: &Path) -> Result<()> {
+        let path = Self::path(log);
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        {
+            let mut f = File::create(&tmp).with_context(|| format!("create {:?}", tmp))?;
+            f.write_all(&serde_json::to_vec_pretty(self)?)?;
+            f.sync_all()?;
+        }
+        std::fs::rename(&tmp, &path).with_context(|| format!("rename {:?}", tmp))?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactReport {
+    /// first seq still in the log (`None` for an empty log)
+    pub first_seq: Option<u64>,
+    pub dropped_records: u64,
+    pub dropped_bytes: u64,
+    /// where the dropped records went, when archiving
+    pub archived: Vec<PathBuf>,
+}
+
+/// The part of an `Event` payload retention needs.
+#[derive(Deserialize)]
+struct SnapshotHead {
+    event_type: EventType,
+    instrument: InstrumentKey,
+}
+
+/// First seq to keep under `retention`, or `None` to keep everything.
+///
+/// The cut is moved back as far as needed for every instrument to keep a
+/// `BookSnapshot` before its later records, so the rest stays replayable.
+/// Sealed payloads are opened with `keys`; one that cannot be opened is
+/// taken not to be a snapshot.
+pub fn retention_cut(log: impl AsRef<Path>, retention: &Retention, keys: Option<SharedKeys>) -> Result<Option<u64>> {
+    let log = log.as_ref();
+    if *retention == (Retention::Snapshots { keep: 0 }) {
+        anyhow::bail!("retention must keep at least one snapshot");
+    }
+
+    let mut r = open_envelopes(log, keys)?;
+    let mut first_seq = None;
+    let mut newest_ts = 0u64;
+    let mut snapshots: HashMap<InstrumentKey, Vec<u64>> = HashMap::new();
+    while let Some((env, payload)) = r.next()? {
+        first_seq.get_or_insert(env.seq);
+        newest_ts = newest_ts.max(env.ts_ns);
+        if let Ok(head) = serde_json::from_slice::<SnapshotHead>(&payload) {
+            if head.event_type == EventType::BookSnapshot {
+                snapshots.entry(head.instrument).or_default().push(env.seq);
+            }
+        }
+    }
+    let Some(first_seq) = first_seq else {
+        return Ok(None);
+    };
+
+    let cut = match *retention {
+        Retention::MaxAge { ns } => {
+            let mut r = open_envelopes(log, None)?;
+            r.seek_ts(newest_ts.saturating_sub(ns))?;
+            let Some((env, _)) = r.next()? else {
+                return Ok(None);
+            };
+            // each instrument restarts from its last snapshot before the cut
+            snapshots
+                .values()
+                .filter_map(|seqs| seqs.iter().rev().find(|s| **s <= env.seq).copied())
+                .fold(env.seq, u64::min)
+        }
+        Retention::Snapshots { keep } => {
+            let Some(cut) = snapshots.values().map(|seqs| seqs[seqs.len().saturating_sub(keep)]).min() else {
+                return Ok(None);
+            };
+            cut
+        }
+    };
+    Ok((cut > first_seq).then_some(cut))
+}
+
+/// Drop the records of `log` (a file or segment directory) that `retention`
+/// no longer needs; see `retention_cut` and `compact_before`.
+pub fn compact(
+    log: impl AsRef<Path>,
+    retention: &Retention,
+    archive: Option<&Path>,
+    keys: Option<SharedKeys>,
+) -> Result<CompactReport> {
+    let log = log.as_ref();
+    match retention_cut(log, retention, keys)? {
+        Some(cut) => compact_before(log, cut, archive),
+        None => Ok(CompactReport { first_seq: first_seq(log)?, ..Default::default() }),
+    }
+}
+
+/// Drop the records before seq `cut` from the front of `log`, moving them
+/// to `archive` (a directory) if given. The records kept are not rewritten:
+/// they keep their seqs, stream seqs and chain digests, and an `Anchor`
+/// records what came before them.
+///
+/// A segment directory only loses whole sealed segments, so it may keep
+/// some records before `cut`. The log must not be open for writing; use
+/// `SegmentedWriter::compact` on a live segment directory.
+pub fn compact_before(log: impl AsRef<Path>, cut: u64, archive: Option<&Path>) -> Result<CompactReport> {
+    let log = log.as_ref();
+    if log.is_dir() {
+        let lock = segment::lock_dir(log)?;
+        lock.try_lock_exclusive().with_context(|| format!("{:?} is open for writing", log))?;
+        let mut manifest = Manifest::load(log)?;
+        return drop_segments(log, &mut manifest, cut, archive);
+    }
+    compact_file(log, cut, archive)
+}
+
+fn first_seq(log: &Path) -> Result<Option<u64>> {
+    Ok(open_envelopes(log, None)?.next()?.map(|(env, _)| env.seq))
+}
+
+/// Reader for seqs, ts and whatever payloads `keys` can open; the others
+/// stay sealed instead of failing the read.
+fn open_envelopes(log: &Path, keys: Option<SharedKeys>) -> Result<LogSource> {
+    let r = LogSource::open(log)?.with_sealed_payloads();
+    Ok(match keys {
+        Some(keys) => r.with_keys(keys),
+        None => r,
+    })
+}
+
+/// Remove the sealed segments that end before `cut` and store the manifest
+/// with their anchor. The active (last) segment is always kept.
+pub(crate) fn drop_segments(dir: &Path, manifest: &mut Manifest, cut: u64, archive: Option<&Path>) -> Result<CompactReport> {
+    let files = segment::segment_files(dir)?;
+    let firsts: Vec<u64> = files.iter().filter_map(|p| segment::segment_first_seq(p)).collect();
+
+    // a segment ends right before the next one starts
+    let dropped = firsts.windows(2).take_while(|w| w[1] <= cut).count();
+    let mut report = CompactReport { first_seq: first_seq(dir)?, ..Default::default() };
+    if dropped == 0 {
+        return Ok(report);
+    }
+    let keep_from = firsts[dropped];
+
+    let mut anchor = manifest.anchor.clone().unwrap_or_default();
+    for p in &files[..dropped] {
+        let name = segment::file_name(p);
+        let meta = match manifest.segments.iter().find(|s| s.file == name) {
+            Some(m) if !m.stream_seqs.is_empty() || m.records == 0 => m.clone(),
+            // scanned after a crash, or sealed before streams had their own seq
+            _ => segment::scan_segment(p)?,
+        };
+        anchor.seq = meta.last_seq;
+        if meta.last_chain.is_some() {
+            anchor.chain = meta.last_chain.clone();
+        }
+        anchor.stream_seqs.extend(meta.stream_seqs);
+        report.dropped_bytes += std::fs::metadata(p)?.len();
+    }
+    report.dropped_records = keep_from - report.first_seq.unwrap_or(keep_from);
+    report.first_seq = Some(keep_from);
+
+    // the manifest goes first: segments it no longer lists but which are
+    // still on disk (a crash below) read as an intact prefix
+    manifest.segments.retain(|s| s.first_seq >= keep_from);
+    manifest.anchor = Some(anchor);
+    manifest.store(dir)?;
+
+    for p in &files[..dropped] {
+        let idx = index::index_path(p);
+        match archive {
+            Some(to) => {
+                std::fs::create_dir_all(to).with_context(|| format!("create_dir_all {:?}", to))?;
+                let dest = to.join(segment::file_name(p));
+                move_file(p, &dest)?;
+                if idx.exists() {
+                    move_file(&idx, &index::index_path(&dest))?;
+                }
+                report.archived.push(dest);
+            }
+            None => {
+                std::fs::remove_file(p).with_context(|| format!("remove {:?}", p))?;
+                let _ = std::fs::remove_file(&idx);
+            }
+        }
+    }
+    if let Some(to) = archive {
+        copy_dictionaries(dir, to)?;
+    }
+    Ok(report)
+}
+
+fn compact_file(log: &Path, cut: u64, archive: Option<&Path>) -> Result<CompactReport> {
+    let mut src = File::open(log).with_context(|| format!("open {:?}", log))?;
+    src.try_lock_exclusive().with_context(|| format!("{:?} is open for writing", log))?;
+    let len = src.metadata()?.len();
+
+    // find where `cut` starts, and the names and anchor in force there
+    let mut r = EventLogReader::open(log)?.with_sealed_payloads();
+    let mut anchor = Anchor::load(log)?.unwrap_or_default();
+    let mut first = None;
+    let (offset, (kinds, streams)) = loop {
+        let at = r.position();
+        let counts = r.dict().counts();
+        let Some((env, _)) = r.next()? else {
+            return Ok(CompactReport { first_seq: first, ..Default::default() });
+        };
+        let start = *first.get_or_insert(env.seq);
+        if env.seq >= cut {
+            if env.seq == start {
+                return Ok(CompactReport { first_seq: Some(start), ..Default::default() });
+            }
+            break (at, counts);
+        }
+        anchor.seq = env.seq;
+        if env.chain.is_some() {
+            anchor.chain = env.chain.clone();
+        }
+        anchor.stream_seqs.insert(env.stream.clone(), env.seq_in_stream());
+    };
+    let first = first.expect("a record before the cut");
+    let (kind_defs, stream_defs) = r.definitions();
+    let format = r.format();
+    drop(r);
+
+    // the kept records go out byte for byte after the names they use
+    let mut head = Vec::new();
+    if format == LogFormat::Binary {
+        head.extend_from_slice(frame::MAGIC);
+        let mut dict = FrameDict::default();
+        for (_, name) in kind_defs.iter().take(kinds) {
+            dict.kind_id(name, &mut head)?;
+        }
+        for (_, name) in stream_defs.iter().take(streams) {
+            dict.stream_id(name, &mut head)?;
+        }
+    }
+    let mut tmp = log.as_os_str().to_owned();
+    tmp.push(".compact");
+    let tmp = PathBuf::from(tmp);
+    {
+        let mut out = File::create(&tmp).with_context(|| format!("create {:?}", tmp))?;
+        out.write_all(&head)?;
+        src.seek(SeekFrom::Start(offset))?;
+        std::io::copy(&mut Read::by_ref(&mut src).take(len - offset), &mut out)?;
+        out.sync_all()?;
+    }
+
+    let mut report = CompactReport {
+        first_seq: Some(anchor.seq + 1),
+        dropped_records: anchor.seq + 1 - first,
+        dropped_bytes: offset - head.len() as u64,
+        archived: Vec::new(),
+    };
+    if let Some(to) = archive {
+        // the dropped prefix is itself a valid log
+        std::fs::create_dir_all(to).with_context(|| format!("create_dir_all {:?}", to))?;
+        let name = format!("{}.{}-{}", segment::file_name(log), first, anchor.seq);
+        let dest = to.join(name);
+        let mut out = File::create(&dest).with_context(|| format!("create {:?}", dest))?;
+        src.seek(SeekFrom::Start(0))?;
+        std::io::copy(&mut Read::by_ref(&mut src).take(offset), &mut out)?;
+        out.sync_all()?;
+        copy_dictionaries(&compress::dict_dir(log), to)?;
+        report.archived.push(dest);
+    }
+
+    // an anchor only applies to a log that starts right after it, so it is
+    // safe to store before the swap
+    anchor.store(log)?;
+    std::fs::rename(&tmp, log).with_context(|| format!("rename {:?}", tmp))?;
+
+    let mut idx = SparseIndex::load(log)?;
+    if !idx.points.is_empty() {
+        let shift = offset - head.len() as u64;
+        idx.points.retain(|p| p.offset >= offset);
+        for p in &mut idx.points {
+            p.offset -= shift;
+        }
+        idx.store(log)?;
+    }
+    Ok(report)
+}
+
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if std::fs::rename(from, to).is_err() {
+        // another filesystem
+        std::fs::copy(from, to).with_context(|| format!("copy {:?} to {:?}", from, to))?;
+        std::fs::remove_file(from).with_context(|| format!("remove {:?}", from))?;
+    }
+    Ok(())
+}
+
+/// Archived records may be compressed with the dictionaries of their log.
+fn copy_dictionaries(from: &Path, to: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(from).with_context(|| format!("read_dir {:?}", from))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == compress::DICT_EXT) {
+            let dest = to.join(segment::file_name(&path));
+            if !dest.exists() {
+                std::fs::copy(&path, &dest).with_context(|| format!("copy {:?}", path))?;
+            }
+        }
+    }
+    Ok(())
+}
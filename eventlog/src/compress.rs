@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Payloads shorter than this are stored as is; zstd framing would not pay off.
+pub const MIN_COMPRESS_LEN: usize = 64;
+
+/// Largest payload a reader will inflate a record to (guards against a corrupt size).
+const MAX_PAYLOAD_LEN: u64 = 256 << 20;
+
+pub(crate) const DICT_EXT: &str = "zdict";
+
+/// Identifies a shared zstd dictionary: first 8 bytes of its blake3 digest.
+/// Dictionaries live next to the logs that use them as `<id>.zdict`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DictId(pub u64);
+
+impl DictId {
+    pub fn of(dict: &[u8]) -> Self {
+        let h = blake3::hash(dict);
+        DictId(u64::from_le_bytes(h.as_bytes()[0..8].try_into().unwrap()))
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("{}.{}", self, DICT_EXT)
+    }
+}
+
+impl std::fmt::Display for DictId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl std::str::FromStr for DictId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(DictId)
+    }
+}
+
+/// Writer setting: how new records are compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    /// zstd at `level`, optionally with a dictionary installed next to the log.
+    Zstd { level: i32, dict: Option<DictId> },
+}
+
+/// How one stored payload is encoded; recorded per record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Zstd { dict: Option<DictId> },
+}
+
+impl Codec {
+    /// Envelope form: `zstd` or `zstd:<dict id>`.
+    pub fn name(&self) -> String {
+        match self {
+            Codec::Zstd { dict: None } => "zstd".to_string(),
+            Codec::Zstd { dict: Some(id) } => format!("zstd:{}", id),
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.split_once(':') {
+            None if name == "zstd" => Some(Codec::Zstd { dict: None }),
+            Some(("zstd", id)) => id.parse().ok().map(|id| Codec::Zstd { dict: Some(id) }),
+            _ => None,
+        }
+    }
+}
+
+/// Build a dictionary from sample payloads (e.g. records of earlier captures).
+pub fn train_dictionary<S: AsRef<[u8]>>(samples: &[S], max_size: usize) -> Result<Vec<u8>> {
+    zstd::dict::from_samples(samples, max_size).context("train zstd dictionary")
+}
+
+/// Store `dict` in `dir` so writers and readers of logs there can find it.
+pub fn install_dictionary(dir: impl AsRef<Path>, dict: &[u8]) -> Result<DictId> {
+    let id = DictId::of(dict);
+    let path = dir.as_ref().join(id.file_name());
+    if !path.exists() {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, dict).with_context(|| format!("write {:?}", tmp))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("rename {:?}", tmp))?;
+    }
+    Ok(id)
+}
+
+pub fn load_dictionary(dir: impl AsRef<Path>, id: DictId) -> Result<Vec<u8>> {
+    let path = dir.as_ref().join(id.file_name());
+    let dict = std::fs::read(&path).with_context(|| format!("read dictionary {:?}", path))?;
+    if DictId::of(&dict) != id {
+        anyhow::bail!("dictionary {:?} does not match its id", path);
+    }
+    Ok(dict)
+}
+
+/// Directory holding the dictionaries of a log file.
+pub(crate) fn dict_dir(log: &Path) -> PathBuf {
+    match log.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Writer side: compresses payloads that shrink.
+pub(crate) struct PayloadCompressor {
+    zstd: zstd::bulk::Compressor<'static>,
+    codec: Codec,
+}
+
+impl PayloadCompressor {
+    pub(crate) fn new(setting: Compression, dir: &Path) -> Result<Option<Self>> {
+        let Compression::Zstd { level, dict } = setting else {
+            return Ok(None);
+        };
+        let zstd = match dict {
+            Some(id) => zstd::bulk::Compressor::with_dictionary(level, &load_dictionary(dir, id)?)?,
+            None => zstd::bulk::Compressor::new(level)?,
+        };
+        Ok(Some(Self { zstd, codec: Codec::Zstd { dict } }))
+    }
+
+    /// Compressed bytes and their codec, or `None` to store the payload as is.
+    pub(crate) fn compress(&mut self, payload: &[u8]) -> Result<Option<(Vec<u8>, Codec)>> {
+        if payload.len() < MIN_COMPRESS_LEN {
+            return Ok(None);
+        }
+        let out = self.zstd.compress(payload)?;
+        Ok((out.len() < payload.len()).then_some((out, self.codec)))
+    }
+}
+
+/// Reader side: decoders per dictionary, loaded on first use.
+pub(crate) struct PayloadDecompressor {
+    dir: PathBuf,
+    plain: Option<zstd::bulk::Decompressor<'static>>,
+    with_dict: HashMap<DictId, zstd::bulk::Decompressor<'static>>,
+}
+
+impl PayloadDecompressor {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self { dir, plain: None, with_dict: HashMap::new() }
+    }
+
+    pub(crate) fn decompress(&mut self, codec: Codec, data: &[u8]) -> Result<Vec<u8>> {
+        let Codec::Zstd { dict } = codec;
+        let d = match dict {
+            None => match &mut self.plain {
+                Some(d) => d,
+                None => self.plain.insert(zstd::bulk::Decompressor::new()?),
+            },
+            Some(id) => match self.with_dict.entry(id) {
+                std::collections::hash_map::Entry::Occupied(e) => e.into_mut(),
+                std::collections::hash_map::Entry::Vacant(e) => {
+                    let dict = load_dictionary(&self.dir, id)?;
+                    e.insert(zstd::bulk::Decompressor::with_dictionary(&dict)?)
+                }
+            },
+        };
+
+        let size = zstd::zstd_safe::get_frame_content_size(data)
+            .ok()
+            .flatten()
+            .context("zstd payload without content size")?;
+        if size > MAX_PAYLOAD_LEN {
+            anyhow::bail!("zstd payload claims {} bytes", size);
+        }
+        Ok(d.decompress(data, size as usize)?)
+    }
+}
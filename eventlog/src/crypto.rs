@@ -0,0 +1,218 @@
+use anyhow::{Context, Result};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// XChaCha20-Poly1305 key.
+pub type Key = [u8; 32];
+
+/// Random nonce stored in front of every sealed payload.
+const NONCE_LEN: usize = 24;
+
+/// Names the key a payload was sealed with; stored in the envelope (and the
+/// binary frame) so keys can be rotated without rewriting old records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeyId(pub u32);
+
+impl std::fmt::Display for KeyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Where readers and writers get keys from.
+pub trait KeyProvider: Send + Sync {
+    /// Key `id`, or `None` when this provider does not hold it.
+    fn key(&self, id: KeyId) -> Result<Option<Key>>;
+}
+
+pub type SharedKeys = Arc<dyn KeyProvider>;
+
+/// Keys of a tool that copies records into a new log (merge, slice,
+/// salvage): sealed inputs are decrypted with `keys` and the copies are
+/// sealed again with `key_id`, since a seal does not survive renumbering.
+#[derive(Clone)]
+pub struct CopyKeys {
+    pub keys: SharedKeys,
+    pub key_id: KeyId,
+}
+
+impl CopyKeys {
+    pub fn new(keys: SharedKeys, key_id: KeyId) -> Self {
+        Self { keys, key_id }
+    }
+}
+
+/// Parse 64 hex digits (surrounding whitespace ignored).
+pub fn key_from_hex(s: &str) -> Option<Key> {
+    let s = s.trim();
+    if s.len() != 64 || !s.is_ascii() {
+        return None;
+    }
+    let mut key = [0u8; 32];
+    for (i, b) in key.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(key)
+}
+
+/// Keys as files `<dir>/<id>.key`, each holding the key in hex.
+#[derive(Debug, Clone)]
+pub struct FileKeys {
+    dir: PathBuf,
+}
+
+impl FileKeys {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn path(&self, id: KeyId) -> PathBuf {
+        self.dir.join(format!("{}.key", id))
+    }
+}
+
+impl KeyProvider for FileKeys {
+    fn key(&self, id: KeyId) -> Result<Option<Key>> {
+        let path = self.path(id);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("read key {:?}", path)),
+        };
+        key_from_hex(&text).map(Some).with_context(|| format!("{:?} does not hold a 64 digit hex key", path))
+    }
+}
+
+/// Keys in environment variables `<prefix><id>`, in hex.
+#[derive(Debug, Clone)]
+pub struct EnvKeys {
+    prefix: String,
+}
+
+impl EnvKeys {
+    pub const DEFAULT_PREFIX: &'static str = "EVENTLOG_KEY_";
+
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self { prefix: prefix.into() }
+    }
+
+    pub fn var(&self, id: KeyId) -> String {
+        format!("{}{}", self.prefix, id)
+    }
+}
+
+impl Default for EnvKeys {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_PREFIX)
+    }
+}
+
+impl KeyProvider for EnvKeys {
+    fn key(&self, id: KeyId) -> Result<Option<Key>> {
+        let var = self.var(id);
+        match std::env::var(&var) {
+            Ok(v) => key_from_hex(&v).map(Some).with_context(|| format!("${} does not hold a 64 digit hex key", var)),
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("read ${}", var)),
+        }
+    }
+}
+
+/// Binds a sealed payload to its record, so it cannot be moved to another seq.
+fn aad(key_id: KeyId, seq: u64, checksum: u32) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    out[0..4].copy_from_slice(&key_id.0.to_le_bytes());
+    out[4..12].copy_from_slice(&seq.to_le_bytes());
+    out[12..16].copy_from_slice(&checksum.to_le_bytes());
+    out
+}
+
+/// Writer side: seals every payload with one key.
+pub(crate) struct PayloadSealer {
+    key_id: KeyId,
+    cipher: XChaCha20Poly1305,
+}
+
+impl PayloadSealer {
+    pub(crate) fn new(keys: &dyn KeyProvider, key_id: KeyId) -> Result<Self> {
+        let key = keys.key(key_id)?.with_context(|| format!("no key {} to encrypt with", key_id))?;
+        Ok(Self { key_id, cipher: XChaCha20Poly1305::new(&key.into()) })
+    }
+
+    pub(crate) fn key_id(&self) -> KeyId {
+        self.key_id
+    }
+
+    /// `nonce | ciphertext | tag` of the stored (possibly compressed) bytes.
+    pub(crate) fn seal(&self, seq: u64, checksum: u32, stored: &[u8]) -> Result<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = self
+            .cipher
+            .encrypt(&nonce, Payload { msg: stored, aad: &aad(self.key_id, seq, checksum) })
+            .map_err(|_| anyhow::anyhow!("encrypt seq={}", seq))?;
+        let mut out = Vec::with_capacity(NONCE_LEN + sealed.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+}
+
+/// Why a sealed payload could not be opened.
+pub(crate) enum OpenError {
+    MissingKey,
+    Failed(String),
+}
+
+/// Reader side: ciphers per key id, looked up on first use.
+pub(crate) struct PayloadOpener {
+    keys: SharedKeys,
+    ciphers: HashMap<KeyId, XChaCha20Poly1305>,
+}
+
+impl PayloadOpener {
+    pub(crate) fn new(keys: SharedKeys) -> Self {
+        Self { keys, ciphers: HashMap::new() }
+    }
+
+    pub(crate) fn open(&mut self, key_id: KeyId, seq: u64, checksum: u32, sealed: &[u8]) -> Result<Vec<u8>, OpenError> {
+        let cipher = match self.ciphers.entry(key_id) {
+            std::collections::hash_map::Entry::Occupied(e) => e.into_mut(),
+            std::collections::hash_map::Entry::Vacant(e) => {
+                let key = self
+                    .keys
+                    .key(key_id)
+                    .map_err(|err| OpenError::Failed(format!("{:#}", err)))?
+                    .ok_or(OpenError::MissingKey)?;
+                e.insert(XChaCha20Poly1305::new(&key.into()))
+            }
+        };
+        if sealed.len() < NONCE_LEN {
+            return Err(OpenError::Failed("sealed payload shorter than its nonce".to_string()));
+        }
+        let (nonce, msg) = sealed.split_at(NONCE_LEN);
+        cipher
+            .decrypt(XNonce::from_slice(nonce), Payload { msg, aad: &aad(key_id, seq, checksum) })
+            .map_err(|_| OpenError::Failed(format!("does not decrypt with key {}", key_id)))
+    }
+}
+
+/// A key file for `id` under `dir` (readable by the owner only), from fresh
+/// random bytes. Fails if the file exists. Returns the key.
+pub fn generate_key_file(dir: impl AsRef<Path>, id: KeyId) -> Result<Key> {
+    let key: Key = XChaCha20Poly1305::generate_key(&mut OsRng).into();
+    let path = FileKeys::new(dir.as_ref()).path(id);
+    let mut opts = std::fs::OpenOptions::new();
+    opts.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+    let mut file = opts.open(&path).with_context(|| format!("create {:?}", path))?;
+    let hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+    writeln!(file, "{}", hex).with_context(|| format!("write {:?}", path))?;
+    Ok(key)
+}
@@ -1,12 +1,41 @@
 use serde::{Deserialize, Serialize};
 
+use crate::crypto::KeyId;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventEnvelope {
+    /// position in the log, across all streams
     pub seq: u64,
+    /// position within `stream`; absent when equal to `seq`
+    /// (always the case for a log with a single stream)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_seq: Option<u64>,
+    /// seq of this record in the log it was copied from (merge, split)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_seq: Option<u64>,
     pub ts_ns: u64,
     pub stream: String,
     pub kind: String,
-    /// base64 bytes in json
+    /// base64 bytes in json; empty for records read from a binary log
+    /// (the reader hands out the raw payload alongside the envelope)
     pub payload_b64: String,
     pub checksum: u32,
+    /// hex blake3 chain digest (see `chain::link`); absent unless the writer chains
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+    /// set when the stored payload is compressed (see `compress::Codec::name`);
+    /// the reader decompresses, and `checksum` covers the uncompressed bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codec: Option<String>,
+    /// set when the stored payload is encrypted (after compression) with
+    /// this key; `checksum` and `chain` cover the plaintext
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<KeyId>,
+}
+
+impl EventEnvelope {
+    /// Seq of this record within its stream.
+    pub fn seq_in_stream(&self) -> u64 {
+        self.stream_seq.unwrap_or(self.seq)
+    }
 }
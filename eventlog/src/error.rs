@@ -1,5 +1,9 @@
+use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::crypto::KeyId;
+use crate::frame::LogFormat;
+
 #[derive(Debug, Error)]
 pub enum EventLogError {
     #[error("io error: {0}")]
@@ -7,4 +11,124 @@ pub enum EventLogError {
 
     #[error("json error: {0}")]
     Json(#[from] serde_json::Error),
+
+    /// A log, segment directory or side file that cannot be opened, created or locked.
+    #[error("{path:?}: {source}")]
+    File {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// An existing log in another encoding than the writer was asked for.
+    #[error("{path:?} is a {found:?} log, writer asked for {wanted:?}")]
+    FormatMismatch { path: PathBuf, found: LogFormat, wanted: LogFormat },
+
+    /// A manifest, anchor, segment name, dictionary or key the log cannot
+    /// be opened or continued with.
+    #[error("{path:?}: {reason}")]
+    Layout { path: PathBuf, reason: String },
+
+    /// A payload the writer cannot compress or encrypt.
+    #[error("payload does not encode: seq={seq}: {reason}")]
+    Encode { seq: u64, reason: String },
+
+    /// A replicated record that is not the next one of this log.
+    #[error("replica {reason} at seq={seq}")]
+    Replica { seq: u64, reason: String },
+
+    #[error("checksum mismatch: seq={seq} offset={offset} stored={stored:#010x} computed={computed:#010x}")]
+    ChecksumMismatch { seq: u64, offset: u64, stored: u32, computed: u32 },
+
+    /// A line or frame that does not decode as a record.
+    #[error("malformed envelope at offset={offset}: {reason}")]
+    MalformedEnvelope { offset: u64, reason: String },
+
+    #[error("base64 payload does not decode: seq={seq} offset={offset}")]
+    Base64 {
+        seq: u64,
+        offset: u64,
+        #[source]
+        source: base64::DecodeError,
+    },
+
+    /// Unknown codec, missing dictionary or a payload that does not decompress
+    /// (or decrypt: damaged, or sealed with a different key of the same id).
+    #[error("payload does not decode: seq={seq} offset={offset}: {reason}")]
+    Payload { seq: u64, offset: u64, reason: String },
+
+    /// An encrypted payload and no key for it (see `EventLogReader::with_keys`).
+    #[error("no key {key_id} for encrypted payload: seq={seq}")]
+    MissingKey { seq: u64, key_id: KeyId },
+
+    /// The file ends inside a record (torn write).
+    #[error("truncated tail at offset={offset}")]
+    TruncatedTail { offset: u64 },
+
+    /// A kind the reader's schema registry does not know.
+    #[error("unknown kind {kind:?}: seq={seq}")]
+    UnknownKind { seq: u64, kind: String },
+
+    /// Written by a newer schema than the reader knows (or version 0).
+    #[error("unsupported {kind:?} version {version} (current {current}): seq={seq}")]
+    UnsupportedVersion { seq: u64, kind: String, version: u16, current: u16 },
+
+    /// A payload that does not match its kind's schema, or fails to migrate.
+    #[error("{kind:?} payload does not match its schema: seq={seq}: {reason}")]
+    Schema { seq: u64, kind: String, reason: String },
+}
+
+impl EventLogError {
+    /// Damage confined to the record at `offset()`; a salvaging reader can skip it.
+    pub fn is_corruption(&self) -> bool {
+        self.offset().is_some()
+    }
+
+    /// Byte offset of the damaged record.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            EventLogError::Io(_)
+            | EventLogError::Json(_)
+            | EventLogError::File { .. }
+            | EventLogError::FormatMismatch { .. }
+            | EventLogError::Layout { .. }
+            | EventLogError::Encode { .. }
+            | EventLogError::Replica { .. }
+            | EventLogError::UnknownKind { .. }
+            | EventLogError::UnsupportedVersion { .. }
+            | EventLogError::Schema { .. }
+            | EventLogError::MissingKey { .. } => None,
+            EventLogError::ChecksumMismatch { offset, .. }
+            | EventLogError::MalformedEnvelope { offset, .. }
+            | EventLogError::Base64 { offset, .. }
+            | EventLogError::Payload { offset, .. }
+            | EventLogError::TruncatedTail { offset } => Some(*offset),
+        }
+    }
+
+    /// Seq of the damaged record, when it could still be read.
+    pub fn seq(&self) -> Option<u64> {
+        match self {
+            EventLogError::ChecksumMismatch { seq, .. }
+            | EventLogError::Base64 { seq, .. }
+            | EventLogError::Payload { seq, .. }
+            | EventLogError::MissingKey { seq, .. }
+            | EventLogError::UnknownKind { seq, .. }
+            | EventLogError::UnsupportedVersion { seq, .. }
+            | EventLogError::Schema { seq, .. }
+            | EventLogError::Encode { seq, .. }
+            | EventLogError::Replica { seq, .. } => Some(*seq),
+            _ => None,
+        }
+    }
+
+    /// `File` error for `path`, for use with `map_err`.
+    pub(crate) fn file(path: impl Into<PathBuf>) -> impl FnOnce(std::io::Error) -> Self {
+        let path = path.into();
+        move |source| EventLogError::File { path, source }
+    }
+
+    pub(crate) fn layout(path: impl Into<PathBuf>, reason: impl ToString) -> Self {
+        EventLogError::Layout { path: path.into(), reason: reason.to_string() }
+    }
 }
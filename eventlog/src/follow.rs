@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::crypto::SharedKeys;
+use crate::envelope::EventEnvelope;
+use crate::frame::MAGIC;
+use crate::reader::EventLogReader;
+
+/// How often a follower re-checks the file when it has caught up.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Identity of the file behind a path, to notice it being replaced.
+#[cfg(unix)]
+fn file_id(meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// `tail -f` for an event log another process is appending to.
+///
+/// Only complete records are returned; a partially written tail is left for
+/// the next poll. A writer restart (which truncates a torn tail and carries
+/// on appending) is transparent. If the file is deleted or replaced, the
+/// follower waits for the new file and reads it from the start.
+pub struct LogFollower {
+    path: PathBuf,
+    reader: Option<EventLogReader>,
+    id: Option<(u64, u64)>,
+    /// skip records below this seq; cleared by the first record delivered
+    start_seq: Option<u64>,
+    poll_interval: Duration,
+    keys: Option<SharedKeys>,
+    sealed_ok: bool,
+}
+
+impl LogFollower {
+    /// Follow `path` from its first record. The file does not have to exist yet.
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            reader: None,
+            id: None,
+            start_seq: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            keys: None,
+            sealed_ok: false,
+        }
+    }
+
+    /// Follow `path` starting at the first record with `seq >= seq`
+    /// (e.g. one past the last seq a consumer already processed).
+    pub fn from_seq(path: impl AsRef<Path>, seq: u64) -> Self {
+        Self { start_seq: Some(seq), ..Self::open(path) }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Decrypt sealed payloads; see `EventLogReader::with_keys`.
+    pub fn with_keys(mut self, keys: SharedKeys) -> Self {
+        self.keys = Some(keys);
+        self
+    }
+
+    /// Hand out payloads that cannot be decrypted as stored; see
+    /// `EventLogReader::with_sealed_payloads`.
+    pub fn with_sealed_payloads(mut self) -> Self {
+        self.sealed_ok = true;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Next complete record if one is available now; never waits.
+    pub fn try_next(&mut self) -> Result<Option<(EventEnvelope, Vec<u8>)>> {
+        // a replaced file gets one retry so its first records are not delayed a poll
+        let mut retried = false;
+        loop {
+            if self.reader.is_none() && !self.attach()? {
+                return Ok(None);
+            }
+            let r = self.reader.as_mut().expect("attached");
+            if let Some(rec) = r.next_complete()? {
+                // the seek on attach may have hit the end before `start_seq` was written
+                if self.start_seq.is_some_and(|s| rec.0.seq < s) {
+                    continue;
+                }
+                self.start_seq = None;
+                return Ok(Some(rec));
+            }
+            if retried || !self.replaced()? {
+                return Ok(None);
+            }
+            self.reader = None;
+            self.id = None;
+            retried = true;
+        }
+    }
+
+    /// Wait until the next record is complete.
+    pub fn next_blocking(&mut self) -> Result<(EventEnvelope, Vec<u8>)> {
+        loop {
+            if let Some(rec) = self.try_next()? {
+                return Ok(rec);
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+    }
+
+    /// Like `next_blocking`, but gives up with `None` after `timeout`.
+    pub fn next_timeout(&mut self, timeout: Duration) -> Result<Option<(EventEnvelope, Vec<u8>)>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(rec) = self.try_next()? {
+                return Ok(Some(rec));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            std::thread::sleep(self.poll_interval.min(deadline - now));
+        }
+    }
+
+    /// Async `next_blocking`: polls on the tokio timer instead of sleeping the thread.
+    pub async fn next_async(&mut self) -> Result<(EventEnvelope, Vec<u8>)> {
+        loop {
+            if let Some(rec) = self.try_next()? {
+                return Ok(rec);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Open the reader once the file exists and its format can be told
+    /// (a binary writer may not have finished the header yet).
+    fn attach(&mut self) -> Result<bool> {
+        let mut file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e).with_context(|| format!("open {:?}", self.path)),
+        };
+        let mut head = Vec::with_capacity(MAGIC.len());
+        Read::by_ref(&mut file).take(MAGIC.len() as u64).read_to_end(&mut head)?;
+        if head.is_empty() || (head.len() < MAGIC.len() && MAGIC.starts_with(&head)) {
+            return Ok(false);
+        }
+
+        let mut r = EventLogReader::open(&self.path)?;
+        if let Some(keys) = &self.keys {
+            r = r.with_keys(keys.clone());
+        }
+        if self.sealed_ok {
+            r = r.with_sealed_payloads();
+        }
+        if let Some(seq) = self.start_seq {
+            r.seek_seq(seq)?;
+        }
+        self.id = file_id(&file.metadata()?);
+        self.reader = Some(r);
+        Ok(true)
+    }
+
+    /// The path no longer names the file being read (deleted, replaced, or
+    /// truncated below what was already read).
+    fn replaced(&self) -> Result<bool> {
+        let meta = match std::fs::metadata(&self.path) {
+            Ok(m) => m,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+            Err(e) => return Err(e).with_context(|| format!("stat {:?}", self.path)),
+        };
+        let pos = self.reader.as_ref().map(|r| r.position()).unwrap_or(0);
+        Ok(file_id(&meta) != self.id || meta.len() < pos)
+    }
+}
@@ -0,0 +1,460 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+use crate::chain::Digest;
+use crate::compress::{Codec, DictId};
+use crate::crypto::KeyId;
+
+/// Header written at offset 0 of every binary log.
+pub const MAGIC: &[u8; 8] = b"ELOGBIN1";
+
+const TAG_RECORD: u8 = 0;
+const TAG_KIND: u8 = 1;
+const TAG_STREAM: u8 = 2;
+const TAG_CHAINED_RECORD: u8 = 3;
+const TAG_FLAGGED_RECORD: u8 = 4;
+
+const FLAG_CHAIN: u8 = 0x1;
+const FLAG_ZSTD: u8 = 0x2;
+const FLAG_DICT: u8 = 0x4;
+const FLAG_STREAM_SEQ: u8 = 0x8;
+const FLAG_ORIGIN_SEQ: u8 = 0x10;
+const FLAG_SEALED: u8 = 0x20;
+const KNOWN_FLAGS: u8 = FLAG_CHAIN | FLAG_ZSTD | FLAG_DICT | FLAG_STREAM_SEQ | FLAG_ORIGIN_SEQ | FLAG_SEALED;
+
+// seq + ts_ns + kind_id + stream_id + checksum
+const RECORD_FIXED_LEN: usize = 8 + 8 + 2 + 2 + 4;
+
+/// On-disk encoding of a log file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// One JSON `EventEnvelope` per line, payload in base64.
+    #[default]
+    JsonLines,
+    /// `MAGIC` header followed by length-prefixed binary frames.
+    Binary,
+}
+
+impl LogFormat {
+    /// Detect format from the first bytes of a file.
+    /// Anything that does not start with `MAGIC` (including an empty file) is JSON lines.
+    pub fn detect(head: &[u8]) -> Self {
+        if head.starts_with(MAGIC) {
+            LogFormat::Binary
+        } else {
+            LogFormat::JsonLines
+        }
+    }
+}
+
+/// Frame layout: `[u32 LE body_len][body]`, body starts with a tag byte.
+///
+/// - record: `seq u64 | ts_ns u64 | kind_id u16 | stream_id u16 | crc32 u32 | payload`
+/// - chained record: same, with `chain [u8; 32]` between crc32 and payload
+/// - flagged record: `flags u8`, the record fields, then `chain` (flag 0x1),
+///   `dict_id u64` (flag 0x4), `stream_seq u64` (flag 0x8),
+///   `origin_seq u64` (flag 0x10) and `key_id u32` (flag 0x20) before the
+///   payload; flag 0x2 marks a zstd payload, flag 0x20 an encrypted one.
+///   Only records that need one of these extras use this form.
+/// - kind / stream definition: `id u16 | utf8 name`
+///
+/// Kind and stream names are interned: a definition frame is written the first
+/// time a name is used and records refer to it by id afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    Record {
+        seq: u64,
+        /// seq within the stream when it differs from `seq`
+        stream_seq: Option<u64>,
+        /// seq in the log this record was copied from
+        origin_seq: Option<u64>,
+        ts_ns: u64,
+        kind_id: u16,
+        stream_id: u16,
+        checksum: u32,
+        chain: Option<Digest>,
+        /// `payload` is stored compressed; the crc covers the uncompressed bytes
+        codec: Option<Codec>,
+        /// `payload` is sealed with this key (after compression)
+        key: Option<KeyId>,
+        payload: Vec<u8>,
+    },
+    Kind { id: u16, name: String },
+    Stream { id: u16, name: String },
+}
+
+pub enum ReadOutcome {
+    /// Clean end of file on a frame boundary.
+    Eof,
+    /// File ends in the middle of a frame (torn write).
+    Truncated,
+    /// A complete frame and its total size on disk (prefix included).
+    Frame(Frame, u64),
+}
+
+fn put_frame(out: &mut Vec<u8>, body: &[u8]) {
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(body);
+}
+
+fn put_definition(out: &mut Vec<u8>, tag: u8, id: u16, name: &str) {
+    let mut body = Vec::with_capacity(3 + name.len());
+    body.push(tag);
+    body.extend_from_slice(&id.to_le_bytes());
+    body.extend_from_slice(name.as_bytes());
+    put_frame(out, &body);
+}
+
+/// Fixed fields of a record frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordHead {
+    pub seq: u64,
+    pub stream_seq: Option<u64>,
+    pub origin_seq: Option<u64>,
+    pub ts_ns: u64,
+    pub kind_id: u16,
+    pub stream_id: u16,
+    pub checksum: u32,
+    pub chain: Option<Digest>,
+    pub codec: Option<Codec>,
+    pub key: Option<KeyId>,
+}
+
+pub fn encode_record(out: &mut Vec<u8>, head: &RecordHead, payload: &[u8]) {
+    let start = out.len();
+    // length prefix, patched once the body is written
+    out.extend_from_slice(&[0u8; 4]);
+
+    let dict = match head.codec {
+        Some(Codec::Zstd { dict }) => dict,
+        None => None,
+    };
+    if head.codec.is_none() && head.stream_seq.is_none() && head.origin_seq.is_none() && head.key.is_none() {
+        out.push(if head.chain.is_some() { TAG_CHAINED_RECORD } else { TAG_RECORD });
+    } else {
+        let mut flags = 0;
+        if head.chain.is_some() {
+            flags |= FLAG_CHAIN;
+        }
+        if head.codec.is_some() {
+            flags |= FLAG_ZSTD;
+        }
+        if dict.is_some() {
+            flags |= FLAG_DICT;
+        }
+        if head.stream_seq.is_some() {
+            flags |= FLAG_STREAM_SEQ;
+        }
+        if head.origin_seq.is_some() {
+            flags |= FLAG_ORIGIN_SEQ;
+        }
+        if head.key.is_some() {
+            flags |= FLAG_SEALED;
+        }
+        out.push(TAG_FLAGGED_RECORD);
+        out.push(flags);
+    }
+
+    out.extend_from_slice(&head.seq.to_le_bytes());
+    out.extend_from_slice(&head.ts_ns.to_le_bytes());
+    out.extend_from_slice(&head.kind_id.to_le_bytes());
+    out.extend_from_slice(&head.stream_id.to_le_bytes());
+    out.extend_from_slice(&head.checksum.to_le_bytes());
+    if let Some(c) = &head.chain {
+        out.extend_from_slice(c);
+    }
+    if let Some(id) = dict {
+        out.extend_from_slice(&id.0.to_le_bytes());
+    }
+    if let Some(s) = head.stream_seq {
+        out.extend_from_slice(&s.to_le_bytes());
+    }
+    if let Some(s) = head.origin_seq {
+        out.extend_from_slice(&s.to_le_bytes());
+    }
+    if let Some(k) = head.key {
+        out.extend_from_slice(&k.0.to_le_bytes());
+    }
+    out.extend_from_slice(payload);
+
+    let body_len = (out.len() - start - 4) as u32;
+    out[start..start + 4].copy_from_slice(&body_len.to_le_bytes());
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn le_u16(b: &[u8]) -> u16 {
+    u16::from_le_bytes(b.try_into().unwrap())
+}
+
+fn le_u32(b: &[u8]) -> u32 {
+    u32::from_le_bytes(b.try_into().unwrap())
+}
+
+fn le_u64(b: &[u8]) -> u64 {
+    u64::from_le_bytes(b.try_into().unwrap())
+}
+
+/// Read as many bytes as possible into `buf`; returns how many were read.
+fn read_full(r: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut got = 0;
+    while got < buf.len() {
+        match r.read(&mut buf[got..]) {
+            Ok(0) => break,
+            Ok(n) => got += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(got)
+}
+
+/// A frame decoded in place: names and payload borrow from the body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameRef<'a> {
+    Record { head: RecordHead, payload: &'a [u8] },
+    Kind { id: u16, name: &'a str },
+    Stream { id: u16, name: &'a str },
+}
+
+impl FrameRef<'_> {
+    pub fn to_frame(&self) -> Frame {
+        match *self {
+            FrameRef::Record { head, payload } => Frame::Record {
+                seq: head.seq,
+                stream_seq: head.stream_seq,
+                origin_seq: head.origin_seq,
+                ts_ns: head.ts_ns,
+                kind_id: head.kind_id,
+                stream_id: head.stream_id,
+                checksum: head.checksum,
+                chain: head.chain,
+                codec: head.codec,
+                key: head.key,
+                payload: payload.to_vec(),
+            },
+            FrameRef::Kind { id, name } => Frame::Kind { id, name: name.to_string() },
+            FrameRef::Stream { id, name } => Frame::Stream { id, name: name.to_string() },
+        }
+    }
+}
+
+pub fn decode_body(body: &[u8]) -> io::Result<Frame> {
+    decode_body_ref(body).map(|f| f.to_frame())
+}
+
+/// Decode a frame body without copying it.
+pub fn decode_body_ref(body: &[u8]) -> io::Result<FrameRef<'_>> {
+    let (&tag, rest) = body.split_first().ok_or_else(|| invalid("empty frame"))?;
+    match tag {
+        TAG_RECORD => decode_record(0, rest),
+        TAG_CHAINED_RECORD => decode_record(FLAG_CHAIN, rest),
+        TAG_FLAGGED_RECORD => {
+            let (&flags, rest) = rest.split_first().ok_or_else(|| invalid("short record frame"))?;
+            if flags & !KNOWN_FLAGS != 0 || flags & (FLAG_ZSTD | FLAG_DICT) == FLAG_DICT {
+                return Err(invalid("unknown record flags"));
+            }
+            decode_record(flags, rest)
+        }
+        TAG_KIND | TAG_STREAM => {
+            if rest.len() < 2 {
+                return Err(invalid("short definition frame"));
+            }
+            let id = le_u16(&rest[0..2]);
+            let name = std::str::from_utf8(&rest[2..]).map_err(|_| invalid("definition name is not utf8"))?;
+            if tag == TAG_KIND {
+                Ok(FrameRef::Kind { id, name })
+            } else {
+                Ok(FrameRef::Stream { id, name })
+            }
+        }
+        _ => Err(invalid("unknown frame tag")),
+    }
+}
+
+fn decode_record(flags: u8, rest: &[u8]) -> io::Result<FrameRef<'_>> {
+    let chain_len = if flags & FLAG_CHAIN != 0 { 32 } else { 0 };
+    let dict_len = if flags & FLAG_DICT != 0 { 8 } else { 0 };
+    let stream_seq_len = if flags & FLAG_STREAM_SEQ != 0 { 8 } else { 0 };
+    let origin_seq_len = if flags & FLAG_ORIGIN_SEQ != 0 { 8 } else { 0 };
+    let key_len = if flags & FLAG_SEALED != 0 { 4 } else { 0 };
+    let payload_at = RECORD_FIXED_LEN + chain_len + dict_len + stream_seq_len + origin_seq_len + key_len;
+    if rest.len() < payload_at {
+        return Err(invalid("short record frame"));
+    }
+
+    let chain_at = RECORD_FIXED_LEN;
+    let dict_at = chain_at + chain_len;
+    let stream_seq_at = dict_at + dict_len;
+    let origin_seq_at = stream_seq_at + stream_seq_len;
+    let key_at = origin_seq_at + origin_seq_len;
+    let dict = (dict_len > 0).then(|| DictId(le_u64(&rest[dict_at..stream_seq_at])));
+    let head = RecordHead {
+        seq: le_u64(&rest[0..8]),
+        stream_seq: (stream_seq_len > 0).then(|| le_u64(&rest[stream_seq_at..origin_seq_at])),
+        origin_seq: (origin_seq_len > 0).then(|| le_u64(&rest[origin_seq_at..key_at])),
+        ts_ns: le_u64(&rest[8..16]),
+        kind_id: le_u16(&rest[16..18]),
+        stream_id: le_u16(&rest[18..20]),
+        checksum: le_u32(&rest[20..24]),
+        chain: (chain_len > 0).then(|| rest[chain_at..dict_at].try_into().unwrap()),
+        codec: (flags & FLAG_ZSTD != 0).then_some(Codec::Zstd { dict }),
+        key: (key_len > 0).then(|| KeyId(le_u32(&rest[key_at..payload_at]))),
+    };
+    Ok(FrameRef::Record { head, payload: &rest[payload_at..] })
+}
+
+/// What the first bytes of a frame body claim it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FramePrefix {
+    Record { seq: u64, kind_id: u16, stream_id: u16 },
+    Kind { id: u16 },
+    Stream { id: u16 },
+}
+
+/// Body bytes `frame_prefix` needs to recognise any frame.
+pub(crate) const PREFIX_LEN: usize = 2 + RECORD_FIXED_LEN;
+
+/// Cheap look at the start of a body (up to `PREFIX_LEN` bytes), used to
+/// find the next frame boundary in a damaged log without reading whole frames.
+pub(crate) fn frame_prefix(body: &[u8]) -> Option<FramePrefix> {
+    let (&tag, rest) = body.split_first()?;
+    let fields = match tag {
+        TAG_RECORD | TAG_CHAINED_RECORD => rest,
+        TAG_FLAGGED_RECORD => {
+            let (&flags, rest) = rest.split_first()?;
+            if flags & !KNOWN_FLAGS != 0 || flags & (FLAG_ZSTD | FLAG_DICT) == FLAG_DICT {
+                return None;
+            }
+            rest
+        }
+        TAG_KIND | TAG_STREAM => {
+            let id = le_u16(rest.get(0..2)?);
+            return Some(if tag == TAG_KIND { FramePrefix::Kind { id } } else { FramePrefix::Stream { id } });
+        }
+        _ => return None,
+    };
+    if fields.len() < RECORD_FIXED_LEN {
+        return None;
+    }
+    Some(FramePrefix::Record {
+        seq: le_u64(&fields[0..8]),
+        kind_id: le_u16(&fields[16..18]),
+        stream_id: le_u16(&fields[18..20]),
+    })
+}
+
+pub fn read_frame(r: &mut impl Read) -> io::Result<ReadOutcome> {
+    let mut len_buf = [0u8; 4];
+    match read_full(r, &mut len_buf)? {
+        0 => return Ok(ReadOutcome::Eof),
+        4 => {}
+        _ => return Ok(ReadOutcome::Truncated),
+    }
+
+    // grown as bytes arrive, so a corrupt length cannot make us allocate it up front
+    let len = le_u32(&len_buf) as usize;
+    let mut body = Vec::new();
+    r.take(len as u64).read_to_end(&mut body)?;
+    if body.len() < len {
+        return Ok(ReadOutcome::Truncated);
+    }
+
+    let frame = decode_body(&body)?;
+    Ok(ReadOutcome::Frame(frame, 4 + len as u64))
+}
+
+/// `(id, name)` pairs of one name table, in id order.
+pub type NameDefs = Vec<(u16, String)>;
+
+/// Interned kind/stream names of a binary log.
+#[derive(Debug, Default, Clone)]
+pub struct FrameDict {
+    kinds: Vec<String>,
+    streams: Vec<String>,
+    kind_ids: HashMap<String, u16>,
+    stream_ids: HashMap<String, u16>,
+}
+
+fn intern(
+    names: &mut Vec<String>,
+    ids: &mut HashMap<String, u16>,
+    name: &str,
+) -> io::Result<(u16, bool)> {
+    if let Some(id) = ids.get(name) {
+        return Ok((*id, false));
+    }
+    let id: u16 = names
+        .len()
+        .try_into()
+        .map_err(|_| invalid("too many distinct names in binary log"))?;
+    names.push(name.to_string());
+    ids.insert(name.to_string(), id);
+    Ok((id, true))
+}
+
+fn define(names: &mut Vec<String>, ids: &mut HashMap<String, u16>, id: u16, name: String) -> io::Result<()> {
+    // seeking readers are primed from the index and then see the same definitions again
+    if names.get(id as usize) == Some(&name) {
+        return Ok(());
+    }
+    if id as usize != names.len() {
+        return Err(invalid("out of order name definition"));
+    }
+    ids.insert(name.clone(), id);
+    names.push(name);
+    Ok(())
+}
+
+impl FrameDict {
+    /// Register a definition frame. Records are ignored.
+    pub fn observe(&mut self, frame: &Frame) -> io::Result<()> {
+        match frame {
+            Frame::Kind { id, name } => define(&mut self.kinds, &mut self.kind_ids, *id, name.clone()),
+            Frame::Stream { id, name } => define(&mut self.streams, &mut self.stream_ids, *id, name.clone()),
+            Frame::Record { .. } => Ok(()),
+        }
+    }
+
+    pub fn kinds(&self) -> impl Iterator<Item = (u16, &str)> {
+        self.kinds.iter().enumerate().map(|(i, n)| (i as u16, n.as_str()))
+    }
+
+    pub fn streams(&self) -> impl Iterator<Item = (u16, &str)> {
+        self.streams.iter().enumerate().map(|(i, n)| (i as u16, n.as_str()))
+    }
+
+    pub(crate) fn counts(&self) -> (usize, usize) {
+        (self.kinds.len(), self.streams.len())
+    }
+
+    pub fn kind(&self, id: u16) -> Option<&str> {
+        self.kinds.get(id as usize).map(String::as_str)
+    }
+
+    pub fn stream(&self, id: u16) -> Option<&str> {
+        self.streams.get(id as usize).map(String::as_str)
+    }
+
+    /// Id for `name` and whether it is new; a new name gets a definition frame appended to `out`.
+    pub fn kind_id(&mut self, name: &str, out: &mut Vec<u8>) -> io::Result<(u16, bool)> {
+        let (id, new) = intern(&mut self.kinds, &mut self.kind_ids, name)?;
+        if new {
+            put_definition(out, TAG_KIND, id, name);
+        }
+        Ok((id, new))
+    }
+
+    /// Id for `name` and whether it is new; a new name gets a definition frame appended to `out`.
+    pub fn stream_id(&mut self, name: &str, out: &mut Vec<u8>) -> io::Result<(u16, bool)> {
+        let (id, new) = intern(&mut self.streams, &mut self.stream_ids, name)?;
+        if new {
+            put_definition(out, TAG_STREAM, id, name);
+        }
+        Ok((id, new))
+    }
+}
@@ -0,0 +1,527 @@
+use anyhow::{Context, Result};
+use std::collections::btree_map::{BTreeMap, Entry};
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Poll, Waker};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::segment::{Rotation, SegmentedWriter};
+use crate::sink::EventSink;
+use crate::writer::{Durability, EventLogWriter, WriterOptions};
+use el_core::clock::{Clock, SharedClock, WallClock};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy)]
+pub struct GroupCommitOptions {
+    /// Records that may wait for the writer thread; `append_bytes` blocks when
+    /// full, `append_bytes_async` waits without blocking the thread.
+    pub queue_capacity: usize,
+    /// Most records written between two fsyncs.
+    pub max_batch: usize,
+}
+
+impl Default for GroupCommitOptions {
+    fn default() -> Self {
+        Self { queue_capacity: 8192, max_batch: 1024 }
+    }
+}
+
+/// Counters shared with the writer thread.
+#[derive(Debug, Default)]
+struct Metrics {
+    queue_depth: AtomicU64,
+    max_queue_depth: AtomicU64,
+    records: AtomicU64,
+    commits: AtomicU64,
+    last_fsync_ns: AtomicU64,
+    max_fsync_ns: AtomicU64,
+    total_fsync_ns: AtomicU64,
+}
+
+/// Point-in-time view of a `GroupCommitWriter`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupCommitMetrics {
+    /// Records queued but not yet written.
+    pub queue_depth: u64,
+    pub max_queue_depth: u64,
+    /// Records made durable.
+    pub records: u64,
+    /// Flush + fsync rounds; `records / commits` is the mean batch size.
+    pub commits: u64,
+    pub last_fsync_ns: u64,
+    pub max_fsync_ns: u64,
+    pub total_fsync_ns: u64,
+}
+
+#[derive(Default)]
+struct DurableState {
+    /// every seq up to here is on disk
+    durable_seq: u64,
+    /// set when the writer thread stopped on an error; nothing becomes durable after it
+    failed: Option<String>,
+    /// one slot per pending commit, by seq; a re-poll replaces its waker
+    wakers: BTreeMap<u64, Waker>,
+}
+
+#[derive(Default)]
+struct Durable {
+    state: Mutex<DurableState>,
+    cond: Condvar,
+}
+
+impl Durable {
+    fn advance(&self, seq: u64) {
+        let mut st = self.state.lock().unwrap();
+        st.durable_seq = st.durable_seq.max(seq);
+        let durable = st.durable_seq;
+        let waiting = st.wakers.split_off(&durable.saturating_add(1));
+        let ready = std::mem::replace(&mut st.wakers, waiting);
+        drop(st);
+        self.cond.notify_all();
+        for (_, w) in ready {
+            w.wake();
+        }
+    }
+
+    fn fail(&self, err: String) {
+        let mut st = self.state.lock().unwrap();
+        st.failed.get_or_insert(err);
+        let wakers = std::mem::take(&mut st.wakers);
+        drop(st);
+        self.cond.notify_all();
+        for (_, w) in wakers {
+            w.wake();
+        }
+    }
+
+    fn check(st: &DurableState, seq: u64) -> Option<Result<()>> {
+        if st.durable_seq >= seq {
+            Some(Ok(()))
+        } else {
+            st.failed.as_ref().map(|e| Err(anyhow::anyhow!("group commit writer failed: {}", e)))
+        }
+    }
+}
+
+/// Resolves once the record with `seq` has been fsynced.
+///
+/// `wait` blocks the thread; the handle is also a `Future` for async callers.
+/// Dropping it does not affect the record.
+pub struct Commit {
+    seq: u64,
+    durable: Arc<Durable>,
+}
+
+impl Commit {
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    pub fn is_durable(&self) -> bool {
+        self.durable.state.lock().unwrap().durable_seq >= self.seq
+    }
+
+    pub fn wait(&self) -> Result<()> {
+        let mut st = self.durable.state.lock().unwrap();
+        loop {
+            if let Some(r) = Durable::check(&st, self.seq) {
+                return r;
+            }
+            st = self.durable.cond.wait(st).unwrap();
+        }
+    }
+
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<bool> {
+        let deadline = Instant::now() + timeout;
+        let mut st = self.durable.state.lock().unwrap();
+        loop {
+            if let Some(r) = Durable::check(&st, self.seq) {
+                return r.map(|_| true);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(false);
+            }
+            st = self.durable.cond.wait_timeout(st, deadline - now).unwrap().0;
+        }
+    }
+}
+
+impl Future for Commit {
+    type Output = Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let mut st = self.durable.state.lock().unwrap();
+        if let Some(r) = Durable::check(&st, self.seq) {
+            return Poll::Ready(r);
+        }
+        match st.wakers.entry(self.seq) {
+            Entry::Occupied(mut slot) => {
+                if !slot.get().will_wake(cx.waker()) {
+                    slot.get_mut().clone_from(cx.waker());
+                }
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(cx.waker().clone());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// An async append waiting for room in the queue.
+#[derive(Default)]
+struct QueueSpace {
+    waiting: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl QueueSpace {
+    fn register(&self, waker: &Waker) {
+        let mut slot = self.waker.lock().unwrap();
+        match slot.as_mut() {
+            Some(w) if w.will_wake(waker) => {}
+            Some(w) => w.clone_from(waker),
+            None => *slot = Some(waker.clone()),
+        }
+        self.waiting.store(true, Ordering::SeqCst);
+    }
+
+    /// The writer thread took a command off the queue, or stopped.
+    fn freed(&self) {
+        if self.waiting.swap(false, Ordering::SeqCst) {
+            if let Some(w) = self.waker.lock().unwrap().take() {
+                w.wake();
+            }
+        }
+    }
+}
+
+enum Cmd {
+    /// `stream: None` appends to the sink's own stream
+    Append { stream: Option<String>, kind: String, ts_ns: u64, payload: Vec<u8> },
+    /// commit everything queued so far, then ack
+    Sync(mpsc::Sender<()>),
+}
+
+/// Front-end that hands records to a dedicated writer thread.
+///
+/// The thread drains the queue in batches and makes each batch durable with a
+/// single flush + fsync (group commit), so callers never wait on the disk
+/// unless they choose to wait on a `Commit`. Seqs are assigned up front: the
+/// queue is FIFO and only this front-end feeds the underlying sink.
+pub struct GroupCommitWriter {
+    tx: Option<SyncSender<Cmd>>,
+    next_seq: u64,
+    durable: Arc<Durable>,
+    metrics: Arc<Metrics>,
+    space: Arc<QueueSpace>,
+    thread: Option<JoinHandle<()>>,
+    /// stamps `write()`; records are queued with their ts already set
+    clock: SharedClock,
+}
+
+impl GroupCommitWriter {
+    /// Single log file; `opts.durability` is replaced by fsync on every commit.
+    pub fn open(
+        path: impl AsRef<Path>,
+        stream: impl Into<String>,
+        opts: WriterOptions,
+        group: GroupCommitOptions,
+    ) -> Result<Self> {
+        let opts = WriterOptions { durability: Durability::FsyncOnFlush, ..opts };
+        Self::spawn(EventLogWriter::open_with(path, stream, opts)?, group)
+    }
+
+    /// Segmented directory; `opts.durability` is replaced by fsync on every commit.
+    pub fn open_segmented(
+        dir: impl AsRef<Path>,
+        stream: impl Into<String>,
+        opts: WriterOptions,
+        rotation: Rotation,
+        group: GroupCommitOptions,
+    ) -> Result<Self> {
+        let opts = WriterOptions { durability: Durability::FsyncOnFlush, ..opts };
+        Self::spawn(SegmentedWriter::open(dir, stream, opts, rotation)?, group)
+    }
+
+    /// Run `sink` on a writer thread. Its `flush` must make records durable.
+    pub fn spawn<W: EventSink + Send + 'static>(sink: W, group: GroupCommitOptions) -> Result<Self> {
+        let (tx, rx) = mpsc::sync_channel(group.queue_capacity.max(1));
+        let next_seq = sink.next_seq();
+        let durable = Arc::new(Durable::default());
+        durable.state.lock().unwrap().durable_seq = next_seq - 1;
+        let metrics = Arc::new(Metrics::default());
+        let space = Arc::new(QueueSpace::default());
+
+        let thread = {
+            let durable = durable.clone();
+            let metrics = metrics.clone();
+            let space = space.clone();
+            let max_batch = group.max_batch.max(1);
+            std::thread::Builder::new()
+                .name("eventlog-group-commit".to_string())
+                .spawn(move || {
+                    if let Err(e) = commit_loop(sink, rx, max_batch, &durable, &metrics, &space) {
+                        durable.fail(format!("{:#}", e));
+                    }
+                    // the queue is gone: a waiting append sees it and fails
+                    space.freed();
+                })
+                .context("spawn group commit thread")?
+        };
+
+        Ok(Self {
+            tx: Some(tx),
+            next_seq,
+            durable,
+            metrics,
+            space,
+            thread: Some(thread),
+            clock: Arc::new(WallClock),
+        })
+    }
+
+    /// Stamp `write()` with `clock` instead of wall time.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Queue a record; blocks while the queue is full.
+    pub fn append_bytes(&mut self, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<Commit> {
+        self.queue(None, kind, ts_ns, payload)
+    }
+
+    /// Queue a record for `stream`. The commit carries the global seq; the
+    /// seq within the stream is assigned by the writer thread.
+    pub fn append_stream(&mut self, stream: &str, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<Commit> {
+        self.queue(Some(stream.to_string()), kind, ts_ns, payload)
+    }
+
+    /// Like `append_bytes`, but while the queue is full the future waits for
+    /// the writer thread to make room instead of blocking the thread, so it
+    /// can be awaited on an async runtime's worker.
+    pub async fn append_bytes_async(&mut self, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<Commit> {
+        self.queue_async(None, kind, ts_ns, payload).await
+    }
+
+    /// `append_stream` that waits for room like `append_bytes_async`.
+    pub async fn append_stream_async(&mut self, stream: &str, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<Commit> {
+        self.queue_async(Some(stream.to_string()), kind, ts_ns, payload).await
+    }
+
+    /// `EventSink::write` that waits for room like `append_bytes_async`.
+    pub async fn write_async<T: Serialize>(&mut self, ev: &T) -> Result<Commit> {
+        let bytes = serde_json::to_vec(ev)?;
+        let ts_ns = EventSink::now_ns(self);
+        self.append_bytes_async("event", ts_ns, &bytes).await
+    }
+
+    fn queue(&mut self, stream: Option<String>, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<Commit> {
+        let cmd = self.count_queued(stream, kind, ts_ns, payload);
+        let sent = self.send(cmd);
+        self.queued(sent)
+    }
+
+    async fn queue_async(&mut self, stream: Option<String>, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<Commit> {
+        let mut pending = Some(self.count_queued(stream, kind, ts_ns, payload));
+        let sent = std::future::poll_fn(|cx| {
+            let Some(tx) = self.tx.as_ref() else {
+                return Poll::Ready(Err(anyhow::anyhow!("group commit writer is closed")));
+            };
+            let mut cmd = pending.take().expect("polled after completion");
+            let mut registered = false;
+            loop {
+                match tx.try_send(cmd) {
+                    Ok(()) => return Poll::Ready(Ok(())),
+                    Err(TrySendError::Disconnected(_)) => return Poll::Ready(Err(self.failure())),
+                    Err(TrySendError::Full(back)) if registered => {
+                        pending = Some(back);
+                        return Poll::Pending;
+                    }
+                    // try once more after registering: the writer may have
+                    // made room before it could see the waker
+                    Err(TrySendError::Full(back)) => {
+                        self.space.register(cx.waker());
+                        registered = true;
+                        cmd = back;
+                    }
+                }
+            }
+        })
+        .await;
+        self.queued(sent)
+    }
+
+    fn count_queued(&self, stream: Option<String>, kind: &str, ts_ns: u64, payload: &[u8]) -> Cmd {
+        // counted before sending so the writer thread never sees it go below zero
+        let depth = self.metrics.queue_depth.fetch_add(1, Ordering::Relaxed) + 1;
+        self.metrics.max_queue_depth.fetch_max(depth, Ordering::Relaxed);
+        Cmd::Append { stream, kind: kind.to_string(), ts_ns, payload: payload.to_vec() }
+    }
+
+    /// Assign the seq of a record handed to the writer thread.
+    fn queued(&mut self, sent: Result<()>) -> Result<Commit> {
+        if let Err(e) = sent {
+            self.metrics.queue_depth.fetch_sub(1, Ordering::Relaxed);
+            return Err(e);
+        }
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        Ok(Commit { seq, durable: self.durable.clone() })
+    }
+
+    /// Wait until everything queued so far is durable.
+    pub fn sync(&mut self) -> Result<()> {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        self.send(Cmd::Sync(ack_tx))?;
+        if ack_rx.recv().is_err() {
+            return Err(self.failure());
+        }
+        Ok(())
+    }
+
+    pub fn metrics(&self) -> GroupCommitMetrics {
+        let m = &self.metrics;
+        GroupCommitMetrics {
+            queue_depth: m.queue_depth.load(Ordering::Relaxed),
+            max_queue_depth: m.max_queue_depth.load(Ordering::Relaxed),
+            records: m.records.load(Ordering::Relaxed),
+            commits: m.commits.load(Ordering::Relaxed),
+            last_fsync_ns: m.last_fsync_ns.load(Ordering::Relaxed),
+            max_fsync_ns: m.max_fsync_ns.load(Ordering::Relaxed),
+            total_fsync_ns: m.total_fsync_ns.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Seq of the last durable record.
+    pub fn durable_seq(&self) -> u64 {
+        self.durable.state.lock().unwrap().durable_seq
+    }
+
+    /// Drain the queue, commit and stop the writer thread.
+    pub fn close(mut self) -> Result<()> {
+        self.shutdown()
+    }
+
+    fn send(&mut self, cmd: Cmd) -> Result<()> {
+        let tx = self.tx.as_ref().context("group commit writer is closed")?;
+        if tx.send(cmd).is_err() {
+            return Err(self.failure());
+        }
+        Ok(())
+    }
+
+    fn failure(&self) -> anyhow::Error {
+        match &self.durable.state.lock().unwrap().failed {
+            Some(e) => anyhow::anyhow!("group commit writer failed: {}", e),
+            None => anyhow::anyhow!("group commit writer stopped"),
+        }
+    }
+
+    fn shutdown(&mut self) -> Result<()> {
+        // closing the channel lets the thread drain what is left and exit
+        self.tx = None;
+        if let Some(t) = self.thread.take() {
+            if t.join().is_err() {
+                anyhow::bail!("group commit thread panicked");
+            }
+        }
+        match &self.durable.state.lock().unwrap().failed {
+            Some(e) => Err(anyhow::anyhow!("group commit writer failed: {}", e)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for GroupCommitWriter {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+impl EventSink for GroupCommitWriter {
+    fn append_bytes(&mut self, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64> {
+        GroupCommitWriter::append_bytes(self, kind, ts_ns, payload).map(|c| c.seq())
+    }
+
+    fn append_stream(&mut self, stream: &str, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64> {
+        GroupCommitWriter::append_stream(self, stream, kind, ts_ns, payload).map(|c| c.seq())
+    }
+
+    /// Queue-only: durability is the writer thread's job, so this does not wait for it.
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    fn now_ns(&self) -> u64 {
+        self.clock.now_ns().max(0) as u64
+    }
+}
+
+fn commit_loop<W: EventSink>(
+    mut sink: W,
+    rx: Receiver<Cmd>,
+    max_batch: usize,
+    durable: &Durable,
+    metrics: &Metrics,
+    space: &QueueSpace,
+) -> Result<()> {
+    let mut acks = Vec::new();
+    // blocks for the first command of a batch; exits once the front-end is gone
+    while let Ok(first) = rx.recv() {
+        space.freed();
+        let mut last_seq = None;
+        let mut n = 0usize;
+        let mut next = Some(first);
+
+        while let Some(cmd) = next.take() {
+            match cmd {
+                Cmd::Append { stream, kind, ts_ns, payload } => {
+                    let seq = match stream {
+                        Some(stream) => sink.append_stream(&stream, &kind, ts_ns, &payload)?,
+                        None => sink.append_bytes(&kind, ts_ns, &payload)?,
+                    };
+                    last_seq = Some(seq);
+                    metrics.queue_depth.fetch_sub(1, Ordering::Relaxed);
+                    n += 1;
+                }
+                Cmd::Sync(ack) => acks.push(ack),
+            }
+            if n < max_batch {
+                next = rx.try_recv().ok();
+                if next.is_some() {
+                    space.freed();
+                }
+            }
+        }
+
+        let started = Instant::now();
+        sink.flush()?;
+        let took = started.elapsed().as_nanos() as u64;
+
+        metrics.commits.fetch_add(1, Ordering::Relaxed);
+        metrics.records.fetch_add(n as u64, Ordering::Relaxed);
+        metrics.last_fsync_ns.store(took, Ordering::Relaxed);
+        metrics.max_fsync_ns.fetch_max(took, Ordering::Relaxed);
+        metrics.total_fsync_ns.fetch_add(took, Ordering::Relaxed);
+
+        if let Some(seq) = last_seq {
+            durable.advance(seq);
+        }
+        for ack in acks.drain(..) {
+            let _ = ack.send(());
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,10 @@
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+pub use el_core::hash::{state_digest, state_hash64, StateDigest, StateHash, StateHasher, STATE_HASH_VERSION};
 
-pub fn stable_hash<T: Hash>(value: &T) -> u64 {
-    let mut h = DefaultHasher::new();
-    value.hash(&mut h);
-    h.finish()
+/// Domain of `stable_hash`.
+pub const STABLE_HASH_DOMAIN: &str = "eventlog.stable_hash";
+
+/// Canonical 64-bit hash of `value`, the same on every toolchain and
+/// platform (see `el_core::hash`).
+pub fn stable_hash<T: StateHash + ?Sized>(value: &T) -> u64 {
+    state_hash64(STABLE_HASH_DOMAIN, value)
 }
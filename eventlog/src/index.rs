@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::EventLogError;
+use crate::frame::{FrameDict, NameDefs};
+use crate::reader::EventLogReader;
+
+/// Index points every `DEFAULT_STRIDE` records unless configured otherwise.
+pub const DEFAULT_STRIDE: u64 = 1024;
+
+/// Side index lives next to the log: `<log>.idx`.
+pub fn index_path(log: impl AsRef<Path>) -> PathBuf {
+    let mut s = log.as_ref().as_os_str().to_owned();
+    s.push(".idx");
+    PathBuf::from(s)
+}
+
+/// One line of the index file (JSON lines, sparse so size does not matter).
+///
+/// Binary logs intern kind/stream names; the index repeats the definitions so
+/// a reader can start decoding in the middle of the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "t", rename_all = "snake_case")]
+pub enum IndexEntry {
+    Point { seq: u64, ts_ns: u64, offset: u64 },
+    Kind { id: u16, name: String },
+    Stream { id: u16, name: String },
+}
+
+/// Byte offset of the record with `seq` (and its `ts_ns`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexPoint {
+    pub seq: u64,
+    pub ts_ns: u64,
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SparseIndex {
+    /// Ordered by seq (and offset).
+    pub points: Vec<IndexPoint>,
+    pub kinds: NameDefs,
+    pub streams: NameDefs,
+}
+
+impl SparseIndex {
+    /// Load `<log>.idx`. A missing index is empty; a torn last line is ignored.
+    pub fn load(log: impl AsRef<Path>) -> Result<Self, EventLogError> {
+        let path = index_path(log);
+        let file = match File::open(&path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(EventLogError::file(path)(e)),
+        };
+
+        let mut idx = Self::default();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            let Ok(entry) = serde_json::from_str::<IndexEntry>(line.trim_end()) else {
+                break;
+            };
+            idx.push(entry);
+        }
+        Ok(idx)
+    }
+
+    fn push(&mut self, entry: IndexEntry) {
+        match entry {
+            IndexEntry::Point { seq, ts_ns, offset } => self.points.push(IndexPoint { seq, ts_ns, offset }),
+            IndexEntry::Kind { id, name } => self.kinds.push((id, name)),
+            IndexEntry::Stream { id, name } => self.streams.push((id, name)),
+        }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = IndexEntry> + '_ {
+        let kinds = self.kinds.iter().map(|(id, name)| IndexEntry::Kind { id: *id, name: name.clone() });
+        let streams = self.streams.iter().map(|(id, name)| IndexEntry::Stream { id: *id, name: name.clone() });
+        let points = self.points.iter().map(|p| IndexEntry::Point { seq: p.seq, ts_ns: p.ts_ns, offset: p.offset });
+        kinds.chain(streams).chain(points)
+    }
+
+    /// Last point at or before `seq`.
+    pub fn floor_seq(&self, seq: u64) -> Option<IndexPoint> {
+        let n = self.points.partition_point(|p| p.seq <= seq);
+        n.checked_sub(1).map(|i| self.points[i])
+    }
+
+    /// Last point at or before `ts_ns`. Assumes envelope timestamps do not go backwards.
+    pub fn floor_ts(&self, ts_ns: u64) -> Option<IndexPoint> {
+        let n = self.points.partition_point(|p| p.ts_ns <= ts_ns);
+        n.checked_sub(1).map(|i| self.points[i])
+    }
+
+    /// Rewrite `<log>.idx` from scratch.
+    pub fn store(&self, log: impl AsRef<Path>) -> Result<(), EventLogError> {
+        let path = index_path(log);
+        let mut out = BufWriter::new(File::create(&path).map_err(EventLogError::file(&path))?);
+        for e in self.entries() {
+            serde_json::to_writer(&mut out, &e)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Scan a log and write a fresh index for it.
+pub fn rebuild(log: impl AsRef<Path>, stride: u64) -> Result<SparseIndex, EventLogError> {
+    let log = log.as_ref();
+    let stride = stride.max(1);
+    let mut r = EventLogReader::open(log)?.with_sealed_payloads();
+    let mut idx = SparseIndex::default();
+
+    let mut n = 0u64;
+    loop {
+        let offset = r.position();
+        let Some((env, _)) = r.next()? else { break };
+        if n.is_multiple_of(stride) {
+            idx.points.push(IndexPoint { seq: env.seq, ts_ns: env.ts_ns, offset });
+        }
+        n += 1;
+    }
+
+    let (kinds, streams) = r.definitions();
+    idx.kinds = kinds;
+    idx.streams = streams;
+    idx.store(log)?;
+    Ok(idx)
+}
+
+/// Appends index entries while `EventLogWriter` writes the log.
+pub(crate) struct IndexWriter {
+    out: BufWriter<File>,
+    stride: u64,
+    since_point: u64,
+}
+
+impl IndexWriter {
+    /// Open the index of a log whose recovered length is `log_len`.
+    /// Points past the end of the log (lost tail, or a log recreated from
+    /// scratch) are dropped; name definitions are taken from the log itself.
+    pub(crate) fn open(log: &Path, stride: u64, log_len: u64, dict: &FrameDict) -> Result<Self, EventLogError> {
+        let mut idx = if log_len == 0 { SparseIndex::default() } else { SparseIndex::load(log)? };
+        idx.points.retain(|p| p.offset < log_len);
+        idx.kinds = dict.kinds().map(|(id, n)| (id, n.to_string())).collect();
+        idx.streams = dict.streams().map(|(id, n)| (id, n.to_string())).collect();
+        idx.store(log)?;
+
+        let path = index_path(log);
+        let file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .map_err(EventLogError::file(&path))?;
+
+        Ok(Self {
+            out: BufWriter::new(file),
+            stride: stride.max(1),
+            // always index the first record written after open
+            since_point: stride.max(1),
+        })
+    }
+
+    fn put(&mut self, e: &IndexEntry) -> Result<(), EventLogError> {
+        serde_json::to_writer(&mut self.out, e)?;
+        self.out.write_all(b"\n")?;
+        Ok(())
+    }
+
+    pub(crate) fn define_kind(&mut self, id: u16, name: &str) -> Result<(), EventLogError> {
+        self.put(&IndexEntry::Kind { id, name: name.to_string() })
+    }
+
+    pub(crate) fn define_stream(&mut self, id: u16, name: &str) -> Result<(), EventLogError> {
+        self.put(&IndexEntry::Stream { id, name: name.to_string() })
+    }
+
+    pub(crate) fn on_record(&mut self, seq: u64, ts_ns: u64, offset: u64) -> Result<(), EventLogError> {
+        if self.since_point >= self.stride {
+            self.put(&IndexEntry::Point { seq, ts_ns, offset })?;
+            self.since_point = 0;
+        }
+        self.since_point += 1;
+        Ok(())
+    }
+
+    pub(crate) fn flush(&mut self) -> Result<(), EventLogError> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -1,9 +1,38 @@
 pub mod snapshot;
 pub mod hash;
 pub mod envelope;
+pub mod error;
+pub mod chain;
+pub mod compress;
+pub mod crypto;
+pub mod frame;
+pub mod index;
 pub mod reader;
 pub mod writer;
+pub mod sink;
+pub mod segment;
+pub mod follow;
+pub mod group;
+pub mod streams;
+pub mod source;
+pub mod merge;
+pub mod slice;
+pub mod salvage;
+pub mod compact;
+pub mod replicate;
+pub mod schema;
+pub mod mmap;
 
 pub use envelope::EventEnvelope;
+pub use error::EventLogError;
+pub use frame::LogFormat;
+pub use index::SparseIndex;
 pub use reader::EventLogReader;
 pub use writer::EventLogWriter;
+pub use sink::EventSink;
+pub use segment::{SegmentedReader, SegmentedWriter};
+pub use follow::LogFollower;
+pub use source::LogSource;
+pub use group::GroupCommitWriter;
+pub use schema::SchemaRegistry;
+pub use mmap::MmapLog;
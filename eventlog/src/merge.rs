@@ -0,0 +1,115 @@
+use anyhow::{Context, Result};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::path::{Path, PathBuf};
+
+use crate::crypto::CopyKeys;
+use crate::envelope::EventEnvelope;
+use crate::source::LogSource;
+use crate::writer::{write_new_log, EventLogWriter, LogRange, WriterOptions};
+
+/// What one input contributed to a merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputReport {
+    pub path: PathBuf,
+    pub records: u64,
+    /// records whose ts_ns is below the previous record of the same input;
+    /// they are merged where they are met, so the output is not ts-sorted there
+    pub out_of_order: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Seq/ts span of the merged log.
+    pub range: LogRange,
+    pub inputs: Vec<InputReport>,
+}
+
+/// One input and the record it is offering to the merge.
+struct Input {
+    source: LogSource,
+    head: Option<(EventEnvelope, Vec<u8>)>,
+    last_ts_ns: u64,
+    report: InputReport,
+}
+
+impl Input {
+    /// Read the next record; checksums (and decompression) are checked by the reader.
+    fn advance(&mut self) -> Result<()> {
+        self.head = self
+            .source
+            .next()
+            .with_context(|| format!("read {:?}", self.report.path))?;
+        if let Some((env, _)) = &self.head {
+            if env.ts_ns < self.last_ts_ns {
+                self.report.out_of_order += 1;
+            }
+            self.last_ts_ns = env.ts_ns;
+        }
+        Ok(())
+    }
+}
+
+/// K-way merge of logs (files or segment directories) into a new log at `out`.
+///
+/// Records are ordered by `ts_ns`; ties go to the input listed first, then to
+/// the lower seq, so the same inputs always give the same log. Records are
+/// renumbered; each keeps its stream, and its seq in the input as `origin_seq`.
+/// A failed merge (e.g. a checksum mismatch in an input) removes the output.
+///
+/// Sealed inputs need `keys`; without them a sealed record fails the merge
+/// rather than being copied in plaintext or unreadable.
+pub fn merge(
+    inputs: &[impl AsRef<Path>],
+    out: impl AsRef<Path>,
+    opts: WriterOptions,
+    keys: Option<&CopyKeys>,
+) -> Result<MergeReport> {
+    let out = out.as_ref();
+    write_new_log(out, || merge_into(inputs, out, opts, keys))
+}
+
+fn merge_into(inputs: &[impl AsRef<Path>], out: &Path, opts: WriterOptions, keys: Option<&CopyKeys>) -> Result<MergeReport> {
+    let mut sources = Vec::with_capacity(inputs.len());
+    for path in inputs {
+        let path = path.as_ref();
+        let mut source = LogSource::open(path)?;
+        if let Some(k) = keys {
+            source = source.with_keys(k.keys.clone());
+        }
+        let mut input = Input {
+            source,
+            head: None,
+            last_ts_ns: 0,
+            report: InputReport { path: path.to_path_buf(), records: 0, out_of_order: 0 },
+        };
+        input.advance()?;
+        sources.push(input);
+    }
+
+    let mut w = EventLogWriter::open_with(out, "el:merge", opts)?;
+    if let Some(k) = keys {
+        w = w.with_encryption(&*k.keys, k.key_id)?;
+    }
+    let mut heap = BinaryHeap::new();
+    for (i, input) in sources.iter().enumerate() {
+        if let Some((env, _)) = &input.head {
+            heap.push(Reverse((env.ts_ns, i, env.seq)));
+        }
+    }
+
+    while let Some(Reverse((_, i, _))) = heap.pop() {
+        let input = &mut sources[i];
+        let (env, payload) = input.head.take().expect("queued input has a record");
+        w.append_copy(&env, &payload)?;
+        input.report.records += 1;
+
+        input.advance()?;
+        if let Some((env, _)) = &input.head {
+            heap.push(Reverse((env.ts_ns, i, env.seq)));
+        }
+    }
+    w.flush()?;
+
+    Ok(MergeReport { range: w.range(), inputs: sources.into_iter().map(|s| s.report).collect() })
+}
@@ -0,0 +1,444 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use crate::chain::{self, Digest};
+use crate::compress::{self, Codec, PayloadDecompressor};
+use crate::envelope::EventEnvelope;
+use crate::error::EventLogError;
+use crate::frame::{self, FramePrefix, FrameRef, LogFormat, NameDefs};
+use crate::index::SparseIndex;
+
+/// A log file mapped read-only, for scanning history without a copy per record.
+///
+/// Binary logs hand out names and uncompressed payloads borrowed from the
+/// map; JSON lines and compressed payloads still have to be decoded. The log
+/// can be split into byte ranges that are scanned independently (`split`,
+/// `par_scan`). Payloads are checked against their checksum but handed out as
+/// stored: there is no schema upcasting here, and an encrypted payload is a
+/// `MissingKey` error.
+///
+/// A shared lock is held while the file is mapped, so it cannot be truncated
+/// under the map: opening fails while a writer has the log, and writers,
+/// `compact` and repair wait until this is dropped.
+pub struct MmapLog {
+    path: PathBuf,
+    /// holds the lock
+    _file: File,
+    /// `None` for an empty file, which cannot be mapped
+    map: Option<Mmap>,
+    format: LogFormat,
+}
+
+/// Part of a log starting on a record boundary, with the names defined before it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MmapRange {
+    pub start: u64,
+    pub end: u64,
+    kinds: NameDefs,
+    streams: NameDefs,
+}
+
+/// One record, borrowing from the map where the stored form allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MmapRecord<'a> {
+    /// byte offset the record starts at
+    pub offset: u64,
+    pub seq: u64,
+    pub stream_seq: Option<u64>,
+    pub origin_seq: Option<u64>,
+    pub ts_ns: u64,
+    pub stream: Cow<'a, str>,
+    pub kind: Cow<'a, str>,
+    pub checksum: u32,
+    pub chain: Option<Digest>,
+    pub codec: Option<Codec>,
+    /// uncompressed; owned only when it had to be decoded
+    pub payload: Cow<'a, [u8]>,
+}
+
+impl MmapRecord<'_> {
+    /// Seq of this record within its stream.
+    pub fn seq_in_stream(&self) -> u64 {
+        self.stream_seq.unwrap_or(self.seq)
+    }
+
+    /// The envelope `EventLogReader` would hand out for this record.
+    pub fn envelope(&self) -> EventEnvelope {
+        EventEnvelope {
+            seq: self.seq,
+            stream_seq: self.stream_seq,
+            origin_seq: self.origin_seq,
+            ts_ns: self.ts_ns,
+            stream: self.stream.to_string(),
+            kind: self.kind.to_string(),
+            payload_b64: String::new(),
+            checksum: self.checksum,
+            chain: self.chain.as_ref().map(chain::to_hex),
+            codec: self.codec.map(|c| c.name()),
+            key_id: None,
+        }
+    }
+}
+
+fn data_start(format: LogFormat) -> u64 {
+    match format {
+        LogFormat::JsonLines => 0,
+        LogFormat::Binary => frame::MAGIC.len() as u64,
+    }
+}
+
+impl MmapLog {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("open {:?}", path))?;
+        file.try_lock_shared().with_context(|| format!("{:?} is open for writing", path))?;
+        let map = match file.metadata()?.len() {
+            0 => None,
+            // SAFETY: the shared lock keeps writers, compaction and repair
+            // from resizing the file while it is mapped
+            _ => Some(unsafe { Mmap::map(&file) }.with_context(|| format!("mmap {:?}", path))?),
+        };
+        let format = LogFormat::detect(map.as_deref().unwrap_or_default());
+        Ok(Self { path: path.to_path_buf(), _file: file, map, format })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> LogFormat {
+        self.format
+    }
+
+    /// Mapped bytes, header included.
+    pub fn bytes(&self) -> &[u8] {
+        self.map.as_deref().unwrap_or_default()
+    }
+
+    /// The whole log as one range.
+    pub fn full_range(&self) -> MmapRange {
+        let start = data_start(self.format).min(self.bytes().len() as u64);
+        MmapRange { start, end: self.bytes().len() as u64, ..Default::default() }
+    }
+
+    /// Records of the whole log, in order.
+    pub fn records(&self) -> MmapRecords<'_> {
+        MmapRecords::new(self, &self.full_range())
+    }
+
+    /// Records starting inside `range`, in order.
+    pub fn records_in<'a>(&'a self, range: &'a MmapRange) -> MmapRecords<'a> {
+        let mut it = MmapRecords::new(self, range);
+        it.kinds = range.kinds.iter().map(|(_, n)| n.as_str()).collect();
+        it.streams = range.streams.iter().map(|(_, n)| n.as_str()).collect();
+        it
+    }
+
+    /// Cut the log into at most `n` ranges of roughly equal size.
+    ///
+    /// JSON lines are cut at line ends. Binary logs are cut at `<log>.idx`
+    /// points, which also carry the names defined before them; without
+    /// enough points the frames are walked once (length prefixes and name
+    /// definitions only).
+    pub fn split(&self, n: usize) -> Result<Vec<MmapRange>> {
+        let full = self.full_range();
+        let n = n.max(1) as u64;
+        let size = full.end - full.start;
+        if n == 1 || size == 0 {
+            return Ok(vec![full]);
+        }
+        let targets: Vec<u64> = (1..n).map(|i| full.start + size * i / n).collect();
+
+        let mut starts = match self.format {
+            LogFormat::JsonLines => self.line_starts(&targets),
+            LogFormat::Binary => {
+                let idx = SparseIndex::load(&self.path)?;
+                let points: Vec<u64> = idx.points.iter().map(|p| p.offset).filter(|o| *o > full.start && *o < full.end).collect();
+                if points.len() + 1 >= n as usize {
+                    let cuts = targets.iter().filter_map(|t| points.get(points.partition_point(|o| o < t)).copied());
+                    cuts.map(|start| (start, idx.kinds.clone(), idx.streams.clone())).collect()
+                } else {
+                    self.frame_starts(&targets)
+                }
+            }
+        };
+        starts.dedup_by_key(|s| s.0);
+
+        let mut ranges = Vec::with_capacity(starts.len() + 1);
+        let mut prev = full;
+        for (start, kinds, streams) in starts {
+            if start <= prev.start || start >= prev.end {
+                continue;
+            }
+            let end = std::mem::replace(&mut prev.end, start);
+            ranges.push(std::mem::replace(&mut prev, MmapRange { start, end, kinds, streams }));
+        }
+        ranges.push(prev);
+        Ok(ranges)
+    }
+
+    /// First line start at or after each target.
+    fn line_starts(&self, targets: &[u64]) -> Vec<(u64, NameDefs, NameDefs)> {
+        let bytes = self.bytes();
+        targets
+            .iter()
+            .filter_map(|&t| {
+                let from = (t as usize).saturating_sub(1);
+                let nl = bytes[from..].iter().position(|b| *b == b'\n')?;
+                Some(((from + nl + 1) as u64, NameDefs::new(), NameDefs::new()))
+            })
+            .collect()
+    }
+
+    /// First frame start at or after each target, with the names defined before it.
+    fn frame_starts(&self, targets: &[u64]) -> Vec<(u64, NameDefs, NameDefs)> {
+        let bytes = self.bytes();
+        let (mut kinds, mut streams) = (NameDefs::new(), NameDefs::new());
+        let mut out = Vec::with_capacity(targets.len());
+        let mut pos = data_start(self.format) as usize;
+        while out.len() < targets.len() && pos + 4 <= bytes.len() {
+            if pos as u64 >= targets[out.len()] {
+                out.push((pos as u64, kinds.clone(), streams.clone()));
+                continue;
+            }
+            let len = u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+            let Some(body) = bytes.get(pos + 4..pos + 4 + len) else { break };
+            match frame::frame_prefix(body) {
+                Some(FramePrefix::Kind { .. } | FramePrefix::Stream { .. }) => match frame::decode_body_ref(body) {
+                    Ok(FrameRef::Kind { id, name }) => kinds.push((id, name.to_string())),
+                    Ok(FrameRef::Stream { id, name }) => streams.push((id, name.to_string())),
+                    // left for the range that holds it to report
+                    _ => break,
+                },
+                Some(FramePrefix::Record { .. }) => {}
+                None => break,
+            }
+            pos += 4 + len;
+        }
+        out
+    }
+
+    /// Scan the log on up to `threads` threads, one range each.
+    ///
+    /// Every range starts with a fresh `init()` state and feeds its records to
+    /// `f` in order; the states come back in range order, for the caller to
+    /// merge. The first error from any range is returned.
+    pub fn par_scan<S, I, F>(&self, threads: usize, init: I, f: F) -> Result<Vec<S>>
+    where
+        S: Send,
+        I: Fn() -> S + Sync,
+        F: Fn(&mut S, MmapRecord<'_>) -> Result<()> + Sync,
+    {
+        let ranges = self.split(threads)?;
+        std::thread::scope(|s| {
+            let scans: Vec<_> = ranges
+                .iter()
+                .map(|range| {
+                    s.spawn(|| {
+                        let mut state = init();
+                        for rec in self.records_in(range) {
+                            f(&mut state, rec?)?;
+                        }
+                        Ok(state)
+                    })
+                })
+                .collect();
+            scans.into_iter().map(|h| h.join().expect("scan thread panicked")).collect()
+        })
+    }
+}
+
+/// Records of one range; ends after the first error.
+pub struct MmapRecords<'a> {
+    bytes: &'a [u8],
+    format: LogFormat,
+    pos: usize,
+    end: usize,
+    /// names by id, borrowed from the range or from definition frames in the map
+    kinds: Vec<&'a str>,
+    streams: Vec<&'a str>,
+    decompressor: PayloadDecompressor,
+}
+
+fn define<'a>(names: &mut Vec<&'a str>, id: u16, name: &'a str) -> Result<(), String> {
+    // a range is primed with names that may be defined again inside it
+    if names.get(id as usize) == Some(&name) {
+        return Ok(());
+    }
+    if id as usize != names.len() {
+        return Err("out of order name definition".to_string());
+    }
+    names.push(name);
+    Ok(())
+}
+
+impl<'a> MmapRecords<'a> {
+    /// Without the names of `range`; the caller primes them.
+    fn new(log: &'a MmapLog, range: &MmapRange) -> Self {
+        let bytes = log.bytes();
+        Self {
+            bytes,
+            format: log.format,
+            pos: range.start as usize,
+            end: (range.end as usize).min(bytes.len()),
+            kinds: Vec::new(),
+            streams: Vec::new(),
+            decompressor: PayloadDecompressor::new(compress::dict_dir(&log.path)),
+        }
+    }
+
+    fn next_json(&mut self) -> Result<Option<MmapRecord<'a>>, EventLogError> {
+        loop {
+            if self.pos >= self.end {
+                return Ok(None);
+            }
+            let offset = self.pos as u64;
+            let rest = &self.bytes[self.pos..];
+            let (line, complete) = match rest.iter().position(|b| *b == b'\n') {
+                Some(n) => (&rest[..n], true),
+                None => (rest, false),
+            };
+            self.pos += line.len() + complete as usize;
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+
+            let env: EventEnvelope = match serde_json::from_slice(line) {
+                Ok(env) => env,
+                // an unterminated last line is a torn write, not a bad record
+                Err(_) if !complete => return Err(EventLogError::TruncatedTail { offset }),
+                Err(e) => return Err(EventLogError::MalformedEnvelope { offset, reason: e.to_string() }),
+            };
+            let malformed = |reason: String| EventLogError::MalformedEnvelope { offset, reason };
+            let chain = match &env.chain {
+                Some(hex) => Some(chain::from_hex(hex).ok_or_else(|| malformed(format!("bad chain digest {:?}", hex)))?),
+                None => None,
+            };
+            let codec = match &env.codec {
+                Some(name) => Some(Codec::parse(name).ok_or_else(|| EventLogError::Payload {
+                    seq: env.seq,
+                    offset,
+                    reason: format!("unknown codec {:?}", name),
+                })?),
+                None => None,
+            };
+            let stored = base64::engine::general_purpose::STANDARD
+                .decode(&env.payload_b64)
+                .map_err(|source| EventLogError::Base64 { seq: env.seq, offset, source })?;
+            if let Some(key_id) = env.key_id {
+                return Err(EventLogError::MissingKey { seq: env.seq, key_id });
+            }
+            let payload = match codec {
+                Some(c) => self.decompress(c, &stored, env.seq, offset)?,
+                None => stored,
+            };
+            return Ok(Some(MmapRecord {
+                offset,
+                seq: env.seq,
+                stream_seq: env.stream_seq,
+                origin_seq: env.origin_seq,
+                ts_ns: env.ts_ns,
+                stream: Cow::Owned(env.stream),
+                kind: Cow::Owned(env.kind),
+                checksum: env.checksum,
+                chain,
+                codec,
+                payload: Cow::Owned(payload),
+            }));
+        }
+    }
+
+    fn next_binary(&mut self) -> Result<Option<MmapRecord<'a>>, EventLogError> {
+        let bytes = self.bytes;
+        loop {
+            if self.pos >= self.end {
+                return Ok(None);
+            }
+            let offset = self.pos as u64;
+            let Some(len) = bytes.get(self.pos..self.pos + 4) else {
+                return Err(EventLogError::TruncatedTail { offset });
+            };
+            let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+            let Some(body) = bytes.get(self.pos + 4..self.pos + 4 + len) else {
+                return Err(EventLogError::TruncatedTail { offset });
+            };
+            self.pos += 4 + len;
+
+            let malformed = |reason: String| EventLogError::MalformedEnvelope { offset, reason };
+            let (head, stored) = match frame::decode_body_ref(body).map_err(|e| malformed(e.to_string()))? {
+                FrameRef::Record { head, payload } => (head, payload),
+                FrameRef::Kind { id, name } => {
+                    define(&mut self.kinds, id, name).map_err(malformed)?;
+                    continue;
+                }
+                FrameRef::Stream { id, name } => {
+                    define(&mut self.streams, id, name).map_err(malformed)?;
+                    continue;
+                }
+            };
+
+            let seq = head.seq;
+            let kind = *self
+                .kinds
+                .get(head.kind_id as usize)
+                .ok_or_else(|| malformed(format!("undefined kind id {} at seq={}", head.kind_id, seq)))?;
+            let stream = *self
+                .streams
+                .get(head.stream_id as usize)
+                .ok_or_else(|| malformed(format!("undefined stream id {} at seq={}", head.stream_id, seq)))?;
+            if let Some(key_id) = head.key {
+                return Err(EventLogError::MissingKey { seq, key_id });
+            }
+            let payload = match head.codec {
+                Some(c) => Cow::Owned(self.decompress(c, stored, seq, offset)?),
+                None => Cow::Borrowed(stored),
+            };
+            return Ok(Some(MmapRecord {
+                offset,
+                seq,
+                stream_seq: head.stream_seq,
+                origin_seq: head.origin_seq,
+                ts_ns: head.ts_ns,
+                stream: Cow::Borrowed(stream),
+                kind: Cow::Borrowed(kind),
+                checksum: head.checksum,
+                chain: head.chain,
+                codec: head.codec,
+                payload,
+            }));
+        }
+    }
+
+    fn decompress(&mut self, codec: Codec, data: &[u8], seq: u64, offset: u64) -> Result<Vec<u8>, EventLogError> {
+        self.decompressor
+            .decompress(codec, data)
+            .map_err(|e| EventLogError::Payload { seq, offset, reason: format!("{:#}", e) })
+    }
+}
+
+impl<'a> Iterator for MmapRecords<'a> {
+    type Item = Result<MmapRecord<'a>, EventLogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rec = match self.format {
+            LogFormat::JsonLines => self.next_json(),
+            LogFormat::Binary => self.next_binary(),
+        };
+        let rec = rec.and_then(|rec| {
+            let Some(rec) = rec else { return Ok(None) };
+            let computed = crc32fast::hash(&rec.payload);
+            if computed != rec.checksum {
+                return Err(EventLogError::ChecksumMismatch { seq: rec.seq, offset: rec.offset, stored: rec.checksum, computed });
+            }
+            Ok(Some(rec))
+        });
+        if rec.is_err() {
+            self.pos = self.end;
+        }
+        rec.transpose()
+    }
+}
@@ -1,11 +1,19 @@
-use anyhow::{Context, Result};
 use crc32fast::Hasher;
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use base64::Engine;
+use crate::chain;
+use crate::compress::{self, Codec, PayloadDecompressor};
+use crate::crypto::{KeyId, OpenError, PayloadOpener, SharedKeys};
 use crate::envelope::EventEnvelope;
+use crate::error::EventLogError;
+use crate::frame::{self, Frame, FrameDict, LogFormat, NameDefs, ReadOutcome};
+use crate::index::{IndexPoint, SparseIndex};
+use crate::schema::SchemaRegistry;
 
 fn crc32(bytes: &[u8]) -> u32 {
     let mut h = Hasher::new();
@@ -13,40 +21,373 @@ fn crc32(bytes: &[u8]) -> u32 {
     h.finalize()
 }
 
+/// A record as read: the offset it starts at, envelope, payload and
+/// whether the payload stays sealed.
+type RawRecord = (u64, EventEnvelope, Vec<u8>, bool);
+
 pub struct EventLogReader {
+    path: PathBuf,
     r: BufReader<File>,
     line_buf: String,
+    format: LogFormat,
+    dict: FrameDict,
+    /// byte offset of the next unread frame / line
+    offset: u64,
+    /// record found by a seek, handed out by the next `next()`
+    pending: Option<(EventEnvelope, Vec<u8>)>,
+    /// `<log>.idx`, loaded on first seek
+    index: Option<SparseIndex>,
+    decompressor: PayloadDecompressor,
+    /// only hand out records of these streams; `None` for all
+    streams: Option<HashSet<String>>,
+    /// checks kinds and upcasts payloads; `None` hands out payloads as stored
+    schemas: Option<Arc<SchemaRegistry>>,
+    /// decrypts sealed payloads; `None` without keys
+    opener: Option<PayloadOpener>,
+    /// hand out payloads that cannot be decrypted as stored instead of failing
+    sealed_ok: bool,
 }
 
 impl EventLogReader {
-    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
-        let file = File::open(path.as_ref())
-            .with_context(|| format!("open {:?}", path.as_ref()))?;
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, EventLogError> {
+        let file = File::open(path.as_ref()).map_err(EventLogError::file(path.as_ref()))?;
+        let mut r = BufReader::new(file);
+
+        // format is detected from the header; JSON lines has none
+        let format = LogFormat::detect(r.fill_buf()?);
+        if format == LogFormat::Binary {
+            r.consume(frame::MAGIC.len());
+        }
+
         Ok(Self {
-            r: BufReader::new(file),
+            path: path.as_ref().to_path_buf(),
+            r,
             line_buf: String::new(),
+            format,
+            dict: FrameDict::default(),
+            offset: data_start(format),
+            pending: None,
+            index: None,
+            decompressor: PayloadDecompressor::new(compress::dict_dir(path.as_ref())),
+            streams: None,
+            schemas: None,
+            opener: None,
+            sealed_ok: false,
         })
     }
 
-    pub fn next(&mut self) -> Result<Option<(EventEnvelope, Vec<u8>)>> {
-        self.line_buf.clear();
-        let n = self.r.read_line(&mut self.line_buf)?;
-        if n == 0 {
-            return Ok(None);
+    /// Only return records of `streams`; the others are skipped without
+    /// decoding their payload. Seeks still position by global seq / ts.
+    pub fn with_streams<S: Into<String>>(mut self, streams: impl IntoIterator<Item = S>) -> Self {
+        self.streams = Some(streams.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Reject kinds and versions `schemas` does not know, and hand out
+    /// payloads migrated to their kind's current version.
+    pub fn with_schemas(mut self, schemas: impl Into<Arc<SchemaRegistry>>) -> Self {
+        self.schemas = Some(schemas.into());
+        self
+    }
+
+    /// Decrypt sealed payloads with keys from `keys`. Without keys (or
+    /// without the one a record names) reading a sealed record fails with
+    /// `EventLogError::MissingKey`, unless `with_sealed_payloads` is set.
+    pub fn with_keys(mut self, keys: SharedKeys) -> Self {
+        self.opener = Some(PayloadOpener::new(keys));
+        self
+    }
+
+    /// Hand out encrypted payloads that cannot be decrypted as stored
+    /// (nonce, ciphertext and tag) instead of failing, for tools that only
+    /// need framing and seqs. Their checksum covers the plaintext, so it is
+    /// not checked, and schemas are not applied to them.
+    pub fn with_sealed_payloads(mut self) -> Self {
+        self.sealed_ok = true;
+        self
+    }
+
+    fn wants(&self, stream: &str) -> bool {
+        self.streams.as_ref().is_none_or(|s| s.contains(stream))
+    }
+
+    pub fn format(&self) -> LogFormat {
+        self.format
+    }
+
+    /// Byte offset of the record the next `next()` returns.
+    /// Only meaningful between records (not after a seek, which buffers one).
+    pub fn position(&self) -> u64 {
+        self.offset
+    }
+
+    /// Kind and stream names seen so far (binary logs only; empty for JSON lines).
+    pub fn definitions(&self) -> (NameDefs, NameDefs) {
+        let kinds = self.dict.kinds().map(|(id, n)| (id, n.to_string())).collect();
+        let streams = self.dict.streams().map(|(id, n)| (id, n.to_string())).collect();
+        (kinds, streams)
+    }
+
+    /// Position so that the next record returned is the first with `seq >= target`.
+    /// Uses `<log>.idx` when present, otherwise scans from the start.
+    pub fn seek_seq(&mut self, target: u64) -> Result<(), EventLogError> {
+        let point = self.index()?.floor_seq(target);
+        self.seek_scan(point, |env| env.seq >= target)
+    }
+
+    /// Position so that the next record returned is the first with `ts_ns >= target`.
+    pub fn seek_ts(&mut self, target_ns: u64) -> Result<(), EventLogError> {
+        let point = self.index()?.floor_ts(target_ns);
+        self.seek_scan(point, |env| env.ts_ns >= target_ns)
+    }
+
+    fn index(&mut self) -> Result<&SparseIndex, EventLogError> {
+        if self.index.is_none() {
+            self.index = Some(SparseIndex::load(&self.path)?);
+        }
+        Ok(self.index.as_ref().unwrap())
+    }
+
+    fn seek_scan(
+        &mut self,
+        point: Option<IndexPoint>,
+        done: impl Fn(&EventEnvelope) -> bool,
+    ) -> Result<(), EventLogError> {
+        let offset = point.map(|p| p.offset).unwrap_or(data_start(self.format));
+        self.r.seek(SeekFrom::Start(offset))?;
+        self.offset = offset;
+        self.pending = None;
+
+        if self.format == LogFormat::Binary {
+            // the definitions may sit before `offset`; the index repeats them
+            let idx = self.index.as_ref().expect("index loaded before seek");
+            for (id, name) in &idx.kinds {
+                self.dict.observe(&Frame::Kind { id: *id, name: name.clone() })?;
+            }
+            for (id, name) in &idx.streams {
+                self.dict.observe(&Frame::Stream { id: *id, name: name.clone() })?;
+            }
+        }
+
+        // stop quietly at a partial tail; the file may still be growing
+        while let Some(rec) = self.next_complete()? {
+            if done(&rec.0) {
+                self.pending = Some(rec);
+                break;
+            }
         }
+        Ok(())
+    }
 
-        let env: EventEnvelope =
-            serde_json::from_str(self.line_buf.trim_end()).context("parse envelope json")?;
+    pub fn next(&mut self) -> Result<Option<(EventEnvelope, Vec<u8>)>, EventLogError> {
+        self.read_record(false)
+    }
 
-        let payload = base64::engine::general_purpose::STANDARD
+    /// Like `next`, but for a file that is still being appended to: a partial
+    /// trailing record is not an error. It returns `None` and leaves the
+    /// position at the start of that record so a later call picks it up.
+    pub fn next_complete(&mut self) -> Result<Option<(EventEnvelope, Vec<u8>)>, EventLogError> {
+        self.read_record(true)
+    }
+
+    fn read_record(&mut self, partial_ok: bool) -> Result<Option<(EventEnvelope, Vec<u8>)>, EventLogError> {
+        if let Some(rec) = self.pending.take() {
+            return Ok(Some(rec));
+        }
+
+        let (offset, env, payload, sealed) = match self.format {
+            LogFormat::JsonLines => match self.next_json(partial_ok)? {
+                Some(x) => x,
+                None => return Ok(None),
+            },
+            LogFormat::Binary => match self.next_binary(partial_ok)? {
+                Some(x) => x,
+                None => return Ok(None),
+            },
+        };
+
+        if sealed {
+            return Ok(Some((env, payload)));
+        }
+        let computed = crc32(&payload);
+        if computed != env.checksum {
+            return Err(EventLogError::ChecksumMismatch { seq: env.seq, offset, stored: env.checksum, computed });
+        }
+
+        match &self.schemas {
+            Some(s) => {
+                let payload = s.upcast(&env, payload)?;
+                Ok(Some((env, payload)))
+            }
+            None => Ok(Some((env, payload))),
+        }
+    }
+
+    /// Drop anything read past `offset` (an incomplete tail).
+    fn rewind(&mut self) -> io::Result<()> {
+        self.r.seek(SeekFrom::Start(self.offset))?;
+        Ok(())
+    }
+
+    /// Continue reading at `offset`, which must be the start of a line / frame.
+    pub(crate) fn reposition(&mut self, offset: u64) -> io::Result<()> {
+        self.offset = offset;
+        self.pending = None;
+        self.rewind()
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn dict(&self) -> &FrameDict {
+        &self.dict
+    }
+
+    /// Stored bytes to payload: decrypt, then decompress. The flag is set
+    /// when the payload stays sealed (see `with_sealed_payloads`).
+    fn decode_payload(
+        &mut self,
+        env: &EventEnvelope,
+        codec: Option<Codec>,
+        stored: Vec<u8>,
+        offset: u64,
+    ) -> Result<(Vec<u8>, bool), EventLogError> {
+        let seq = env.seq;
+        let plain = match env.key_id {
+            Some(key_id) => match self.decrypt(key_id, seq, env.checksum, &stored, offset)? {
+                Some(p) => p,
+                None => return Ok((stored, true)),
+            },
+            None => stored,
+        };
+        let payload = match codec {
+            Some(c) => self
+                .decompressor
+                .decompress(c, &plain)
+                .map_err(|e| EventLogError::Payload { seq, offset, reason: format!("{:#}", e) })?,
+            None => plain,
+        };
+        Ok((payload, false))
+    }
+
+    fn decrypt(&mut self, key_id: KeyId, seq: u64, checksum: u32, sealed: &[u8], offset: u64) -> Result<Option<Vec<u8>>, EventLogError> {
+        let opened = match &mut self.opener {
+            Some(o) => o.open(key_id, seq, checksum, sealed),
+            None => Err(OpenError::MissingKey),
+        };
+        match opened {
+            Ok(p) => Ok(Some(p)),
+            Err(OpenError::MissingKey) if self.sealed_ok => Ok(None),
+            Err(OpenError::MissingKey) => Err(EventLogError::MissingKey { seq, key_id }),
+            Err(OpenError::Failed(reason)) => Err(EventLogError::Payload { seq, offset, reason }),
+        }
+    }
+
+    /// Next record of a wanted stream.
+    fn next_json(&mut self, partial_ok: bool) -> Result<Option<RawRecord>, EventLogError> {
+        let (offset, env) = loop {
+            self.line_buf.clear();
+            let n = self.r.read_line(&mut self.line_buf)?;
+            if n == 0 {
+                return Ok(None);
+            }
+            let complete = self.line_buf.ends_with('\n');
+            if partial_ok && !complete {
+                self.rewind()?;
+                return Ok(None);
+            }
+            let offset = self.offset;
+            self.offset += n as u64;
+
+            let env: EventEnvelope = match serde_json::from_str(self.line_buf.trim_end()) {
+                Ok(env) => env,
+                // an unterminated last line is a torn write, not a bad record
+                Err(_) if !complete => return Err(EventLogError::TruncatedTail { offset }),
+                Err(e) => return Err(EventLogError::MalformedEnvelope { offset, reason: e.to_string() }),
+            };
+            if self.wants(&env.stream) {
+                break (offset, env);
+            }
+        };
+
+        let codec = match &env.codec {
+            Some(name) => Some(Codec::parse(name).ok_or_else(|| EventLogError::Payload {
+                seq: env.seq,
+                offset,
+                reason: format!("unknown codec {:?}", name),
+            })?),
+            None => None,
+        };
+        let stored = base64::engine::general_purpose::STANDARD
             .decode(&env.payload_b64)
-            .context("base64 decode payload")?;
+            .map_err(|source| EventLogError::Base64 { seq: env.seq, offset, source })?;
+        let (payload, sealed) = self.decode_payload(&env, codec, stored, offset)?;
+
+        Ok(Some((offset, env, payload, sealed)))
+    }
+
+    /// Next record of a wanted stream.
+    fn next_binary(&mut self, partial_ok: bool) -> Result<Option<RawRecord>, EventLogError> {
+        loop {
+            let offset = self.offset;
+            let outcome = frame::read_frame(&mut self.r).map_err(|e| match e.kind() {
+                io::ErrorKind::InvalidData => EventLogError::MalformedEnvelope { offset, reason: e.to_string() },
+                _ => EventLogError::Io(e),
+            })?;
+            let f = match outcome {
+                ReadOutcome::Eof => return Ok(None),
+                ReadOutcome::Truncated if partial_ok => {
+                    self.rewind()?;
+                    return Ok(None);
+                }
+                ReadOutcome::Truncated => return Err(EventLogError::TruncatedTail { offset }),
+                ReadOutcome::Frame(f, size) => {
+                    self.offset += size;
+                    f
+                }
+            };
 
-        let checksum = crc32(&payload);
-        if checksum != env.checksum {
-            anyhow::bail!("checksum mismatch: seq={}", env.seq);
+            let malformed = |reason: String| EventLogError::MalformedEnvelope { offset, reason };
+            let Frame::Record { seq, stream_seq, origin_seq, ts_ns, kind_id, stream_id, checksum, chain, codec, key, payload } = f else {
+                self.dict.observe(&f).map_err(|e| malformed(e.to_string()))?;
+                continue;
+            };
+
+            let kind = self
+                .dict
+                .kind(kind_id)
+                .ok_or_else(|| malformed(format!("undefined kind id {} at seq={}", kind_id, seq)))?;
+            let stream = self
+                .dict
+                .stream(stream_id)
+                .ok_or_else(|| malformed(format!("undefined stream id {} at seq={}", stream_id, seq)))?;
+            if !self.wants(stream) {
+                continue;
+            }
+
+            let env = EventEnvelope {
+                seq,
+                stream_seq,
+                origin_seq,
+                ts_ns,
+                stream: stream.to_string(),
+                kind: kind.to_string(),
+                payload_b64: String::new(),
+                checksum,
+                chain: chain.as_ref().map(chain::to_hex),
+                codec: codec.map(|c| c.name()),
+                key_id: key,
+            };
+            let (payload, sealed) = self.decode_payload(&env, codec, payload, offset)?;
+            return Ok(Some((offset, env, payload, sealed)));
         }
+    }
+}
 
-        Ok(Some((env, payload)))
+fn data_start(format: LogFormat) -> u64 {
+    match format {
+        LogFormat::JsonLines => 0,
+        LogFormat::Binary => frame::MAGIC.len() as u64,
     }
 }
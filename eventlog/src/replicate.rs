@@ -0,0 +1,436 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use crate::compact::Anchor;
+use crate::envelope::EventEnvelope;
+use crate::follow::{LogFollower, DEFAULT_POLL_INTERVAL};
+use crate::frame::{self, LogFormat};
+use crate::writer::{Durability, EventLogWriter, WriterOptions};
+
+/// Records a replica writes before it fsyncs and acks, even if more are waiting.
+pub const DEFAULT_ACK_EVERY: u64 = 1024;
+
+/// One line of the replication protocol (JSON lines in both directions).
+///
+/// The replica opens with `Hello`; the primary answers with `Start` before
+/// its first record and then streams `Record`s; the replica sends `Ack` for
+/// every seq it has fsynced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "t", rename_all = "snake_case")]
+enum Message {
+    Hello { next_seq: u64 },
+    Start { format: LogFormat, anchor: Option<Anchor> },
+    /// payload in `payload_b64`, uncompressed; a sealed one (`key_id` set)
+    /// as stored, with its codec
+    Record { env: EventEnvelope },
+    Ack { seq: u64 },
+}
+
+fn send(out: &mut impl Write, msg: &Message) -> Result<()> {
+    serde_json::to_writer(&mut *out, msg)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Next message, or `None` when the peer closed the connection.
+fn receive(r: &mut impl BufRead, line: &mut String) -> Result<Option<Message>> {
+    line.clear();
+    if r.read_line(line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(line.trim_end()).context("malformed replication message")?))
+}
+
+/// Where a primary listens: `host:port`, or `unix:<path>` for a Unix socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl std::str::FromStr for Endpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.strip_prefix("unix:") {
+            #[cfg(unix)]
+            Some(path) => Ok(Endpoint::Unix(path.into())),
+            #[cfg(not(unix))]
+            Some(_) => anyhow::bail!("unix sockets are not supported here"),
+            None => Ok(Endpoint::Tcp(s.to_string())),
+        }
+    }
+}
+
+impl Endpoint {
+    pub fn listen(&self) -> Result<Listener> {
+        match self {
+            Endpoint::Tcp(addr) => Ok(Listener::Tcp(TcpListener::bind(addr).with_context(|| format!("bind {}", addr))?)),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => {
+                // a socket file left by an earlier primary
+                let _ = std::fs::remove_file(path);
+                Ok(Listener::Unix(UnixListener::bind(path).with_context(|| format!("bind {:?}", path))?))
+            }
+        }
+    }
+
+    pub fn connect(&self) -> Result<Connection> {
+        match self {
+            Endpoint::Tcp(addr) => {
+                let s = TcpStream::connect(addr).with_context(|| format!("connect {}", addr))?;
+                s.set_nodelay(true)?;
+                Ok(Connection::Tcp(s))
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(path) => {
+                Ok(Connection::Unix(UnixStream::connect(path).with_context(|| format!("connect {:?}", path))?))
+            }
+        }
+    }
+}
+
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    pub fn accept(&self) -> Result<Connection> {
+        match self {
+            Listener::Tcp(l) => {
+                let (s, _) = l.accept()?;
+                s.set_nodelay(true)?;
+                Ok(Connection::Tcp(s))
+            }
+            #[cfg(unix)]
+            Listener::Unix(l) => Ok(Connection::Unix(l.accept()?.0)),
+        }
+    }
+
+    /// Address to connect to (resolves a `:0` port).
+    pub fn endpoint(&self) -> Result<Endpoint> {
+        match self {
+            Listener::Tcp(l) => Ok(Endpoint::Tcp(l.local_addr()?.to_string())),
+            #[cfg(unix)]
+            Listener::Unix(l) => {
+                let addr = l.local_addr()?;
+                let path = addr.as_pathname().context("unnamed unix socket")?;
+                Ok(Endpoint::Unix(path.to_path_buf()))
+            }
+        }
+    }
+}
+
+/// A TCP or Unix socket connection between a primary and a replica.
+pub enum Connection {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Connection {
+    fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Connection::Tcp(s) => s.try_clone().map(Connection::Tcp),
+            #[cfg(unix)]
+            Connection::Unix(s) => s.try_clone().map(Connection::Unix),
+        }
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        match self {
+            Connection::Tcp(s) => s.shutdown(how),
+            #[cfg(unix)]
+            Connection::Unix(s) => s.shutdown(how),
+        }
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Connection::Tcp(s) => s.read(buf),
+            #[cfg(unix)]
+            Connection::Unix(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Connection::Tcp(s) => s.write(buf),
+            #[cfg(unix)]
+            Connection::Unix(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Connection::Tcp(s) => s.flush(),
+            #[cfg(unix)]
+            Connection::Unix(s) => s.flush(),
+        }
+    }
+}
+
+/// Serves a log that another process (the recorder) is appending to, to
+/// one replica at a time. Records are read back from the file with a
+/// `LogFollower`, so the recorder itself is not involved. Sealed records
+/// are sent as stored, so neither side needs the keys.
+pub struct Primary {
+    log: PathBuf,
+    poll_interval: Duration,
+    acked: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Primary {
+    pub fn new(log: impl AsRef<Path>) -> Self {
+        Self {
+            log: log.as_ref().to_path_buf(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            acked: Arc::new(AtomicU64::new(0)),
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Highest seq a replica has reported durable.
+    pub fn acked_seq(&self) -> u64 {
+        self.acked.load(Ordering::Acquire)
+    }
+
+    /// Flag that makes `serve` finish: it sends what the log holds at that
+    /// point, waits for the replica's last ack and returns.
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    /// Replicate to the replica on `conn` from the seq it asks for, until it
+    /// disconnects or `stop_handle` is set.
+    pub fn serve(&self, conn: Connection) -> Result<()> {
+        let mut input = BufReader::new(conn.try_clone()?);
+        let mut line = String::new();
+        let next_seq = match receive(&mut input, &mut line)? {
+            Some(Message::Hello { next_seq }) => next_seq,
+            Some(m) => anyhow::bail!("expected hello from replica, got {:?}", m),
+            None => return Ok(()),
+        };
+
+        // acks arrive on their own thread so sending never waits for them
+        let closed = Arc::new(AtomicBool::new(false));
+        let acks = {
+            let (acked, closed) = (self.acked.clone(), closed.clone());
+            std::thread::spawn(move || -> Result<()> {
+                let mut line = String::new();
+                let res = loop {
+                    match receive(&mut input, &mut line) {
+                        Ok(Some(Message::Ack { seq })) => {
+                            acked.fetch_max(seq, Ordering::AcqRel);
+                        }
+                        Ok(Some(m)) => break Err(anyhow::anyhow!("unexpected message from replica: {:?}", m)),
+                        Ok(None) => break Ok(()),
+                        Err(e) => break Err(e),
+                    }
+                };
+                closed.store(true, Ordering::Release);
+                res
+            })
+        };
+
+        let res = self.stream(&conn, next_seq, &closed);
+        // the replica sees the end of the stream, sends its last ack and hangs up
+        let _ = conn.shutdown(Shutdown::Write);
+        let acked = acks.join().map_err(|_| anyhow::anyhow!("ack reader panicked"))?;
+        res.and(acked)
+    }
+
+    fn stream(&self, conn: &Connection, next_seq: u64, closed: &AtomicBool) -> Result<()> {
+        let mut out = BufWriter::new(conn.try_clone()?);
+        let mut follower = LogFollower::from_seq(&self.log, next_seq)
+            .with_poll_interval(self.poll_interval)
+            .with_sealed_payloads();
+        let mut started = false;
+        loop {
+            if closed.load(Ordering::Acquire) {
+                return Ok(());
+            }
+            // checked before reading so everything written until now still goes out
+            let stopping = self.stop.load(Ordering::Acquire);
+            let Some((mut env, payload)) = follower.try_next()? else {
+                out.flush()?;
+                if stopping {
+                    return Ok(());
+                }
+                std::thread::sleep(self.poll_interval);
+                continue;
+            };
+            if !started {
+                let format = LogFormat::detect(&read_head(&self.log)?);
+                send(&mut out, &Message::Start { format, anchor: Anchor::load(&self.log)? })?;
+                started = true;
+            }
+            env.payload_b64 = base64::engine::general_purpose::STANDARD.encode(&payload);
+            // the follower has no keys: a sealed payload is still compressed inside
+            if env.key_id.is_none() {
+                env.codec = None;
+            }
+            send(&mut out, &Message::Record { env })?;
+        }
+    }
+}
+
+fn read_head(log: &Path) -> Result<Vec<u8>> {
+    let mut head = Vec::new();
+    File::open(log)
+        .with_context(|| format!("open {:?}", log))?
+        .take(frame::MAGIC.len() as u64)
+        .read_to_end(&mut head)?;
+    Ok(head)
+}
+
+#[derive(Clone, Copy)]
+pub struct ReplicaOptions {
+    /// Options of the replica's log; a new log takes the primary's format.
+    /// Every ack is fsynced regardless of `durability`.
+    pub writer: WriterOptions,
+    /// Most records written between two acks.
+    pub ack_every: u64,
+}
+
+impl Default for ReplicaOptions {
+    fn default() -> Self {
+        Self { writer: WriterOptions::default(), ack_every: DEFAULT_ACK_EVERY }
+    }
+}
+
+/// Keeps a copy of a primary's log: the same records with the same seqs,
+/// stream seqs and chain digests.
+///
+/// Opening recovers the local log like `EventLogWriter::open_append` (a torn
+/// tail is dropped), so after a crash or a lost connection `run` simply
+/// resumes from the local last seq.
+pub struct Replica {
+    path: PathBuf,
+    opts: ReplicaOptions,
+    /// `None` until the log has a format (a new log waits for the primary's)
+    writer: Option<EventLogWriter>,
+    durable_seq: u64,
+}
+
+impl Replica {
+    pub fn open(path: impl AsRef<Path>, opts: ReplicaOptions) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut replica = Self { path, opts, writer: None, durable_seq: 0 };
+        let has_records = std::fs::metadata(&replica.path).is_ok_and(|m| m.len() > frame::MAGIC.len() as u64);
+        if has_records {
+            let format = LogFormat::detect(&read_head(&replica.path)?);
+            replica.open_writer(format)?;
+        }
+        replica.durable_seq = replica.next_seq()? - 1;
+        Ok(replica)
+    }
+
+    fn open_writer(&mut self, format: LogFormat) -> Result<()> {
+        let opts = WriterOptions { format, durability: Durability::FsyncOnFlush, ..self.opts.writer };
+        self.writer = Some(EventLogWriter::open_with(&self.path, "el:replica", opts)?);
+        Ok(())
+    }
+
+    /// Seq the replica asks the primary for next.
+    pub fn next_seq(&self) -> Result<u64> {
+        Ok(match &self.writer {
+            Some(w) => w.next_seq(),
+            None => Anchor::load(&self.path)?.map_or(1, |a| a.seq + 1),
+        })
+    }
+
+    /// Every seq up to here is fsynced (and acked, once connected).
+    pub fn durable_seq(&self) -> u64 {
+        self.durable_seq
+    }
+
+    /// Replicate from the primary on `conn` until it closes the connection.
+    pub fn run(&mut self, conn: Connection) -> Result<()> {
+        let mut input = BufReader::new(conn.try_clone()?);
+        let mut out = BufWriter::new(conn);
+        send(&mut out, &Message::Hello { next_seq: self.next_seq()? })?;
+        out.flush()?;
+
+        let mut line = String::new();
+        let mut unacked = 0u64;
+        loop {
+            let Some(msg) = receive(&mut input, &mut line)? else {
+                self.commit(&mut out)?;
+                return Ok(());
+            };
+            match msg {
+                Message::Start { format, anchor } => self.start(format, anchor)?,
+                Message::Record { env } => {
+                    let payload = base64::engine::general_purpose::STANDARD
+                        .decode(&env.payload_b64)
+                        .with_context(|| format!("replicated payload at seq={}", env.seq))?;
+                    let w = self.writer.as_mut().context("record before start")?;
+                    w.append_replica(&env, &payload)?;
+                    unacked += 1;
+                }
+                m => anyhow::bail!("unexpected message from primary: {:?}", m),
+            }
+            // group commit: one fsync for whatever arrived together
+            if unacked >= self.opts.ack_every || (unacked > 0 && input.buffer().is_empty()) {
+                self.commit(&mut out)?;
+                unacked = 0;
+            }
+        }
+    }
+
+    fn start(&mut self, format: LogFormat, anchor: Option<Anchor>) -> Result<()> {
+        if let Some(w) = &self.writer {
+            if w.format() != format {
+                anyhow::bail!("replica log {:?} is {:?}, primary is {:?}", self.path, w.format(), format);
+            }
+            if w.range().records > 0 {
+                return Ok(());
+            }
+        }
+        // a new replica of a compacted primary starts where the primary does
+        if let Some(anchor) = anchor.filter(|a| a.seq >= self.next_seq().unwrap_or(1)) {
+            self.writer = None;
+            anchor.store(&self.path)?;
+        }
+        self.open_writer(format)
+    }
+
+    fn commit(&mut self, out: &mut impl Write) -> Result<()> {
+        let Some(w) = &mut self.writer else {
+            return Ok(());
+        };
+        w.flush()?;
+        let seq = w.next_seq() - 1;
+        if seq > self.durable_seq {
+            self.durable_seq = seq;
+            send(out, &Message::Ack { seq })?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
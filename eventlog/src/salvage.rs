@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::crypto::SharedKeys;
+use crate::envelope::EventEnvelope;
+use crate::error::EventLogError;
+use crate::frame::{self, Frame, FramePrefix, LogFormat};
+use crate::reader::EventLogReader;
+
+/// Largest seq jump a resync candidate may claim over the last good record.
+const MAX_SEQ_JUMP: u64 = 1 << 32;
+
+/// Longest kind/stream definition body a resync candidate may have.
+const MAX_DEFINITION_LEN: u64 = 3 + 1024;
+
+/// Bytes skipped over one damaged record (or run of garbage).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    pub offset: u64,
+    pub bytes: u64,
+    /// seq of the damaged record when its header could still be read
+    pub seq: Option<u64>,
+    pub error: String,
+}
+
+/// What a salvage pass recovered and lost.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SalvageReport {
+    pub records: u64,
+    pub skipped: Vec<Skipped>,
+    /// offset of a torn last record, which is dropped
+    pub truncated_tail: Option<u64>,
+    pub tail_bytes: u64,
+    /// seqs absent between recovered records
+    pub missing_seqs: u64,
+}
+
+impl SalvageReport {
+    pub fn bytes_lost(&self) -> u64 {
+        self.skipped.iter().map(|s| s.bytes).sum::<u64>() + self.tail_bytes
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty() && self.truncated_tail.is_none()
+    }
+}
+
+/// Reads whatever is still readable from a damaged log.
+///
+/// Corrupt records are skipped and reported instead of ending the read:
+/// JSON lines resume at the next line; binary logs resume after the frame, or
+/// when its length prefix cannot be trusted, at the next offset that holds a
+/// plausible frame (a record must also pass its checksum). Only I/O errors
+/// are returned.
+pub struct SalvageReader {
+    reader: EventLogReader,
+    /// separate handle for probing resync candidates in binary logs
+    file: File,
+    len: u64,
+    last_seq: Option<u64>,
+    report: SalvageReport,
+}
+
+impl SalvageReader {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let reader = EventLogReader::open(path)?;
+        let file = File::open(path).with_context(|| format!("open {:?}", path))?;
+        let len = file.metadata()?.len();
+        Ok(Self { reader, file, len, last_seq: None, report: SalvageReport::default() })
+    }
+
+    /// Decrypt sealed payloads; see `EventLogReader::with_keys`. Without
+    /// the key a sealed record fails the read: it is not damage.
+    pub fn with_keys(mut self, keys: SharedKeys) -> Self {
+        self.reader = self.reader.with_keys(keys);
+        self
+    }
+
+    /// Hand out sealed payloads as stored instead of failing; see
+    /// `EventLogReader::with_sealed_payloads`. Enough to find the damage.
+    pub fn with_sealed_payloads(mut self) -> Self {
+        self.reader = self.reader.with_sealed_payloads();
+        self
+    }
+
+    pub fn report(&self) -> &SalvageReport {
+        &self.report
+    }
+
+    pub fn finish(self) -> SalvageReport {
+        self.report
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<(EventEnvelope, Vec<u8>)>> {
+        loop {
+            let err = match self.reader.next() {
+                Ok(Some((env, payload))) => {
+                    if let Some(last) = self.last_seq {
+                        self.report.missing_seqs += env.seq.saturating_sub(last + 1);
+                    }
+                    self.last_seq = Some(env.seq);
+                    self.report.records += 1;
+                    return Ok(Some((env, payload)));
+                }
+                Ok(None) => return Ok(None),
+                Err(e) if e.is_corruption() => e,
+                Err(e) => return Err(e).with_context(|| format!("read {:?}", self.reader.path())),
+            };
+            let at = err.offset().expect("corruption has an offset");
+
+            let truncated = matches!(err, EventLogError::TruncatedTail { .. });
+            let resume = if self.reader.format() == LogFormat::JsonLines {
+                if truncated {
+                    return Ok(self.tail(at));
+                }
+                // the bad line was consumed; carry on with the next one
+                self.reader.position()
+            } else if !truncated && self.reader.position() > at {
+                // the frame itself was intact (bad checksum, payload or name id)
+                self.reader.position()
+            } else {
+                // the length prefix cannot be trusted: it may be what runs past the end
+                let next = self.find_frame(at + 1)?;
+                if truncated && next == self.len {
+                    return Ok(self.tail(at));
+                }
+                next
+            };
+
+            self.report.skipped.push(Skipped {
+                offset: at,
+                bytes: resume - at,
+                seq: err.seq(),
+                error: err.to_string(),
+            });
+            self.reader.reposition(resume)?;
+        }
+    }
+
+    fn tail(&mut self, at: u64) -> Option<(EventEnvelope, Vec<u8>)> {
+        self.report.truncated_tail = Some(at);
+        self.report.tail_bytes = self.len - at;
+        None
+    }
+
+    /// First offset at or after `from` that holds a plausible frame, or the end of the file.
+    fn find_frame(&mut self, from: u64) -> io::Result<u64> {
+        let (kinds, streams) = self.reader.dict().counts();
+        for pos in from..self.len {
+            if self.plausible(pos, kinds, streams, true)?.is_some() {
+                return Ok(pos);
+            }
+        }
+        Ok(self.len)
+    }
+
+    /// Size of the frame at `pos` if it looks genuine. A definition frame is
+    /// only trusted when it continues the name table and is followed by
+    /// another plausible frame (or the end of the file).
+    fn plausible(&mut self, pos: u64, kinds: usize, streams: usize, follow: bool) -> io::Result<Option<u64>> {
+        let head = self.read_at(pos, 4 + frame::PREFIX_LEN)?;
+        if head.len() < 5 {
+            return Ok(None);
+        }
+        let len = u32::from_le_bytes(head[0..4].try_into().unwrap()) as u64;
+        if len == 0 || pos + 4 + len > self.len {
+            return Ok(None);
+        }
+        let body_head = &head[4..head.len().min(4 + len as usize)];
+        let size = 4 + len;
+
+        match frame::frame_prefix(body_head) {
+            Some(FramePrefix::Record { seq, kind_id, stream_id }) => {
+                let after_last = match self.last_seq {
+                    Some(last) => seq > last && seq - last <= MAX_SEQ_JUMP,
+                    None => true,
+                };
+                if !after_last || kind_id as usize >= kinds || stream_id as usize >= streams {
+                    return Ok(None);
+                }
+                let body = self.read_at(pos + 4, len as usize)?;
+                let ok = match frame::decode_body(&body) {
+                    Ok(Frame::Record { checksum, codec: None, key: None, payload, .. }) => crc32fast::hash(&payload) == checksum,
+                    // compressed or encrypted: checked when the reader decodes it
+                    Ok(Frame::Record { .. }) => true,
+                    _ => false,
+                };
+                Ok(ok.then_some(size))
+            }
+            Some(FramePrefix::Kind { id }) if id as usize == kinds && len <= MAX_DEFINITION_LEN => {
+                self.plausible_definition(pos, size, kinds + 1, streams, follow)
+            }
+            Some(FramePrefix::Stream { id }) if id as usize == streams && len <= MAX_DEFINITION_LEN => {
+                self.plausible_definition(pos, size, kinds, streams + 1, follow)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn plausible_definition(
+        &mut self,
+        pos: u64,
+        size: u64,
+        kinds: usize,
+        streams: usize,
+        follow: bool,
+    ) -> io::Result<Option<u64>> {
+        let body = self.read_at(pos + 4, (size - 4) as usize)?;
+        if body.len() < 4 || std::str::from_utf8(&body[3..]).is_err() {
+            return Ok(None);
+        }
+        let next = pos + size;
+        if !follow || next == self.len || self.plausible(next, kinds, streams, false)?.is_some() {
+            return Ok(Some(size));
+        }
+        Ok(None)
+    }
+
+    fn read_at(&mut self, pos: u64, n: usize) -> io::Result<Vec<u8>> {
+        self.file.seek(SeekFrom::Start(pos))?;
+        let mut buf = Vec::with_capacity(n);
+        Read::by_ref(&mut self.file).take(n as u64).read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+}
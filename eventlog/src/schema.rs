@@ -0,0 +1,165 @@
+use el_core::exec::{OrderType, Side, TimeInForce};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::envelope::EventEnvelope;
+use crate::error::EventLogError;
+
+/// Upcasts a JSON payload by one version; the registry then stamps the new
+/// version into the payload's version field, if it has one.
+pub type Migration = fn(Value) -> anyhow::Result<Value>;
+
+/// `Event` v1 -> v2: `OrderSubmit.side` was a free-form string ("buy",
+/// "BUY", "Buy"); v2 types it and spells out the limit/GTC defaults.
+pub fn event_v1_to_v2(mut ev: Value) -> anyhow::Result<Value> {
+    let Some(submit) = ev.pointer_mut("/payload/OrderSubmit").and_then(Value::as_object_mut) else {
+        return Ok(ev);
+    };
+    let side = submit.get("side").and_then(Value::as_str).and_then(Side::from_name);
+    let Some(side) = side else {
+        anyhow::bail!("unknown side {}", submit.get("side").unwrap_or(&Value::Null));
+    };
+    submit.insert("side".into(), serde_json::to_value(side)?);
+    submit.entry("order_type").or_insert(serde_json::to_value(OrderType::Limit)?);
+    submit.entry("time_in_force").or_insert(serde_json::to_value(TimeInForce::Gtc)?);
+    Ok(ev)
+}
+
+/// How payloads of one kind are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadCodec {
+    /// a JSON document, versioned by a field of its top-level object
+    Json { version_field: Option<&'static str> },
+    /// opaque bytes, optionally of a fixed length; always the current version
+    Raw { len: Option<usize> },
+}
+
+/// Codec and version history of one envelope kind.
+#[derive(Debug, Clone)]
+pub struct KindSchema {
+    codec: PayloadCodec,
+    /// `migrations[i]` upcasts version `i + 1` to `i + 2`
+    migrations: Vec<Migration>,
+}
+
+impl KindSchema {
+    /// JSON payloads at version 1. Payloads without `version_field` (or when
+    /// it is `None`) are version 1.
+    pub fn json(version_field: Option<&'static str>) -> Self {
+        Self { codec: PayloadCodec::Json { version_field }, migrations: Vec::new() }
+    }
+
+    pub fn raw(len: Option<usize>) -> Self {
+        Self { codec: PayloadCodec::Raw { len }, migrations: Vec::new() }
+    }
+
+    /// Declare the next version, reached from the current one by `step`.
+    pub fn migrate(mut self, step: Migration) -> Self {
+        assert!(matches!(self.codec, PayloadCodec::Json { .. }), "only JSON payloads migrate");
+        self.migrations.push(step);
+        self
+    }
+
+    pub fn codec(&self) -> PayloadCodec {
+        self.codec
+    }
+
+    /// Version current code writes and decodes.
+    pub fn version(&self) -> u16 {
+        self.migrations.len() as u16 + 1
+    }
+}
+
+/// Kinds a reader accepts and how their payloads are brought up to date.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    kinds: BTreeMap<String, KindSchema>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The kinds the platform writes: `event` (JSON, versioned by
+    /// `schema_version`), `snapshot` (opaque) and `snapshot_hash` (u64 LE).
+    pub fn core() -> Self {
+        Self::new()
+            .register("event", KindSchema::json(Some("schema_version")).migrate(event_v1_to_v2))
+            .register("snapshot", KindSchema::raw(None))
+            .register("snapshot_hash", KindSchema::raw(Some(8)))
+    }
+
+    pub fn register(mut self, kind: impl Into<String>, schema: KindSchema) -> Self {
+        self.kinds.insert(kind.into(), schema);
+        self
+    }
+
+    pub fn get(&self, kind: &str) -> Option<&KindSchema> {
+        self.kinds.get(kind)
+    }
+
+    pub fn kinds(&self) -> impl Iterator<Item = (&str, &KindSchema)> {
+        self.kinds.iter().map(|(k, s)| (k.as_str(), s))
+    }
+
+    fn schema(&self, seq: u64, kind: &str) -> Result<&KindSchema, EventLogError> {
+        self.get(kind).ok_or_else(|| EventLogError::UnknownKind { seq, kind: kind.to_string() })
+    }
+
+    /// Check the record's kind and version and bring its payload up to the
+    /// current version. Current payloads are returned as they are.
+    pub fn upcast(&self, env: &EventEnvelope, payload: Vec<u8>) -> Result<Vec<u8>, EventLogError> {
+        self.upcast_record(env.seq, &env.kind, payload)
+    }
+
+    /// `upcast` for a record read without an `EventEnvelope` (e.g. `MmapRecord`).
+    pub fn upcast_record(&self, seq: u64, kind: &str, payload: Vec<u8>) -> Result<Vec<u8>, EventLogError> {
+        let schema = self.schema(seq, kind)?;
+        let invalid = |reason: String| EventLogError::Schema { seq, kind: kind.to_string(), reason };
+
+        let version_field = match schema.codec {
+            PayloadCodec::Raw { len } => {
+                return match len {
+                    Some(n) if payload.len() != n => {
+                        Err(invalid(format!("payload is {} bytes, expected {}", payload.len(), n)))
+                    }
+                    _ => Ok(payload),
+                };
+            }
+            PayloadCodec::Json { version_field } => version_field,
+        };
+
+        let mut doc: Value = serde_json::from_slice(&payload).map_err(|e| invalid(e.to_string()))?;
+        let version = match version_field.and_then(|f| doc.get(f)) {
+            None => 1,
+            Some(v) => v
+                .as_u64()
+                .and_then(|v| u16::try_from(v).ok())
+                .ok_or_else(|| invalid(format!("bad version {}", v)))?,
+        };
+        let current = schema.version();
+        if version == 0 || version > current {
+            return Err(EventLogError::UnsupportedVersion { seq, kind: kind.to_string(), version, current });
+        }
+        if version == current {
+            return Ok(payload);
+        }
+
+        for (from, step) in (version..).zip(&schema.migrations[version as usize - 1..]) {
+            doc = step(doc).map_err(|e| invalid(format!("migrating from v{}: {:#}", from, e)))?;
+            if let Some(v) = version_field.and_then(|f| doc.get_mut(f)) {
+                *v = Value::from(from + 1);
+            }
+        }
+        serde_json::to_vec(&doc).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Upcast a JSON payload and deserialize it as the current `T`.
+    pub fn decode<T: DeserializeOwned>(&self, env: &EventEnvelope, payload: &[u8]) -> Result<T, EventLogError> {
+        let current = self.upcast(env, payload.to_vec())?;
+        serde_json::from_slice(&current)
+            .map_err(|e| EventLogError::Schema { seq: env.seq, kind: env.kind.clone(), reason: e.to_string() })
+    }
+}
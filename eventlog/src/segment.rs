@@ -0,0 +1,561 @@
+use anyhow::Result;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use crate::chain::{self, Digest};
+use crate::compact::{self, Anchor, CompactReport, Retention};
+use crate::crypto::{KeyId, KeyProvider, PayloadSealer, SharedKeys};
+use crate::envelope::EventEnvelope;
+use crate::error::EventLogError;
+use crate::reader::EventLogReader;
+use crate::schema::SchemaRegistry;
+use crate::sink::EventSink;
+use crate::writer::{Continuation, EventLogWriter, LogRange, WriterOptions};
+use el_core::clock::{SharedClock, WallClock};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+const LOCK_FILE: &str = "LOCK";
+const SEGMENT_EXT: &str = "log";
+
+/// When the active segment is sealed and a new one started.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rotation {
+    /// Seal once the active segment reaches this many bytes.
+    pub max_bytes: Option<u64>,
+    /// Seal when wall-clock time enters a new `interval_ns`-aligned window
+    /// (e.g. `3_600_000_000_000` for hourly segments).
+    pub interval_ns: Option<u64>,
+}
+
+/// A sealed segment as recorded in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentMeta {
+    pub file: String,
+    pub first_seq: u64,
+    pub last_seq: u64,
+    pub first_ts_ns: u64,
+    pub last_ts_ns: u64,
+    pub records: u64,
+    pub bytes: u64,
+    /// hex chain digest of the last record; the next segment links to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_chain: Option<String>,
+    /// last seq of each stream as of the end of this segment
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stream_seqs: BTreeMap<String, u64>,
+}
+
+impl SegmentMeta {
+    fn new(
+        file: String,
+        range: LogRange,
+        bytes: u64,
+        last_chain: Option<Digest>,
+        stream_seqs: BTreeMap<String, u64>,
+    ) -> Self {
+        Self {
+            file,
+            first_seq: range.first_seq,
+            last_seq: range.last_seq,
+            first_ts_ns: range.first_ts_ns,
+            last_ts_ns: range.last_ts_ns,
+            records: range.records,
+            bytes,
+            last_chain: last_chain.as_ref().map(chain::to_hex),
+            stream_seqs,
+        }
+    }
+}
+
+/// Sealed segments of a log directory, oldest first.
+/// The active (last) segment is never listed here.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub segments: Vec<SegmentMeta>,
+    /// what preceded the oldest segment, once older ones were compacted away
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<Anchor>,
+}
+
+impl Manifest {
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, EventLogError> {
+        let path = dir.as_ref().join(MANIFEST_FILE);
+        match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| EventLogError::layout(&path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(EventLogError::file(path)(e)),
+        }
+    }
+
+    /// Atomically replace the manifest (write temp file, fsync, rename).
+    pub fn store(&self, dir: impl AsRef<Path>) -> Result<(), EventLogError> {
+        let dir = dir.as_ref();
+        let tmp = dir.join(format!("{}.tmp", MANIFEST_FILE));
+        {
+            let mut f = File::create(&tmp).map_err(EventLogError::file(&tmp))?;
+            f.write_all(&serde_json::to_vec_pretty(self)?)?;
+            f.sync_all()?;
+        }
+        std::fs::rename(&tmp, dir.join(MANIFEST_FILE)).map_err(EventLogError::file(&tmp))?;
+        Ok(())
+    }
+
+    fn contains(&self, file: &str) -> bool {
+        self.segments.iter().any(|s| s.file == file)
+    }
+}
+
+/// Open (creating) the lock file of a segment directory; the caller locks it.
+pub(crate) fn lock_dir(dir: &Path) -> Result<File, EventLogError> {
+    let path = dir.join(LOCK_FILE);
+    OpenOptions::new().create(true).truncate(false).write(true).open(&path).map_err(EventLogError::file(&path))
+}
+
+/// Segment files are named after their first seq, zero padded so that
+/// lexical order is seq order.
+pub fn segment_file_name(first_seq: u64) -> String {
+    format!("{:020}.{}", first_seq, SEGMENT_EXT)
+}
+
+/// First seq of a segment file, from its name.
+pub(crate) fn segment_first_seq(path: &Path) -> Option<u64> {
+    parse_segment_file_name(&file_name(path))
+}
+
+fn parse_segment_file_name(name: &str) -> Option<u64> {
+    let stem = name.strip_suffix(SEGMENT_EXT)?.strip_suffix('.')?;
+    if stem.len() != 20 {
+        return None;
+    }
+    stem.parse().ok()
+}
+
+/// All segment files of `dir`, oldest first.
+pub fn segment_files(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, EventLogError> {
+    let dir = dir.as_ref();
+    let mut out: Vec<(u64, PathBuf)> = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(EventLogError::file(dir))? {
+        let entry = entry.map_err(EventLogError::file(dir))?;
+        let name = entry.file_name();
+        if let Some(first) = name.to_str().and_then(parse_segment_file_name) {
+            out.push((first, entry.path()));
+        }
+    }
+    out.sort();
+    Ok(out.into_iter().map(|(_, p)| p).collect())
+}
+
+pub(crate) fn file_name(path: &Path) -> String {
+    path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string()
+}
+
+pub(crate) fn scan_segment(path: &Path) -> Result<SegmentMeta, EventLogError> {
+    let mut r = EventLogReader::open(path)?.with_sealed_payloads();
+    let mut range = LogRange::default();
+    let mut last_chain = None;
+    let mut stream_seqs = BTreeMap::new();
+    while let Some((env, _)) = r.next()? {
+        range.observe(env.seq, env.ts_ns);
+        last_chain = env.chain.as_deref().and_then(chain::from_hex);
+        stream_seqs.insert(env.stream.clone(), env.seq_in_stream());
+    }
+    let bytes = std::fs::metadata(path)?.len();
+    Ok(SegmentMeta::new(file_name(path), range, bytes, last_chain, stream_seqs))
+}
+
+/// Rolling writer over a directory of segments.
+///
+/// Opening only recovers the last (active) segment; sealed segments are known
+/// from the manifest. A segment that was sealed but not yet recorded (crash
+/// during rotation) is scanned once and added.
+pub struct SegmentedWriter {
+    dir: PathBuf,
+    stream: String,
+    opts: WriterOptions,
+    rotation: Rotation,
+    manifest: Manifest,
+    active: EventLogWriter,
+    active_file: String,
+    /// clock ns when the active segment got its first record
+    active_since_ns: Option<u64>,
+    /// drives interval rotation and stamps `write()`
+    clock: SharedClock,
+    /// carried over to every new segment
+    sealer: Option<Arc<PayloadSealer>>,
+    _lock: File,
+}
+
+fn modified_ns(path: &Path) -> Option<u64> {
+    let t = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(t.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
+
+impl SegmentedWriter {
+    pub fn open(
+        dir: impl AsRef<Path>,
+        stream: impl Into<String>,
+        opts: WriterOptions,
+        rotation: Rotation,
+    ) -> Result<Self, EventLogError> {
+        let dir = dir.as_ref().to_path_buf();
+        let stream = stream.into();
+        std::fs::create_dir_all(&dir).map_err(EventLogError::file(&dir))?;
+
+        let lock = lock_dir(&dir)?;
+        lock.lock_exclusive().map_err(EventLogError::file(dir.join(LOCK_FILE)))?;
+
+        let mut manifest = Manifest::load(&dir)?;
+        let mut files = segment_files(&dir)?;
+
+        // the last file is active unless the manifest already sealed it
+        let mut active_path = files.pop();
+        if let Some(p) = &active_path {
+            if manifest.contains(&file_name(p)) {
+                files.push(p.clone());
+                active_path = None;
+            }
+        }
+
+        let mut changed = false;
+        for p in &files {
+            // left behind by an interrupted compaction; readers still see a whole prefix
+            let compacted = manifest
+                .anchor
+                .as_ref()
+                .is_some_and(|a| parse_segment_file_name(&file_name(p)).is_some_and(|first| first <= a.seq));
+            if !compacted && !manifest.contains(&file_name(p)) {
+                manifest.segments.push(scan_segment(p)?);
+                changed = true;
+            }
+        }
+        if changed {
+            manifest.segments.sort_by_key(|s| s.first_seq);
+            manifest.store(&dir)?;
+        }
+
+        let mut base = manifest.anchor.as_ref().map(Anchor::continuation).unwrap_or_default();
+        if let Some(last) = manifest.segments.last() {
+            base.seq = last.last_seq;
+            if let Some(d) = last.last_chain.as_deref().and_then(chain::from_hex) {
+                base.chain = Some(d);
+            }
+        }
+        // a stream may not appear in every segment (or in a segment scanned after a crash)
+        for s in &manifest.segments {
+            if s.stream_seqs.is_empty() && s.records > 0 {
+                // sealed before streams had their own seq: one stream, numbered by seq
+                base.stream_seqs.insert(stream.clone(), s.last_seq);
+            }
+            base.stream_seqs.extend(s.stream_seqs.iter().map(|(k, v)| (k.clone(), *v)));
+        }
+        let active_path =
+            active_path.unwrap_or_else(|| dir.join(segment_file_name(base.seq + 1)));
+        if let Some(first) = parse_segment_file_name(&file_name(&active_path)) {
+            // seqs start at 1, so no segment can start at 0
+            base.seq = first
+                .checked_sub(1)
+                .ok_or_else(|| EventLogError::layout(&active_path, "segment name starts at seq 0"))?;
+        }
+        let active = EventLogWriter::open_after(&active_path, stream.clone(), opts, base)?;
+        // a reopened segment belongs to the window it was last written in
+        let active_since_ns = if active.range().records > 0 { modified_ns(&active_path) } else { None };
+
+        Ok(Self {
+            dir,
+            stream,
+            opts,
+            rotation,
+            manifest,
+            active,
+            active_file: file_name(&active_path),
+            active_since_ns,
+            clock: Arc::new(WallClock),
+            sealer: None,
+            _lock: lock,
+        })
+    }
+
+    /// Rotate by, and stamp `write()` with, `clock` instead of wall time.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.active = self.active.with_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    /// Encrypt new payloads, in this and every later segment; see
+    /// `EventLogWriter::with_encryption`.
+    pub fn with_encryption(mut self, keys: &dyn KeyProvider, key_id: KeyId) -> Result<Self, EventLogError> {
+        let sealer = PayloadSealer::new(keys, key_id).map_err(|e| EventLogError::layout(&self.dir, format!("{:#}", e)))?;
+        let sealer = Some(Arc::new(sealer));
+        self.active = self.active.with_sealer(sealer.clone());
+        self.sealer = sealer;
+        Ok(self)
+    }
+
+    /// Current time of the writer's clock, as a record `ts_ns`.
+    pub fn now_ns(&self) -> u64 {
+        self.active.now_ns()
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    pub fn active_range(&self) -> LogRange {
+        self.active.range()
+    }
+
+    /// Seq the next appended record will get.
+    pub fn next_seq(&self) -> u64 {
+        self.active.next_seq()
+    }
+
+    fn should_roll(&self, now_ns: u64) -> bool {
+        if self.active.range().records == 0 {
+            return false;
+        }
+        let by_size = self.rotation.max_bytes.is_some_and(|m| self.active.position() >= m);
+        let by_time = match (self.rotation.interval_ns, self.active_since_ns) {
+            (Some(i), Some(since)) if i > 0 => now_ns / i != since / i,
+            _ => false,
+        };
+        by_size || by_time
+    }
+
+    /// Drop the sealed segments `retention` no longer needs while the log
+    /// stays open; see `compact::compact_before`.
+    pub fn compact(&mut self, retention: &Retention, archive: Option<&Path>) -> Result<CompactReport> {
+        self.active.flush()?;
+        match compact::retention_cut(&self.dir, retention, None)? {
+            Some(cut) => compact::drop_segments(&self.dir, &mut self.manifest, cut, archive),
+            None => Ok(CompactReport {
+                first_seq: SegmentedReader::open(&self.dir)?.with_sealed_payloads().next()?.map(|(env, _)| env.seq),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Seal the active segment and start a new one. No-op on an empty segment.
+    pub fn roll(&mut self) -> Result<(), EventLogError> {
+        let range = self.active.range();
+        if range.records == 0 {
+            return Ok(());
+        }
+        self.active.flush()?;
+
+        let meta = SegmentMeta::new(
+            self.active_file.clone(),
+            range,
+            self.active.position(),
+            self.active.chain_head(),
+            self.active.stream_seqs().clone(),
+        );
+        self.manifest.segments.push(meta);
+        self.manifest.store(&self.dir)?;
+
+        let next = self.active.next_seq();
+        let name = segment_file_name(next);
+        let base = Continuation {
+            seq: next - 1,
+            chain: self.active.chain_head(),
+            stream_seqs: self.active.stream_seqs().clone(),
+        };
+        self.active = EventLogWriter::open_after(self.dir.join(&name), self.stream.clone(), self.opts, base)?
+            .with_clock(self.clock.clone())
+            .with_sealer(self.sealer.clone());
+        self.active_file = name;
+        self.active_since_ns = None;
+        Ok(())
+    }
+
+    pub fn append_bytes(&mut self, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64, EventLogError> {
+        self.before_append()?;
+        self.active.append_bytes(kind, ts_ns, payload)
+    }
+
+    /// Append to `stream`; see `EventLogWriter::append_stream`.
+    pub fn append_stream(
+        &mut self,
+        stream: &str,
+        kind: &str,
+        ts_ns: u64,
+        payload: &[u8],
+    ) -> Result<(u64, u64), EventLogError> {
+        self.before_append()?;
+        self.active.append_stream(stream, kind, ts_ns, payload)
+    }
+
+    fn before_append(&mut self) -> Result<(), EventLogError> {
+        let now = self.now_ns();
+        if self.should_roll(now) {
+            self.roll()?;
+        }
+        self.active_since_ns.get_or_insert(now);
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), EventLogError> {
+        self.active.flush()
+    }
+}
+
+impl EventSink for SegmentedWriter {
+    fn append_bytes(&mut self, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64> {
+        Ok(SegmentedWriter::append_bytes(self, kind, ts_ns, payload)?)
+    }
+
+    fn append_stream(&mut self, stream: &str, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64> {
+        Ok(SegmentedWriter::append_stream(self, stream, kind, ts_ns, payload)?.0)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(SegmentedWriter::flush(self)?)
+    }
+
+    fn next_seq(&self) -> u64 {
+        SegmentedWriter::next_seq(self)
+    }
+
+    fn now_ns(&self) -> u64 {
+        SegmentedWriter::now_ns(self)
+    }
+}
+
+/// Reads every segment of a directory in seq order as one log.
+pub struct SegmentedReader {
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+    next_file: usize,
+    cur: Option<EventLogReader>,
+    streams: Option<Vec<String>>,
+    schemas: Option<Arc<SchemaRegistry>>,
+    keys: Option<SharedKeys>,
+    sealed_ok: bool,
+}
+
+impl SegmentedReader {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, EventLogError> {
+        let dir = dir.as_ref().to_path_buf();
+        Ok(Self {
+            files: segment_files(&dir)?,
+            dir,
+            next_file: 0,
+            cur: None,
+            streams: None,
+            schemas: None,
+            keys: None,
+            sealed_ok: false,
+        })
+    }
+
+    /// Only return records of `streams`; see `EventLogReader::with_streams`.
+    pub fn with_streams<S: Into<String>>(mut self, streams: impl IntoIterator<Item = S>) -> Self {
+        self.streams = Some(streams.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Check and upcast payloads; see `EventLogReader::with_schemas`.
+    pub fn with_schemas(mut self, schemas: impl Into<Arc<SchemaRegistry>>) -> Self {
+        self.schemas = Some(schemas.into());
+        self
+    }
+
+    /// Decrypt sealed payloads; see `EventLogReader::with_keys`.
+    pub fn with_keys(mut self, keys: SharedKeys) -> Self {
+        self.keys = Some(keys);
+        self
+    }
+
+    /// See `EventLogReader::with_sealed_payloads`.
+    pub fn with_sealed_payloads(mut self) -> Self {
+        self.sealed_ok = true;
+        self
+    }
+
+    fn open_file(&self, path: &Path) -> Result<EventLogReader, EventLogError> {
+        let mut r = EventLogReader::open(path)?;
+        if let Some(s) = &self.streams {
+            r = r.with_streams(s.iter().cloned());
+        }
+        if let Some(s) = &self.schemas {
+            r = r.with_schemas(s.clone());
+        }
+        if let Some(k) = &self.keys {
+            r = r.with_keys(k.clone());
+        }
+        if self.sealed_ok {
+            r = r.with_sealed_payloads();
+        }
+        Ok(r)
+    }
+
+    /// Position at the first record with `seq >= target`, using segment
+    /// names to pick the file and its index within it.
+    pub fn seek_seq(&mut self, target: u64) -> Result<(), EventLogError> {
+        let i = self
+            .files
+            .iter()
+            .rposition(|p| parse_segment_file_name(&file_name(p)).is_some_and(|first| first <= target))
+            .unwrap_or(0);
+        self.seek_in(i, |r| r.seek_seq(target))
+    }
+
+    /// Position at the first record with `ts_ns >= target_ns`, using the
+    /// manifest ts ranges to pick the file.
+    pub fn seek_ts(&mut self, target_ns: u64) -> Result<(), EventLogError> {
+        let manifest = Manifest::load(&self.dir)?;
+        let mut i = 0;
+        for (k, p) in self.files.iter().enumerate() {
+            let name = file_name(p);
+            let first_ts = match manifest.segments.iter().find(|s| s.file == name) {
+                Some(meta) => Some(meta.first_ts_ns),
+                // active segment: not in the manifest yet
+                None => EventLogReader::open(p)?.with_sealed_payloads().next()?.map(|(env, _)| env.ts_ns),
+            };
+            if first_ts.is_some_and(|ts| ts <= target_ns) {
+                i = k;
+            }
+        }
+        self.seek_in(i, |r| r.seek_ts(target_ns))
+    }
+
+    fn seek_in(
+        &mut self,
+        i: usize,
+        seek: impl FnOnce(&mut EventLogReader) -> Result<(), EventLogError>,
+    ) -> Result<(), EventLogError> {
+        self.cur = None;
+        self.next_file = self.files.len();
+        if let Some(p) = self.files.get(i) {
+            let mut r = self.open_file(p)?;
+            seek(&mut r)?;
+            self.cur = Some(r);
+            self.next_file = i + 1;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<(EventEnvelope, Vec<u8>)>, EventLogError> {
+        loop {
+            if let Some(r) = &mut self.cur {
+                if let Some(x) = r.next()? {
+                    return Ok(Some(x));
+                }
+            }
+            match self.files.get(self.next_file) {
+                Some(p) => {
+                    self.cur = Some(self.open_file(p)?);
+                    self.next_file += 1;
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use el_core::clock::{Clock, WallClock};
+use serde::Serialize;
+
+/// Anything records can be appended to: a single log file or a segmented directory.
+pub trait EventSink {
+    /// Append to the sink's own stream; returns the record's seq.
+    fn append_bytes(&mut self, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64>;
+
+    /// Append to a named stream interleaved in the same log; returns the global seq.
+    fn append_stream(&mut self, stream: &str, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64>;
+
+    fn flush(&mut self) -> Result<()>;
+
+    /// Seq the next appended record will get.
+    fn next_seq(&self) -> u64;
+
+    /// Current time as a record `ts_ns`; sinks with an injected clock use it.
+    fn now_ns(&self) -> u64 {
+        WallClock.now_ns().max(0) as u64
+    }
+
+    /// Append `ev` as JSON, stamped with `now_ns()`.
+    fn write<T: Serialize>(&mut self, ev: &T) -> Result<u64> {
+        let bytes = serde_json::to_vec(ev)?;
+        let ts_ns = self.now_ns();
+        self.append_bytes("event", ts_ns, &bytes)
+    }
+}
+
+impl EventSink for crate::writer::EventLogWriter {
+    fn append_bytes(&mut self, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64> {
+        Ok(crate::writer::EventLogWriter::append_bytes(self, kind, ts_ns, payload)?)
+    }
+
+    fn append_stream(&mut self, stream: &str, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64> {
+        Ok(crate::writer::EventLogWriter::append_stream(self, stream, kind, ts_ns, payload)?.0)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(crate::writer::EventLogWriter::flush(self)?)
+    }
+
+    fn next_seq(&self) -> u64 {
+        crate::writer::EventLogWriter::next_seq(self)
+    }
+
+    fn now_ns(&self) -> u64 {
+        crate::writer::EventLogWriter::now_ns(self)
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use el_core::instrument::InstrumentKey;
+use serde::Deserialize;
+use std::path::Path;
+
+use crate::crypto::CopyKeys;
+use crate::envelope::EventEnvelope;
+use crate::source::LogSource;
+use crate::writer::{write_new_log, EventLogWriter, LogRange, WriterOptions};
+
+/// Which records a slice keeps. Unset criteria match everything; a record
+/// is kept only if it matches all of the set ones.
+#[derive(Debug, Clone, Default)]
+pub struct SliceFilter {
+    /// any of these streams (empty: all)
+    pub streams: Vec<String>,
+    /// any of these kinds (empty: all)
+    pub kinds: Vec<String>,
+    /// any of these instruments, read from `el_core::event::Event` payloads
+    /// (empty: all). Payloads that are not events never match.
+    pub instruments: Vec<InstrumentKey>,
+    /// inclusive seq range of the input
+    pub seq_range: Option<(u64, u64)>,
+    /// `[from, to)` in ts_ns
+    pub ts_range_ns: Option<(u64, u64)>,
+}
+
+/// The part of an `Event` payload the instrument filter needs.
+#[derive(Deserialize)]
+struct InstrumentHead {
+    instrument: InstrumentKey,
+}
+
+impl SliceFilter {
+    /// Whether the record is kept; `None` when the instrument filter is set
+    /// and the payload is not an event.
+    pub fn matches(&self, env: &EventEnvelope, payload: &[u8]) -> Option<bool> {
+        let in_seq = self.seq_range.is_none_or(|(from, to)| (from..=to).contains(&env.seq));
+        let in_ts = self.ts_range_ns.is_none_or(|(from, to)| (from..to).contains(&env.ts_ns));
+        let kind = self.kinds.is_empty() || self.kinds.contains(&env.kind);
+        let stream = self.streams.is_empty() || self.streams.contains(&env.stream);
+        if !(in_seq && in_ts && kind && stream) {
+            return Some(false);
+        }
+        if self.instruments.is_empty() {
+            return Some(true);
+        }
+        let head: InstrumentHead = serde_json::from_slice(payload).ok()?;
+        Some(self.instruments.contains(&head.instrument))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliceReport {
+    /// Seq/ts span of the new log.
+    pub range: LogRange,
+    /// records looked at (after the stream filter and seq seek)
+    pub scanned: u64,
+    /// records skipped because the instrument filter could not decode them
+    pub undecoded: u64,
+}
+
+/// Copy the records of `input` (a file or segment directory) that match
+/// `filter` into a new log at `out`. Records are renumbered from 1, per
+/// stream as well; each keeps its seq in the input as `origin_seq`.
+/// A failed slice removes the output. A sealed input needs `keys`, as for
+/// `merge`.
+pub fn slice(
+    input: impl AsRef<Path>,
+    out: impl AsRef<Path>,
+    filter: &SliceFilter,
+    opts: WriterOptions,
+    keys: Option<&CopyKeys>,
+) -> Result<SliceReport> {
+    let out = out.as_ref();
+    write_new_log(out, || slice_into(input.as_ref(), out, filter, opts, keys))
+}
+
+fn slice_into(
+    input: &Path,
+    out: &Path,
+    filter: &SliceFilter,
+    opts: WriterOptions,
+    keys: Option<&CopyKeys>,
+) -> Result<SliceReport> {
+    let mut r = LogSource::open(input)?;
+    if let Some(k) = keys {
+        r = r.with_keys(k.keys.clone());
+    }
+    if !filter.streams.is_empty() {
+        // skipped records are not even decoded
+        r = r.with_streams(filter.streams.iter().cloned());
+    }
+    if let Some((from, _)) = filter.seq_range {
+        r.seek_seq(from)?;
+    }
+
+    let mut w = EventLogWriter::open_with(out, "el:slice", opts)?;
+    if let Some(k) = keys {
+        w = w.with_encryption(&*k.keys, k.key_id)?;
+    }
+    let mut scanned = 0u64;
+    let mut undecoded = 0u64;
+    while let Some((env, payload)) = r.next()? {
+        if filter.seq_range.is_some_and(|(_, to)| env.seq > to) {
+            break;
+        }
+        scanned += 1;
+        match filter.matches(&env, &payload) {
+            Some(true) => {
+                w.append_copy(&env, &payload)?;
+            }
+            Some(false) => {}
+            None => undecoded += 1,
+        }
+    }
+    w.flush()?;
+
+    Ok(SliceReport { range: w.range(), scanned, undecoded })
+}
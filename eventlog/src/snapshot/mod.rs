@@ -1,4 +1,4 @@
-use crate::hash::stable_hash;
+use crate::hash::{stable_hash, StateHash};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Snapshot<T> {
@@ -6,7 +6,7 @@ pub struct Snapshot<T> {
     pub hash: u64,
 }
 
-impl<T: std::hash::Hash> Snapshot<T> {
+impl<T: StateHash> Snapshot<T> {
     pub fn new(state: T) -> Self {
         let hash = stable_hash(&state);
         Self { state, hash }
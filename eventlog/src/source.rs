@@ -0,0 +1,83 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::crypto::SharedKeys;
+use crate::envelope::EventEnvelope;
+use crate::error::EventLogError;
+use crate::reader::EventLogReader;
+use crate::schema::SchemaRegistry;
+use crate::segment::SegmentedReader;
+
+/// A log file or a segmented directory, read as one log.
+pub enum LogSource {
+    File(EventLogReader),
+    Segments(SegmentedReader),
+}
+
+impl LogSource {
+    /// A directory is read as segments, anything else as a single file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, EventLogError> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            Ok(LogSource::Segments(SegmentedReader::open(path)?))
+        } else {
+            Ok(LogSource::File(EventLogReader::open(path)?))
+        }
+    }
+
+    /// Only return records of `streams`.
+    pub fn with_streams<S: Into<String>>(self, streams: impl IntoIterator<Item = S>) -> Self {
+        match self {
+            LogSource::File(r) => LogSource::File(r.with_streams(streams)),
+            LogSource::Segments(r) => LogSource::Segments(r.with_streams(streams)),
+        }
+    }
+
+    /// Check kinds and upcast payloads with `schemas`.
+    pub fn with_schemas(self, schemas: impl Into<Arc<SchemaRegistry>>) -> Self {
+        match self {
+            LogSource::File(r) => LogSource::File(r.with_schemas(schemas)),
+            LogSource::Segments(r) => LogSource::Segments(r.with_schemas(schemas)),
+        }
+    }
+
+    /// Decrypt sealed payloads with keys from `keys`.
+    pub fn with_keys(self, keys: SharedKeys) -> Self {
+        match self {
+            LogSource::File(r) => LogSource::File(r.with_keys(keys)),
+            LogSource::Segments(r) => LogSource::Segments(r.with_keys(keys)),
+        }
+    }
+
+    /// Hand out payloads that cannot be decrypted as stored instead of failing.
+    pub fn with_sealed_payloads(self) -> Self {
+        match self {
+            LogSource::File(r) => LogSource::File(r.with_sealed_payloads()),
+            LogSource::Segments(r) => LogSource::Segments(r.with_sealed_payloads()),
+        }
+    }
+
+    /// Position at the first record with `seq >= target`.
+    pub fn seek_seq(&mut self, target: u64) -> Result<(), EventLogError> {
+        match self {
+            LogSource::File(r) => r.seek_seq(target),
+            LogSource::Segments(r) => r.seek_seq(target),
+        }
+    }
+
+    /// Position at the first record with `ts_ns >= target_ns`.
+    pub fn seek_ts(&mut self, target_ns: u64) -> Result<(), EventLogError> {
+        match self {
+            LogSource::File(r) => r.seek_ts(target_ns),
+            LogSource::Segments(r) => r.seek_ts(target_ns),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<(EventEnvelope, Vec<u8>)>, EventLogError> {
+        match self {
+            LogSource::File(r) => r.next(),
+            LogSource::Segments(r) => r.next(),
+        }
+    }
+}
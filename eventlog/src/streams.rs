@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use crate::envelope::EventEnvelope;
+
+/// Records missing from one stream: its seq jumped from `prev` to `current`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamGap {
+    pub stream: String,
+    pub prev: u64,
+    pub current: u64,
+    /// global seq of the record after the gap
+    pub seq: u64,
+}
+
+impl std::fmt::Display for StreamGap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stream {:?} seq gap: prev={} current={} (seq={})",
+            self.stream, self.prev, self.current, self.seq
+        )
+    }
+}
+
+/// What one stream of a log holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamSpan {
+    pub first_seq: u64,
+    pub last_seq: u64,
+    pub records: u64,
+}
+
+/// Checks that each stream's seq advances by one, independently of the
+/// other streams interleaved with it.
+///
+/// A stream may start at any seq (a log or segment that begins mid-stream);
+/// only jumps between its records are gaps.
+#[derive(Debug, Clone, Default)]
+pub struct StreamSeqAudit {
+    streams: BTreeMap<String, StreamSpan>,
+}
+
+impl StreamSeqAudit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn check(&mut self, env: &EventEnvelope) -> Result<(), StreamGap> {
+        let seq = env.seq_in_stream();
+        let Some(span) = self.streams.get_mut(&env.stream) else {
+            self.streams
+                .insert(env.stream.clone(), StreamSpan { first_seq: seq, last_seq: seq, records: 1 });
+            return Ok(());
+        };
+        if seq != span.last_seq + 1 {
+            return Err(StreamGap {
+                stream: env.stream.clone(),
+                prev: span.last_seq,
+                current: seq,
+                seq: env.seq,
+            });
+        }
+        span.last_seq = seq;
+        span.records += 1;
+        Ok(())
+    }
+
+    /// Streams seen so far, by name.
+    pub fn streams(&self) -> impl Iterator<Item = (&str, &StreamSpan)> {
+        self.streams.iter().map(|(k, v)| (k.as_str(), v))
+    }
+}
@@ -1,12 +1,22 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use base64::Engine;
 use crc32fast::Hasher as Crc32;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use crate::chain::{self, Digest, Linked};
+use crate::compact::Anchor;
+use crate::compress::{self, Compression, PayloadCompressor};
+use crate::crypto::{KeyId, KeyProvider, PayloadSealer};
 use crate::envelope::EventEnvelope;
+use crate::error::EventLogError;
+use crate::frame::{self, FrameDict, LogFormat, ReadOutcome, RecordHead};
+use crate::index::IndexWriter;
+use el_core::clock::{Clock, SharedClock, WallClock};
 use fs2::FileExt;
 
 fn crc32(bytes: &[u8]) -> u32 {
@@ -15,21 +25,115 @@ fn crc32(bytes: &[u8]) -> u32 {
     h.finalize()
 }
 
-#[derive(Clone, Copy)]
+/// A record ready to be written: `bytes` as they go to disk.
+struct Stored<'a> {
+    stream: &'a str,
+    kind: &'a str,
+    ts_ns: u64,
+    origin_seq: Option<u64>,
+    checksum: u32,
+    bytes: &'a [u8],
+    codec: Option<compress::Codec>,
+    key: Option<KeyId>,
+    chain: Option<Digest>,
+}
+
+#[derive(Clone, Copy, Default)]
 pub enum Durability {
+    #[default]
     Buffered,
     FsyncOnFlush,
     FsyncEvery { n: u64 },
 }
 
+#[derive(Clone, Copy, Default)]
+pub struct WriterOptions {
+    pub durability: Durability,
+    /// Encoding for a new file. An existing non-empty file must already use it.
+    pub format: LogFormat,
+    /// Maintain a sparse `<log>.idx` with a point every `n` records.
+    pub index_stride: Option<u64>,
+    /// Chain every record to the previous one with a blake3 digest
+    /// (see `chain`). A log that already has a chain keeps chaining.
+    pub hash_chain: bool,
+    /// Compression of new records; existing records keep whatever they used.
+    pub compression: Compression,
+}
+
+/// Seq and ts_ns span of the records in one log file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogRange {
+    pub records: u64,
+    pub first_seq: u64,
+    pub last_seq: u64,
+    pub first_ts_ns: u64,
+    pub last_ts_ns: u64,
+}
+
+impl LogRange {
+    pub fn observe(&mut self, seq: u64, ts_ns: u64) {
+        if self.records == 0 {
+            self.first_seq = seq;
+            self.first_ts_ns = ts_ns;
+        }
+        self.records += 1;
+        self.last_seq = seq;
+        self.last_ts_ns = ts_ns;
+    }
+}
+
+/// Where a log picks up: the last seq, chain digest and per-stream seqs
+/// before its first record. Default for a log that starts from scratch.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Continuation {
+    pub(crate) seq: u64,
+    pub(crate) chain: Option<Digest>,
+    pub(crate) stream_seqs: BTreeMap<String, u64>,
+}
+
+impl Continuation {
+    fn observe(&mut self, seq: u64, stream: &str, stream_seq: u64, chain: Option<Digest>) {
+        self.seq = seq;
+        self.chain = chain;
+        set_stream_seq(&mut self.stream_seqs, stream, stream_seq);
+    }
+}
+
+/// Record `seq` as the last one of `stream` (allocates only for a new stream).
+fn set_stream_seq(seqs: &mut BTreeMap<String, u64>, stream: &str, seq: u64) {
+    match seqs.get_mut(stream) {
+        Some(s) => *s = seq,
+        None => {
+            seqs.insert(stream.to_string(), seq);
+        }
+    }
+}
+
 pub struct EventLogWriter {
     #[allow(dead_code)]
     path: PathBuf,
+    /// stream of `append_bytes`; `append_stream` names its own
     stream: String,
     next_seq: u64,
+    /// last seq handed out in each stream
+    stream_seqs: BTreeMap<String, u64>,
+    range: LogRange,
+    /// logical end of file, including bytes still sitting in `out`
+    position: u64,
     out: BufWriter<File>,
     durability: Durability,
     since_fsync: u64,
+    format: LogFormat,
+    dict: FrameDict,
+    frame_buf: Vec<u8>,
+    index: Option<IndexWriter>,
+    /// digest of the last record; `None` when not chaining
+    chain: Option<Digest>,
+    compressor: Option<PayloadCompressor>,
+    /// encrypts stored payloads; set by `with_encryption`
+    sealer: Option<Arc<PayloadSealer>>,
+    /// stamps `write()`; wall time unless replaced by `with_clock`
+    clock: SharedClock,
 }
 
 impl EventLogWriter {
@@ -37,7 +141,33 @@ impl EventLogWriter {
         path: impl AsRef<Path>,
         stream: impl Into<String>,
         durability: Durability,
-    ) -> Result<Self> {
+    ) -> Result<Self, EventLogError> {
+        Self::open_with(path, stream, WriterOptions { durability, ..Default::default() })
+    }
+
+    pub fn open_with(
+        path: impl AsRef<Path>,
+        stream: impl Into<String>,
+        opts: WriterOptions,
+    ) -> Result<Self, EventLogError> {
+        // a compacted log carries on from the records it dropped
+        let base = match Anchor::load(path.as_ref())? {
+            Some(anchor) => anchor.continuation(),
+            None => Continuation::default(),
+        };
+        Self::open_after(path, stream, opts, base)
+    }
+
+    /// Like `open_with`, but the file continues `base`: an empty file starts
+    /// numbering at `base.seq + 1`, links its first record to `base.chain`
+    /// and carries on the stream seqs. Used by the segmented log, where each
+    /// segment continues the previous one.
+    pub(crate) fn open_after(
+        path: impl AsRef<Path>,
+        stream: impl Into<String>,
+        opts: WriterOptions,
+        base: Continuation,
+    ) -> Result<Self, EventLogError> {
         let path = path.as_ref().to_path_buf();
 
         let mut file = OpenOptions::new()
@@ -45,43 +175,351 @@ impl EventLogWriter {
             .read(true)
             .append(true)
             .open(&path)
-            .with_context(|| format!("open {:?}", path))?;
+            .map_err(EventLogError::file(&path))?;
 
-        file.lock_exclusive().context("lock_exclusive")?;
+        file.lock_exclusive().map_err(EventLogError::file(&path))?;
 
-        let last_seq = recover_tail_and_last_seq(&mut file)?;
-        file.seek(SeekFrom::End(0))?;
+        let head = read_head(&mut file)?;
+        let mut len = file.metadata()?.len();
+        if len > 0 && len < frame::MAGIC.len() as u64 && frame::MAGIC.starts_with(&head) {
+            // torn header from a binary writer that never got to its first record
+            file.set_len(0)?;
+            len = 0;
+        }
+
+        let mut last = base;
+        let (format, range, dict) = match (len, LogFormat::detect(&head)) {
+            (0, _) => {
+                if opts.format == LogFormat::Binary {
+                    file.write_all(frame::MAGIC)?;
+                }
+                (opts.format, LogRange::default(), FrameDict::default())
+            }
+            (_, found) if found != opts.format => {
+                return Err(EventLogError::FormatMismatch { path, found, wanted: opts.format })
+            }
+            (_, LogFormat::JsonLines) => {
+                let range = recover_tail_and_last_seq(&mut file, &mut last)?;
+                (LogFormat::JsonLines, range, FrameDict::default())
+            }
+            (_, LogFormat::Binary) => {
+                let (range, dict) = recover_binary_tail(&mut file, &mut last)?;
+                (LogFormat::Binary, range, dict)
+            }
+        };
+        let chain = match last.chain {
+            Some(d) => Some(d),
+            None if opts.hash_chain => Some(chain::GENESIS),
+            None => None,
+        };
+        let position = file.seek(SeekFrom::End(0))?;
+
+        let dict_dir = compress::dict_dir(&path);
+        let compressor =
+            PayloadCompressor::new(opts.compression, &dict_dir).map_err(|e| EventLogError::layout(&dict_dir, format!("{:#}", e)))?;
+
+        let index = match opts.index_stride {
+            Some(stride) => {
+                let log_len = if range.records == 0 { 0 } else { position };
+                Some(IndexWriter::open(&path, stride, log_len, &dict)?)
+            }
+            None => None,
+        };
 
         Ok(Self {
             path,
             stream: stream.into(),
-            next_seq: last_seq + 1,
+            next_seq: last.seq + 1,
+            stream_seqs: last.stream_seqs,
+            range,
+            position,
             out: BufWriter::new(file),
-            durability,
+            durability: opts.durability,
             since_fsync: 0,
+            format,
+            dict,
+            frame_buf: Vec::new(),
+            index,
+            chain,
+            compressor,
+            sealer: None,
+            clock: Arc::new(WallClock),
         })
     }
 
-    pub fn append_bytes(&mut self, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64> {
+    /// Stamp `write()` with `clock` (a `ManualClock` makes captures reproducible).
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Encrypt the payloads of new records with key `key_id` from `keys`.
+    /// Existing records keep whatever they used.
+    pub fn with_encryption(self, keys: &dyn KeyProvider, key_id: KeyId) -> Result<Self, EventLogError> {
+        let sealer = PayloadSealer::new(keys, key_id).map_err(|e| EventLogError::layout(&self.path, format!("{:#}", e)))?;
+        Ok(self.with_sealer(Some(Arc::new(sealer))))
+    }
+
+    pub(crate) fn with_sealer(mut self, sealer: Option<Arc<PayloadSealer>>) -> Self {
+        self.sealer = sealer;
+        self
+    }
+
+    /// Key new payloads are encrypted with, if any.
+    pub fn key_id(&self) -> Option<KeyId> {
+        self.sealer.as_ref().map(|s| s.key_id())
+    }
+
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    /// Current time of the writer's clock, as a record `ts_ns`.
+    pub fn now_ns(&self) -> u64 {
+        self.clock.now_ns().max(0) as u64
+    }
+
+    pub fn format(&self) -> LogFormat {
+        self.format
+    }
+
+    /// Seq/ts span of everything in the file, including records not yet flushed.
+    pub fn range(&self) -> LogRange {
+        self.range
+    }
+
+    /// Seq the next appended record will get.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Last seq of every stream written so far (carried over from earlier
+    /// segments in a segmented log).
+    pub fn stream_seqs(&self) -> &BTreeMap<String, u64> {
+        &self.stream_seqs
+    }
+
+    /// Chain digest of the last record (`GENESIS` before the first), if chaining.
+    pub fn chain_head(&self) -> Option<Digest> {
+        self.chain
+    }
+
+    /// Size of the file once buffered bytes are flushed.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Append to the writer's own stream.
+    pub fn append_bytes(&mut self, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64, EventLogError> {
+        let stream = std::mem::take(&mut self.stream);
+        let res = self.append_stream(&stream, kind, ts_ns, payload);
+        self.stream = stream;
+        res.map(|(seq, _)| seq)
+    }
+
+    /// Append to `stream`, which may be any name; streams are interleaved in
+    /// one log. Returns the global seq and the seq within the stream.
+    pub fn append_stream(
+        &mut self,
+        stream: &str,
+        kind: &str,
+        ts_ns: u64,
+        payload: &[u8],
+    ) -> Result<(u64, u64), EventLogError> {
+        self.append_record(stream, kind, ts_ns, payload, None, self.chain)
+    }
+
+    /// Append a record read from another log: same stream, kind, ts and
+    /// payload, renumbered here, with its old seq kept as `origin_seq`.
+    ///
+    /// A record that was sealed there (`key_id` set) is refused unless this
+    /// writer seals too: its payload must have been decrypted by the reader
+    /// and is sealed again here, since a seal is bound to the old seq.
+    pub fn append_copy(&mut self, env: &EventEnvelope, payload: &[u8]) -> Result<(u64, u64), EventLogError> {
+        if let Some(key) = env.key_id {
+            let refuse = |reason: String| EventLogError::Encode { seq: self.next_seq, reason };
+            if self.sealer.is_none() {
+                return Err(refuse(format!("record seq={} is sealed with key {}; this writer does not encrypt", env.seq, key)));
+            }
+            if crc32(payload) != env.checksum {
+                return Err(refuse(format!("record seq={} is still sealed; read it with key {}", env.seq, key)));
+            }
+        }
+        self.append_record(&env.stream, &env.kind, env.ts_ns, payload, Some(env.seq), self.chain)
+    }
+
+    /// Append a record of another log as is: it must be the next one here
+    /// (same seq and stream seq), and it is chained only if it was chained
+    /// there, to the same digest. Used by replication, where this log is a
+    /// copy of the other one.
+    ///
+    /// A sealed record (`key_id` set) comes as stored there and is written
+    /// verbatim, codec and key included: the seal is bound to the seq and
+    /// checksum, which stay the same. Its chain digest covers the plaintext,
+    /// so it is taken over unchecked. A dictionary codec needs the
+    /// dictionary next to this log as well.
+    pub fn append_replica(&mut self, env: &EventEnvelope, payload: &[u8]) -> Result<(), EventLogError> {
+        let diverges = |reason: String| EventLogError::Replica { seq: env.seq, reason };
+        let stream_seq = self.stream_seqs.get(&env.stream).map_or(1, |s| s + 1);
+        if env.seq != self.next_seq || env.seq_in_stream() != stream_seq {
+            return Err(diverges(format!(
+                "expected seq={} stream_seq={} in {:?}, got stream_seq={}",
+                self.next_seq,
+                stream_seq,
+                env.stream,
+                env.seq_in_stream()
+            )));
+        }
+        if let Some(key) = env.key_id {
+            let codec = match &env.codec {
+                Some(name) => Some(compress::Codec::parse(name).ok_or_else(|| diverges(format!("unknown codec {:?}", name)))?),
+                None => None,
+            };
+            let chain = match &env.chain {
+                Some(hex) => Some(chain::from_hex(hex).ok_or_else(|| diverges("malformed chain digest".into()))?),
+                None => None,
+            };
+            self.write_stored(Stored {
+                stream: &env.stream,
+                kind: &env.kind,
+                ts_ns: env.ts_ns,
+                origin_seq: env.origin_seq,
+                checksum: env.checksum,
+                bytes: payload,
+                codec,
+                key: Some(key),
+                chain,
+            })?;
+            return Ok(());
+        }
+        if crc32(payload) != env.checksum {
+            return Err(diverges("payload checksum mismatch".into()));
+        }
+        let prev = match &env.chain {
+            Some(hex) => {
+                let prev = self.chain.unwrap_or(chain::GENESIS);
+                let want = chain::from_hex(hex).ok_or_else(|| diverges("malformed chain digest".into()))?;
+                if chain::link(&prev, &Linked::of(env, payload)) != want {
+                    return Err(diverges("chain diverges".into()));
+                }
+                Some(prev)
+            }
+            None => None,
+        };
+        self.append_record(&env.stream, &env.kind, env.ts_ns, payload, env.origin_seq, prev)?;
+        Ok(())
+    }
+
+    fn append_record(
+        &mut self,
+        stream: &str,
+        kind: &str,
+        ts_ns: u64,
+        payload: &[u8],
+        origin_seq: Option<u64>,
+        chain_from: Option<Digest>,
+    ) -> Result<(u64, u64), EventLogError> {
         let checksum = crc32(payload);
-        let payload_b64 = base64::engine::general_purpose::STANDARD.encode(payload);
 
         let seq = self.next_seq;
-        self.next_seq += 1;
+        let stream_seq = self.stream_seqs.get(stream).map_or(1, |s| s + 1);
+        let chain = chain_from.map(|prev| {
+            let rec = Linked { seq, stream_seq, origin_seq, ts_ns, stream, kind, payload };
+            chain::link(&prev, &rec)
+        });
 
-        let env = EventEnvelope {
-            seq,
-            ts_ns,
-            stream: self.stream.clone(),
-            kind: kind.to_string(),
-            payload_b64,
-            checksum,
+        // crc and chain cover the payload as given; only the stored bytes are compressed
+        let encode = |e: anyhow::Error| EventLogError::Encode { seq, reason: format!("{:#}", e) };
+        let compressed = match &mut self.compressor {
+            Some(c) => c.compress(payload).map_err(encode)?,
+            None => None,
+        };
+        let (stored, codec) = match &compressed {
+            Some((bytes, codec)) => (bytes.as_slice(), Some(*codec)),
+            None => (payload, None),
         };
+        // ...and then encrypted
+        let sealed = match &self.sealer {
+            Some(s) => Some(s.seal(seq, checksum, stored).map_err(encode)?),
+            None => None,
+        };
+        let stored = sealed.as_deref().unwrap_or(stored);
+        let key = self.key_id();
+
+        self.write_stored(Stored { stream, kind, ts_ns, origin_seq, checksum, bytes: stored, codec, key, chain })
+    }
+
+    /// Write a record whose stored bytes are final (compressed and sealed
+    /// as `codec` and `key` say) under the next seq.
+    fn write_stored(&mut self, rec: Stored) -> Result<(u64, u64), EventLogError> {
+        let Stored { stream, kind, ts_ns, origin_seq, checksum, bytes: stored, codec, key, chain } = rec;
+        let seq = self.next_seq;
+        let stream_seq = self.stream_seqs.get(stream).map_or(1, |s| s + 1);
+        let offset = self.position;
+        let own_stream_seq = (stream_seq != seq).then_some(stream_seq);
 
-        let line = serde_json::to_string(&env)?;
-        self.out.write_all(line.as_bytes())?;
-        self.out.write_all(b"\n")?;
+        match self.format {
+            LogFormat::JsonLines => {
+                let env = EventEnvelope {
+                    seq,
+                    stream_seq: own_stream_seq,
+                    origin_seq,
+                    ts_ns,
+                    stream: stream.to_string(),
+                    kind: kind.to_string(),
+                    payload_b64: base64::engine::general_purpose::STANDARD.encode(stored),
+                    checksum,
+                    chain: chain.as_ref().map(chain::to_hex),
+                    codec: codec.map(|c| c.name()),
+                    key_id: key,
+                };
 
+                let line = serde_json::to_string(&env)?;
+                self.out.write_all(line.as_bytes())?;
+                self.out.write_all(b"\n")?;
+                self.position += line.len() as u64 + 1;
+            }
+            LogFormat::Binary => {
+                // definitions and the record go out in one write so a torn
+                // write never leaves a record without its names
+                self.frame_buf.clear();
+                let (kind_id, new_kind) = self.dict.kind_id(kind, &mut self.frame_buf)?;
+                let (stream_id, new_stream) = self.dict.stream_id(stream, &mut self.frame_buf)?;
+                if let Some(idx) = &mut self.index {
+                    if new_kind {
+                        idx.define_kind(kind_id, kind)?;
+                    }
+                    if new_stream {
+                        idx.define_stream(stream_id, stream)?;
+                    }
+                }
+                let head = RecordHead {
+                    seq,
+                    stream_seq: own_stream_seq,
+                    origin_seq,
+                    ts_ns,
+                    kind_id,
+                    stream_id,
+                    checksum,
+                    chain,
+                    codec,
+                    key,
+                };
+                frame::encode_record(&mut self.frame_buf, &head, stored);
+                self.out.write_all(&self.frame_buf)?;
+                self.position += self.frame_buf.len() as u64;
+            }
+        }
+
+        if let Some(idx) = &mut self.index {
+            idx.on_record(seq, ts_ns, offset)?;
+        }
+        self.range.observe(seq, ts_ns);
+        if chain.is_some() {
+            self.chain = chain;
+        }
+        self.next_seq += 1;
+        set_stream_seq(&mut self.stream_seqs, stream, stream_seq);
         self.since_fsync += 1;
 
         if let Durability::FsyncEvery { n } = self.durability {
@@ -90,7 +528,7 @@ impl EventLogWriter {
             }
         }
 
-        Ok(seq)
+        Ok((seq, stream_seq))
     }
 
     pub fn append_json_value(
@@ -98,22 +536,28 @@ impl EventLogWriter {
         kind: &str,
         ts_ns: u64,
         payload: &serde_json::Value,
-    ) -> Result<u64> {
+    ) -> Result<u64, EventLogError> {
         let bytes = serde_json::to_vec(payload)?;
         self.append_bytes(kind, ts_ns, &bytes)
     }
 
-    pub fn write<T: Serialize>(&mut self, ev: &T) -> Result<u64> {
+    /// Append `ev` as JSON, stamped with the writer's clock.
+    pub fn write<T: Serialize>(&mut self, ev: &T) -> Result<u64, EventLogError> {
         let bytes = serde_json::to_vec(ev)?;
-        self.append_bytes("event", 0, &bytes)
+        let ts_ns = self.now_ns();
+        self.append_bytes("event", ts_ns, &bytes)
     }
 
-    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, EventLogError> {
         Self::open_append(path, "el:eventlog", Durability::Buffered)
     }
 
-    pub fn flush(&mut self) -> Result<()> {
+    pub fn flush(&mut self) -> Result<(), EventLogError> {
         self.out.flush()?;
+        // the index is rebuildable, so it is flushed but never fsynced
+        if let Some(idx) = &mut self.index {
+            idx.flush()?;
+        }
         match self.durability {
             Durability::Buffered => {}
             _ => {
@@ -125,14 +569,38 @@ impl EventLogWriter {
     }
 }
 
-fn recover_tail_and_last_seq(file: &mut File) -> Result<u64> {
+/// Run `write` to produce a new log at `out`, which must not exist or be
+/// empty. If `write` fails, the partial log and its index are removed.
+pub(crate) fn write_new_log<T>(out: &Path, write: impl FnOnce() -> Result<T>) -> Result<T> {
+    if std::fs::metadata(out).is_ok_and(|m| m.len() > 0 || m.is_dir()) {
+        anyhow::bail!("output {:?} already exists", out);
+    }
+    let res = write();
+    if res.is_err() {
+        let _ = std::fs::remove_file(out);
+        let _ = std::fs::remove_file(crate::index::index_path(out));
+    }
+    res
+}
+
+fn read_head(file: &mut File) -> std::io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut head = Vec::with_capacity(frame::MAGIC.len());
+    Read::by_ref(file)
+        .take(frame::MAGIC.len() as u64)
+        .read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// Drops a torn tail; returns what the good part holds and advances `last` past its records.
+fn recover_tail_and_last_seq(file: &mut File, last: &mut Continuation) -> std::io::Result<LogRange> {
     file.seek(SeekFrom::Start(0))?;
     let mut reader = BufReader::new(&mut *file);
 
     let mut buf = String::new();
     let mut offset = 0u64;
     let mut last_good = 0u64;
-    let mut last_seq = 0u64;
+    let mut range = LogRange::default();
 
     loop {
         buf.clear();
@@ -149,7 +617,9 @@ fn recover_tail_and_last_seq(file: &mut File) -> Result<u64> {
 
         match serde_json::from_str::<EventEnvelope>(line) {
             Ok(env) => {
-                last_seq = env.seq;
+                range.observe(env.seq, env.ts_ns);
+                let chain = env.chain.as_deref().and_then(chain::from_hex);
+                last.observe(env.seq, &env.stream, env.seq_in_stream(), chain);
                 last_good = offset;
             }
             Err(_) => break,
@@ -164,5 +634,39 @@ fn recover_tail_and_last_seq(file: &mut File) -> Result<u64> {
         file.sync_all().ok();
     }
 
-    Ok(last_seq)
+    Ok(range)
+}
+
+fn recover_binary_tail(file: &mut File, last: &mut Continuation) -> std::io::Result<(LogRange, FrameDict)> {
+    file.seek(SeekFrom::Start(frame::MAGIC.len() as u64))?;
+    let mut reader = BufReader::new(&mut *file);
+
+    let mut dict = FrameDict::default();
+    let mut offset = frame::MAGIC.len() as u64;
+    let mut range = LogRange::default();
+
+    // stop at the first torn or undecodable frame; everything after it is dropped
+    while let Ok(ReadOutcome::Frame(f, n)) = frame::read_frame(&mut reader) {
+        if dict.observe(&f).is_err() {
+            break;
+        }
+        if let frame::Frame::Record { seq, stream_seq, ts_ns, stream_id, chain, .. } = f {
+            let Some(stream) = dict.stream(stream_id) else {
+                break;
+            };
+            range.observe(seq, ts_ns);
+            last.observe(seq, stream, stream_seq.unwrap_or(seq), chain);
+        }
+        offset += n;
+    }
+
+    drop(reader);
+
+    let len = file.metadata()?.len();
+    if offset < len {
+        file.set_len(offset)?;
+        file.sync_all().ok();
+    }
+
+    Ok((range, dict))
 }
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use eventlog::writer::{Durability, WriterOptions};
+use eventlog::{EventLogReader, EventLogWriter, LogFormat};
+use std::path::{Path, PathBuf};
+
+type Record = (u64, String, String, Vec<u8>);
+
+fn tmp(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+fn binary() -> WriterOptions {
+    WriterOptions { durability: Durability::Buffered, format: LogFormat::Binary, ..Default::default() }
+}
+
+fn read_all(path: &Path) -> Result<Vec<Record>> {
+    let mut r = EventLogReader::open(path)?;
+    let mut out = Vec::new();
+    while let Some((env, payload)) = r.next()? {
+        out.push((env.seq, env.kind, env.stream, payload));
+    }
+    Ok(out)
+}
+
+#[test]
+fn binary_roundtrip_and_reopen_continues_seq() -> Result<()> {
+    let path = tmp("binary_roundtrip.log");
+
+    {
+        let mut w = EventLogWriter::open_with(&path, "el:test", binary())?;
+        w.append_bytes("event", 1, b"a")?;
+        w.append_bytes("snapshot", 2, &[0u8, 1, 2])?;
+        w.flush()?;
+    }
+    {
+        let mut w = EventLogWriter::open_with(&path, "el:test", binary())?;
+        assert_eq!(w.append_bytes("event", 3, b"")?, 3);
+        w.flush()?;
+    }
+
+    assert_eq!(EventLogReader::open(&path)?.format(), LogFormat::Binary);
+    let got = read_all(&path)?;
+    assert_eq!(
+        got,
+        vec![
+            (1, "event".into(), "el:test".into(), b"a".to_vec()),
+            (2, "snapshot".into(), "el:test".into(), vec![0, 1, 2]),
+            (3, "event".into(), "el:test".into(), vec![]),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn binary_torn_tail_is_truncated_on_open() -> Result<()> {
+    let path = tmp("binary_torn.log");
+
+    {
+        let mut w = EventLogWriter::open_with(&path, "el:test", binary())?;
+        w.append_bytes("event", 1, b"first")?;
+        w.append_bytes("event", 2, b"second")?;
+        w.flush()?;
+    }
+
+    // chop the last record in half
+    let len = std::fs::metadata(&path)?.len();
+    std::fs::OpenOptions::new().write(true).open(&path)?.set_len(len - 4)?;
+    assert!(read_all(&path).is_err());
+
+    {
+        let mut w = EventLogWriter::open_with(&path, "el:test", binary())?;
+        assert_eq!(w.append_bytes("event", 3, b"third")?, 2);
+        w.flush()?;
+    }
+
+    let got = read_all(&path)?;
+    assert_eq!(got.len(), 2);
+    assert_eq!(got[1].3, b"third".to_vec());
+    Ok(())
+}
+
+#[test]
+fn json_lines_stays_default_and_format_mismatch_is_rejected() -> Result<()> {
+    let path = tmp("json_default.log");
+
+    {
+        let mut w = EventLogWriter::open(&path)?;
+        w.append_bytes("event", 1, b"x")?;
+        w.flush()?;
+    }
+
+    assert_eq!(EventLogReader::open(&path)?.format(), LogFormat::JsonLines);
+    assert!(std::fs::read_to_string(&path)?.starts_with('{'));
+    assert!(EventLogWriter::open_with(&path, "el:eventlog", binary()).is_err());
+    Ok(())
+}
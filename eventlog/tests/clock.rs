@@ -0,0 +1,84 @@
+use anyhow::Result;
+use el_core::clock::{Clock, ManualClock, MonotonicClock, SharedClock};
+use eventlog::group::{GroupCommitOptions, GroupCommitWriter};
+use eventlog::segment::{segment_files, Rotation};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter, EventSink, LogFormat, SegmentedWriter};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    let _ = std::fs::remove_dir_all(&p);
+    p
+}
+
+fn read_ts(path: &Path) -> Result<Vec<u64>> {
+    let mut r = EventLogReader::open(path)?;
+    let mut out = Vec::new();
+    while let Some((env, _)) = r.next()? {
+        out.push(env.ts_ns);
+    }
+    Ok(out)
+}
+
+fn capture(w: &mut impl EventSink) -> Result<()> {
+    for i in 0..3 {
+        w.write(&json!({ "i": i }))?;
+    }
+    w.flush()
+}
+
+#[test]
+fn manual_clock_makes_captures_byte_identical() -> Result<()> {
+    let opts = WriterOptions { format: LogFormat::Binary, hash_chain: true, ..Default::default() };
+    let mut files = Vec::new();
+    for name in ["clock_live.bin", "clock_replay.bin"] {
+        let path = tmp_log(name);
+        let clock: SharedClock = Arc::new(ManualClock::new(1_000).with_step(10));
+        capture(&mut EventLogWriter::open_with(&path, "md", opts)?.with_clock(clock))?;
+        assert_eq!(read_ts(&path)?, vec![1_000, 1_010, 1_020]);
+        files.push(std::fs::read(&path)?);
+    }
+    assert_eq!(files[0], files[1]);
+
+    // through the group-commit front-end, the stamp is taken when the record is queued
+    let path = tmp_log("clock_group.bin");
+    let clock = Arc::new(ManualClock::new(5_000));
+    let mut w = GroupCommitWriter::open(&path, "md", opts, GroupCommitOptions::default())?.with_clock(clock.clone());
+    w.write(&json!({ "i": 0 }))?;
+    clock.advance(7);
+    w.write(&json!({ "i": 1 }))?;
+    w.close()?;
+    assert_eq!(read_ts(&path)?, vec![5_000, 5_007]);
+    Ok(())
+}
+
+#[test]
+fn default_clock_stamps_wall_time() -> Result<()> {
+    let path = tmp_log("clock_wall.jsonl");
+    let before = MonotonicClock::new().now_ns() as u64;
+    capture(&mut EventLogWriter::open(&path)?)?;
+    let ts = read_ts(&path)?;
+    assert!(ts.iter().all(|&t| t >= before), "{:?} < {}", ts, before);
+    assert!(ts.windows(2).all(|w| w[0] <= w[1]));
+    Ok(())
+}
+
+#[test]
+fn interval_rotation_follows_the_injected_clock() -> Result<()> {
+    let dir = tmp_log("clock_segments");
+    let clock = Arc::new(ManualClock::new(0));
+    let rotation = Rotation { max_bytes: None, interval_ns: Some(1_000) };
+    let mut w = SegmentedWriter::open(&dir, "md", WriterOptions::default(), rotation)?.with_clock(clock.clone());
+
+    capture(&mut w)?;
+    assert_eq!(segment_files(&dir)?.len(), 1);
+    clock.advance(1_000);
+    capture(&mut w)?;
+    assert_eq!(segment_files(&dir)?.len(), 2);
+    assert_eq!(w.manifest().segments[0].last_ts_ns, 0);
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use anyhow::Result;
+use el_core::event::{Event, EventId, EventPayload, EventType, Exchange};
+use el_core::instrument::InstrumentKey;
+use el_core::num::{Price, Qty};
+use el_core::time::{TimeSource, Timestamp};
+use eventlog::chain::ChainVerifier;
+use eventlog::compact::{self, Anchor, Retention};
+use eventlog::crypto::{generate_key_file, FileKeys, KeyId, SharedKeys};
+use eventlog::segment::{segment_files, Rotation};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter, EventSink, LogFormat, LogSource, SegmentedWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&p);
+    std::fs::create_dir_all(&p).unwrap();
+    p
+}
+
+const BTC_SNAPSHOTS: [u64; 4] = [1, 11, 21, 31];
+const ETH_SNAPSHOTS: [u64; 3] = [2, 16, 30];
+
+/// Record `i` (at ts i*1000): exec notes at 3 and 5, otherwise book events,
+/// BTC on odd seqs and ETH on even ones.
+fn record(i: u64) -> Result<(&'static str, Vec<u8>)> {
+    if i == 3 || i == 5 {
+        return Ok(("exec", format!("note {}", i).into_bytes()));
+    }
+    let (symbol, snapshot) = match i % 2 {
+        1 => ("BTCUSDT", BTC_SNAPSHOTS.contains(&i)),
+        _ => ("ETHUSDT", ETH_SNAPSHOTS.contains(&i)),
+    };
+    let levels = vec![(Price::from_int(100 + i as i64), Qty::from_int(1))];
+    let (event_type, payload) = match snapshot {
+        true => (EventType::BookSnapshot, EventPayload::BookSnapshot { bids: levels, asks: vec![] }),
+        false => (EventType::BookDelta, EventPayload::BookDelta { bids: levels, asks: vec![] }),
+    };
+    let ts = (i * 1000) as i64;
+    let ev = Event {
+        id: EventId::nil(),
+        event_type,
+        exchange: Exchange::Binance,
+        symbol: symbol.to_string(),
+        instrument: InstrumentKey::new(Exchange::Binance, symbol),
+        ts_exchange: None,
+        ts_recv: Timestamp::new(ts, TimeSource::Receive),
+        ts_proc: Timestamp::new(ts, TimeSource::Process),
+        seq: None,
+        schema_version: 1,
+        integrity_flags: vec![],
+        payload,
+        meta: Default::default(),
+    };
+    Ok(("md", serde_json::to_vec(&ev)?))
+}
+
+fn capture(w: &mut impl EventSink, seqs: std::ops::RangeInclusive<u64>) -> Result<()> {
+    for i in seqs {
+        let (stream, payload) = record(i)?;
+        w.append_stream(stream, "event", i * 1000, &payload)?;
+    }
+    w.flush()
+}
+
+/// (seq, stream seq, chain) of every record; the chain checked from the log's anchor.
+type Rec = (u64, u64, Option<String>);
+
+fn read_verified(path: &Path) -> Result<Vec<Rec>> {
+    let anchor = Anchor::load(path)?;
+    let mut r = LogSource::open(path)?;
+    let mut chain = ChainVerifier::new();
+    let mut out = Vec::new();
+    while let Some((env, payload)) = r.next()? {
+        if out.is_empty() {
+            if let Some(prev) = anchor.as_ref().and_then(|a| a.chain_before(env.seq)) {
+                chain = ChainVerifier::anchored(prev);
+            }
+        }
+        chain.check(&env, &payload).map_err(|b| anyhow::anyhow!("{}", b))?;
+        assert_eq!(payload, record(env.seq)?.1);
+        out.push((env.seq, env.seq_in_stream(), env.chain.clone()));
+    }
+    Ok(out)
+}
+
+#[test]
+fn compacting_a_file_keeps_seqs_chain_and_index() -> Result<()> {
+    for format in [LogFormat::JsonLines, LogFormat::Binary] {
+        let dir = tmp_dir(&format!("compact_file_{:?}", format));
+        let path = dir.join("depth.log");
+        let opts = WriterOptions { format, index_stride: Some(4), hash_chain: true, ..Default::default() };
+        capture(&mut EventLogWriter::open_with(&path, "md", opts)?, 1..=40)?;
+        let before = read_verified(&path)?;
+
+        let archive = dir.join("archive");
+        let report = compact::compact(&path, &Retention::Snapshots { keep: 2 }, Some(&archive), None)?;
+        assert_eq!(report.first_seq, Some(16), "ETH's second to last snapshot");
+        assert_eq!(report.dropped_records, 15);
+
+        // kept records are unchanged and still verify, from the anchor
+        assert_eq!(read_verified(&path)?, before[15..]);
+        assert_eq!(read_verified(&report.archived[0])?, before[..15]);
+        let mut r = EventLogReader::open(&path)?;
+        r.seek_seq(25)?;
+        assert_eq!(r.next()?.map(|(env, _)| env.seq), Some(25));
+
+        // nothing left to drop
+        let again = compact::compact(&path, &Retention::Snapshots { keep: 2 }, None, None)?;
+        assert_eq!((again.first_seq, again.dropped_records), (Some(16), 0));
+
+        // writing on continues every stream, including one compacted away entirely
+        let mut w = EventLogWriter::open_with(&path, "md", opts)?;
+        assert_eq!(w.append_stream("exec", "event", 41_000, &record(41)?.1)?, (41, 3));
+        drop(w);
+        assert_eq!(read_verified(&path)?.last().map(|r| (r.0, r.1)), Some((41, 3)));
+    }
+    Ok(())
+}
+
+#[test]
+fn retention_cut_keeps_a_snapshot_per_instrument() -> Result<()> {
+    let dir = tmp_dir("compact_cut");
+    let path = dir.join("depth.log");
+    capture(&mut EventLogWriter::open_with(&path, "md", WriterOptions::default())?, 1..=40)?;
+
+    assert_eq!(compact::retention_cut(&path, &Retention::Snapshots { keep: 1 }, None)?, Some(30));
+    assert_eq!(compact::retention_cut(&path, &Retention::Snapshots { keep: 2 }, None)?, Some(16));
+    // BTC has fewer than 9: it keeps its oldest, seq 1
+    assert_eq!(compact::retention_cut(&path, &Retention::Snapshots { keep: 9 }, None)?, None);
+    // 20s keeps seq 20 on; BTC needs its snapshot at 11
+    assert_eq!(compact::retention_cut(&path, &Retention::MaxAge { ns: 20_000 }, None)?, Some(11));
+    assert_eq!(compact::retention_cut(&path, &Retention::MaxAge { ns: 100_000 }, None)?, None);
+    assert!(compact::retention_cut(&path, &Retention::Snapshots { keep: 0 }, None).is_err());
+
+    let notes = dir.join("notes.log");
+    let mut w = EventLogWriter::open_with(&notes, "exec", WriterOptions::default())?;
+    w.append_bytes("note", 1, b"no snapshots here")?;
+    w.flush()?;
+    assert_eq!(compact::retention_cut(&notes, &Retention::Snapshots { keep: 1 }, None)?, None);
+    Ok(())
+}
+
+#[test]
+fn encrypted_log_compacts_with_or_without_keys() -> Result<()> {
+    let dir = tmp_dir("compact_encrypted");
+    let key_dir = dir.join("keys");
+    std::fs::create_dir_all(&key_dir)?;
+    generate_key_file(&key_dir, KeyId(1))?;
+    let keys: SharedKeys = Arc::new(FileKeys::new(&key_dir));
+
+    let path = dir.join("depth.log");
+    let opts = WriterOptions { format: LogFormat::Binary, hash_chain: true, ..Default::default() };
+    capture(&mut EventLogWriter::open_with(&path, "md", opts)?.with_encryption(&*keys, KeyId(1))?, 1..=40)?;
+
+    // without keys no snapshot can be seen, so nothing is cut
+    let retention = Retention::Snapshots { keep: 2 };
+    assert_eq!(compact::retention_cut(&path, &retention, None)?, None);
+    let report = compact::compact(&path, &retention, None, None)?;
+    assert_eq!((report.first_seq, report.dropped_records), (Some(1), 0));
+
+    let report = compact::compact(&path, &retention, None, Some(keys.clone()))?;
+    assert_eq!((report.first_seq, report.dropped_records), (Some(16), 15));
+    let mut r = LogSource::open(&path)?.with_keys(keys);
+    let (env, payload) = r.next()?.expect("kept records");
+    assert_eq!((env.seq, payload), (16, record(16)?.1));
+    Ok(())
+}
+
+#[test]
+fn compacting_segments_drops_whole_sealed_segments() -> Result<()> {
+    let dir = tmp_dir("compact_segments");
+    let log = dir.join("depth");
+    let opts = WriterOptions { hash_chain: true, ..Default::default() };
+    let rotation = Rotation { max_bytes: Some(1500), interval_ns: None };
+
+    let mut w = SegmentedWriter::open(&log, "md", opts, rotation)?;
+    capture(&mut w, 1..=40)?;
+    let before = read_verified(&log)?;
+    let files = segment_files(&log)?;
+
+    // while the writer is open
+    let report = w.compact(&Retention::Snapshots { keep: 2 }, None)?;
+    let first = report.first_seq.unwrap();
+    assert!(first > 1 && first <= 16, "kept from {}", first);
+    assert_eq!(report.dropped_records, first - 1);
+    assert_eq!(segment_files(&log)?, files[files.len() - segment_files(&log)?.len()..]);
+    assert_eq!(read_verified(&log)?, before[first as usize - 1..]);
+    assert!(compact::compact_before(&log, 30, None).is_err(), "the writer holds the directory");
+
+    capture(&mut w, 41..=45)?;
+    drop(w);
+
+    // every sealed segment, then reopen on the anchor alone
+    compact::compact_before(&log, 1000, None)?;
+    let mut w = SegmentedWriter::open(&log, "md", opts, rotation)?;
+    assert!(w.manifest().segments.is_empty());
+    assert_eq!(w.append_stream("exec", "event", 46_000, &record(46)?.1)?, (46, 3));
+    drop(w);
+
+    let after = read_verified(&log)?;
+    assert_eq!(after.first().map(|r| r.0), Anchor::load(&log)?.map(|a| a.seq + 1));
+    assert_eq!(after.last().map(|r| (r.0, r.1)), Some((46, 3)));
+    Ok(())
+}
+
+#[test]
+fn compact_bin_refuses_negative_or_nan_days() -> Result<()> {
+    let dir = tmp_dir("compact_bin_days");
+    let log = dir.join("capture.log");
+    let mut w = EventLogWriter::open(&log)?;
+    capture(&mut w, 1..=5)?;
+    drop(w);
+
+    for days in ["-1", "NaN", "inf"] {
+        let out = std::process::Command::new(env!("CARGO_BIN_EXE_compact"))
+            .args([log.to_str().unwrap(), "--days", days])
+            .output()?;
+        assert!(!out.status.success(), "--days {}", days);
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(stderr.contains("--days must be a non-negative number"), "{}", stderr);
+    }
+    assert_eq!(EventLogReader::open(&log)?.next()?.map(|(env, _)| env.seq), Some(1), "nothing was dropped");
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use anyhow::Result;
+use eventlog::chain::ChainVerifier;
+use eventlog::compress::{install_dictionary, train_dictionary, Codec, Compression};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter, LogFormat};
+use std::path::{Path, PathBuf};
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&p);
+    std::fs::create_dir_all(&p).unwrap();
+    p
+}
+
+/// Something shaped like a serialized book snapshot.
+fn snapshot(i: u64) -> Vec<u8> {
+    let levels: Vec<String> = (0..40)
+        .map(|l| format!("[{}.{:02},{}.{:03}]", 60000 + l * 5 + i % 7, l % 100, (i + l) % 9, l * 13 % 1000))
+        .collect();
+    format!(
+        "{{\"event_type\":\"BookSnapshot\",\"exchange\":\"Binance\",\"symbol\":\"BTCUSDT\",\"seq\":{},\"payload\":{{\"BookSnapshot\":{{\"bids\":[{}],\"asks\":[]}}}}}}",
+        i,
+        levels.join(",")
+    )
+    .into_bytes()
+}
+
+fn write(path: &Path, format: LogFormat, compression: Compression, payloads: &[Vec<u8>]) -> Result<()> {
+    let opts = WriterOptions { format, compression, hash_chain: true, ..Default::default() };
+    let mut w = EventLogWriter::open_with(path, "el:test", opts)?;
+    for (i, p) in payloads.iter().enumerate() {
+        w.append_bytes("event", i as u64, p)?;
+    }
+    Ok(w.flush()?)
+}
+
+fn read(path: &Path) -> Result<Vec<(Option<String>, Vec<u8>)>> {
+    let mut r = EventLogReader::open(path)?;
+    let mut chain = ChainVerifier::new();
+    let mut out = Vec::new();
+    while let Some((env, payload)) = r.next()? {
+        assert_eq!(env.checksum, crc32fast::hash(&payload), "crc covers uncompressed bytes");
+        chain.check(&env, &payload).expect("chain over uncompressed bytes");
+        out.push((env.codec, payload));
+    }
+    Ok(out)
+}
+
+#[test]
+fn zstd_round_trips_and_shrinks_in_both_formats() -> Result<()> {
+    let dir = tmp_dir("compress_plain");
+    let mut payloads: Vec<Vec<u8>> = (0..50).map(snapshot).collect();
+    payloads.push(b"tiny".to_vec());
+
+    for format in [LogFormat::JsonLines, LogFormat::Binary] {
+        let raw = dir.join(format!("{:?}_raw.log", format));
+        let packed = dir.join(format!("{:?}_zstd.log", format));
+        write(&raw, format, Compression::None, &payloads)?;
+        write(&packed, format, Compression::Zstd { level: 3, dict: None }, &payloads)?;
+
+        let got = read(&packed)?;
+        assert_eq!(got.iter().map(|(_, p)| p.clone()).collect::<Vec<_>>(), payloads);
+        assert_eq!(got[0].0.as_deref(), Some("zstd"));
+        assert_eq!(got.last().unwrap().0, None, "short payloads are stored as is");
+
+        let (raw_len, packed_len) = (std::fs::metadata(&raw)?.len(), std::fs::metadata(&packed)?.len());
+        assert!(packed_len < raw_len, "{:?}: {} vs {}", format, packed_len, raw_len);
+    }
+    Ok(())
+}
+
+#[test]
+fn dictionary_is_found_next_to_the_log() -> Result<()> {
+    let dir = tmp_dir("compress_dict");
+    let samples: Vec<Vec<u8>> = (1000..1500).map(snapshot).collect();
+    let dict = train_dictionary(&samples, 16 * 1024)?;
+    let id = install_dictionary(&dir, &dict)?;
+
+    let payloads: Vec<Vec<u8>> = (0..20).map(snapshot).collect();
+    for format in [LogFormat::JsonLines, LogFormat::Binary] {
+        let path = dir.join(format!("{:?}.log", format));
+        write(&path, format, Compression::Zstd { level: 3, dict: Some(id) }, &payloads)?;
+
+        let got = read(&path)?;
+        assert_eq!(got.iter().map(|(_, p)| p.clone()).collect::<Vec<_>>(), payloads);
+        assert_eq!(got[0].0.as_deref().and_then(Codec::parse), Some(Codec::Zstd { dict: Some(id) }));
+    }
+
+    // without the dictionary the payloads cannot be recovered
+    let moved = tmp_dir("compress_dict_moved");
+    std::fs::copy(dir.join("Binary.log"), moved.join("Binary.log"))?;
+    let err = read(&moved.join("Binary.log")).unwrap_err();
+    assert!(format!("{:#}", err).contains("dictionary"), "{:#}", err);
+    Ok(())
+}
+
+#[test]
+fn compressed_and_plain_records_mix_across_restarts() -> Result<()> {
+    let dir = tmp_dir("compress_mixed");
+    let path = dir.join("mixed.log");
+    let payloads: Vec<Vec<u8>> = (0..6).map(snapshot).collect();
+
+    write(&path, LogFormat::Binary, Compression::Zstd { level: 1, dict: None }, &payloads[..3])?;
+    write(&path, LogFormat::Binary, Compression::None, &payloads[3..])?;
+
+    let got = read(&path)?;
+    assert_eq!(got.iter().map(|(_, p)| p.clone()).collect::<Vec<_>>(), payloads);
+    assert_eq!(got.iter().filter(|(c, _)| c.is_some()).count(), 3);
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use anyhow::Result;
+use eventlog::compress::Compression;
+use eventlog::crypto::{generate_key_file, CopyKeys, EnvKeys, FileKeys, KeyId, KeyProvider, SharedKeys};
+use eventlog::merge::merge;
+use eventlog::segment::Rotation;
+use eventlog::slice::{slice, SliceFilter};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogError, EventLogReader, EventLogWriter, LogFormat, LogSource, SegmentedWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn tmp(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    let _ = std::fs::remove_dir_all(&p);
+    let _ = std::fs::remove_file(eventlog::index::index_path(&p));
+    p
+}
+
+/// A fresh key directory holding key 1.
+fn key_dir(name: &str) -> Result<FileKeys> {
+    let dir = tmp(name);
+    std::fs::create_dir_all(&dir)?;
+    generate_key_file(&dir, KeyId(1))?;
+    Ok(FileKeys::new(dir))
+}
+
+fn payload(i: u64) -> Vec<u8> {
+    match i % 5 {
+        0 => format!("{{\"secret\":\"{}\"}}", "s".repeat(300)).into_bytes(),
+        _ => format!("{{\"secret\":{}}}", i).into_bytes(),
+    }
+}
+
+/// 50 records over two streams, sealed with key 1.
+fn write(path: &Path, format: LogFormat, keys: &dyn KeyProvider) -> Result<()> {
+    let opts = WriterOptions {
+        format,
+        hash_chain: true,
+        compression: Compression::Zstd { level: 3, dict: None },
+        ..Default::default()
+    };
+    let mut w = EventLogWriter::open_with(path, "md", opts)?.with_encryption(keys, KeyId(1))?;
+    assert_eq!(w.key_id(), Some(KeyId(1)));
+    for i in 0..50u64 {
+        w.append_stream(["md", "exec"][i as usize % 2], "event", i, &payload(i))?;
+    }
+    Ok(w.flush()?)
+}
+
+#[test]
+fn sealed_payloads_round_trip_with_the_key() -> Result<()> {
+    let keys = key_dir("encryption_keys_round_trip")?;
+    for format in [LogFormat::Binary, LogFormat::JsonLines] {
+        let path = tmp(&format!("encryption_round_trip_{:?}.log", format));
+        write(&path, format, &keys)?;
+
+        let raw = std::fs::read(&path)?;
+        assert!(!raw.windows(8).any(|w| w == b"\"secret\""), "{:?} plaintext on disk", format);
+
+        let mut r = EventLogReader::open(&path)?.with_keys(Arc::new(keys.clone()));
+        let mut n = 0u64;
+        while let Some((env, p)) = r.next()? {
+            assert_eq!(env.key_id, Some(KeyId(1)));
+            assert_eq!(env.seq, n + 1);
+            assert_eq!(p, payload(n), "{:?} seq={}", format, env.seq);
+            n += 1;
+        }
+        assert_eq!(n, 50);
+    }
+    Ok(())
+}
+
+#[test]
+fn keyless_readers_check_framing_and_seqs_only() -> Result<()> {
+    let keys = key_dir("encryption_keys_keyless")?;
+    let path = tmp("encryption_keyless.log");
+    write(&path, LogFormat::Binary, &keys)?;
+
+    let err = EventLogReader::open(&path)?.next().unwrap_err();
+    assert!(matches!(err, EventLogError::MissingKey { seq: 1, key_id: KeyId(1) }), "{:?}", err);
+    let other: SharedKeys = Arc::new(FileKeys::new(tmp("encryption_keys_empty")));
+    let err = EventLogReader::open(&path)?.with_keys(other).next().unwrap_err();
+    assert!(matches!(err, EventLogError::MissingKey { .. }), "{:?}", err);
+
+    let mut r = LogSource::open(&path)?.with_sealed_payloads();
+    let mut seqs = Vec::new();
+    while let Some((env, p)) = r.next()? {
+        assert!(!p.windows(6).any(|w| w == b"secret"));
+        seqs.push(env.seq);
+    }
+    assert_eq!(seqs, (1..=50).collect::<Vec<_>>());
+
+    // a flipped ciphertext byte fails authentication; the keyless reader cannot tell
+    let mut bytes = std::fs::read(&path)?;
+    let last = bytes.len() - 3;
+    bytes[last] ^= 1;
+    std::fs::write(&path, &bytes)?;
+    let mut r = EventLogReader::open(&path)?.with_keys(Arc::new(keys));
+    let err = loop {
+        match r.next() {
+            Ok(Some(_)) => continue,
+            Ok(None) => panic!("damage not found"),
+            Err(e) => break e,
+        }
+    };
+    assert!(matches!(err, EventLogError::Payload { seq: 50, .. }), "{:?}", err);
+    let mut r = EventLogReader::open(&path)?.with_sealed_payloads();
+    while r.next()?.is_some() {}
+    Ok(())
+}
+
+#[test]
+fn wrong_key_is_a_payload_error() -> Result<()> {
+    let keys = key_dir("encryption_keys_right")?;
+    let wrong = key_dir("encryption_keys_wrong")?;
+    let path = tmp("encryption_wrong_key.log");
+    write(&path, LogFormat::JsonLines, &keys)?;
+
+    let err = EventLogReader::open(&path)?.with_keys(Arc::new(wrong)).next().unwrap_err();
+    match err {
+        EventLogError::Payload { seq: 1, reason, .. } => assert!(reason.contains("key 1"), "{}", reason),
+        other => panic!("unexpected {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn copies_of_a_sealed_log_stay_sealed() -> Result<()> {
+    let keys = key_dir("encryption_keys_copies")?;
+    let shared: SharedKeys = Arc::new(keys.clone());
+    let path = tmp("encryption_copies.log");
+    write(&path, LogFormat::Binary, &keys)?;
+
+    // without keys nothing is copied, let alone in plaintext
+    let out = tmp("encryption_copies_refused.log");
+    assert!(merge(&[&path], &out, WriterOptions::default(), None).is_err());
+    assert!(!out.exists());
+
+    let (env, sealed) = EventLogReader::open(&path)?.with_sealed_payloads().next()?.expect("record");
+    let (_, plain) = EventLogReader::open(&path)?.with_keys(shared.clone()).next()?.expect("record");
+    let mut w = EventLogWriter::open(&out)?;
+    for p in [&sealed, &plain] {
+        let err = w.append_copy(&env, p).unwrap_err();
+        assert!(err.to_string().contains("does not encrypt"), "{}", err);
+    }
+    let mut w = EventLogWriter::open(tmp("encryption_copies_resealed.log"))?.with_encryption(&keys, KeyId(1))?;
+    let err = w.append_copy(&env, &sealed).unwrap_err();
+    assert!(err.to_string().contains("still sealed"), "{}", err);
+
+    let copy_keys = CopyKeys::new(shared.clone(), KeyId(1));
+    let merged = tmp("encryption_copies_merged.log");
+    merge(&[&path], &merged, WriterOptions::default(), Some(&copy_keys))?;
+    let sliced = tmp("encryption_copies_sliced.log");
+    let exec = SliceFilter { streams: vec!["exec".into()], ..Default::default() };
+    slice(&path, &sliced, &exec, WriterOptions::default(), Some(&copy_keys))?;
+
+    for (out, records) in [(&merged, 50), (&sliced, 25)] {
+        let raw = std::fs::read(out)?;
+        assert!(!raw.windows(6).any(|w| w == b"secret"), "{:?} plaintext on disk", out);
+        let mut r = EventLogReader::open(out)?.with_keys(shared.clone());
+        let mut n = 0;
+        while let Some((env, p)) = r.next()? {
+            assert_eq!(env.key_id, Some(KeyId(1)));
+            assert_eq!(p, payload(env.origin_seq.expect("copied") - 1));
+            n += 1;
+        }
+        assert_eq!(n, records, "{:?}", out);
+    }
+    Ok(())
+}
+
+#[test]
+fn segments_stay_sealed_across_rotation() -> Result<()> {
+    let keys = key_dir("encryption_keys_segments")?;
+    let dir = tmp("encryption_segments");
+    let rotation = Rotation { max_bytes: Some(512), interval_ns: None };
+    let mut w = SegmentedWriter::open(&dir, "md", WriterOptions::default(), rotation)?.with_encryption(&keys, KeyId(1))?;
+    for i in 0..40u64 {
+        w.append_stream("md", "event", i, &payload(i))?;
+    }
+    w.flush()?;
+    drop(w);
+    assert!(eventlog::segment::segment_files(&dir)?.len() > 2);
+
+    let mut r = LogSource::open(&dir)?.with_keys(Arc::new(keys));
+    let mut n = 0u64;
+    while let Some((env, p)) = r.next()? {
+        assert_eq!(env.key_id, Some(KeyId(1)), "seq={}", env.seq);
+        assert_eq!(p, payload(n));
+        n += 1;
+    }
+    assert_eq!(n, 40);
+    Ok(())
+}
+
+#[test]
+fn key_providers() -> Result<()> {
+    let keys = key_dir("encryption_keys_providers")?;
+    let key = keys.key(KeyId(1))?.expect("generated");
+    assert_eq!(keys.key(KeyId(2))?, None);
+    assert!(generate_key_file(keys.path(KeyId(1)).parent().unwrap(), KeyId(1)).is_err(), "never overwrites");
+    std::fs::write(keys.path(KeyId(3)), "not hex")?;
+    assert!(keys.key(KeyId(3)).is_err());
+
+    let env = EnvKeys::new("ELOG_TEST_KEY_");
+    assert_eq!(env.var(KeyId(1)), "ELOG_TEST_KEY_1");
+    std::env::set_var(env.var(KeyId(1)), std::fs::read_to_string(keys.path(KeyId(1)))?);
+    assert_eq!(env.key(KeyId(1))?, Some(key));
+    assert_eq!(env.key(KeyId(2))?, None);
+
+    // a log written with one provider reads with the other
+    let path = tmp("encryption_env_keys.log");
+    write(&path, LogFormat::Binary, &env)?;
+    let mut r = EventLogReader::open(&path)?.with_keys(Arc::new(keys));
+    assert_eq!(r.next()?.unwrap().1, payload(0));
+    Ok(())
+}
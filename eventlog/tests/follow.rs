@@ -0,0 +1,186 @@
+use anyhow::Result;
+use eventlog::crypto::{generate_key_file, FileKeys, KeyId};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogError, EventLogWriter, LogFollower, LogFormat};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+fn writer(path: &Path, format: LogFormat) -> Result<EventLogWriter> {
+    Ok(EventLogWriter::open_with(path, "el:test", WriterOptions { format, ..Default::default() })?)
+}
+
+fn append_raw(path: &Path, bytes: &[u8]) -> Result<()> {
+    OpenOptions::new().append(true).open(path)?.write_all(bytes)?;
+    Ok(())
+}
+
+fn seq(f: &mut LogFollower) -> Result<Option<u64>> {
+    Ok(f.try_next()?.map(|(env, _)| env.seq))
+}
+
+#[test]
+fn waits_for_file_and_skips_partial_tail() -> Result<()> {
+    for (name, format) in [("follow_json.log", LogFormat::JsonLines), ("follow_bin.log", LogFormat::Binary)] {
+        let path = tmp_log(name);
+        let mut f = LogFollower::open(&path);
+        assert_eq!(seq(&mut f)?, None);
+
+        let mut w = writer(&path, format)?;
+        w.append_bytes("event", 1, b"one")?;
+        w.flush()?;
+        assert_eq!(seq(&mut f)?, Some(1));
+        assert_eq!(seq(&mut f)?, None);
+
+        // a record split across two flushes
+        let tail = std::fs::read(&path)?;
+        w.append_bytes("event", 2, b"two")?;
+        w.flush()?;
+        let full = std::fs::read(&path)?;
+        let record = full[tail.len()..].to_vec();
+        std::fs::write(&path, &tail)?;
+        append_raw(&path, &record[..record.len() / 2])?;
+        assert_eq!(seq(&mut f)?, None);
+        append_raw(&path, &record[record.len() / 2..])?;
+
+        let (env, payload) = f.try_next()?.expect("completed record");
+        assert_eq!((env.seq, payload.as_slice()), (2, &b"two"[..]));
+    }
+    Ok(())
+}
+
+#[test]
+fn survives_writer_restart_after_torn_tail() -> Result<()> {
+    let path = tmp_log("follow_restart.log");
+    let mut f = LogFollower::open(&path);
+    {
+        let mut w = writer(&path, LogFormat::JsonLines)?;
+        for i in 1..=3 {
+            w.append_bytes("event", i, b"x")?;
+        }
+        w.flush()?;
+    }
+    append_raw(&path, b"{\"seq\":4,\"ts_n")?;
+
+    let mut got = Vec::new();
+    while let Some(s) = seq(&mut f)? {
+        got.push(s);
+    }
+    assert_eq!(got, vec![1, 2, 3]);
+
+    // the new writer drops the torn line and continues at seq 4
+    let mut w = writer(&path, LogFormat::JsonLines)?;
+    w.append_bytes("event", 4, b"x")?;
+    w.append_bytes("event", 5, b"x")?;
+    w.flush()?;
+    assert_eq!(seq(&mut f)?, Some(4));
+    assert_eq!(seq(&mut f)?, Some(5));
+    Ok(())
+}
+
+#[test]
+fn replaced_file_is_read_from_start() -> Result<()> {
+    let path = tmp_log("follow_replaced.log");
+    let mut f = LogFollower::open(&path);
+    {
+        let mut w = writer(&path, LogFormat::Binary)?;
+        w.append_bytes("event", 1, b"old")?;
+        w.flush()?;
+    }
+    assert_eq!(seq(&mut f)?, Some(1));
+
+    std::fs::remove_file(&path)?;
+    assert_eq!(seq(&mut f)?, None);
+
+    let mut w = writer(&path, LogFormat::JsonLines)?;
+    w.append_bytes("event", 1, b"new")?;
+    w.flush()?;
+    let (env, payload) = f.try_next()?.expect("record from new file");
+    assert_eq!((env.seq, payload.as_slice()), (1, &b"new"[..]));
+    Ok(())
+}
+
+#[test]
+fn blocking_follower_sees_records_from_another_thread() -> Result<()> {
+    let path = tmp_log("follow_blocking.log");
+    let mut f = LogFollower::from_seq(&path, 3).with_poll_interval(Duration::from_millis(1));
+
+    let wpath = path.clone();
+    let h = std::thread::spawn(move || -> Result<()> {
+        let mut w = writer(&wpath, LogFormat::Binary)?;
+        for i in 1..=10 {
+            w.append_bytes("event", i, &i.to_le_bytes())?;
+            w.flush()?;
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        Ok(())
+    });
+
+    let mut got = Vec::new();
+    while got.len() < 8 {
+        got.push(f.next_blocking()?.0.seq);
+    }
+    h.join().unwrap()?;
+
+    assert_eq!(got, (3..=10).collect::<Vec<_>>());
+    assert!(f.next_timeout(Duration::from_millis(5))?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn async_follower_wakes_on_append() -> Result<()> {
+    let path = tmp_log("follow_async.log");
+    let mut f = LogFollower::open(&path).with_poll_interval(Duration::from_millis(1));
+
+    let wpath = path.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let mut w = writer(&wpath, LogFormat::JsonLines).unwrap();
+        w.append_bytes("event", 7, b"late").unwrap();
+        w.flush().unwrap();
+    });
+
+    let (env, payload) = tokio::time::timeout(Duration::from_secs(5), f.next_async()).await??;
+    assert_eq!((env.seq, env.ts_ns, payload.as_slice()), (1, 7, &b"late"[..]));
+    Ok(())
+}
+
+#[test]
+fn sealed_log_is_followed_with_keys_or_as_stored() -> Result<()> {
+    let key_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("follow_sealed_keys");
+    let _ = std::fs::remove_dir_all(&key_dir);
+    std::fs::create_dir_all(&key_dir)?;
+    generate_key_file(&key_dir, KeyId(1))?;
+    let keys = FileKeys::new(&key_dir);
+
+    let path = tmp_log("follow_sealed.log");
+    let mut w = writer(&path, LogFormat::Binary)?.with_encryption(&keys, KeyId(1))?;
+    for i in 1..=3u64 {
+        w.append_bytes("event", i, format!("secret {}", i).as_bytes())?;
+    }
+    w.flush()?;
+
+    let err = LogFollower::open(&path).try_next().unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(EventLogError::MissingKey { seq: 1, .. })), "{:#}", err);
+
+    let mut f = LogFollower::from_seq(&path, 2).with_keys(Arc::new(keys));
+    let (env, payload) = f.try_next()?.expect("record 2");
+    assert_eq!((env.seq, payload.as_slice()), (2, &b"secret 2"[..]));
+
+    let mut f = LogFollower::open(&path).with_sealed_payloads();
+    for want in 1..=3 {
+        let (env, payload) = f.try_next()?.expect("sealed record");
+        assert_eq!(env.seq, want);
+        assert!(!payload.windows(6).any(|w| w == b"secret"));
+    }
+    assert_eq!(seq(&mut f)?, None);
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use anyhow::Result;
+use eventlog::group::{GroupCommitOptions, GroupCommitWriter};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventSink};
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Wake, Waker};
+use std::time::Duration;
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+fn read_seqs(path: &PathBuf) -> Result<Vec<u64>> {
+    let mut r = EventLogReader::open(path)?;
+    let mut out = Vec::new();
+    while let Some((env, payload)) = r.next()? {
+        assert_eq!(payload, env.seq.to_le_bytes());
+        out.push(env.seq);
+    }
+    Ok(out)
+}
+
+/// In-memory sink with a slow or failing fsync.
+struct TestSink {
+    next_seq: u64,
+    flush_delay: Duration,
+    fail_flush: bool,
+}
+
+impl EventSink for TestSink {
+    fn append_bytes(&mut self, _kind: &str, _ts_ns: u64, _payload: &[u8]) -> Result<u64> {
+        self.next_seq += 1;
+        Ok(self.next_seq - 1)
+    }
+
+    fn append_stream(&mut self, _stream: &str, kind: &str, ts_ns: u64, payload: &[u8]) -> Result<u64> {
+        self.append_bytes(kind, ts_ns, payload)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        std::thread::sleep(self.flush_delay);
+        if self.fail_flush {
+            anyhow::bail!("disk on fire");
+        }
+        Ok(())
+    }
+
+    fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+}
+
+#[test]
+fn records_become_durable_in_order_and_seq_survives_reopen() -> Result<()> {
+    let path = tmp_log("group_commit.log");
+
+    {
+        let mut w = GroupCommitWriter::open(&path, "el:test", WriterOptions::default(), GroupCommitOptions::default())?;
+        let mut last = None;
+        for i in 1..=500u64 {
+            let c = w.append_bytes("event", i, &i.to_le_bytes())?;
+            assert_eq!(c.seq(), i);
+            last = Some(c);
+        }
+        last.unwrap().wait()?;
+        assert_eq!(w.durable_seq(), 500);
+
+        let m = w.metrics();
+        assert_eq!((m.records, m.queue_depth), (500, 0));
+        assert!(m.commits >= 1 && m.commits <= 500);
+        assert!(m.max_fsync_ns >= m.last_fsync_ns);
+        w.close()?;
+    }
+
+    let mut w = GroupCommitWriter::open(&path, "el:test", WriterOptions::default(), GroupCommitOptions::default())?;
+    assert_eq!(w.append_bytes("event", 501, &501u64.to_le_bytes())?.seq(), 501);
+    w.sync()?;
+    drop(w);
+
+    assert_eq!(read_seqs(&path)?, (1..=501).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn bounded_queue_applies_backpressure() -> Result<()> {
+    let sink = TestSink { next_seq: 1, flush_delay: Duration::from_millis(2), fail_flush: false };
+    let mut w = GroupCommitWriter::spawn(sink, GroupCommitOptions { queue_capacity: 2, max_batch: 1 })?;
+
+    for i in 0..20u64 {
+        w.append_bytes("event", i, b"x")?;
+    }
+    w.sync()?;
+
+    let m = w.metrics();
+    assert_eq!(m.records, 20);
+    // queue slots + the caller blocked in send + the record being committed
+    assert!(m.max_queue_depth <= 4, "max_queue_depth={}", m.max_queue_depth);
+    Ok(())
+}
+
+#[test]
+fn fsync_failure_reaches_waiters_and_later_appends() -> Result<()> {
+    let sink = TestSink { next_seq: 1, flush_delay: Duration::ZERO, fail_flush: true };
+    let mut w = GroupCommitWriter::spawn(sink, GroupCommitOptions::default())?;
+
+    let c = w.append_bytes("event", 1, b"x")?;
+    let err = c.wait().unwrap_err();
+    assert!(format!("{:#}", err).contains("disk on fire"), "{:#}", err);
+    assert!(!c.is_durable());
+
+    assert!(w.append_bytes("event", 2, b"x").is_err());
+    assert!(w.close().is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn commit_handle_is_a_future() -> Result<()> {
+    let sink = TestSink { next_seq: 10, flush_delay: Duration::from_millis(5), fail_flush: false };
+    let mut w = GroupCommitWriter::spawn(sink, GroupCommitOptions::default())?;
+
+    let c = w.append_bytes("event", 1, b"x")?;
+    assert_eq!(c.seq(), 10);
+    tokio::time::timeout(Duration::from_secs(5), c).await??;
+    assert_eq!(w.durable_seq(), 10);
+    Ok(())
+}
+
+/// Counts its wake-ups.
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn repolled_commit_keeps_one_waker() -> Result<()> {
+    let sink = TestSink { next_seq: 1, flush_delay: Duration::from_millis(50), fail_flush: false };
+    let mut w = GroupCommitWriter::spawn(sink, GroupCommitOptions::default())?;
+
+    let mut c = w.append_bytes("event", 1, b"x")?;
+    let (first, last) = (Arc::new(CountingWaker(AtomicUsize::new(0))), Arc::new(CountingWaker(AtomicUsize::new(0))));
+    for waker in std::iter::repeat_n(&first, 100).chain([&last]) {
+        let waker = Waker::from(waker.clone());
+        assert!(Pin::new(&mut c).poll(&mut Context::from_waker(&waker)).is_pending());
+    }
+    c.wait()?;
+    // only the waker of the latest poll is woken, and once
+    assert_eq!((first.0.load(Ordering::SeqCst), last.0.load(Ordering::SeqCst)), (0, 1));
+    Ok(())
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn async_append_waits_for_room_without_blocking_the_runtime() -> Result<()> {
+    let sink = TestSink { next_seq: 1, flush_delay: Duration::from_millis(5), fail_flush: false };
+    let mut w = GroupCommitWriter::spawn(sink, GroupCommitOptions { queue_capacity: 1, max_batch: 1 })?;
+
+    // runs only while the appends below yield to the (single-threaded) runtime
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let ticker = tokio::spawn({
+        let ticks = ticks.clone();
+        async move {
+            loop {
+                ticks.fetch_add(1, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+            }
+        }
+    });
+
+    let mut last = None;
+    for i in 1..=10u64 {
+        let c = w.append_bytes_async("event", i, b"x").await?;
+        assert_eq!(c.seq(), i);
+        last = Some(c);
+    }
+    tokio::time::timeout(Duration::from_secs(5), last.unwrap()).await??;
+    ticker.abort();
+    assert!(ticks.load(Ordering::SeqCst) > 0, "appends blocked the runtime");
+    assert_eq!(w.metrics().records, 10);
+
+    let sink = TestSink { next_seq: 1, flush_delay: Duration::ZERO, fail_flush: true };
+    let mut w = GroupCommitWriter::spawn(sink, GroupCommitOptions { queue_capacity: 1, max_batch: 1 })?;
+    let c = w.append_bytes_async("event", 1, b"x").await?;
+    assert!(c.await.is_err());
+    let Err(err) = w.append_bytes_async("event", 2, b"x").await else {
+        panic!("append after a failed fsync succeeded");
+    };
+    assert!(format!("{:#}", err).contains("disk on fire"), "{:#}", err);
+    Ok(())
+}
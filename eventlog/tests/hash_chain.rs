@@ -0,0 +1,136 @@
+use anyhow::Result;
+use base64::Engine;
+use eventlog::chain::{ChainBreak, ChainVerifier};
+use eventlog::envelope::EventEnvelope;
+use eventlog::segment::Rotation;
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter, LogFormat, SegmentedReader, SegmentedWriter};
+use std::path::{Path, PathBuf};
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+fn chained(format: LogFormat) -> WriterOptions {
+    WriterOptions { format, hash_chain: true, ..Default::default() }
+}
+
+fn write(path: &Path, opts: WriterOptions, seqs: std::ops::RangeInclusive<u64>) -> Result<()> {
+    let mut w = EventLogWriter::open_with(path, "el:test", opts)?;
+    for i in seqs {
+        assert_eq!(w.append_bytes("event", i * 10, format!("payload {}", i).as_bytes())?, i);
+    }
+    Ok(w.flush()?)
+}
+
+/// Returns the verifier after the whole log, or the first break.
+fn verify(path: &Path) -> Result<Result<ChainVerifier, ChainBreak>> {
+    let mut r = EventLogReader::open(path)?;
+    let mut v = ChainVerifier::new();
+    while let Some((env, payload)) = r.next()? {
+        if let Err(b) = v.check(&env, &payload) {
+            return Ok(Err(b));
+        }
+    }
+    Ok(Ok(v))
+}
+
+fn json_lines(path: &Path) -> Result<Vec<EventEnvelope>> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(text.lines().map(|l| serde_json::from_str(l).unwrap()).collect())
+}
+
+fn store_lines(path: &Path, envs: &[EventEnvelope]) -> Result<()> {
+    let mut text = String::new();
+    for e in envs {
+        text.push_str(&serde_json::to_string(e)?);
+        text.push('\n');
+    }
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+#[test]
+fn intact_log_verifies_in_both_formats_with_same_head() -> Result<()> {
+    let mut heads = Vec::new();
+    for (name, format) in [("chain_json.log", LogFormat::JsonLines), ("chain_bin.log", LogFormat::Binary)] {
+        let path = tmp_log(name);
+        write(&path, chained(format), 1..=5)?;
+        let v = verify(&path)?.expect("intact chain");
+        assert_eq!((v.chained(), v.first_chained_seq()), (5, Some(1)));
+        heads.push(v.head());
+    }
+    assert_eq!(heads[0], heads[1]);
+    Ok(())
+}
+
+#[test]
+fn edits_removals_and_reorders_are_reported_at_first_broken_link() -> Result<()> {
+    let path = tmp_log("chain_tamper.log");
+    write(&path, chained(LogFormat::JsonLines), 1..=5)?;
+    let original = json_lines(&path)?;
+
+    // payload rewritten with a matching crc
+    let mut edited = original.clone();
+    let forged = b"payload 999";
+    edited[2].payload_b64 = base64::engine::general_purpose::STANDARD.encode(forged);
+    edited[2].checksum = crc32fast::hash(forged);
+    store_lines(&path, &edited)?;
+    assert_eq!(verify(&path)?.unwrap_err(), ChainBreak::Mismatch { seq: 3 });
+
+    let mut removed = original.clone();
+    removed.remove(1);
+    store_lines(&path, &removed)?;
+    assert_eq!(verify(&path)?.unwrap_err(), ChainBreak::Mismatch { seq: 3 });
+
+    let mut reordered = original.clone();
+    reordered.swap(3, 4);
+    store_lines(&path, &reordered)?;
+    assert_eq!(verify(&path)?.unwrap_err(), ChainBreak::Mismatch { seq: 5 });
+
+    let mut stripped = original;
+    stripped[4].chain = None;
+    store_lines(&path, &stripped)?;
+    assert_eq!(verify(&path)?.unwrap_err(), ChainBreak::Missing { seq: 5 });
+    Ok(())
+}
+
+#[test]
+fn restart_continues_chain_after_torn_tail() -> Result<()> {
+    for (name, format) in [("chain_restart_json.log", LogFormat::JsonLines), ("chain_restart_bin.log", LogFormat::Binary)] {
+        let path = tmp_log(name);
+        write(&path, chained(format), 1..=3)?;
+        std::fs::OpenOptions::new().append(true).open(&path)?.set_len(std::fs::metadata(&path)?.len() - 3)?;
+
+        // chaining is kept even if the reopening writer did not ask for it
+        write(&path, WriterOptions { format, ..Default::default() }, 3..=6)?;
+        let v = verify(&path)?.expect("chain continues across restart");
+        assert_eq!(v.chained(), 6);
+    }
+    Ok(())
+}
+
+#[test]
+fn chain_links_segments() -> Result<()> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("chain_segmented");
+    let _ = std::fs::remove_dir_all(&dir);
+    let rotation = Rotation { max_bytes: Some(400), interval_ns: None };
+
+    for range in [1..=10u64, 11..=15] {
+        let mut w = SegmentedWriter::open(&dir, "el:test", chained(LogFormat::JsonLines), rotation)?;
+        for i in range {
+            w.append_bytes("event", i, &i.to_le_bytes())?;
+        }
+        w.flush()?;
+    }
+
+    let mut r = SegmentedReader::open(&dir)?;
+    let mut v = ChainVerifier::new();
+    while let Some((env, payload)) = r.next()? {
+        v.check(&env, &payload).expect("chain spans segments");
+    }
+    assert_eq!(v.chained(), 15);
+    Ok(())
+}
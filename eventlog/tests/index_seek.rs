@@ -0,0 +1,141 @@
+use anyhow::Result;
+use eventlog::index::{self, index_path};
+use eventlog::segment::Rotation;
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter, LogFormat, SegmentedReader, SegmentedWriter, SparseIndex};
+use std::path::{Path, PathBuf};
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    let _ = std::fs::remove_file(index_path(&p));
+    p
+}
+
+fn opts(format: LogFormat) -> WriterOptions {
+    WriterOptions { format, index_stride: Some(4), ..Default::default() }
+}
+
+/// seq n gets ts 10 * n and a kind that changes every 7 records
+fn write_log(path: &Path, format: LogFormat, from: u64, to: u64) -> Result<()> {
+    let mut w = EventLogWriter::open_with(path, "el:test", opts(format))?;
+    for i in from..=to {
+        let kind = format!("k{}", i / 7);
+        assert_eq!(w.append_bytes(&kind, 10 * i, &i.to_le_bytes())?, i);
+    }
+    Ok(w.flush()?)
+}
+
+fn next_seq(r: &mut EventLogReader) -> Result<Option<u64>> {
+    Ok(match r.next()? {
+        Some((env, payload)) => {
+            assert_eq!(payload, env.seq.to_le_bytes());
+            assert_eq!(env.kind, format!("k{}", env.seq / 7));
+            Some(env.seq)
+        }
+        None => None,
+    })
+}
+
+fn check_seeks(path: &Path) -> Result<()> {
+    let mut r = EventLogReader::open(path)?;
+
+    r.seek_seq(17)?;
+    assert_eq!(next_seq(&mut r)?, Some(17));
+    assert_eq!(next_seq(&mut r)?, Some(18));
+
+    // backwards, and between timestamps
+    r.seek_ts(55)?;
+    assert_eq!(next_seq(&mut r)?, Some(6));
+
+    r.seek_seq(1)?;
+    assert_eq!(next_seq(&mut r)?, Some(1));
+
+    r.seek_seq(1000)?;
+    assert_eq!(next_seq(&mut r)?, None);
+    Ok(())
+}
+
+#[test]
+fn writer_maintains_index_and_reader_seeks_json() -> Result<()> {
+    let path = tmp_log("index_seek_json.log");
+    write_log(&path, LogFormat::JsonLines, 1, 30)?;
+
+    let idx = SparseIndex::load(&path)?;
+    assert_eq!(idx.points.iter().map(|p| p.seq).collect::<Vec<_>>(), vec![1, 5, 9, 13, 17, 21, 25, 29]);
+    check_seeks(&path)
+}
+
+#[test]
+fn binary_seek_decodes_names_defined_before_the_point() -> Result<()> {
+    let path = tmp_log("index_seek_bin.log");
+    write_log(&path, LogFormat::Binary, 1, 30)?;
+
+    let idx = SparseIndex::load(&path)?;
+    assert_eq!(idx.kinds.len(), 5);
+    assert_eq!(idx.streams.len(), 1);
+    check_seeks(&path)
+}
+
+#[test]
+fn rebuild_matches_writer_index() -> Result<()> {
+    for (name, format) in [("index_rebuild_json.log", LogFormat::JsonLines), ("index_rebuild_bin.log", LogFormat::Binary)] {
+        let path = tmp_log(name);
+        write_log(&path, format, 1, 30)?;
+        let written = SparseIndex::load(&path)?;
+
+        std::fs::remove_file(index_path(&path))?;
+        assert_eq!(index::rebuild(&path, 4)?, written);
+        assert_eq!(SparseIndex::load(&path)?, written);
+        check_seeks(&path)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn reopen_keeps_index_and_drops_stale_points() -> Result<()> {
+    let path = tmp_log("index_reopen.log");
+    write_log(&path, LogFormat::Binary, 1, 10)?;
+    write_log(&path, LogFormat::Binary, 11, 30)?;
+    check_seeks(&path)?;
+
+    // log recreated from scratch: the old index must not be trusted
+    std::fs::remove_file(&path)?;
+    write_log(&path, LogFormat::Binary, 1, 3)?;
+    let idx = SparseIndex::load(&path)?;
+    assert_eq!(idx.points.len(), 1);
+    assert_eq!(idx.points[0].seq, 1);
+    Ok(())
+}
+
+#[test]
+fn seek_without_index_scans_from_start() -> Result<()> {
+    let path = tmp_log("index_missing.log");
+    write_log(&path, LogFormat::Binary, 1, 30)?;
+    std::fs::remove_file(index_path(&path))?;
+    check_seeks(&path)
+}
+
+#[test]
+fn segmented_reader_seeks_across_segments() -> Result<()> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("index_segmented");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let rotation = Rotation { max_bytes: Some(300), interval_ns: None };
+    {
+        let mut w = SegmentedWriter::open(&dir, "el:test", opts(LogFormat::JsonLines), rotation)?;
+        for i in 1..=20u64 {
+            w.append_bytes("event", 10 * i, &i.to_le_bytes())?;
+        }
+        w.flush()?;
+    }
+
+    let mut r = SegmentedReader::open(&dir)?;
+    r.seek_seq(13)?;
+    assert_eq!(r.next()?.map(|(e, _)| e.seq), Some(13));
+
+    r.seek_ts(45)?;
+    let seqs: Vec<u64> = std::iter::from_fn(|| r.next().unwrap().map(|(e, _)| e.seq)).collect();
+    assert_eq!(seqs, (5..=20).collect::<Vec<_>>());
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use anyhow::Result;
+use eventlog::chain::ChainVerifier;
+use eventlog::merge::merge;
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter, LogFormat};
+use std::path::{Path, PathBuf};
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&p);
+    std::fs::create_dir_all(&p).unwrap();
+    p
+}
+
+/// A capture of `stream` with one record per ts.
+fn capture(path: &Path, format: LogFormat, stream: &str, ts: &[u64]) -> Result<()> {
+    let opts = WriterOptions { format, ..Default::default() };
+    let mut w = EventLogWriter::open_with(path, stream, opts)?;
+    for t in ts {
+        w.append_bytes("event", *t, format!("{}@{}", stream, t).as_bytes())?;
+    }
+    Ok(w.flush()?)
+}
+
+/// (seq, stream, stream seq, origin seq, ts)
+type Merged = (u64, String, u64, Option<u64>, u64);
+
+fn read(path: &Path) -> Result<Vec<Merged>> {
+    let mut r = EventLogReader::open(path)?;
+    let mut chain = ChainVerifier::new();
+    let mut out = Vec::new();
+    while let Some((env, payload)) = r.next()? {
+        assert_eq!(payload, format!("{}@{}", env.stream, env.ts_ns).into_bytes());
+        chain.check(&env, &payload).unwrap();
+        out.push((env.seq, env.stream.clone(), env.seq_in_stream(), env.origin_seq, env.ts_ns));
+    }
+    assert_eq!(chain.chained(), out.len() as u64);
+    Ok(out)
+}
+
+#[test]
+fn merges_by_ts_with_input_order_tie_break() -> Result<()> {
+    let dir = tmp_dir("merge_basic");
+    let btc = dir.join("btc.log");
+    let eth = dir.join("eth.log");
+    capture(&btc, LogFormat::Binary, "md:BTCUSDT", &[10, 20, 20, 40])?;
+    capture(&eth, LogFormat::JsonLines, "md:ETHUSDT", &[5, 20, 30])?;
+
+    let opts = WriterOptions { format: LogFormat::Binary, hash_chain: true, ..Default::default() };
+    let out = dir.join("merged.log");
+    let report = merge(&[&eth, &btc], &out, opts, None)?;
+    assert_eq!((report.range.records, report.range.first_seq, report.range.last_seq), (7, 1, 7));
+    assert_eq!(report.inputs.iter().map(|i| i.records).collect::<Vec<_>>(), vec![3, 4]);
+
+    let eth_s = "md:ETHUSDT".to_string();
+    let btc_s = "md:BTCUSDT".to_string();
+    assert_eq!(
+        read(&out)?,
+        vec![
+            (1, eth_s.clone(), 1, Some(1), 5),
+            (2, btc_s.clone(), 1, Some(1), 10),
+            // eth is listed first, so it wins the tie at ts 20
+            (3, eth_s.clone(), 2, Some(2), 20),
+            (4, btc_s.clone(), 2, Some(2), 20),
+            (5, btc_s.clone(), 3, Some(3), 20),
+            (6, eth_s, 3, Some(3), 30),
+            (7, btc_s, 4, Some(4), 40),
+        ]
+    );
+
+    // same inputs, same bytes
+    let again = dir.join("again.log");
+    merge(&[&eth, &btc], &again, opts, None)?;
+    assert_eq!(std::fs::read(&out)?, std::fs::read(&again)?);
+
+    // an existing output is never appended to
+    assert!(merge(&[&eth], &out, opts, None).is_err());
+    Ok(())
+}
+
+#[test]
+fn out_of_order_input_is_reported() -> Result<()> {
+    let dir = tmp_dir("merge_unsorted");
+    let a = dir.join("a.log");
+    let b = dir.join("b.log");
+    capture(&a, LogFormat::JsonLines, "a", &[10, 30, 20])?;
+    capture(&b, LogFormat::JsonLines, "b", &[25])?;
+
+    let report = merge(&[&a, &b], dir.join("merged.log"), WriterOptions::default(), None)?;
+    assert_eq!(report.inputs.iter().map(|i| i.out_of_order).collect::<Vec<_>>(), vec![1, 0]);
+    assert_eq!(report.range.records, 4);
+    Ok(())
+}
+
+#[test]
+fn corrupt_input_fails_the_merge_and_leaves_no_output() -> Result<()> {
+    let dir = tmp_dir("merge_corrupt");
+    let a = dir.join("a.log");
+    let b = dir.join("b.log");
+    capture(&a, LogFormat::JsonLines, "a", &[1, 2, 3])?;
+    capture(&b, LogFormat::JsonLines, "b", &[1, 2, 3])?;
+
+    // flip the stored checksum of b's last record
+    let text = std::fs::read_to_string(&b)?;
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let last = lines.pop().unwrap();
+    let mut env: serde_json::Value = serde_json::from_str(&last)?;
+    env["checksum"] = serde_json::json!(env["checksum"].as_u64().unwrap() ^ 1);
+    lines.push(env.to_string());
+    std::fs::write(&b, lines.join("\n") + "\n")?;
+
+    let out = dir.join("merged.log");
+    let err = merge(&[&a, &b], &out, WriterOptions::default(), None).unwrap_err();
+    assert!(format!("{:#}", err).contains("checksum mismatch"), "{:#}", err);
+    assert!(!out.exists());
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use eventlog::compress::Compression;
+use eventlog::mmap::MmapLog;
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogError, EventLogReader, EventLogWriter, LogFormat};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    let _ = std::fs::remove_file(eventlog::index::index_path(&p));
+    p
+}
+
+/// 300 records over three streams; every 10th payload is long enough to compress.
+fn write(path: &Path, format: LogFormat, index_stride: Option<u64>) -> Result<()> {
+    let opts = WriterOptions {
+        format,
+        index_stride,
+        hash_chain: true,
+        compression: Compression::Zstd { level: 3, dict: None },
+        ..Default::default()
+    };
+    let mut w = EventLogWriter::open_with(path, "md", opts)?;
+    for i in 0..300u64 {
+        let payload = match i % 10 {
+            0 => "x".repeat(200).into_bytes(),
+            _ => format!("{{\"i\":{}}}", i).into_bytes(),
+        };
+        let stream = ["md", "exec", "risk"][i as usize % 3];
+        let kind = if i % 7 == 0 { "snapshot" } else { "event" };
+        w.append_stream(stream, kind, i * 1000, &payload)?;
+    }
+    Ok(w.flush()?)
+}
+
+/// What `EventLogReader` sees, as `(envelope json, payload)`; `payload_b64` left out.
+fn read_all(path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut r = EventLogReader::open(path)?;
+    let mut out = Vec::new();
+    while let Some((mut env, payload)) = r.next()? {
+        env.payload_b64.clear();
+        out.push((serde_json::to_string(&env)?, payload));
+    }
+    Ok(out)
+}
+
+fn envelope_json(rec: &eventlog::mmap::MmapRecord) -> String {
+    serde_json::to_string(&rec.envelope()).unwrap()
+}
+
+#[test]
+fn records_match_the_reader_and_borrow_plain_binary_payloads() -> Result<()> {
+    for format in [LogFormat::Binary, LogFormat::JsonLines] {
+        let path = tmp_log(&format!("mmap_records_{:?}.log", format));
+        write(&path, format, None)?;
+        let expected = read_all(&path)?;
+
+        let log = MmapLog::open(&path)?;
+        assert_eq!(log.format(), format);
+        let mut got = Vec::new();
+        for rec in log.records() {
+            let rec = rec?;
+            let borrowed = matches!(rec.payload, Cow::Borrowed(_));
+            assert_eq!(borrowed, format == LogFormat::Binary && rec.codec.is_none(), "seq={}", rec.seq);
+            assert!(matches!(rec.kind, Cow::Borrowed(_)) == (format == LogFormat::Binary));
+            got.push((envelope_json(&rec), rec.payload.into_owned()));
+        }
+        assert_eq!(got.len(), 300);
+        assert_eq!(got, expected, "{:?}", format);
+    }
+    Ok(())
+}
+
+#[test]
+fn split_ranges_cover_the_log_once() -> Result<()> {
+    for (format, stride) in [(LogFormat::Binary, Some(16)), (LogFormat::Binary, None), (LogFormat::JsonLines, None)] {
+        let path = tmp_log(&format!("mmap_split_{:?}_{:?}.log", format, stride));
+        write(&path, format, stride)?;
+        let log = MmapLog::open(&path)?;
+
+        let ranges = log.split(4)?;
+        assert_eq!(ranges.len(), 4, "{:?} {:?}", format, stride);
+        assert_eq!(ranges[0].start, log.full_range().start);
+        assert_eq!(ranges[3].end, log.bytes().len() as u64);
+        assert!(ranges.windows(2).all(|w| w[0].end == w[1].start));
+
+        let mut seqs = Vec::new();
+        for range in &ranges {
+            for rec in log.records_in(range) {
+                seqs.push(rec?.seq);
+            }
+        }
+        assert_eq!(seqs, (1..=300).collect::<Vec<_>>(), "{:?} {:?}", format, stride);
+
+        // per-range (records, first seq, last seq) come back in range order
+        let spans = log.par_scan(
+            4,
+            || (0u64, None, 0u64),
+            |s, rec| {
+                s.0 += 1;
+                s.1.get_or_insert(rec.seq);
+                s.2 = rec.seq;
+                Ok(())
+            },
+        )?;
+        assert_eq!(spans.iter().map(|s| s.0).sum::<u64>(), 300);
+        assert!(spans.windows(2).all(|w| w[0].2 + 1 == w[1].1.unwrap()));
+
+        let many = log.split(1000)?;
+        let total: usize = many.iter().map(|r| log.records_in(r).count()).sum();
+        assert_eq!(total, 300);
+    }
+    Ok(())
+}
+
+#[test]
+fn damage_and_writers_are_reported() -> Result<()> {
+    let path = tmp_log("mmap_damage.log");
+    write(&path, LogFormat::Binary, None)?;
+
+    {
+        let _w = EventLogWriter::open_with(&path, "md", WriterOptions { format: LogFormat::Binary, ..Default::default() })?;
+        let err = MmapLog::open(&path).err().expect("locked by the writer");
+        assert!(format!("{:#}", err).contains("open for writing"), "{:#}", err);
+    }
+
+    let mut bytes = std::fs::read(&path)?;
+    let at = bytes.windows(8).position(|w| w == b"{\"i\":11}").unwrap();
+    bytes[at + 6] = b'2';
+    std::fs::write(&path, &bytes[..bytes.len() - 1])?;
+
+    let log = MmapLog::open(&path)?;
+    let results: Vec<_> = log.records().collect();
+    assert_eq!(results.len(), 12, "stops after the first error");
+    match &results[11] {
+        Err(EventLogError::ChecksumMismatch { seq: 12, .. }) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // the torn tail is reported by the range that holds it
+    let last = log.split(2)?.pop().unwrap();
+    let err = log.records_in(&last).find_map(Result::err).expect("torn tail");
+    assert!(matches!(err, EventLogError::TruncatedTail { .. }), "{:?}", err);
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use anyhow::Result;
+use eventlog::chain::ChainVerifier;
+use eventlog::envelope::EventEnvelope;
+use eventlog::segment::Rotation;
+use eventlog::streams::{StreamGap, StreamSeqAudit};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter, LogFormat, SegmentedReader, SegmentedWriter};
+use std::path::PathBuf;
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+const STREAMS: [&str; 3] = ["md:BTCUSDT", "md:ETHUSDT", "exec"];
+
+/// Every 5th record is exec, the others alternate between the two instruments.
+fn stream_of(i: u64) -> &'static str {
+    if i.is_multiple_of(5) {
+        "exec"
+    } else {
+        STREAMS[(i % 2) as usize]
+    }
+}
+
+fn write(w: &mut EventLogWriter, seqs: std::ops::RangeInclusive<u64>) -> Result<()> {
+    for i in seqs {
+        w.append_stream(stream_of(i), "event", i * 10, format!("payload {}", i).as_bytes())?;
+    }
+    Ok(w.flush()?)
+}
+
+fn read_all(mut next: impl FnMut() -> Result<Option<(EventEnvelope, Vec<u8>)>>) -> Result<Vec<EventEnvelope>> {
+    let mut out = Vec::new();
+    while let Some((env, payload)) = next()? {
+        assert_eq!(payload, format!("payload {}", env.seq).into_bytes());
+        out.push(env);
+    }
+    Ok(out)
+}
+
+fn check_numbering(envs: &[EventEnvelope]) {
+    let mut audit = StreamSeqAudit::new();
+    let mut chain = ChainVerifier::new();
+    for env in envs {
+        assert_eq!(env.stream, stream_of(env.seq));
+        audit.check(env).unwrap();
+        chain.check(env, format!("payload {}", env.seq).as_bytes()).unwrap();
+    }
+    let expected: Vec<u64> = STREAMS
+        .iter()
+        .map(|s| envs.iter().filter(|e| e.stream == *s).count() as u64)
+        .collect();
+    for (s, n) in STREAMS.iter().zip(expected) {
+        let (_, span) = audit.streams().find(|(name, _)| name == s).unwrap();
+        assert_eq!((span.first_seq, span.last_seq, span.records), (1, n, n), "{}", s);
+    }
+}
+
+#[test]
+fn streams_have_their_own_seq_in_both_formats_and_across_reopen() -> Result<()> {
+    for format in [LogFormat::JsonLines, LogFormat::Binary] {
+        let path = tmp_log(&format!("multi_stream_{:?}.log", format));
+        let opts = WriterOptions { format, hash_chain: true, ..Default::default() };
+
+        let mut w = EventLogWriter::open_with(&path, "exec", opts)?;
+        write(&mut w, 1..=20)?;
+        drop(w);
+
+        let mut w = EventLogWriter::open_with(&path, "exec", opts)?;
+        assert_eq!(w.stream_seqs().get("exec"), Some(&4));
+        assert_eq!(w.append_stream("md:ETHUSDT", "event", 210, b"payload 21")?, (21, 9));
+        write(&mut w, 22..=30)?;
+        drop(w);
+
+        let mut r = EventLogReader::open(&path)?;
+        let envs = read_all(|| Ok(r.next()?))?;
+        assert_eq!(envs.iter().map(|e| e.seq).collect::<Vec<_>>(), (1..=30).collect::<Vec<_>>());
+        check_numbering(&envs);
+    }
+    Ok(())
+}
+
+#[test]
+fn reader_filters_by_stream() -> Result<()> {
+    for format in [LogFormat::JsonLines, LogFormat::Binary] {
+        let path = tmp_log(&format!("multi_stream_filter_{:?}.log", format));
+        let opts = WriterOptions { format, index_stride: Some(4), ..Default::default() };
+        let mut w = EventLogWriter::open_with(&path, "exec", opts)?;
+        write(&mut w, 1..=40)?;
+        drop(w);
+
+        let mut r = EventLogReader::open(&path)?.with_streams(["exec"]);
+        let envs = read_all(|| Ok(r.next()?))?;
+        assert_eq!(envs.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![5, 10, 15, 20, 25, 30, 35, 40]);
+        assert_eq!(envs.iter().map(|e| e.seq_in_stream()).collect::<Vec<_>>(), (1..=8).collect::<Vec<_>>());
+
+        // seeks go by global seq, then skip to the next record of the stream
+        let mut r = EventLogReader::open(&path)?.with_streams(["exec", "md:ETHUSDT"]);
+        r.seek_seq(21)?;
+        let (env, _) = r.next()?.unwrap();
+        assert_eq!((env.seq, env.stream.as_str()), (21, "md:ETHUSDT"));
+        let (env, _) = r.next()?.unwrap();
+        assert_eq!((env.seq, env.stream.as_str()), (23, "md:ETHUSDT"));
+    }
+    Ok(())
+}
+
+#[test]
+fn stream_seqs_continue_across_segments() -> Result<()> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("multi_stream_segments");
+    let _ = std::fs::remove_dir_all(&dir);
+    let opts = WriterOptions { format: LogFormat::Binary, hash_chain: true, ..Default::default() };
+    let rotation = Rotation { max_bytes: Some(300), interval_ns: None };
+
+    let mut w = SegmentedWriter::open(&dir, "exec", opts, rotation)?;
+    for i in 1..=20 {
+        w.append_stream(stream_of(i), "event", i * 10, format!("payload {}", i).as_bytes())?;
+    }
+    drop(w);
+    let mut w = SegmentedWriter::open(&dir, "exec", opts, rotation)?;
+    for i in 21..=40 {
+        w.append_stream(stream_of(i), "event", i * 10, format!("payload {}", i).as_bytes())?;
+    }
+    drop(w);
+    assert!(eventlog::segment::segment_files(&dir)?.len() > 2);
+
+    let mut r = SegmentedReader::open(&dir)?;
+    let envs = read_all(|| Ok(r.next()?))?;
+    assert_eq!(envs.len(), 40);
+    check_numbering(&envs);
+
+    let mut r = SegmentedReader::open(&dir)?.with_streams(["md:BTCUSDT"]);
+    let envs = read_all(|| Ok(r.next()?))?;
+    assert_eq!(envs.iter().map(|e| e.seq_in_stream()).collect::<Vec<_>>(), (1..=16).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn audit_reports_gaps_per_stream() -> Result<()> {
+    let path = tmp_log("multi_stream_gap.log");
+    let mut w = EventLogWriter::open_with(&path, "exec", WriterOptions::default())?;
+    write(&mut w, 1..=20)?;
+    drop(w);
+
+    // lose exec's second record (seq 10)
+    let text = std::fs::read_to_string(&path)?;
+    let kept: Vec<&str> = text.lines().filter(|l| !l.contains("\"seq\":10,")).collect();
+    std::fs::write(&path, kept.join("\n") + "\n")?;
+
+    // the other streams are still complete
+    let mut r = EventLogReader::open(&path)?.with_streams(["md:BTCUSDT", "md:ETHUSDT"]);
+    let mut audit = StreamSeqAudit::new();
+    for env in read_all(|| Ok(r.next()?))? {
+        audit.check(&env).unwrap();
+    }
+
+    let mut r = EventLogReader::open(&path)?;
+    let mut audit = StreamSeqAudit::new();
+    let gap = read_all(|| Ok(r.next()?))?.iter().find_map(|env| audit.check(env).err());
+    assert_eq!(gap, Some(StreamGap { stream: "exec".to_string(), prev: 1, current: 3, seq: 15 }));
+    Ok(())
+}
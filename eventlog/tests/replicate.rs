@@ -0,0 +1,171 @@
+use anyhow::Result;
+use eventlog::chain::ChainVerifier;
+use eventlog::compact::{self, Anchor};
+use eventlog::compress::Compression;
+use eventlog::crypto::{generate_key_file, FileKeys, KeyId, SharedKeys};
+use eventlog::replicate::{Endpoint, Primary, Replica, ReplicaOptions};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter, LogFormat};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&p);
+    std::fs::create_dir_all(&p).unwrap();
+    p
+}
+
+fn record(w: &mut EventLogWriter, i: u64) -> Result<()> {
+    let stream = if i.is_multiple_of(3) { "exec" } else { "md" };
+    w.append_stream(stream, "event", i * 10, format!("payload {}", i).as_bytes())?;
+    Ok(())
+}
+
+fn write(path: &Path, opts: WriterOptions, seqs: std::ops::RangeInclusive<u64>) -> Result<()> {
+    let mut w = EventLogWriter::open_with(path, "md", opts)?;
+    for i in seqs {
+        record(&mut w, i)?;
+    }
+    Ok(w.flush()?)
+}
+
+/// (seq, stream seq, stream, chain, payload) of every record, with the chain
+/// checked from the log's anchor.
+type Rec = (u64, u64, String, Option<String>, Vec<u8>);
+
+fn read(path: &Path) -> Result<Vec<Rec>> {
+    read_with(path, None)
+}
+
+fn read_with(path: &Path, keys: Option<SharedKeys>) -> Result<Vec<Rec>> {
+    let anchor = Anchor::load(path)?;
+    let mut r = EventLogReader::open(path)?;
+    if let Some(keys) = keys {
+        r = r.with_keys(keys);
+    }
+    let mut chain = ChainVerifier::new();
+    let mut out = Vec::new();
+    while let Some((env, payload)) = r.next()? {
+        if out.is_empty() {
+            if let Some(prev) = anchor.as_ref().and_then(|a| a.chain_before(env.seq)) {
+                chain = ChainVerifier::anchored(prev);
+            }
+        }
+        chain.check(&env, &payload).map_err(|b| anyhow::anyhow!("{}", b))?;
+        out.push((env.seq, env.seq_in_stream(), env.stream, env.chain, payload));
+    }
+    Ok(out)
+}
+
+/// Serve `log` on `endpoint` to one replica at `replica`, stopping once
+/// the log has been sent up to its current end.
+fn replicate(log: &Path, endpoint: &Endpoint, replica: &Path) -> Result<(u64, u64)> {
+    let listener = endpoint.listen()?;
+    let endpoint = listener.endpoint()?;
+    let primary = Primary::new(log).with_poll_interval(Duration::from_millis(1));
+    let stop = primary.stop_handle();
+
+    let follower = {
+        let replica = replica.to_path_buf();
+        std::thread::spawn(move || -> Result<u64> {
+            let mut r = Replica::open(&replica, ReplicaOptions { ack_every: 4, ..Default::default() })?;
+            r.run(endpoint.connect()?)?;
+            Ok(r.durable_seq())
+        })
+    };
+    stop.store(true, Ordering::Release);
+    primary.serve(listener.accept()?)?;
+    let durable = follower.join().unwrap()?;
+    Ok((primary.acked_seq(), durable))
+}
+
+#[test]
+fn replica_copies_the_log_and_catches_up_after_reconnecting() -> Result<()> {
+    let dir = tmp_dir("replicate_tcp");
+    let (log, copy) = (dir.join("primary.log"), dir.join("replica.log"));
+    let opts = WriterOptions { format: LogFormat::Binary, hash_chain: true, ..Default::default() };
+    let tcp: Endpoint = "127.0.0.1:0".parse()?;
+
+    write(&log, opts, 1..=30)?;
+    assert_eq!(replicate(&log, &tcp, &copy)?, (30, 30));
+    assert_eq!(read(&copy)?, read(&log)?);
+    assert_eq!(std::fs::read(&copy)?, std::fs::read(&log)?, "same format and options: same bytes");
+
+    // a torn write on the replica is dropped when it reopens
+    let mut torn = std::fs::read(&copy)?;
+    torn.truncate(torn.len() - 5);
+    std::fs::write(&copy, &torn)?;
+    write(&log, opts, 31..=40)?;
+    assert_eq!(Replica::open(&copy, ReplicaOptions::default())?.next_seq()?, 30);
+    assert_eq!(replicate(&log, &tcp, &copy)?, (40, 40));
+    assert_eq!(read(&copy)?, read(&log)?);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn new_replica_of_a_compacted_log_starts_at_its_anchor() -> Result<()> {
+    let dir = tmp_dir("replicate_unix");
+    let (log, copy) = (dir.join("primary.log"), dir.join("replica.log"));
+    let unix: Endpoint = format!("unix:{}", dir.join("primary.sock").display()).parse()?;
+
+    write(&log, WriterOptions { hash_chain: true, ..Default::default() }, 1..=20)?;
+    compact::compact_before(&log, 11, None)?;
+
+    assert_eq!(replicate(&log, &unix, &copy)?, (20, 20));
+    let copied = read(&copy)?;
+    assert_eq!(copied.first().map(|r| r.0), Some(11));
+    assert_eq!(copied, read(&log)?);
+    assert_eq!(Anchor::load(&copy)?, Anchor::load(&log)?);
+    Ok(())
+}
+
+#[test]
+fn diverged_replica_is_refused() -> Result<()> {
+    let dir = tmp_dir("replicate_diverged");
+    let (log, copy) = (dir.join("primary.log"), dir.join("replica.log"));
+    let opts = WriterOptions { hash_chain: true, ..Default::default() };
+    write(&log, opts, 1..=5)?;
+
+    let mut w = EventLogWriter::open_with(&copy, "md", opts)?;
+    w.append_stream("md", "event", 10, b"something else")?;
+    drop(w);
+
+    let err = replicate(&log, &"127.0.0.1:0".parse()?, &copy).unwrap_err();
+    assert!(format!("{:#}", err).contains("chain diverges at seq=2"), "{:#}", err);
+    Ok(())
+}
+
+#[test]
+fn sealed_records_are_replicated_as_stored() -> Result<()> {
+    let dir = tmp_dir("replicate_sealed");
+    let key_dir = dir.join("keys");
+    std::fs::create_dir_all(&key_dir)?;
+    generate_key_file(&key_dir, KeyId(1))?;
+    let keys = FileKeys::new(&key_dir);
+
+    for format in [LogFormat::Binary, LogFormat::JsonLines] {
+        let log = dir.join(format!("primary_{:?}.log", format));
+        let copy = dir.join(format!("replica_{:?}.log", format));
+        let compression = Compression::Zstd { level: 3, dict: None };
+        let opts = WriterOptions { format, hash_chain: true, compression, ..Default::default() };
+        let mut w = EventLogWriter::open_with(&log, "md", opts)?.with_encryption(&keys, KeyId(1))?;
+        for i in 1..=20 {
+            record(&mut w, i)?;
+        }
+        w.flush()?;
+        drop(w);
+
+        // neither side holds the keys
+        assert_eq!(replicate(&log, &"127.0.0.1:0".parse()?, &copy)?, (20, 20));
+        assert!(!std::fs::read(&copy)?.windows(8).any(|w| w == b"payload "), "{:?} plaintext in the replica", format);
+        let keys: SharedKeys = Arc::new(keys.clone());
+        let copied = read_with(&copy, Some(keys.clone()))?;
+        assert_eq!(copied.len(), 20);
+        assert_eq!(copied, read_with(&log, Some(keys))?);
+    }
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use anyhow::Result;
+use eventlog::salvage::SalvageReader;
+use eventlog::segment::{segment_files, Rotation, SegmentedReader, SegmentedWriter};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogError, EventLogReader, EventLogWriter, LogFormat};
+use std::path::{Path, PathBuf};
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+/// Records 1..=n; from seq 11 on they go to a second stream.
+fn write(path: &Path, format: LogFormat, n: u64) -> Result<Vec<u64>> {
+    let mut w = EventLogWriter::open_with(path, "a", WriterOptions { format, ..Default::default() })?;
+    for i in 1..=n {
+        let stream = if i > 10 { "b" } else { "a" };
+        w.append_stream(stream, "event", i, format!("payload {:04}", i).as_bytes())?;
+    }
+    w.flush()?;
+
+    // offset of each record, including the definitions written just before it
+    let mut r = EventLogReader::open(path)?;
+    let mut offsets = vec![r.position()];
+    while r.next()?.is_some() {
+        offsets.push(r.position());
+    }
+    Ok(offsets)
+}
+
+fn salvage(path: &Path) -> Result<(Vec<u64>, eventlog::salvage::SalvageReport)> {
+    let mut s = SalvageReader::open(path)?;
+    let mut seqs = Vec::new();
+    while let Some((env, payload)) = s.next()? {
+        assert_eq!(payload, format!("payload {:04}", env.seq).into_bytes());
+        seqs.push(env.seq);
+    }
+    Ok((seqs, s.finish()))
+}
+
+fn first_error(path: &Path) -> Result<EventLogError> {
+    let mut r = EventLogReader::open(path)?;
+    loop {
+        match r.next() {
+            Ok(Some(_)) => {}
+            Ok(None) => panic!("no error"),
+            Err(e) => return Ok(e),
+        }
+    }
+}
+
+#[test]
+fn json_lines_skip_bad_lines_and_report_the_torn_tail() -> Result<()> {
+    let path = tmp_log("salvage.jsonl");
+    let offsets = write(&path, LogFormat::JsonLines, 12)?;
+
+    let text = std::fs::read_to_string(&path)?;
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let mut env: serde_json::Value = serde_json::from_str(&lines[2])?;
+    env["checksum"] = serde_json::json!(env["checksum"].as_u64().unwrap() ^ 1);
+    lines[2] = env.to_string();
+    lines[4] = "{\"seq\": garbage".to_string();
+    lines[6] = lines[6].replace("\"payload_b64\":\"", "\"payload_b64\":\"*");
+    let mut damaged = lines.join("\n") + "\n";
+    damaged.truncate(damaged.len() - 10);
+    std::fs::write(&path, &damaged)?;
+
+    let third = offsets[2];
+    match first_error(&path)? {
+        EventLogError::ChecksumMismatch { seq, offset, .. } => assert_eq!((seq, offset), (3, third)),
+        e => panic!("{:?}", e),
+    }
+
+    let (seqs, report) = salvage(&path)?;
+    assert_eq!(seqs, vec![1, 2, 4, 6, 8, 9, 10, 11]);
+    assert_eq!(report.records, 8);
+    assert_eq!(report.missing_seqs, 3);
+    assert_eq!(report.skipped.iter().map(|s| s.seq).collect::<Vec<_>>(), vec![Some(3), None, Some(7)]);
+    assert!(report.skipped[1].error.contains("malformed envelope"), "{}", report.skipped[1].error);
+    assert!(report.skipped[2].error.contains("base64"), "{}", report.skipped[2].error);
+    assert!(report.truncated_tail.is_some());
+    Ok(())
+}
+
+#[test]
+fn binary_skips_bad_frames_and_resyncs_after_a_broken_length() -> Result<()> {
+    let path = tmp_log("salvage.bin");
+    let offsets = write(&path, LogFormat::Binary, 14)?;
+    let mut bytes = std::fs::read(&path)?;
+
+    // seq 3: payload byte flipped, framing intact
+    let end3 = offsets[3] as usize;
+    bytes[end3 - 1] ^= 0xff;
+    // seq 10: length prefix destroyed; seq 11 starts with the definition of stream "b"
+    let at10 = offsets[9] as usize;
+    bytes[at10..at10 + 4].copy_from_slice(&[0xee, 0xee, 0xee, 0x0e]);
+    // torn last record
+    bytes.truncate(bytes.len() - 3);
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(first_error(&path)?, EventLogError::ChecksumMismatch { seq: 3, .. }));
+
+    let (seqs, report) = salvage(&path)?;
+    assert_eq!(seqs, vec![1, 2, 4, 5, 6, 7, 8, 9, 11, 12, 13]);
+    assert_eq!(report.skipped.len(), 2);
+    assert_eq!((report.skipped[0].offset, report.skipped[0].seq), (offsets[2], Some(3)));
+    assert_eq!(report.skipped[1].offset, offsets[9]);
+    assert_eq!(report.skipped[1].bytes, offsets[10] - offsets[9], "resynced on the definition frame");
+    assert_eq!(report.truncated_tail, Some(offsets[13]));
+    assert_eq!(report.missing_seqs, 2);
+    Ok(())
+}
+
+#[test]
+fn clean_log_salvages_clean() -> Result<()> {
+    let path = tmp_log("salvage_clean.bin");
+    write(&path, LogFormat::Binary, 5)?;
+    let (seqs, report) = salvage(&path)?;
+    assert_eq!(seqs, vec![1, 2, 3, 4, 5]);
+    assert!(report.is_clean());
+    assert_eq!(report.bytes_lost(), 0);
+    Ok(())
+}
+
+#[test]
+fn segment_reader_and_writer_errors_are_typed() -> Result<()> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("salvage_segments");
+    let _ = std::fs::remove_dir_all(&dir);
+    let opts = WriterOptions { format: LogFormat::JsonLines, ..Default::default() };
+    let rotation = Rotation { max_bytes: Some(200), ..Default::default() };
+    let mut w = SegmentedWriter::open(&dir, "a", opts, rotation)?;
+    for i in 1..=6 {
+        w.append_bytes("event", i, format!("payload {:04}", i).as_bytes())?;
+    }
+    drop(w);
+
+    let files = segment_files(&dir)?;
+    assert!(files.len() > 1);
+    let text = std::fs::read_to_string(&files[1])?;
+    let mut env: serde_json::Value = serde_json::from_str(text.lines().next().unwrap())?;
+    let seq = env["seq"].as_u64().unwrap();
+    env["checksum"] = serde_json::json!(env["checksum"].as_u64().unwrap() ^ 1);
+    let rest: String = text.lines().skip(1).map(|l| format!("{}\n", l)).collect();
+    std::fs::write(&files[1], format!("{}\n{}", env, rest))?;
+
+    let mut r = SegmentedReader::open(&dir)?;
+    let err = loop {
+        match r.next() {
+            Ok(Some(_)) => {}
+            Ok(None) => panic!("no error"),
+            Err(e) => break e,
+        }
+    };
+    assert!(err.is_corruption(), "{:?}", err);
+    assert!(matches!(err, EventLogError::ChecksumMismatch { seq: s, offset: 0, .. } if s == seq), "{:?}", err);
+
+    let binary = WriterOptions { format: LogFormat::Binary, ..Default::default() };
+    match EventLogWriter::open_with(&files[0], "a", binary) {
+        Err(EventLogError::FormatMismatch { found: LogFormat::JsonLines, wanted: LogFormat::Binary, .. }) => {}
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!("opened a JSON lines log as binary"),
+    }
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use anyhow::Result;
+use el_core::event::{Event, EventId, EventPayload, EventType, Exchange, SCHEMA_VERSION};
+use el_core::exec::{OrderType, Side, TimeInForce};
+use el_core::instrument::InstrumentKey;
+use el_core::num::{Price, Qty};
+use el_core::time::{TimeSource, Timestamp};
+use eventlog::schema::{event_v1_to_v2, KindSchema, SchemaRegistry};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogError, EventLogReader, EventLogWriter, LogFormat, LogSource};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+fn order_submit(side: Side) -> Event {
+    Event {
+        id: EventId::nil(),
+        event_type: EventType::OrderSubmit,
+        exchange: Exchange::Binance,
+        symbol: "BTCUSDT".to_string(),
+        instrument: InstrumentKey::new(Exchange::Binance, "BTCUSDT"),
+        ts_exchange: None,
+        ts_recv: Timestamp::new(1, TimeSource::Receive),
+        ts_proc: Timestamp::new(1, TimeSource::Process),
+        seq: None,
+        schema_version: SCHEMA_VERSION,
+        integrity_flags: vec![],
+        payload: EventPayload::OrderSubmit {
+            order_id: "o1".to_string(),
+            client_order_id: None,
+            side,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::Gtc,
+            price: Some(Price::from_int(100)),
+            qty: Qty::from_int(1),
+            flags: Default::default(),
+        },
+        meta: Default::default(),
+    }
+}
+
+/// The same order as a v1 capture: sides were free-form strings ("buy", "BUY", "Buy").
+fn order_submit_v1(side: &str) -> Result<Vec<u8>> {
+    let mut ev = serde_json::to_value(order_submit(Side::Buy))?;
+    ev["schema_version"] = json!(1);
+    ev["payload"] = json!({"OrderSubmit": {"order_id": "o1", "side": side, "price": 100.0, "qty": 1.0}});
+    Ok(serde_json::to_vec(&ev)?)
+}
+
+/// A future v3 that changes nothing.
+fn v3_registry() -> SchemaRegistry {
+    let event = KindSchema::json(Some("schema_version")).migrate(event_v1_to_v2).migrate(Ok);
+    SchemaRegistry::core().register("event", event)
+}
+
+fn write(path: &Path, records: &[(&str, Vec<u8>)]) -> Result<()> {
+    let opts = WriterOptions { format: LogFormat::Binary, ..Default::default() };
+    let mut w = EventLogWriter::open_with(path, "exec", opts)?;
+    for (i, (kind, payload)) in records.iter().enumerate() {
+        w.append_bytes(kind, i as u64, payload)?;
+    }
+    Ok(w.flush()?)
+}
+
+fn read_all(r: &mut EventLogReader) -> Result<Vec<Vec<u8>>, EventLogError> {
+    let mut out = Vec::new();
+    while let Some((_, payload)) = r.next()? {
+        out.push(payload);
+    }
+    Ok(out)
+}
+
+#[test]
+fn core_registry_matches_the_event_schema() {
+    let core = SchemaRegistry::core();
+    assert_eq!(core.get("event").map(|s| s.version()), Some(SCHEMA_VERSION));
+    assert_eq!(core.kinds().map(|(k, _)| k).collect::<Vec<_>>(), vec!["event", "snapshot", "snapshot_hash"]);
+}
+
+#[test]
+fn old_payloads_are_upcast_on_read() -> Result<()> {
+    let path = tmp_log("schema_upcast.bin");
+    write(&path, &[
+        ("event", order_submit_v1("buy")?),
+        ("event", order_submit_v1("SELL")?),
+        // an exec event without a version field is v1
+        ("event", br#"{"OrderPlaced":{"order_id":"o2"}}"#.to_vec()),
+        ("snapshot_hash", 7u64.to_le_bytes().to_vec()),
+    ])?;
+
+    // without a registry, payloads are handed out as stored
+    let stored = read_all(&mut EventLogReader::open(&path)?)?;
+    assert!(serde_json::from_slice::<Event>(&stored[0]).is_err());
+
+    let mut r = LogSource::open(&path)?.with_schemas(SchemaRegistry::core());
+    let mut sides = Vec::new();
+    while let Some((env, payload)) = r.next()? {
+        if env.seq <= 2 {
+            let ev: Event = serde_json::from_slice(&payload)?;
+            assert_eq!(ev.schema_version, 2);
+            let EventPayload::OrderSubmit { order_id, side, order_type, time_in_force, price, .. } = ev.payload else {
+                panic!("seq {}: {:?}", env.seq, ev.payload);
+            };
+            assert_eq!((order_id.as_str(), order_type, time_in_force), ("o1", OrderType::Limit, TimeInForce::Gtc));
+            assert_eq!(price, Some(Price::from_int(100)));
+            sides.push(side);
+        } else {
+            assert_eq!(payload, stored[env.seq as usize - 1], "seq {}", env.seq);
+        }
+    }
+    assert_eq!(sides, vec![Side::Buy, Side::Sell]);
+
+    // decode straight from a stored payload
+    let mut raw = EventLogReader::open(&path)?;
+    let (env, payload) = raw.next()?.unwrap();
+    let ev: Event = SchemaRegistry::core().decode(&env, &payload)?;
+    assert!(matches!(ev.payload, EventPayload::OrderSubmit { side: Side::Buy, .. }));
+
+    // current payloads pass through untouched
+    let current = serde_json::to_vec(&order_submit(Side::Sell))?;
+    assert_eq!(SchemaRegistry::core().upcast(&env, current.clone())?, current);
+    Ok(())
+}
+
+#[test]
+fn unknown_kinds_and_versions_are_rejected() -> Result<()> {
+    let path = tmp_log("schema_unknown_kind.bin");
+    write(&path, &[("event", order_submit_v1("buy")?), ("note", b"hello".to_vec())])?;
+    let mut r = EventLogReader::open(&path)?.with_schemas(SchemaRegistry::core());
+    assert!(r.next()?.is_some());
+    match r.next() {
+        Err(EventLogError::UnknownKind { seq: 2, kind }) => assert_eq!(kind, "note"),
+        other => panic!("{:?}", other),
+    }
+
+    // a v3 capture read by v2 code
+    let path = tmp_log("schema_newer.bin");
+    let mut ev = serde_json::to_value(order_submit(Side::Buy))?;
+    ev["schema_version"] = json!(3);
+    write(&path, &[("event", serde_json::to_vec(&ev)?)])?;
+    let err = read_all(&mut EventLogReader::open(&path)?.with_schemas(SchemaRegistry::core())).unwrap_err();
+    assert!(matches!(err, EventLogError::UnsupportedVersion { version: 3, current: 2, .. }), "{:?}", err);
+    assert!(!err.is_corruption());
+    assert!(read_all(&mut EventLogReader::open(&path)?.with_schemas(v3_registry())).is_ok());
+
+    // payloads that do not match their kind, or do not migrate
+    let path = tmp_log("schema_invalid.bin");
+    write(&path, &[("snapshot_hash", vec![1, 2, 3])])?;
+    let err = read_all(&mut EventLogReader::open(&path)?.with_schemas(SchemaRegistry::core())).unwrap_err();
+    assert!(err.to_string().contains("payload is 3 bytes, expected 8"), "{}", err);
+
+    let path = tmp_log("schema_no_migration.bin");
+    write(&path, &[("event", order_submit_v1("hold")?)])?;
+    let err = read_all(&mut EventLogReader::open(&path)?.with_schemas(SchemaRegistry::core())).unwrap_err();
+    assert!(err.to_string().contains("migrating from v1: unknown side"), "{}", err);
+    Ok(())
+}
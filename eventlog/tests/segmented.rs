@@ -0,0 +1,129 @@
+use anyhow::Result;
+use eventlog::segment::{segment_file_name, segment_files, Manifest, Rotation};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogError, SegmentedReader, SegmentedWriter};
+use std::path::{Path, PathBuf};
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&p);
+    p
+}
+
+fn by_size(max_bytes: u64) -> Rotation {
+    Rotation { max_bytes: Some(max_bytes), interval_ns: None }
+}
+
+fn read_seqs(dir: &Path) -> Result<Vec<u64>> {
+    let mut r = SegmentedReader::open(dir)?;
+    let mut out = Vec::new();
+    while let Some((env, payload)) = r.next()? {
+        assert_eq!(payload, env.ts_ns.to_le_bytes());
+        out.push(env.seq);
+    }
+    Ok(out)
+}
+
+#[test]
+fn size_rotation_writes_manifest_and_reads_across_segments() -> Result<()> {
+    let dir = tmp_dir("segmented_size");
+
+    {
+        let mut w = SegmentedWriter::open(&dir, "el:test", WriterOptions::default(), by_size(300))?;
+        for i in 1..=20u64 {
+            w.append_bytes("event", i, &i.to_le_bytes())?;
+        }
+        w.flush()?;
+    }
+
+    let files = segment_files(&dir)?;
+    assert!(files.len() > 2, "expected several segments, got {}", files.len());
+
+    let m = Manifest::load(&dir)?;
+    assert_eq!(m.segments.len(), files.len() - 1);
+    assert_eq!(m.segments[0].first_seq, 1);
+    for w in m.segments.windows(2) {
+        assert_eq!(w[0].last_seq + 1, w[1].first_seq);
+        assert_eq!(w[0].last_ts_ns + 1, w[1].first_ts_ns);
+    }
+
+    assert_eq!(read_seqs(&dir)?, (1..=20).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn reopen_continues_seq_in_active_segment() -> Result<()> {
+    let dir = tmp_dir("segmented_reopen");
+
+    {
+        let mut w = SegmentedWriter::open(&dir, "el:test", WriterOptions::default(), by_size(300))?;
+        for i in 1..=7u64 {
+            w.append_bytes("event", i, &i.to_le_bytes())?;
+        }
+        w.flush()?;
+    }
+    let files_before = segment_files(&dir)?.len();
+
+    {
+        let mut w = SegmentedWriter::open(&dir, "el:test", WriterOptions::default(), by_size(300))?;
+        assert_eq!(w.append_bytes("event", 8, &8u64.to_le_bytes())?, 8);
+        w.flush()?;
+    }
+
+    assert!(segment_files(&dir)?.len() >= files_before);
+    assert_eq!(read_seqs(&dir)?, (1..=8).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn unrecorded_sealed_segment_is_rebuilt_on_open() -> Result<()> {
+    let dir = tmp_dir("segmented_rebuild");
+
+    {
+        let mut w = SegmentedWriter::open(&dir, "el:test", WriterOptions::default(), by_size(200))?;
+        for i in 1..=10u64 {
+            w.append_bytes("event", i, &i.to_le_bytes())?;
+        }
+        w.flush()?;
+    }
+
+    // simulate a crash between rotation and the manifest update
+    let full = Manifest::load(&dir)?;
+    Manifest::default().store(&dir)?;
+
+    let w = SegmentedWriter::open(&dir, "el:test", WriterOptions::default(), by_size(200))?;
+    assert_eq!(w.manifest(), &full);
+    Ok(())
+}
+
+#[test]
+fn time_rotation_seals_on_new_window() -> Result<()> {
+    let dir = tmp_dir("segmented_time");
+    let rotation = Rotation { max_bytes: None, interval_ns: Some(20_000_000) };
+
+    {
+        let mut w = SegmentedWriter::open(&dir, "el:test", WriterOptions::default(), rotation)?;
+        w.append_bytes("event", 1, &1u64.to_le_bytes())?;
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        w.append_bytes("event", 2, &2u64.to_le_bytes())?;
+        w.flush()?;
+    }
+
+    assert_eq!(segment_files(&dir)?.len(), 2);
+    assert_eq!(read_seqs(&dir)?, vec![1, 2]);
+    Ok(())
+}
+
+#[test]
+fn segment_named_for_seq_zero_is_refused() -> Result<()> {
+    let dir = tmp_dir("segmented_seq_zero");
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join(segment_file_name(0)), b"")?;
+
+    match SegmentedWriter::open(&dir, "el:test", WriterOptions::default(), by_size(300)) {
+        Err(EventLogError::Layout { reason, .. }) => assert!(reason.contains("seq 0"), "{}", reason),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!("opened a segment starting at seq 0"),
+    }
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use el_core::event::{Event, EventId, EventPayload, EventType, Exchange};
+use el_core::instrument::InstrumentKey;
+use el_core::num::{Price, Qty};
+use el_core::time::{TimeSource, Timestamp};
+use eventlog::slice::{slice, SliceFilter};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogReader, EventLogWriter, LogFormat};
+use std::path::{Path, PathBuf};
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&p);
+    std::fs::create_dir_all(&p).unwrap();
+    p
+}
+
+fn trade(symbol: &str, ts: u64) -> Event {
+    Event {
+        id: EventId::nil(),
+        event_type: EventType::Trade,
+        exchange: Exchange::Binance,
+        symbol: symbol.to_string(),
+        instrument: InstrumentKey::new(Exchange::Binance, symbol),
+        ts_exchange: None,
+        ts_recv: Timestamp::new(ts as i64, TimeSource::Receive),
+        ts_proc: Timestamp::new(ts as i64, TimeSource::Process),
+        seq: None,
+        schema_version: 1,
+        integrity_flags: vec![],
+        payload: EventPayload::Trade {
+            price: Price::from_int(100),
+            qty: Qty::from_int(1),
+            is_maker: false,
+            trade_id: None,
+            aggressor: None,
+        },
+        meta: Default::default(),
+    }
+}
+
+/// seq i (1..=30) at ts i*10: BTC trades on even seqs, ETH on odd ones,
+/// and every 10th record is a plain-text exec note.
+fn capture(path: &Path) -> Result<()> {
+    let opts = WriterOptions { format: LogFormat::Binary, index_stride: Some(4), ..Default::default() };
+    let mut w = EventLogWriter::open_with(path, "md", opts)?;
+    for i in 1..=30u64 {
+        if i.is_multiple_of(10) {
+            w.append_stream("exec", "note", i * 10, format!("note {}", i).as_bytes())?;
+        } else {
+            let symbol = if i.is_multiple_of(2) { "BTCUSDT" } else { "ETHUSDT" };
+            w.append_stream("md", "event", i * 10, &serde_json::to_vec(&trade(symbol, i * 10))?)?;
+        }
+    }
+    Ok(w.flush()?)
+}
+
+/// (seq, stream seq, origin seq) of every record.
+fn read(path: &Path) -> Result<Vec<(u64, u64, Option<u64>)>> {
+    let mut r = EventLogReader::open(path)?;
+    let mut out = Vec::new();
+    while let Some((env, _)) = r.next()? {
+        out.push((env.seq, env.seq_in_stream(), env.origin_seq));
+    }
+    Ok(out)
+}
+
+#[test]
+fn slices_by_instrument_and_seq_range() -> Result<()> {
+    let dir = tmp_dir("slice_instrument");
+    let input = dir.join("capture.log");
+    capture(&input)?;
+
+    let filter = SliceFilter {
+        instruments: vec![InstrumentKey::new(Exchange::Binance, "BTCUSDT")],
+        seq_range: Some((5, 14)),
+        ..Default::default()
+    };
+    let out = dir.join("btc.log");
+    let report = slice(&input, &out, &filter, WriterOptions::default(), None)?;
+    assert_eq!(report.scanned, 10);
+    assert_eq!(report.undecoded, 1, "the exec note at seq 10");
+
+    // BTC trades at 6, 8, 12, 14, renumbered from 1
+    assert_eq!(read(&out)?, vec![(1, 1, Some(6)), (2, 2, Some(8)), (3, 3, Some(12)), (4, 4, Some(14))]);
+    Ok(())
+}
+
+#[test]
+fn slices_by_stream_kind_and_ts() -> Result<()> {
+    let dir = tmp_dir("slice_stream");
+    let input = dir.join("capture.log");
+    capture(&input)?;
+
+    let exec = SliceFilter { streams: vec!["exec".to_string()], ..Default::default() };
+    let out = dir.join("exec.log");
+    let report = slice(&input, &out, &exec, WriterOptions::default(), None)?;
+    assert_eq!(report.scanned, 3, "other streams are skipped by the reader");
+    assert_eq!(read(&out)?, vec![(1, 1, Some(10)), (2, 2, Some(20)), (3, 3, Some(30))]);
+
+    let window = SliceFilter { kinds: vec!["event".to_string()], ts_range_ns: Some((95, 130)), ..Default::default() };
+    let out = dir.join("window.log");
+    slice(&input, &out, &window, WriterOptions::default(), None)?;
+    assert_eq!(read(&out)?.iter().map(|r| r.2).collect::<Vec<_>>(), vec![Some(11), Some(12)]);
+
+    // output already exists
+    assert!(slice(&input, &out, &window, WriterOptions::default(), None).is_err());
+    Ok(())
+}
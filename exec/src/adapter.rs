@@ -1,26 +1,92 @@
-use el_core::instrument::InstrumentKey;
+use el_core::exec::{OrderFlags, OrderParams, OrderType, TimeInForce};
+use el_core::instrument::{InstrumentKey, InstrumentRegistry, SpecError};
+use std::sync::Arc;
+use thiserror::Error;
 use crate::events::OrderId;
-type Price = f64;
-type Qty = f64;
+use el_core::num::{Price, Qty};
+
+pub use el_core::exec::Side;
 
 #[derive(Debug, Clone)]
 pub struct PlaceOrder {
     pub instrument: InstrumentKey,
     pub order_id: OrderId,
-    pub price: Price,
-    pub qty: Qty,
-    pub side: Side,
+    pub params: OrderParams,
 }
 
-#[derive(Debug, Clone)]
-pub struct CancelOrder {
-    pub order_id: OrderId,
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum OrderCheckError {
+    #[error("invalid order: {0}")]
+    Params(String),
+
+    #[error(transparent)]
+    Spec(#[from] SpecError),
+}
+
+impl PlaceOrder {
+    /// A GTC limit order without flags.
+    pub fn limit(instrument: InstrumentKey, order_id: OrderId, side: Side, price: Price, qty: Qty) -> Self {
+        let params = OrderParams {
+            side,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::Gtc,
+            price: Some(price),
+            qty,
+            flags: OrderFlags::default(),
+            client_order_id: None,
+        };
+        Self { instrument, order_id, params }
+    }
+
+    /// A market order; it never rests, so its time in force is IOC.
+    pub fn market(instrument: InstrumentKey, order_id: OrderId, side: Side, qty: Qty) -> Self {
+        let params = OrderParams {
+            side,
+            order_type: OrderType::Market,
+            time_in_force: TimeInForce::Ioc,
+            price: None,
+            qty,
+            flags: OrderFlags::default(),
+            client_order_id: None,
+        };
+        Self { instrument, order_id, params }
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.params.time_in_force = time_in_force;
+        self
+    }
+
+    /// `post_only` is checked by `validate`; `reduce_only` is passed on to
+    /// the exchange as is.
+    pub fn with_flags(mut self, flags: OrderFlags) -> Self {
+        self.params.flags = flags;
+        self
+    }
+
+    pub fn with_client_order_id(mut self, client_order_id: impl Into<String>) -> Self {
+        self.params.client_order_id = Some(client_order_id.into());
+        self
+    }
+
+    /// Consistent params, then price on tick, qty on lot and minimums met,
+    /// per the instrument's spec. Market orders skip the price checks.
+    pub fn validate(&self, specs: &InstrumentRegistry) -> Result<(), OrderCheckError> {
+        self.params.check().map_err(OrderCheckError::Params)?;
+        let spec = specs.require(&self.instrument)?;
+        match self.params.price {
+            Some(price) => {
+                spec.check_order(price, self.params.qty)?;
+            }
+            None => spec.check_qty(self.params.qty)?,
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
-pub enum Side {
-    Buy,
-    Sell,
+pub struct CancelOrder {
+    pub order_id: OrderId,
 }
 
 #[derive(Debug, Clone)]
@@ -33,3 +99,32 @@ pub trait ExecAdapter {
     fn place_order(&mut self, cmd: PlaceOrder) -> ExecResult;
     fn cancel_order(&mut self, cmd: CancelOrder) -> ExecResult;
 }
+
+/// Rejects orders that fail `PlaceOrder::validate` before they reach `inner`.
+pub struct SpecCheckedAdapter<A> {
+    inner: A,
+    specs: Arc<InstrumentRegistry>,
+}
+
+impl<A: ExecAdapter> SpecCheckedAdapter<A> {
+    pub fn new(inner: A, specs: Arc<InstrumentRegistry>) -> Self {
+        Self { inner, specs }
+    }
+
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl<A: ExecAdapter> ExecAdapter for SpecCheckedAdapter<A> {
+    fn place_order(&mut self, cmd: PlaceOrder) -> ExecResult {
+        match cmd.validate(&self.specs) {
+            Ok(()) => self.inner.place_order(cmd),
+            Err(e) => ExecResult::Rejected { reason: e.to_string() },
+        }
+    }
+
+    fn cancel_order(&mut self, cmd: CancelOrder) -> ExecResult {
+        self.inner.cancel_order(cmd)
+    }
+}
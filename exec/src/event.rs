@@ -1,7 +1,11 @@
+#![allow(deprecated)]
+
 use crate::events::OrderId;
-type Price = f64;
-type Qty = f64;
+use el_core::instrument::InstrumentKey;
+use el_core::num::{Price, Qty};
 
+/// Adapter-level outcomes without an instrument.
+#[deprecated(note = "use el_core::exec::ExecEvent")]
 #[derive(Debug, Clone)]
 pub enum ExecEvent {
     OrderAccepted { order_id: OrderId },
@@ -9,3 +13,18 @@ pub enum ExecEvent {
     OrderFilled { order_id: OrderId, qty: Qty, price: Price },
     OrderCancelled { order_id: OrderId },
 }
+
+impl ExecEvent {
+    /// The canonical event for an order on `instrument`.
+    pub fn into_canonical(self, instrument: InstrumentKey) -> el_core::exec::ExecEvent {
+        use el_core::exec::ExecEvent as E;
+        match self {
+            ExecEvent::OrderAccepted { order_id } => E::OrderAcked { instrument, id: order_id },
+            ExecEvent::OrderRejected { order_id, reason } => E::OrderRejected { instrument, id: order_id, reason },
+            ExecEvent::OrderFilled { order_id, qty, price } => {
+                E::OrderFill { instrument, id: order_id, filled_qty: qty, avg_px: price }
+            }
+            ExecEvent::OrderCancelled { order_id } => E::OrderCancelled { instrument, id: order_id },
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use anyhow::Result;
 
 use el_core::event::{Event, EventPayload, EventType};
+use el_core::exec::OrderParams;
 
 use crate::events::{ExecEvent, OrderId};
-use crate::util::instrument::InstrumentKey;
 
 fn hash_order_id(s: &str) -> OrderId {
     let h = blake3::hash(s.as_bytes());
@@ -12,26 +12,26 @@ fn hash_order_id(s: &str) -> OrderId {
     OrderId(u64::from_le_bytes(b))
 }
 
-fn exch_to_string(e: &el_core::event::Exchange) -> String {
-    match e {
-        el_core::event::Exchange::Binance => "binance".to_string(),
-        el_core::event::Exchange::Okx => "okx".to_string(),
-        el_core::event::Exchange::Bybit => "bybit".to_string(),
-        el_core::event::Exchange::Other(s) => s.clone(),
-    }
-}
-
-fn instrument_of(ev: &Event) -> InstrumentKey {
-    InstrumentKey::new(exch_to_string(&ev.exchange), ev.symbol.clone())
-}
-
 pub fn to_exec_event(ev: &Event) -> Result<Option<ExecEvent>> {
-    let instrument = instrument_of(ev);
+    let instrument = ev.instrument.clone();
 
     match (&ev.event_type, &ev.payload) {
-        (EventType::OrderSubmit, EventPayload::OrderSubmit { order_id, .. }) => Ok(Some(
-            ExecEvent::OrderCreated { instrument, id: hash_order_id(order_id) },
-        )),
+        (
+            EventType::OrderSubmit,
+            EventPayload::OrderSubmit { order_id, client_order_id, side, order_type, time_in_force, price, qty, flags },
+        ) => {
+            let params = OrderParams {
+                side: *side,
+                order_type: *order_type,
+                time_in_force: *time_in_force,
+                price: *price,
+                qty: *qty,
+                flags: *flags,
+                client_order_id: client_order_id.clone(),
+            };
+            params.check().map_err(|e| anyhow::anyhow!("invalid OrderSubmit {}: {}", order_id, e))?;
+            Ok(Some(ExecEvent::OrderCreated { instrument, id: hash_order_id(order_id), params: Some(params) }))
+        }
 
         (EventType::OrderAck, EventPayload::OrderAck { order_id }) => Ok(Some(
             ExecEvent::OrderAcked { instrument, id: hash_order_id(order_id) },
@@ -42,7 +42,7 @@ pub fn to_exec_event(ev: &Event) -> Result<Option<ExecEvent>> {
         )),
 
         (EventType::Fill, EventPayload::Fill { order_id, price, qty, .. }) => {
-            if !price.is_finite() || !qty.is_finite() || *price <= 0.0 || *qty < 0.0 {
+            if !price.is_positive() || qty.is_negative() {
                 anyhow::bail!("invalid Fill numbers: price={} qty={}", price, qty);
             }
             Ok(Some(ExecEvent::OrderFill {
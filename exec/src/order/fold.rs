@@ -2,37 +2,37 @@ use std::collections::HashSet;
 
 use super::events::OrderEvent;
 use super::fold_error::OrderFoldError;
-use super::types::{OrderState, OrderView};
+use super::types::{OrderState, OrderView, AVG_PX_SCALE};
 
 use el_core::event::{EventPayload, EventType};
+use el_core::exec::{is_immediate, TimeInForce};
+use el_core::num::{Notional, Price, Qty};
 
-fn finite(x: f64) -> bool {
-    x.is_finite()
+fn valid_price_qty(price: Price, qty: Qty) -> bool {
+    price.is_positive() && !qty.is_negative()
 }
 
-fn valid_price_qty(price: f64, qty: f64) -> bool {
-    finite(price) && finite(qty) && price > 0.0 && qty >= 0.0
-}
-
-pub fn fold_view(events: &[OrderEvent], order_qty: f64) -> Result<OrderView, OrderFoldError> {
-    if !finite(order_qty) || order_qty <= 0.0 {
+pub fn fold_view(events: &[OrderEvent], order_qty: Qty) -> Result<OrderView, OrderFoldError> {
+    if !order_qty.is_positive() {
         return Err(OrderFoldError::InvalidNumber);
     }
 
     let mut view = OrderView::new();
     let mut seen_fill_ids: HashSet<String> = HashSet::new();
 
-    let mut filled_qty: f64 = 0.0;
-    let mut notional: f64 = 0.0;
-
-    const EPS: f64 = 1e-12;
+    let mut filled_qty = Qty::ZERO;
+    let mut notional = Notional::ZERO;
+    let mut immediate = false;
+    let mut fok = false;
 
     for e in events {
         let state = view.state;
 
         view.state = match (state, &e.event_type, &e.payload) {
             // submit/ack/reject
-            (OrderState::Created, EventType::OrderSubmit, EventPayload::OrderSubmit { .. }) => {
+            (OrderState::Created, EventType::OrderSubmit, EventPayload::OrderSubmit { order_type, time_in_force, .. }) => {
+                immediate = is_immediate(*order_type, *time_in_force);
+                fok = *time_in_force == TimeInForce::Fok;
                 OrderState::Sent
             }
             (OrderState::Sent, EventType::OrderAck, EventPayload::OrderAck { .. }) => {
@@ -51,9 +51,9 @@ pub fn fold_view(events: &[OrderEvent], order_qty: f64) -> Result<OrderView, Ord
             | (OrderState::PartiallyFilled, EventType::Fill, EventPayload::Fill { fill_id, price, qty, .. }) => {
                 if !seen_fill_ids.insert(fill_id.clone()) {
                     // duplicate fill => idempotent noop on accounting/state
-                    if (filled_qty - order_qty).abs() <= EPS {
+                    if filled_qty == order_qty {
                         OrderState::Filled
-                    } else if filled_qty > 0.0 {
+                    } else if filled_qty.is_positive() {
                         OrderState::PartiallyFilled
                     } else {
                         OrderState::Acknowledged
@@ -63,18 +63,27 @@ pub fn fold_view(events: &[OrderEvent], order_qty: f64) -> Result<OrderView, Ord
                         return Err(OrderFoldError::InvalidNumber);
                     }
 
-                    filled_qty += *qty;
-                    notional += *qty * *price;
+                    filled_qty = filled_qty.checked_add(*qty).ok_or(OrderFoldError::InvalidNumber)?;
+                    notional = price
+                        .checked_mul(*qty)
+                        .and_then(|n| notional.checked_add(n))
+                        .ok_or(OrderFoldError::InvalidNumber)?;
 
-                    if filled_qty > order_qty + EPS {
+                    if filled_qty > order_qty {
                         return Err(OrderFoldError::Overfill {
                             filled_qty,
                             order_qty,
                         });
                     }
 
-                    if (filled_qty - order_qty).abs() <= EPS {
+                    if filled_qty == order_qty {
                         OrderState::Filled
+                    } else if fok {
+                        // FOK fills in full or not at all
+                        return Err(OrderFoldError::InvalidTransition {
+                            state,
+                            event_type: e.event_type.clone(),
+                        });
                     } else {
                         OrderState::PartiallyFilled
                     }
@@ -91,6 +100,14 @@ pub fn fold_view(events: &[OrderEvent], order_qty: f64) -> Result<OrderView, Ord
                 OrderState::Cancelled
             }
 
+            // IOC/FOK/market: the exchange cancels the remainder once the match is done
+            (OrderState::Acknowledged, EventType::CancelAck, EventPayload::CancelAck { .. })
+            | (OrderState::PartiallyFilled, EventType::CancelAck, EventPayload::CancelAck { .. })
+                if immediate =>
+            {
+                OrderState::Expired
+            }
+
             // terminal states: nothing allowed after
             (OrderState::Filled, _, _)
             | (OrderState::Cancelled, _, _)
@@ -112,11 +129,10 @@ pub fn fold_view(events: &[OrderEvent], order_qty: f64) -> Result<OrderView, Ord
         };
 
         view.filled_qty = filled_qty;
-        view.avg_px = if filled_qty > 0.0 { notional / filled_qty } else { 0.0 };
-
-        if !finite(view.filled_qty) || !finite(view.avg_px) {
-            return Err(OrderFoldError::InvalidNumber);
-        }
+        view.avg_px = match filled_qty.is_positive() {
+            true => notional.checked_div(filled_qty, AVG_PX_SCALE).ok_or(OrderFoldError::InvalidNumber)?,
+            false => Price::ZERO,
+        };
     }
 
     Ok(view)
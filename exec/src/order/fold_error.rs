@@ -2,6 +2,7 @@ use thiserror::Error;
 
 use crate::order::types::OrderState;
 use el_core::event::EventType;
+use el_core::num::Qty;
 
 #[derive(Debug, Error)]
 pub enum OrderFoldError {
@@ -9,7 +10,7 @@ pub enum OrderFoldError {
     InvalidTransition { state: OrderState, event_type: EventType },
 
     #[error("invalid fill accounting: filled_qty={filled_qty} order_qty={order_qty}")]
-    Overfill { filled_qty: f64, order_qty: f64 },
+    Overfill { filled_qty: Qty, order_qty: Qty },
 
     #[error("invalid numeric value in execution payload")]
     InvalidNumber,
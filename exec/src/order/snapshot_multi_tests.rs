@@ -1,6 +1,6 @@
 use crate::events::{ExecEvent, OrderId};
 use crate::order::snapshot::build_snapshot_multi;
-use crate::util::instrument::InstrumentKey;
+use el_core::instrument::InstrumentKey;
 
 #[test]
 fn multi_snapshot_hash_is_deterministic() {
@@ -8,9 +8,9 @@ fn multi_snapshot_hash_is_deterministic() {
     let b = InstrumentKey::new("binance", "ETHUSDT");
 
     let events = vec![
-        ExecEvent::OrderCreated { instrument: a.clone(), id: OrderId(1) },
+        ExecEvent::OrderCreated { instrument: a.clone(), id: OrderId(1), params: None },
         ExecEvent::OrderAcked { instrument: a.clone(), id: OrderId(1) },
-        ExecEvent::OrderCreated { instrument: b.clone(), id: OrderId(2) },
+        ExecEvent::OrderCreated { instrument: b.clone(), id: OrderId(2), params: None },
         ExecEvent::OrderAcked { instrument: b.clone(), id: OrderId(2) },
     ];
 
@@ -1,20 +1,28 @@
 use std::collections::HashMap;
 
 use anyhow::Result;
+use el_core::hash::{self, StateHash, StateHasher};
+use el_core::exec::{OrderParams, TimeInForce};
+use el_core::num::Notional;
 
 use crate::events::{ExecEvent, OrderId};
-use super::types::{OrderState, OrderView};
+use super::types::{OrderState, OrderView, AVG_PX_SCALE};
+
+/// Domain of `OrderStore::state_hash64`.
+pub const STATE_HASH_DOMAIN: &str = "exec.order_store";
 
 #[derive(Debug, Default)]
 pub struct OrderStore {
     by_id: HashMap<OrderId, OrderView>,
     // internal accounting for avg
-    filled_notional: HashMap<OrderId, f64>,
+    filled_notional: HashMap<OrderId, Notional>,
+    // as submitted; absent for orders created without params
+    params: HashMap<OrderId, OrderParams>,
 }
 
 impl OrderStore {
     pub fn new() -> Self {
-        Self { by_id: HashMap::new(), filled_notional: HashMap::new() }
+        Self { by_id: HashMap::new(), filled_notional: HashMap::new(), params: HashMap::new() }
     }
 
     pub fn apply_all(&mut self, events: &[ExecEvent]) -> Result<()> {
@@ -26,9 +34,13 @@ impl OrderStore {
 
     pub fn apply(&mut self, ev: &ExecEvent) -> Result<()> {
         match ev {
-            ExecEvent::OrderCreated { id, .. } => {
+            ExecEvent::OrderCreated { id, params, .. } => {
                 self.by_id.insert(*id, OrderView::new());
-                self.filled_notional.insert(*id, 0.0);
+                self.filled_notional.insert(*id, Notional::ZERO);
+                match params {
+                    Some(p) => self.params.insert(*id, p.clone()),
+                    None => self.params.remove(id),
+                };
             }
             ExecEvent::OrderValidated { id, .. } => {
                 let v = self.by_id.entry(*id).or_insert_with(OrderView::new);
@@ -45,23 +57,38 @@ impl OrderStore {
 
             // Treat OrderFill as DELTA: (filled_qty += delta_qty), avg_px recomputed from notional.
             ExecEvent::OrderFill { id, filled_qty, avg_px, .. } => {
-                if !filled_qty.is_finite() || !avg_px.is_finite() || *filled_qty < 0.0 || *avg_px <= 0.0 {
+                if filled_qty.is_negative() || !avg_px.is_positive() {
                     anyhow::bail!("invalid fill numbers: filled_qty={} avg_px={}", filled_qty, avg_px);
                 }
+                let overflow = || anyhow::anyhow!("fill accounting overflows: filled_qty={} avg_px={}", filled_qty, avg_px);
 
-                let v = self.by_id.entry(*id).or_insert_with(OrderView::new);
-                let n = self.filled_notional.entry(*id).or_insert(0.0);
+                // worked out on copies: a rejected fill leaves the store as it was
+                let mut v = self.by_id.get(id).cloned().unwrap_or_else(OrderView::new);
+                let n = self.filled_notional.get(id).copied().unwrap_or(Notional::ZERO);
 
-                v.filled_qty += *filled_qty;
-                *n += *filled_qty * *avg_px;
+                v.filled_qty = v.filled_qty.checked_add(*filled_qty).ok_or_else(overflow)?;
+                let n = avg_px.checked_mul(*filled_qty).and_then(|x| n.checked_add(x)).ok_or_else(overflow)?;
 
-                if v.filled_qty > 0.0 {
-                    v.avg_px = *n / v.filled_qty;
+                if v.filled_qty.is_positive() {
+                    v.avg_px = n.checked_div(v.filled_qty, AVG_PX_SCALE).ok_or_else(overflow)?;
                 }
 
                 if v.state == OrderState::Acknowledged || v.state == OrderState::PartiallyFilled {
-                    v.state = OrderState::PartiallyFilled;
+                    // without params the order qty is unknown: every fill is partial
+                    v.state = match self.params.get(id) {
+                        Some(p) if v.filled_qty > p.qty => {
+                            anyhow::bail!("overfill: filled_qty={} order qty={}", v.filled_qty, p.qty)
+                        }
+                        Some(p) if v.filled_qty == p.qty => OrderState::Filled,
+                        Some(p) if p.time_in_force == TimeInForce::Fok => {
+                            anyhow::bail!("partial fill of FOK order: filled_qty={} order qty={}", v.filled_qty, p.qty)
+                        }
+                        _ => OrderState::PartiallyFilled,
+                    };
                 }
+
+                self.by_id.insert(*id, v);
+                self.filled_notional.insert(*id, n);
             }
 
             ExecEvent::OrderCancelRequested { id, .. } => {
@@ -70,7 +97,12 @@ impl OrderStore {
             }
             ExecEvent::OrderCancelled { id, .. } => {
                 let v = self.by_id.entry(*id).or_insert_with(OrderView::new);
-                v.state = OrderState::Cancelled;
+                let unsolicited = matches!(v.state, OrderState::Acknowledged | OrderState::PartiallyFilled);
+                // IOC/FOK/market: the exchange cancels the remainder once the match is done
+                v.state = match self.params.get(id) {
+                    Some(p) if unsolicited && p.is_immediate() => OrderState::Expired,
+                    _ => OrderState::Cancelled,
+                };
             }
 
             ExecEvent::OrderRejected { id, .. } => {
@@ -88,4 +120,24 @@ impl OrderStore {
     pub fn view(&self, id: OrderId) -> Option<&OrderView> {
         self.by_id.get(&id)
     }
+
+    /// Deterministic hash of every order view, stable across toolchains.
+    pub fn state_hash64(&self) -> u64 {
+        hash::state_hash64(STATE_HASH_DOMAIN, self)
+    }
+}
+
+impl StateHash for OrderStore {
+    /// Views and params in order id order; the notional behind `avg_px`
+    /// is derived.
+    fn hash_state(&self, h: &mut StateHasher) {
+        let mut ids: Vec<&OrderId> = self.by_id.keys().collect();
+        ids.sort_unstable_by_key(|id| id.0);
+        h.write_len(ids.len());
+        for id in ids {
+            h.write_u64(id.0);
+            self.by_id[id].hash_state(h);
+            self.params.get(id).hash_state(h);
+        }
+    }
 }
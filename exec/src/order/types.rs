@@ -1,32 +1,34 @@
+use el_core::hash::{StateHash, StateHasher};
+use el_core::num::{Price, Qty};
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub enum OrderState {
-    Created,
-    Validated,
-    Sent,
-    Acknowledged,
-    PartiallyFilled,
-    Filled,
-    CancelRequested,
-    Cancelled,
-    Rejected,
-    Expired,
-}
+pub use el_core::exec::OrderState;
+
+/// Digits after the point kept in `OrderView::avg_px`; averages are
+/// rounded half-even.
+pub const AVG_PX_SCALE: u32 = 12;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct OrderView {
     pub state: OrderState,
-    pub filled_qty: f64,
-    pub avg_px: f64,
+    pub filled_qty: Qty,
+    pub avg_px: Price,
+}
+
+impl StateHash for OrderView {
+    fn hash_state(&self, h: &mut StateHasher) {
+        self.state.hash_state(h);
+        self.filled_qty.hash_state(h);
+        self.avg_px.hash_state(h);
+    }
 }
 
 impl OrderView {
     pub fn new() -> Self {
         Self {
             state: OrderState::Created,
-            filled_qty: 0.0,
-            avg_px: 0.0,
+            filled_qty: Qty::ZERO,
+            avg_px: Price::ZERO,
         }
     }
 }
@@ -1,6 +1,10 @@
+#![allow(deprecated)]
+
 use crate::order_state::OrderState;
 use el_core::event::ExecEvent;
 
+/// Coarse lifecycle FSM over `el_core::event::ExecEvent` kinds.
+#[deprecated(note = "use exec::order::OrderStore over el_core::exec::ExecEvent")]
 #[derive(Debug)]
 pub struct OrderFsm {
     pub state: OrderState,
@@ -1,3 +1,5 @@
+#![allow(deprecated)]
+
 use thiserror::Error;
 use crate::order_state::OrderState;
 use el_core::event::ExecEvent;
@@ -1,3 +1,7 @@
+#![allow(deprecated)]
+
+/// States of the old coarse `order_fsm`.
+#[deprecated(note = "use el_core::exec::OrderState")]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OrderState {
     New,
@@ -8,3 +12,18 @@ pub enum OrderState {
     Filled,
     Canceled,
 }
+
+impl From<OrderState> for el_core::exec::OrderState {
+    fn from(s: OrderState) -> Self {
+        use el_core::exec::OrderState as S;
+        match s {
+            OrderState::New => S::Created,
+            OrderState::Placed => S::Sent,
+            OrderState::Accepted => S::Acknowledged,
+            OrderState::Rejected => S::Rejected,
+            OrderState::PartiallyFilled => S::PartiallyFilled,
+            OrderState::Filled => S::Filled,
+            OrderState::Canceled => S::Cancelled,
+        }
+    }
+}
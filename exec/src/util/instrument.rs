@@ -1,16 +1,4 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
-pub struct InstrumentKey {
-    pub exchange: String,
-    pub symbol: String,
-}
-
-impl InstrumentKey {
-    pub fn new(exchange: impl Into<String>, symbol: impl Into<String>) -> Self {
-        Self {
-            exchange: exchange.into(),
-            symbol: symbol.into(),
-        }
-    }
-}
+/// Kept for old imports; the exchange is now `el_core::event::Exchange`,
+/// and `InstrumentKey::new("binance", ..)` still works.
+#[deprecated(note = "use el_core::instrument::InstrumentKey")]
+pub type InstrumentKey = el_core::instrument::InstrumentKey;
@@ -1,4 +1,5 @@
 /// Deterministic stable hash for golden tests (FNV-1a 64-bit).
+#[deprecated(note = "not versioned or domain-separated; use el_core::hash::state_hash64")]
 pub fn stable_hash_u64(bytes: &[u8]) -> u64 {
     const FNV_OFFSET: u64 = 14695981039346656037;
     const FNV_PRIME: u64 = 1099511628211;
@@ -3,18 +3,56 @@ use exec::mock_adapter::MockAdapter;
 use el_core::instrument::InstrumentKey;
 use exec::events::OrderId;
 use el_core::event::Exchange;
+use el_core::num::{Price, Qty};
 
 #[test]
 fn mock_adapter_accepts_order() {
     let mut adapter = MockAdapter;
 
-    let res = adapter.place_order(PlaceOrder {
-        instrument: InstrumentKey::new(Exchange::Binance, "BTCUSDT"),
-        order_id: OrderId(1),
-        price: 100.0,
-        qty: 1.0,
-        side: Side::Buy,
-    });
+    let res = adapter.place_order(PlaceOrder::limit(
+        InstrumentKey::new(Exchange::Binance, "BTCUSDT"),
+        OrderId(1),
+        Side::Buy,
+        Price::from_int(100),
+        Qty::from_int(1),
+    ));
 
     assert!(matches!(res, exec::adapter::ExecResult::Accepted));
 }
+
+#[test]
+fn spec_checked_adapter_rejects_off_grid_orders() {
+    use el_core::exec::{OrderFlags, TimeInForce};
+    use el_core::instrument::{InstrumentRegistry, SpecError};
+    use exec::adapter::{ExecResult, OrderCheckError, SpecCheckedAdapter};
+    use std::sync::Arc;
+
+    let specs = InstrumentRegistry::from_json_str(
+        r#"{"instruments": [{"exchange": "Binance", "symbol": "BTCUSDT", "base": "BTC", "quote": "USDT",
+            "kind": "spot", "tick_size": "0.1", "lot_step": "0.001", "min_notional": "5"}]}"#,
+    )
+    .unwrap();
+    let specs = Arc::new(specs);
+    let mut adapter = SpecCheckedAdapter::new(MockAdapter, specs.clone());
+    let btc = || InstrumentKey::new(Exchange::Binance, "BTCUSDT");
+    let order = |price: &str, qty: &str| PlaceOrder::limit(btc(), OrderId(1), Side::Buy, price.parse().unwrap(), qty.parse().unwrap());
+
+    assert!(matches!(adapter.place_order(order("100.1", "0.05")), ExecResult::Accepted));
+    assert!(matches!(
+        order("100.15", "0.05").validate(&specs),
+        Err(OrderCheckError::Spec(SpecError::PriceOffTick { .. }))
+    ));
+    match adapter.place_order(order("100.15", "0.05")) {
+        ExecResult::Rejected { reason } => assert!(reason.contains("tick 0.1"), "{}", reason),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(adapter.place_order(order("100", "0.01")), ExecResult::Rejected { .. }));
+
+    // market orders skip the price checks; post-only cannot be immediate
+    let market = PlaceOrder::market(btc(), OrderId(2), Side::Sell, "0.002".parse().unwrap()).with_client_order_id("c-2");
+    assert_eq!(market.validate(&specs), Ok(()));
+    let post_only = OrderFlags { post_only: true, ..Default::default() };
+    let ioc = order("100.1", "0.05").with_time_in_force(TimeInForce::Ioc).with_flags(post_only);
+    assert!(matches!(ioc.validate(&specs), Err(OrderCheckError::Params(_))));
+    assert_eq!(order("100.1", "0.05").with_flags(post_only).validate(&specs), Ok(()));
+}
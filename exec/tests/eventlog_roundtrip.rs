@@ -1,7 +1,9 @@
 use anyhow::Result;
 
 use exec::events::{ExecEvent, OrderId};
-use exec::util::instrument::InstrumentKey;
+use el_core::exec::{OrderFlags, OrderParams, OrderType, Side, TimeInForce};
+use el_core::instrument::InstrumentKey;
+use el_core::num::{Price, Qty};
 
 use eventlog::{EventLogReader, EventLogWriter};
 
@@ -16,11 +18,23 @@ fn eventlog_roundtrip_exec_events() -> Result<()> {
     let id = OrderId(123);
 
     let events = vec![
-        ExecEvent::OrderCreated { instrument: instrument.clone(), id },
+        ExecEvent::OrderCreated {
+            instrument: instrument.clone(),
+            id,
+            params: Some(OrderParams {
+                side: Side::Sell,
+                order_type: OrderType::Limit,
+                time_in_force: TimeInForce::Gtc,
+                price: Some(Price::from_int(100)),
+                qty: Qty::from_int(2),
+                flags: OrderFlags { post_only: true, reduce_only: false },
+                client_order_id: Some("c-123".into()),
+            }),
+        },
         ExecEvent::OrderValidated { instrument: instrument.clone(), id },
         ExecEvent::OrderSent { instrument: instrument.clone(), id },
         ExecEvent::OrderAcked { instrument: instrument.clone(), id },
-        ExecEvent::OrderFill { instrument: instrument.clone(), id, filled_qty: 1.0, avg_px: 100.0 },
+        ExecEvent::OrderFill { instrument: instrument.clone(), id, filled_qty: Qty::from_int(1), avg_px: Price::from_int(100) },
         ExecEvent::OrderCancelRequested { instrument: instrument.clone(), id },
         ExecEvent::OrderCancelled { instrument: instrument.clone(), id },
     ];
@@ -45,3 +59,48 @@ fn eventlog_roundtrip_exec_events() -> Result<()> {
     assert_eq!(events, got);
     Ok(())
 }
+
+#[test]
+fn exec_logs_written_before_the_core_model_still_decode() -> Result<()> {
+    // exchange was a lowercase string in exec's own InstrumentKey
+    let old = r#"{"OrderFill":{"instrument":{"exchange":"binance","symbol":"BTCUSDT"},"id":5,"filled_qty":1.5,"avg_px":"100.25"}}"#;
+    let ev: ExecEvent = serde_json::from_str(old)?;
+    assert_eq!(
+        ev,
+        ExecEvent::OrderFill {
+            instrument: InstrumentKey::new(el_core::event::Exchange::Binance, "BTCUSDT"),
+            id: OrderId(5),
+            filled_qty: "1.5".parse()?,
+            avg_px: "100.25".parse()?,
+        }
+    );
+    let other: ExecEvent = serde_json::from_str(r#"{"OrderAcked":{"instrument":{"exchange":"deribit","symbol":"BTC-PERP"},"id":1}}"#)?;
+    assert_eq!(other.instrument().to_string(), "deribit:BTC-PERP");
+    Ok(())
+}
+
+#[test]
+fn bridge_keeps_the_event_instrument() -> Result<()> {
+    use el_core::event::{Event, EventPayload, EventType, Exchange, SCHEMA_VERSION};
+    use el_core::time::{TimeSource, Timestamp};
+
+    let instrument = InstrumentKey::new(Exchange::Other("Deribit".into()), "BTC-PERP");
+    let ev = Event {
+        id: uuid::Uuid::nil(),
+        event_type: EventType::OrderAck,
+        exchange: instrument.exchange.clone(),
+        symbol: instrument.symbol.0.clone(),
+        instrument: instrument.clone(),
+        ts_exchange: None,
+        ts_recv: Timestamp::new(0, TimeSource::Receive),
+        ts_proc: Timestamp::new(0, TimeSource::Process),
+        seq: None,
+        schema_version: SCHEMA_VERSION,
+        integrity_flags: vec![],
+        payload: EventPayload::OrderAck { order_id: "o-1".into() },
+        meta: Default::default(),
+    };
+    let exec_ev = exec::order::to_exec_event(&ev)?.expect("ack maps");
+    assert_eq!(exec_ev.instrument(), &instrument);
+    Ok(())
+}
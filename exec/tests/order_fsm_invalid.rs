@@ -1,3 +1,6 @@
+// The coarse FSM is kept for old callers; see el_core::exec for the current model.
+#![allow(deprecated)]
+
 use exec::order_fsm::OrderFsm;
 use el_core::event::ExecEvent::*;
 use exec::order_state::OrderState::*;
@@ -0,0 +1,185 @@
+use anyhow::Result;
+use el_core::event::{Event, EventPayload, EventType, Exchange, SCHEMA_VERSION};
+use el_core::exec::{ExecEvent, OrderState, TimeInForce};
+use el_core::instrument::InstrumentKey;
+use el_core::num::{Price, Qty};
+use el_core::time::{TimeSource, Timestamp};
+use exec::order::{fold_view, to_exec_event, OrderEvent, OrderFoldError, OrderStore};
+
+fn submit(time_in_force: TimeInForce) -> EventPayload {
+    EventPayload::OrderSubmit {
+        order_id: "o1".into(),
+        client_order_id: Some("c1".into()),
+        side: exec::adapter::Side::Buy,
+        order_type: Default::default(),
+        time_in_force,
+        price: Some(Price::from_int(100)),
+        qty: Qty::from_int(3),
+        flags: Default::default(),
+    }
+}
+
+fn fill(fill_id: &str, qty: i64) -> EventPayload {
+    EventPayload::Fill { order_id: "o1".into(), fill_id: fill_id.into(), price: Price::from_int(100), qty: Qty::from_int(qty) }
+}
+
+fn lifecycle(time_in_force: TimeInForce, fills: &[i64]) -> Vec<(EventType, EventPayload)> {
+    let mut evs = vec![
+        (EventType::OrderSubmit, submit(time_in_force)),
+        (EventType::OrderAck, EventPayload::OrderAck { order_id: "o1".into() }),
+    ];
+    for (i, q) in fills.iter().enumerate() {
+        evs.push((EventType::Fill, fill(&format!("f{}", i), *q)));
+    }
+    evs
+}
+
+fn event(event_type: EventType, payload: EventPayload) -> Event {
+    Event {
+        id: uuid::Uuid::nil(),
+        event_type,
+        exchange: Exchange::Binance,
+        symbol: "BTCUSDT".into(),
+        instrument: InstrumentKey::new(Exchange::Binance, "BTCUSDT"),
+        ts_exchange: None,
+        ts_recv: Timestamp::new(0, TimeSource::Receive),
+        ts_proc: Timestamp::new(0, TimeSource::Process),
+        seq: None,
+        schema_version: SCHEMA_VERSION,
+        integrity_flags: vec![],
+        payload,
+        meta: Default::default(),
+    }
+}
+
+/// State after running the lifecycle through the bridge into an `OrderStore`.
+fn store_state(evs: &[(EventType, EventPayload)]) -> Result<OrderState> {
+    let mut store = OrderStore::new();
+    let mut id = None;
+    for (t, p) in evs {
+        if let Some(ev) = to_exec_event(&event(t.clone(), p.clone()))? {
+            if let ExecEvent::OrderCreated { id: created, .. } = ev {
+                id = Some(created);
+            }
+            store.apply(&ev)?;
+        }
+    }
+    Ok(store.view(id.unwrap()).unwrap().state)
+}
+
+fn fold_state(evs: &[(EventType, EventPayload)]) -> Result<OrderState> {
+    let evs: Vec<OrderEvent> = evs.iter().map(|(t, p)| OrderEvent::new(t.clone(), p.clone())).collect();
+    Ok(fold_view(&evs, Qty::from_int(3))?.state)
+}
+
+fn cancel_ack() -> (EventType, EventPayload) {
+    (EventType::CancelAck, EventPayload::CancelAck { order_id: "o1".into() })
+}
+
+#[test]
+fn ioc_remainder_expires_on_the_exchange_cancel() -> Result<()> {
+    for (tif, fills, cancelled, want) in [
+        (TimeInForce::Gtc, &[1][..], false, OrderState::PartiallyFilled),
+        (TimeInForce::Gtc, &[1, 2][..], false, OrderState::Filled),
+        // one match may report several fills
+        (TimeInForce::Ioc, &[1][..], false, OrderState::PartiallyFilled),
+        (TimeInForce::Ioc, &[1, 1][..], false, OrderState::PartiallyFilled),
+        (TimeInForce::Ioc, &[1, 1][..], true, OrderState::Expired),
+        (TimeInForce::Ioc, &[][..], true, OrderState::Expired),
+        (TimeInForce::Ioc, &[3][..], false, OrderState::Filled),
+        (TimeInForce::Fok, &[3][..], false, OrderState::Filled),
+        (TimeInForce::Fok, &[][..], true, OrderState::Expired),
+    ] {
+        let mut evs = lifecycle(tif, fills);
+        if cancelled {
+            evs.push(cancel_ack());
+        }
+        assert_eq!(store_state(&evs)?, want, "store {:?} {:?} {}", tif, fills, cancelled);
+        assert_eq!(fold_state(&evs)?, want, "fold {:?} {:?} {}", tif, fills, cancelled);
+    }
+
+    // nothing more fills once the remainder has expired
+    let mut evs = lifecycle(TimeInForce::Ioc, &[1]);
+    evs.push(cancel_ack());
+    evs.push((EventType::Fill, fill("f9", 1)));
+    assert!(fold_state(&evs).is_err());
+
+    // a resting order still needs a cancel request first
+    let mut evs = lifecycle(TimeInForce::Gtc, &[1]);
+    evs.push(cancel_ack());
+    assert!(fold_state(&evs).is_err());
+    Ok(())
+}
+
+#[test]
+fn partial_fok_fill_is_invalid() {
+    let evs = lifecycle(TimeInForce::Fok, &[2]);
+    assert!(store_state(&evs).is_err());
+    assert!(matches!(
+        fold_state(&evs).unwrap_err().downcast_ref::<OrderFoldError>(),
+        Some(OrderFoldError::InvalidTransition { state: OrderState::Acknowledged, event_type: EventType::Fill })
+    ));
+}
+
+#[test]
+fn rejected_fills_leave_the_store_unchanged() -> Result<()> {
+    // an overfill of a resting order, and a partial fill of a FOK one
+    for (tif, fills, bad) in [(TimeInForce::Gtc, &[1][..], 3), (TimeInForce::Fok, &[][..], 2)] {
+        let mut store = OrderStore::new();
+        let mut id = None;
+        for (t, p) in lifecycle(tif, fills) {
+            let ev = to_exec_event(&event(t, p))?.unwrap();
+            if let ExecEvent::OrderCreated { id: created, .. } = ev {
+                id = Some(created);
+            }
+            store.apply(&ev)?;
+        }
+        let (id, hash) = (id.unwrap(), store.state_hash64());
+        let view = store.view(id).cloned();
+
+        let ev = to_exec_event(&event(EventType::Fill, fill("bad", bad)))?.unwrap();
+        assert!(store.apply(&ev).is_err(), "{:?} fill of {}", tif, bad);
+        assert_eq!(store.view(id).cloned(), view);
+        assert_eq!(store.state_hash64(), hash);
+    }
+    Ok(())
+}
+
+#[test]
+fn reduce_only_is_carried_into_the_order_params() -> Result<()> {
+    let mut hashes = Vec::new();
+    for reduce_only in [false, true] {
+        let mut payload = submit(TimeInForce::Gtc);
+        if let EventPayload::OrderSubmit { flags, .. } = &mut payload {
+            flags.reduce_only = reduce_only;
+        }
+        // as logged
+        let payload: EventPayload = serde_json::from_slice(&serde_json::to_vec(&payload)?)?;
+        let ev = to_exec_event(&event(EventType::OrderSubmit, payload))?.unwrap();
+        match &ev {
+            ExecEvent::OrderCreated { params: Some(p), .. } => assert_eq!(p.flags.reduce_only, reduce_only),
+            other => panic!("{:?}", other),
+        }
+        let mut store = OrderStore::new();
+        store.apply(&ev)?;
+        hashes.push(store.state_hash64());
+    }
+    assert_ne!(hashes[0], hashes[1], "the store keeps the flag");
+    Ok(())
+}
+
+#[test]
+fn bridge_rejects_inconsistent_submits() {
+    let market_with_price = EventPayload::OrderSubmit {
+        order_id: "o1".into(),
+        client_order_id: None,
+        side: exec::adapter::Side::Sell,
+        order_type: el_core::exec::OrderType::Market,
+        time_in_force: TimeInForce::Ioc,
+        price: Some(Price::from_int(1)),
+        qty: Qty::from_int(1),
+        flags: Default::default(),
+    };
+    let err = to_exec_event(&event(EventType::OrderSubmit, market_with_price)).unwrap_err();
+    assert!(err.to_string().contains("market order with a price"), "{}", err);
+}
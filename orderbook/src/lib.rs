@@ -1,10 +1,11 @@
 use std::collections::BTreeMap;
-use ordered_float::OrderedFloat;
+use el_core::instrument::{InstrumentSpec, SpecError};
+use el_core::num::{Price, Qty};
 
 #[derive(Debug, Clone)]
 pub struct OrderBook {
-    pub bids: BTreeMap<OrderedFloat<f64>, f64>,
-    pub asks: BTreeMap<OrderedFloat<f64>, f64>,
+    pub bids: BTreeMap<Price, Qty>,
+    pub asks: BTreeMap<Price, Qty>,
 }
 
 impl OrderBook {
@@ -15,35 +16,23 @@ impl OrderBook {
         }
     }
 
-    pub fn apply_bid(&mut self, mut price: f64, mut qty: f64) {
-        if !price.is_finite() || !qty.is_finite() {
-            panic!("non-finite bid update: price={} qty={}", price, qty);
-        }
-        if price == 0.0 { price = 0.0; }
-        if qty == 0.0 { qty = 0.0; }
-        let p = OrderedFloat(price);
-        if qty == 0.0 {
-            self.bids.remove(&p);
+    pub fn apply_bid(&mut self, price: Price, qty: Qty) {
+        if qty.is_zero() {
+            self.bids.remove(&price);
         } else {
-            self.bids.insert(p, qty);
+            self.bids.insert(price, qty);
         }
     }
 
-    pub fn apply_ask(&mut self, mut price: f64, mut qty: f64) {
-        if !price.is_finite() || !qty.is_finite() {
-            panic!("non-finite ask update: price={} qty={}", price, qty);
-        }
-        if price == 0.0 { price = 0.0; }
-        if qty == 0.0 { qty = 0.0; }
-        let p = OrderedFloat(price);
-        if qty == 0.0 {
-            self.asks.remove(&p);
+    pub fn apply_ask(&mut self, price: Price, qty: Qty) {
+        if qty.is_zero() {
+            self.asks.remove(&price);
         } else {
-            self.asks.insert(p, qty);
+            self.asks.insert(price, qty);
         }
     }
 
-    pub fn apply_levels(&mut self, bids: &[(f64, f64)], asks: &[(f64, f64)]) {
+    pub fn apply_levels(&mut self, bids: &[(Price, Qty)], asks: &[(Price, Qty)]) {
         for (p, q) in bids {
             self.apply_bid(*p, *q);
         }
@@ -52,12 +41,30 @@ impl OrderBook {
         }
     }
 
-    pub fn top_bid(&self) -> Option<(f64, f64)> {
-        self.bids.iter().next_back().map(|(p, q)| (p.0, *q))
+    /// `apply_levels` on the instrument grid: quantities are truncated to
+    /// the lot. An off-tick price, or a non-zero qty below one lot (which
+    /// would turn into a delete), fails the whole update and leaves the book
+    /// untouched.
+    pub fn apply_levels_normalized(
+        &mut self,
+        spec: &InstrumentSpec,
+        bids: &[(Price, Qty)],
+        asks: &[(Price, Qty)],
+    ) -> Result<(), SpecError> {
+        let norm = |levels: &[(Price, Qty)]| -> Result<Vec<(Price, Qty)>, SpecError> {
+            levels.iter().map(|&(p, q)| spec.normalize_level(p, q)).collect()
+        };
+        let (bids, asks) = (norm(bids)?, norm(asks)?);
+        self.apply_levels(&bids, &asks);
+        Ok(())
+    }
+
+    pub fn top_bid(&self) -> Option<(Price, Qty)> {
+        self.bids.iter().next_back().map(|(p, q)| (*p, *q))
     }
 
-    pub fn top_ask(&self) -> Option<(f64, f64)> {
-        self.asks.iter().next().map(|(p, q)| (p.0, *q))
+    pub fn top_ask(&self) -> Option<(Price, Qty)> {
+        self.asks.iter().next().map(|(p, q)| (*p, *q))
     }
 }
 
@@ -1,29 +1,21 @@
 use crate::OrderBook;
-use blake3::Hasher;
+use el_core::hash::{self, StateHash, StateHasher};
 
-impl OrderBook {
-    /// Deterministic hash of the current book state.
-    /// NOTE: This assumes invariants have already guaranteed finite values.
-    pub fn state_hash64(&self) -> u64 {
-        let mut h = Hasher::new();
-
-        // Domain separator
-        h.update(b"orderbook:v1|bids|");
-
-        for (p, q) in self.bids.iter() {
-            h.update(&p.0.to_le_bytes());
-            h.update(&q.to_le_bytes());
-        }
-
-        h.update(b"|asks|");
+/// Domain of `OrderBook::state_hash64`.
+pub const STATE_HASH_DOMAIN: &str = "orderbook";
 
-        for (p, q) in self.asks.iter() {
-            h.update(&p.0.to_le_bytes());
-            h.update(&q.to_le_bytes());
-        }
+impl StateHash for OrderBook {
+    /// Bids then asks, each in ascending price order.
+    fn hash_state(&self, h: &mut StateHasher) {
+        self.bids.hash_state(h);
+        self.asks.hash_state(h);
+    }
+}
 
-        let out = h.finalize();
-        let bytes = out.as_bytes();
-        u64::from_le_bytes(bytes[0..8].try_into().unwrap())
+impl OrderBook {
+    /// Deterministic hash of the current book state, stable across
+    /// toolchains (see `el_core::hash`).
+    pub fn state_hash64(&self) -> u64 {
+        hash::state_hash64(STATE_HASH_DOMAIN, self)
     }
 }
@@ -0,0 +1,59 @@
+use el_core::event::Exchange;
+use el_core::instrument::{ContractKind, InstrumentSpec, SpecError};
+use el_core::num::{Decimal, Price, Qty};
+use orderbook::OrderBook;
+
+fn px(s: &str) -> Price {
+    s.parse().unwrap()
+}
+
+fn qty(s: &str) -> Qty {
+    s.parse().unwrap()
+}
+
+fn spec() -> InstrumentSpec {
+    InstrumentSpec {
+        exchange: Exchange::Binance,
+        symbol: "BTCUSDT".to_string(),
+        base: "BTC".to_string(),
+        quote: "USDT".to_string(),
+        kind: ContractKind::Spot,
+        tick_size: px("0.01"),
+        lot_step: qty("0.001"),
+        min_qty: qty("0.001"),
+        min_notional: Default::default(),
+        contract_multiplier: Decimal::from_int(1),
+    }
+}
+
+fn book() -> OrderBook {
+    let mut book = OrderBook::new();
+    book.apply_levels(&[(px("100"), qty("1"))], &[(px("101"), qty("2"))]);
+    book
+}
+
+#[test]
+fn qty_is_truncated_to_the_lot() {
+    let mut b = book();
+    b.apply_levels_normalized(&spec(), &[(px("100"), qty("0.0019"))], &[(px("101"), qty("0"))]).unwrap();
+    assert_eq!(b.top_bid(), Some((px("100"), qty("0.001"))));
+    assert_eq!(b.top_ask(), None, "an explicit zero still deletes");
+}
+
+#[test]
+fn nonzero_qty_below_a_lot_is_not_a_delete() {
+    let mut b = book();
+    let err = b.apply_levels_normalized(&spec(), &[(px("100"), qty("0.0004"))], &[]).unwrap_err();
+    assert!(matches!(err, SpecError::QtyOffLot { .. }), "{}", err);
+    assert_eq!(b.top_bid(), Some((px("100"), qty("1"))));
+}
+
+#[test]
+fn off_tick_price_is_an_error_not_snapped() {
+    let mut b = book();
+    let err = b.apply_levels_normalized(&spec(), &[(px("99.5"), qty("1"))], &[(px("101.005"), qty("1"))]).unwrap_err();
+    assert!(matches!(err, SpecError::PriceOffTick { .. }), "{}", err);
+    // nothing of the update is applied
+    assert_eq!((b.bids.len(), b.asks.len()), (1, 1));
+    assert_eq!(b.asks.get(&px("101")), Some(&qty("2")));
+}
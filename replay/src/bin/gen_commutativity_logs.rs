@@ -1,13 +1,14 @@
 use anyhow::Result;
-use el_core::event::{Event, EventPayload, EventType, Exchange};
+use el_core::event::{Event, EventPayload, EventType, Exchange, SCHEMA_VERSION};
 use el_core::time::{Timestamp, TimeSource};
 use el_core::instrument::InstrumentKey;
+use el_core::num::{Price, Qty};
 use eventlog::EventLogWriter;
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
 
-fn make_delta(p: f64, q: f64) -> Event {
+fn make_delta(p: Price, q: Qty) -> Event {
     Event {
         id: uuid::Uuid::new_v4(),
         event_type: EventType::BookDelta,
@@ -18,7 +19,7 @@ fn make_delta(p: f64, q: f64) -> Event {
         ts_recv: Timestamp::new(0, TimeSource::Receive),
         ts_proc: Timestamp::new(0, TimeSource::Process),
         seq: None,
-        schema_version: 1,
+        schema_version: SCHEMA_VERSION,
         integrity_flags: vec![],
         payload: EventPayload::BookDelta {
             bids: vec![(p, q)],
@@ -43,7 +44,8 @@ fn main() -> Result<()> {
     let mut deltas: Vec<Event> = vec![];
     for i in 0..40 {
         // enough steps to be meaningful
-        deltas.push(make_delta(50_000.0 + i as f64, 1.0 + (i % 7) as f64 * 0.1));
+        let qty = Qty::from_units(10 + (i % 7) as i128, 1).expect("scale 1");
+        deltas.push(make_delta(Price::from_int(50_000 + i), qty));
     }
 
     // A: original order
@@ -1,7 +1,8 @@
 use anyhow::Result;
-use el_core::event::{Event, EventPayload, EventType, Exchange};
+use el_core::event::{Event, EventPayload, EventType, Exchange, SCHEMA_VERSION};
 use el_core::time::{TimeSource, Timestamp};
 use el_core::instrument::InstrumentKey;
+use el_core::num::{Price, Qty};
 use eventlog::EventLogWriter;
 use eventlog::writer::Durability;
 use std::collections::HashMap;
@@ -11,6 +12,11 @@ fn ts(nanos: i64) -> Timestamp {
     Timestamp::new(nanos, TimeSource::Process)
 }
 
+/// A level from price and qty in tenths.
+fn lvl(px: u64, qty: u64) -> (Price, Qty) {
+    (Price::from_units(px as i128, 1).unwrap(), Qty::from_units(qty as i128, 1).unwrap())
+}
+
 fn main() -> Result<()> {
     let out_path = std::env::args()
         .nth(1)
@@ -29,11 +35,11 @@ fn main() -> Result<()> {
         ts_recv: ts(1),
         ts_proc: ts(1),
         seq: Some(1),
-        schema_version: 1,
+        schema_version: SCHEMA_VERSION,
         integrity_flags: vec![],
         payload: EventPayload::BookSnapshot {
-            bids: vec![(100.0, 1.0), (99.5, 2.0), (99.0, 3.0)],
-            asks: vec![(100.5, 1.5), (101.0, 2.5), (101.5, 3.5)],
+            bids: vec![lvl(1000, 10), lvl(995, 20), lvl(990, 30)],
+            asks: vec![lvl(1005, 15), lvl(1010, 25), lvl(1015, 35)],
         },
         meta: HashMap::new(),
     };
@@ -42,11 +48,11 @@ fn main() -> Result<()> {
     // 2) Deltas: deterministic pattern
     for i in 0..250u64 {
         let k = i + 2;
-        let bid_px = 100.0 - (i % 20) as f64 * 0.5;
-        let ask_px = 100.5 + (i % 20) as f64 * 0.5;
+        let bid_px = 1000 - (i % 20) * 5;
+        let ask_px = 1005 + (i % 20) * 5;
 
-        let bid_qty = if i % 17 == 0 { 0.0 } else { 1.0 + (i % 5) as f64 * 0.1 };
-        let ask_qty = if i % 19 == 0 { 0.0 } else { 1.5 + (i % 7) as f64 * 0.1 };
+        let bid_qty = if i % 17 == 0 { 0 } else { 10 + i % 5 };
+        let ask_qty = if i % 19 == 0 { 0 } else { 15 + i % 7 };
 
         let ev = Event {
             id: Uuid::new_v4(),
@@ -58,11 +64,11 @@ fn main() -> Result<()> {
             ts_recv: ts(1 + k as i64),
             ts_proc: ts(1 + k as i64),
             seq: Some(k),
-            schema_version: 1,
+            schema_version: SCHEMA_VERSION,
             integrity_flags: vec![],
             payload: EventPayload::BookDelta {
-                bids: vec![(bid_px, bid_qty)],
-                asks: vec![(ask_px, ask_qty)],
+                bids: vec![lvl(bid_px, bid_qty)],
+                asks: vec![lvl(ask_px, ask_qty)],
             },
             meta: HashMap::new(),
         };
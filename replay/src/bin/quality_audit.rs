@@ -1,19 +1,46 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
 
 use anyhow::{Context, Result};
 
 use el_core::event::Exchange;
+use eventlog::{EventEnvelope, LogFormat};
 use replay::decode::from_wire::decode_event;
+use replay::quality::audit::{audit_log, AuditOptions};
 use replay::quality::seq::{Gap, SeqTracker};
 use replay::wire::WireEvent;
 
-fn parse_exchange(s: &str) -> Exchange {
-    match s {
-        "Binance" => Exchange::Binance,
-        "Okx" => Exchange::Okx,
-        "Bybit" => Exchange::Bybit,
-        other => Exchange::Other(other.to_string()),
+/// An eventlog (binary, or JSON envelope lines) rather than a dump of wire events.
+fn is_eventlog(path: &str) -> Result<bool> {
+    let mut head = Vec::new();
+    File::open(path)
+        .with_context(|| format!("open {}", path))?
+        .take(64 << 10)
+        .read_to_end(&mut head)?;
+    if LogFormat::detect(&head) == LogFormat::Binary {
+        return Ok(true);
+    }
+    let first = head.split(|b| *b == b'\n').next().unwrap_or_default();
+    Ok(serde_json::from_slice::<EventEnvelope>(first).is_ok())
+}
+
+fn print_gaps(gaps: &[Gap]) {
+    println!("gaps_detected={}", gaps.len());
+
+    if !gaps.is_empty() {
+        let mut total_missing: u64 = 0;
+        for g in gaps {
+            total_missing += g.to - g.from + 1;
+        }
+        println!("missing_seq_total={}", total_missing);
+
+        let show = gaps.len().min(10);
+        for (i, g) in gaps.iter().take(show).enumerate() {
+            println!("gap[{}] {}..{}", i, g.from, g.to);
+        }
+        if gaps.len() > show {
+            println!("(showing first {} gaps)", show);
+        }
     }
 }
 
@@ -24,12 +51,19 @@ fn main() -> Result<()> {
     let mut exchange = None::<String>;
     let mut symbol = None::<String>;
     let mut max_lines: Option<usize> = None;
+    let mut threads = 0usize;
 
     while let Some(a) = args.next() {
         match a.as_str() {
             "--file" => file = args.next(),
             "--exchange" => exchange = args.next(),
             "--symbol" => symbol = args.next(),
+            "--threads" => {
+                threads = args
+                    .next()
+                    .map(|x| x.parse::<usize>().expect("threads must be usize"))
+                    .unwrap_or(0);
+            }
             "--max-lines" => {
                 max_lines = args
                     .next()
@@ -43,9 +77,21 @@ fn main() -> Result<()> {
     let exchange_s = exchange.context("missing --exchange")?;
     let symbol_s = symbol.context("missing --symbol")?;
 
-    let want_ex = parse_exchange(&exchange_s);
+    let want_ex = Exchange::from_name(&exchange_s);
     let want_sym = symbol_s;
 
+    // eventlogs are memory-mapped and scanned in parallel
+    if is_eventlog(&file)? {
+        let opts = AuditOptions { threads, max_records: max_lines };
+        let report = audit_log(&file, &want_ex, &want_sym, &opts)?;
+        println!("file={}", file);
+        println!("records_read={}", report.records);
+        println!("decoded_events={}", report.decoded);
+        println!("matched_stream={}", report.matched);
+        print_gaps(&report.gaps);
+        return Ok(());
+    }
+
     let f = File::open(&file).with_context(|| format!("open {}", file))?;
     let r = BufReader::new(f);
 
@@ -102,23 +148,7 @@ fn main() -> Result<()> {
     println!("wire_parsed={}", parsed);
     println!("matched_stream={}", matched);
     println!("decoded_events={}", decoded);
-    println!("gaps_detected={}", gaps.len());
-
-    if !gaps.is_empty() {
-        let mut total_missing: u64 = 0;
-        for g in &gaps {
-            total_missing += g.to - g.from + 1;
-        }
-        println!("missing_seq_total={}", total_missing);
-
-        let show = gaps.len().min(10);
-        for (i, g) in gaps.iter().take(show).enumerate() {
-            println!("gap[{}] {}..{}", i, g.from, g.to);
-        }
-        if gaps.len() > show {
-            println!("(showing first {} gaps)", show);
-        }
-    }
+    print_gaps(&gaps);
 
     Ok(())
 }
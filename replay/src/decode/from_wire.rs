@@ -1,8 +1,10 @@
 use crate::decode::DecodeError;
-use crate::wire::{BookLevels, TickerBbo, WireEvent, WireTs};
+use crate::wire::{BookLevels, FundingRate, Liquidation, OpenInterest, RefPrice, TickerBbo, Trade, WireEvent, WireTs};
 use el_core::event::{Event, EventPayload, EventType, Exchange};
+use el_core::exec::Side;
 use el_core::instrument::InstrumentKey;
 use el_core::time::{TimeSource, Timestamp};
+use serde::de::DeserializeOwned;
 use uuid::Uuid;
 
 const EVENT_ID_NAMESPACE: Uuid = Uuid::from_bytes([0x45,0x4c,0x2d,0x45,0x56,0x54,0x2d,0x49,0x44,0x2d,0x4e,0x53,0x50,0x41,0x43,0x45]);
@@ -44,6 +46,11 @@ fn event_id_from_wire(w: &WireEvent, event_type: &EventType) -> Uuid {
         EventType::BookDelta => "BookDelta",
         EventType::Trade => "Trade",
         EventType::TickerBbo => "TickerBbo",
+        EventType::MarkPrice => "MarkPrice",
+        EventType::IndexPrice => "IndexPrice",
+        EventType::FundingRate => "FundingRate",
+        EventType::Liquidation => "Liquidation",
+        EventType::OpenInterest => "OpenInterest",
         _ => "Other",
     });
     key.push('|');
@@ -64,13 +71,21 @@ fn event_id_from_wire(w: &WireEvent, event_type: &EventType) -> Uuid {
     Uuid::new_v5(&EVENT_ID_NAMESPACE, key.as_bytes())
 }
 
-fn exchange_from_str(s: &str) -> Exchange {
-    match s {
-        "Binance" => Exchange::Binance,
-        "Okx" => Exchange::Okx,
-        "Bybit" => Exchange::Bybit,
-        other => Exchange::Other(other.to_string()),
-    }
+/// Payload under `key`, which must match the event type.
+fn payload<T: DeserializeOwned>(w: &WireEvent, key: &'static str, field: &'static str) -> Result<T, DecodeError> {
+    let v = w
+        .payload
+        .get(key)
+        .ok_or(DecodeError::PayloadKeyMismatch {
+            event_type: w.event_type.clone(),
+            expected: key,
+        })?
+        .clone();
+    serde_json::from_value(v).map_err(|_| DecodeError::Invalid(field))
+}
+
+fn side_from_wire(s: &str, field: &'static str) -> Result<Side, DecodeError> {
+    Side::from_name(s).ok_or(DecodeError::Invalid(field))
 }
 
 pub fn decode_event(w: WireEvent) -> Result<Event, DecodeError> {
@@ -79,47 +94,62 @@ pub fn decode_event(w: WireEvent) -> Result<Event, DecodeError> {
         "BookDelta" => EventType::BookDelta,
         "Trade" => EventType::Trade,
         "TickerBbo" => EventType::TickerBbo,
+        "MarkPrice" => EventType::MarkPrice,
+        "IndexPrice" => EventType::IndexPrice,
+        "FundingRate" => EventType::FundingRate,
+        "Liquidation" => EventType::Liquidation,
+        "OpenInterest" => EventType::OpenInterest,
         other => return Err(DecodeError::Unsupported(other.to_string())),
     };
 
     let payload: EventPayload = match event_type {
         EventType::BookSnapshot => {
-            let v = w
-                .payload
-                .get("BookSnapshot")
-                .ok_or(DecodeError::PayloadKeyMismatch {
-                    event_type: w.event_type.clone(),
-                    expected: "BookSnapshot",
-                })?
-                .clone();
-            let lv: BookLevels = serde_json::from_value(v).map_err(|_| DecodeError::Invalid("payload.BookSnapshot"))?;
+            let lv: BookLevels = payload(&w, "BookSnapshot", "payload.BookSnapshot")?;
             EventPayload::BookSnapshot { bids: lv.bids, asks: lv.asks }
         }
         EventType::BookDelta => {
-            let v = w
-                .payload
-                .get("BookDelta")
-                .ok_or(DecodeError::PayloadKeyMismatch {
-                    event_type: w.event_type.clone(),
-                    expected: "BookDelta",
-                })?
-                .clone();
-            let lv: BookLevels = serde_json::from_value(v).map_err(|_| DecodeError::Invalid("payload.BookDelta"))?;
+            let lv: BookLevels = payload(&w, "BookDelta", "payload.BookDelta")?;
             EventPayload::BookDelta { bids: lv.bids, asks: lv.asks }
         }
+        EventType::Trade => {
+            let t: Trade = payload(&w, "Trade", "payload.Trade")?;
+            EventPayload::Trade {
+                price: t.price,
+                qty: t.qty,
+                is_maker: t.is_maker,
+                trade_id: t.trade_id.map(|id| id.into_string()),
+                aggressor: t.aggressor.map(|s| side_from_wire(&s, "payload.Trade.aggressor")).transpose()?,
+            }
+        }
         EventType::TickerBbo => {
-            let v = w
-                .payload
-                .get("TickerBbo")
-                .ok_or(DecodeError::PayloadKeyMismatch {
-                    event_type: w.event_type.clone(),
-                    expected: "TickerBbo",
-                })?
-                .clone();
-            let b: TickerBbo = serde_json::from_value(v).map_err(|_| DecodeError::Invalid("payload.TickerBbo"))?;
-            EventPayload::TickerBbo { bid: b.bid, ask: b.ask }
+            let b: TickerBbo = payload(&w, "TickerBbo", "payload.TickerBbo")?;
+            EventPayload::TickerBbo { bid: b.bid, ask: b.ask, bid_qty: b.bid_qty, ask_qty: b.ask_qty }
+        }
+        EventType::MarkPrice => {
+            let p: RefPrice = payload(&w, "MarkPrice", "payload.MarkPrice")?;
+            EventPayload::MarkPrice { price: p.price }
+        }
+        EventType::IndexPrice => {
+            let p: RefPrice = payload(&w, "IndexPrice", "payload.IndexPrice")?;
+            EventPayload::IndexPrice { price: p.price }
+        }
+        EventType::FundingRate => {
+            let f: FundingRate = payload(&w, "FundingRate", "payload.FundingRate")?;
+            EventPayload::FundingRate { rate: f.rate, next_funding_ts: ts_opt_from_wire(&f.next_funding_ts)? }
+        }
+        EventType::Liquidation => {
+            let l: Liquidation = payload(&w, "Liquidation", "payload.Liquidation")?;
+            EventPayload::Liquidation {
+                side: side_from_wire(&l.side, "payload.Liquidation.side")?,
+                price: l.price,
+                qty: l.qty,
+            }
+        }
+        EventType::OpenInterest => {
+            let oi: OpenInterest = payload(&w, "OpenInterest", "payload.OpenInterest")?;
+            EventPayload::OpenInterest { qty: oi.qty, notional: oi.notional }
         }
-        // not in your sample logs yet; keep strict
+        // execution, infra and risk events are not market data; keep strict
         _ => return Err(DecodeError::Unsupported(w.event_type)),
     };
 
@@ -128,10 +158,10 @@ pub fn decode_event(w: WireEvent) -> Result<Event, DecodeError> {
     Ok(Event {
         id: event_id_from_wire(&w, &event_type),
         event_type,
-        exchange: exchange_from_str(&w.exchange),
+        exchange: Exchange::from_name(&w.exchange),
         symbol: w.symbol,
 
-        instrument: InstrumentKey::new(exchange_from_str(&w.exchange), symbol),
+        instrument: InstrumentKey::new(Exchange::from_name(&w.exchange), symbol),
 
         ts_exchange: ts_opt_from_wire(&w.ts_exchange)?,
         ts_recv: ts_from_wire(&w.ts_recv)?,
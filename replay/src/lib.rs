@@ -2,6 +2,7 @@ pub mod state;
 pub mod wire;
 pub mod decode;
 pub mod quality;
+pub mod seek;
 
 use state::ReplayHealth;
 
@@ -1,69 +1,71 @@
 use anyhow::{Context, Result};
-use blake3::Hasher;
-use el_core::event::{Event, EventPayload, EventType};
-use eventlog::EventLogReader;
+use el_core::hash;
+use el_core::event::Event;
+use eventlog::crypto::{FileKeys, SharedKeys};
+use eventlog::{EventLogReader, SchemaRegistry};
 use orderbook::OrderBook;
+use replay::seek::open_at_snapshot_before;
+use replay::state::ReplayState;
+use std::sync::Arc;
 
 fn hash_book(book: &OrderBook) -> String {
-    let mut h = Hasher::new();
-
-    // BTreeMap iteration order is deterministic
-    for (p, q) in book.bids.iter() {
-        h.update(&p.0.to_le_bytes());
-        h.update(&q.to_le_bytes());
-    }
-    h.update(b"|");
-
-    for (p, q) in book.asks.iter() {
-        h.update(&p.0.to_le_bytes());
-        h.update(&q.to_le_bytes());
-    }
-
-    h.finalize().to_hex().to_string()
+    hash::to_hex(&hash::state_digest(orderbook::state_hash::STATE_HASH_DOMAIN, book))
 }
 
 fn main() -> Result<()> {
-    let path = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| "events_book.log".to_string());
+    let mut path = None::<String>;
+    let mut from_ts: Option<u64> = None;
+    let mut keys: Option<SharedKeys> = None;
 
-    let mut r = EventLogReader::open(&path).with_context(|| format!("open log: {}", path))?;
+    let mut args = std::env::args().skip(1);
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--from-ts" => {
+                from_ts = args
+                    .next()
+                    .map(|x| x.parse::<u64>().expect("from-ts must be u64 ns"));
+            }
+            // key directory for encrypted logs (see eventlog::crypto::FileKeys)
+            "--keys" => keys = args.next().map(|d| Arc::new(FileKeys::new(d)) as SharedKeys),
+            _ => path = Some(a),
+        }
+    }
+    let path = path.unwrap_or_else(|| "events_book.log".to_string());
+
+    // with --from-ts, start at the last snapshot before that time instead of byte 0
+    let r = match (from_ts, keys) {
+        (Some(ts), keys) => open_at_snapshot_before(&path, ts, keys),
+        (None, Some(keys)) => EventLogReader::open(&path).map(|r| r.with_keys(keys)).map_err(Into::into),
+        (None, None) => EventLogReader::open(&path).map_err(Into::into),
+    }
+    .with_context(|| format!("open log: {}", path))?;
+    // unknown kinds and versions fail; older events are upcast
+    let mut r = r.with_schemas(SchemaRegistry::core());
 
-    let mut book = OrderBook::new();
-    let mut last_seq: Option<u64> = None;
+    let mut state = ReplayState::new();
     let mut n: u64 = 0;
 
     while let Some((env, payload_bytes)) = r.next()? {
         n += 1;
-        last_seq = Some(env.seq);
 
         // Payload is JSON bytes of el_core::event::Event
         let ev: Event = serde_json::from_slice(&payload_bytes)
             .with_context(|| format!("parse core::Event json (seq={})", env.seq))?;
 
-        match (&ev.event_type, &ev.payload) {
-            (EventType::BookSnapshot, EventPayload::BookSnapshot { bids, asks }) => {
-                book = OrderBook::new();
-                book.apply_levels(&bids, &asks);
-            }
-            (EventType::BookDelta, EventPayload::BookDelta { bids, asks }) => {
-                book.apply_levels(&bids, &asks);
-            }
-            _ => {}
-        }
+        state.apply(env.seq, &ev);
 
         if n % 2000 == 0 {
-            let bid = book.top_bid();
-            let ask = book.top_ask();
-            let h = hash_book(&book);
-            println!("n={} seq={:?} bid={:?} ask={:?} hash={}", n, last_seq, bid, ask, h);
+            let bid = state.book.top_bid();
+            let ask = state.book.top_ask();
+            let h = hash_book(&state.book);
+            println!("n={} seq={:?} bid={:?} ask={:?} hash={}", n, state.last_seq, bid, ask, h);
         }
     }
 
-    let bid = book.top_bid();
-    let ask = book.top_ask();
-    let h = hash_book(&book);
-    println!("FINAL n={} seq={:?} bid={:?} ask={:?} hash={}", n, last_seq, bid, ask, h);
+    let bid = state.book.top_bid();
+    let ask = state.book.top_ask();
+    let h = hash_book(&state.book);
+    println!("FINAL n={} seq={:?} bid={:?} ask={:?} hash={}", n, state.last_seq, bid, ask, h);
 
     Ok(())
 }
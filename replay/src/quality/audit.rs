@@ -0,0 +1,99 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use el_core::event::{Event, Exchange};
+use eventlog::mmap::{MmapLog, MmapRecord};
+use eventlog::SchemaRegistry;
+
+use crate::quality::seq::{Gap, SeqTracker};
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditOptions {
+    /// ranges scanned in parallel; 0 for one per core
+    pub threads: usize,
+    /// stop after this many records (scans on one thread)
+    pub max_records: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct AuditReport {
+    pub records: u64,
+    /// "event" records whose payload is a core `Event`
+    pub decoded: u64,
+    /// decoded events of the audited exchange and symbol
+    pub matched: u64,
+    /// in seq order
+    pub gaps: Vec<Gap>,
+}
+
+impl AuditReport {
+    pub fn missing_seqs(&self) -> u64 {
+        self.gaps.iter().map(|g| g.to - g.from + 1).sum()
+    }
+}
+
+/// One range of a split scan.
+#[derive(Default)]
+struct RangeAudit {
+    report: AuditReport,
+    tracker: SeqTracker,
+}
+
+impl RangeAudit {
+    fn observe(&mut self, rec: &MmapRecord<'_>, schemas: &SchemaRegistry, exchange: &Exchange, symbol: &str) -> Result<()> {
+        self.report.records += 1;
+        let payload = schemas.upcast_record(rec.seq, &rec.kind, rec.payload.to_vec())?;
+        if rec.kind != "event" {
+            return Ok(());
+        }
+        // exec events share the kind
+        let Ok(ev) = serde_json::from_slice::<Event>(&payload) else {
+            return Ok(());
+        };
+        self.report.decoded += 1;
+        if ev.exchange != *exchange || ev.symbol != symbol {
+            return Ok(());
+        }
+        self.report.matched += 1;
+        if let Some(g) = self.tracker.observe(&ev).with_context(|| format!("seq={}", rec.seq))? {
+            self.report.gaps.push(g);
+        }
+        Ok(())
+    }
+}
+
+/// Check the exchange seqs of one instrument in an eventlog for gaps and
+/// regressions. The log is memory-mapped and its ranges scanned in parallel;
+/// gaps across range boundaries are found when the ranges are joined.
+/// Kinds and versions `SchemaRegistry::core` does not know fail the audit.
+pub fn audit_log(path: impl AsRef<Path>, exchange: &Exchange, symbol: &str, opts: &AuditOptions) -> Result<AuditReport> {
+    let log = MmapLog::open(path)?;
+    let schemas = SchemaRegistry::core();
+
+    if let Some(max) = opts.max_records {
+        let mut scan = RangeAudit::default();
+        for rec in log.records().take(max) {
+            scan.observe(&rec?, &schemas, exchange, symbol)?;
+        }
+        return Ok(scan.report);
+    }
+
+    let threads = match opts.threads {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    let ranges = log.par_scan(threads, RangeAudit::default, |scan, rec| scan.observe(&rec, &schemas, exchange, symbol))?;
+
+    let mut ranges = ranges.into_iter();
+    let mut total = ranges.next().unwrap_or_default();
+    for next in ranges {
+        let joins = total.tracker.append(&next.tracker)?;
+        let r = &mut total.report;
+        r.records += next.report.records;
+        r.decoded += next.report.decoded;
+        r.matched += next.report.matched;
+        r.gaps.extend(joins);
+        r.gaps.extend(next.report.gaps);
+    }
+    Ok(total.report)
+}
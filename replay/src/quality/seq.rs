@@ -27,12 +27,13 @@ pub enum SeqError {
 
 #[derive(Debug, Default)]
 pub struct SeqTracker {
+    first: HashMap<StreamKey, u64>,
     last: HashMap<StreamKey, u64>,
 }
 
 impl SeqTracker {
     pub fn new() -> Self {
-        Self { last: HashMap::new() }
+        Self { first: HashMap::new(), last: HashMap::new() }
     }
 
     /// Update tracker with an event.
@@ -53,6 +54,7 @@ impl SeqTracker {
 
         match self.last.get(&key).copied() {
             None => {
+                self.first.insert(key.clone(), curr);
                 self.last.insert(key, curr);
                 Ok(None)
             }
@@ -77,6 +79,33 @@ impl SeqTracker {
         }
     }
 
+    /// Continue with a tracker that saw the events right after this one's
+    /// (the next range of a split scan). Returns the gaps at the join, by seq.
+    pub fn append(&mut self, later: &SeqTracker) -> Result<Vec<Gap>, SeqError> {
+        let mut gaps = Vec::new();
+        for (key, &first) in &later.first {
+            let last = later.last[key];
+            match self.last.get(key).copied() {
+                None => {
+                    self.first.insert(key.clone(), first);
+                }
+                Some(prev) if first <= prev => {
+                    return Err(SeqError::Regression {
+                        exchange: key.exchange.clone(),
+                        symbol: key.symbol.clone(),
+                        prev,
+                        curr: first,
+                    });
+                }
+                Some(prev) if first > prev + 1 => gaps.push(Gap { from: prev + 1, to: first - 1 }),
+                Some(_) => {}
+            }
+            self.last.insert(key.clone(), last);
+        }
+        gaps.sort_by_key(|g| g.from);
+        Ok(gaps)
+    }
+
     pub fn last_seq(&self, exchange: &Exchange, symbol: &str) -> Option<u64> {
         self.last
             .get(&StreamKey {
@@ -60,4 +60,19 @@ mod tests {
         assert!(msg.contains("regression"));
     }
 
+    #[test]
+    fn append_checks_the_join() {
+        let mut a = SeqTracker::new();
+        a.observe(&mk(10)).unwrap();
+        let mut b = SeqTracker::new();
+        b.observe(&mk(13)).unwrap();
+        b.observe(&mk(14)).unwrap();
+        assert_eq!(a.append(&b).unwrap(), vec![Gap { from: 11, to: 12 }]);
+        assert_eq!(a.last_seq(&Exchange::Binance, "BTCUSDT"), Some(14));
+
+        let mut c = SeqTracker::new();
+        c.observe(&mk(14)).unwrap();
+        assert!(a.append(&c).is_err());
+        assert!(SeqTracker::new().append(&b).unwrap().is_empty());
+    }
 }
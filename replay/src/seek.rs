@@ -0,0 +1,72 @@
+use anyhow::Result;
+use el_core::event::EventType;
+use eventlog::crypto::SharedKeys;
+use eventlog::{EventLogReader, SparseIndex};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Only the part of a core::Event payload needed to spot snapshots.
+#[derive(Deserialize)]
+struct EventHead {
+    event_type: EventType,
+}
+
+fn is_snapshot(payload: &[u8]) -> bool {
+    serde_json::from_slice::<EventHead>(payload)
+        .map(|h| h.event_type == EventType::BookSnapshot)
+        .unwrap_or(false)
+}
+
+/// Reader with `keys` if given; payloads it cannot open stay sealed.
+fn open_reader(path: &Path, keys: Option<&SharedKeys>) -> Result<EventLogReader> {
+    let r = EventLogReader::open(path)?;
+    Ok(match keys {
+        Some(k) => r.with_keys(k.clone()),
+        None => r,
+    })
+}
+
+/// Last `BookSnapshot` with `ts_ns <= target_ns`, scanning one index window
+/// at a time backwards from `target_ns`. Sealed payloads `keys` cannot open
+/// are skipped over as non-snapshots.
+pub fn snapshot_seq_before(path: impl AsRef<Path>, target_ns: u64, keys: Option<&SharedKeys>) -> Result<Option<u64>> {
+    let path = path.as_ref();
+    let idx = SparseIndex::load(path)?;
+
+    // window starts, newest first; seq 0 stands for "start of file"
+    let mut starts: Vec<u64> = vec![0];
+    starts.extend(idx.points.iter().filter(|p| p.ts_ns <= target_ns).map(|p| p.seq));
+    starts.dedup();
+
+    let mut end = u64::MAX;
+    for &start in starts.iter().rev() {
+        let mut r = open_reader(path, keys)?.with_sealed_payloads();
+        r.seek_seq(start)?;
+
+        let mut found = None;
+        while let Some((env, payload)) = r.next()? {
+            if env.seq >= end || env.ts_ns > target_ns {
+                break;
+            }
+            if is_snapshot(&payload) {
+                found = Some(env.seq);
+            }
+        }
+        if found.is_some() {
+            return Ok(found);
+        }
+        end = start;
+    }
+    Ok(None)
+}
+
+/// Reader positioned at the last `BookSnapshot` at or before `target_ns`,
+/// or at the start of the log if there is none. It decrypts with `keys`.
+pub fn open_at_snapshot_before(path: impl AsRef<Path>, target_ns: u64, keys: Option<SharedKeys>) -> Result<EventLogReader> {
+    let path = path.as_ref();
+    let mut r = open_reader(path, keys.as_ref())?;
+    if let Some(seq) = snapshot_seq_before(path, target_ns, keys.as_ref())? {
+        r.seek_seq(seq)?;
+    }
+    Ok(r)
+}
@@ -1,5 +1,84 @@
+use el_core::event::{Event, EventPayload, EventType};
+use el_core::hash::{self, StateHash, StateHasher};
+use orderbook::OrderBook;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ReplayHealth {
     Healthy,
     NeedSnapshot,
 }
+
+impl StateHash for ReplayHealth {
+    fn hash_state(&self, h: &mut StateHasher) {
+        h.write_u8(match self {
+            ReplayHealth::Healthy => 0,
+            ReplayHealth::NeedSnapshot => 1,
+        });
+    }
+}
+
+/// Domain of `ReplayState::state_hash64`.
+pub const REPLAY_STATE_DOMAIN: &str = "replay.state";
+
+/// What a book replay has built so far: the book, the last applied
+/// envelope seq and whether the stream is still trusted.
+#[derive(Debug, Clone)]
+pub struct ReplayState {
+    pub book: OrderBook,
+    pub last_seq: Option<u64>,
+    pub health: ReplayHealth,
+}
+
+impl ReplayState {
+    pub fn new() -> Self {
+        Self {
+            book: OrderBook::new(),
+            last_seq: None,
+            health: ReplayHealth::Healthy,
+        }
+    }
+
+    /// Applies the event stored at `seq`. A snapshot replaces the book
+    /// and restores health; a delta is applied on top.
+    pub fn apply(&mut self, seq: u64, ev: &Event) {
+        self.last_seq = Some(seq);
+        match (&ev.event_type, &ev.payload) {
+            (EventType::BookSnapshot, EventPayload::BookSnapshot { bids, asks }) => {
+                self.book = OrderBook::new();
+                self.book.apply_levels(bids, asks);
+                self.health = ReplayHealth::Healthy;
+            }
+            (EventType::BookDelta, EventPayload::BookDelta { bids, asks }) => {
+                self.book.apply_levels(bids, asks);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn state_hash64(&self) -> u64 {
+        hash::state_hash64(REPLAY_STATE_DOMAIN, self)
+    }
+}
+
+impl Default for ReplayState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StateHash for ReplayState {
+    fn hash_state(&self, h: &mut StateHasher) {
+        self.book.hash_state(h);
+        self.last_seq.hash_state(h);
+        self.health.hash_state(h);
+    }
+}
+
+/// Domain of `chain_step`.
+pub const CHAIN_DOMAIN: &str = "replay.chain";
+
+/// Next link of a replay hash chain: the previous link folded with the
+/// state hash after one step. Golden replay runs compare these links.
+pub fn chain_step(prev: u64, state: u64) -> u64 {
+    hash::state_hash64(CHAIN_DOMAIN, &(prev, state))
+}
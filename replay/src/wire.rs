@@ -1,3 +1,4 @@
+use el_core::num::{Decimal, Notional, Price, Qty};
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -27,14 +28,77 @@ pub struct WireEvent {
     pub meta: HashMap<String, String>,
 }
 
+/// Prices and quantities as decimal strings or (older logs) JSON numbers.
 #[derive(Debug, Deserialize, Clone)]
 pub struct BookLevels {
-    pub bids: Vec<(f64, f64)>,
-    pub asks: Vec<(f64, f64)>,
+    pub bids: Vec<(Price, Qty)>,
+    pub asks: Vec<(Price, Qty)>,
 }
 
+/// Exchange trade id: a string, or a number as Binance sends it.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum WireId {
+    Str(String),
+    Num(u64),
+}
+
+impl WireId {
+    pub fn into_string(self) -> String {
+        match self {
+            WireId::Str(s) => s,
+            WireId::Num(n) => n.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Trade {
+    pub price: Price,
+    pub qty: Qty,
+    pub is_maker: bool,
+    #[serde(default)]
+    pub trade_id: Option<WireId>,
+    /// `Buy` / `Sell`, any case
+    #[serde(default)]
+    pub aggressor: Option<String>,
+}
+
+/// Sizes are absent in older logs.
 #[derive(Debug, Deserialize, Clone)]
 pub struct TickerBbo {
-    pub bid: f64,
-    pub ask: f64,
+    pub bid: Price,
+    pub ask: Price,
+    #[serde(default)]
+    pub bid_qty: Option<Qty>,
+    #[serde(default)]
+    pub ask_qty: Option<Qty>,
+}
+
+/// Mark or index price.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RefPrice {
+    pub price: Price,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct FundingRate {
+    pub rate: Decimal,
+    #[serde(default)]
+    pub next_funding_ts: Option<WireTs>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Liquidation {
+    /// `Buy` / `Sell`, any case
+    pub side: String,
+    pub price: Price,
+    pub qty: Qty,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OpenInterest {
+    pub qty: Qty,
+    #[serde(default)]
+    pub notional: Option<Notional>,
 }
@@ -0,0 +1,118 @@
+use el_core::event::{EventPayload, EventType};
+use el_core::exec::Side;
+use el_core::num::{Decimal, Notional, Price, Qty};
+use el_core::time::{TimeSource, Timestamp};
+use replay::decode::from_wire::decode_event;
+use replay::decode::DecodeError;
+use replay::wire::WireEvent;
+use serde_json::{json, Value};
+
+fn wire(event_type: &str, payload: Value) -> WireEvent {
+    serde_json::from_value(json!({
+        "event_type": event_type,
+        "exchange": "Binance",
+        "symbol": "BTCUSDT",
+        "ts_exchange": {"nanos": 5, "source": "Exchange"},
+        "ts_recv": {"nanos": 10, "source": "Receive"},
+        "ts_proc": {"nanos": 11, "source": "Process"},
+        "seq": 7,
+        "schema_version": 2,
+        "integrity_flags": [],
+        "payload": {event_type: payload},
+        "meta": {},
+    }))
+    .unwrap()
+}
+
+fn decode(event_type: &str, payload: Value) -> EventPayload {
+    let ev = decode_event(wire(event_type, payload)).unwrap();
+    assert_eq!(format!("{:?}", ev.event_type), event_type);
+    ev.payload
+}
+
+fn px(s: &str) -> Price {
+    s.parse().unwrap()
+}
+
+#[test]
+fn trade_with_id_and_aggressor() {
+    // Binance sends numeric trade ids
+    let p = decode("Trade", json!({"price": "100.5", "qty": "0.25", "is_maker": true, "trade_id": 42, "aggressor": "SELL"}));
+    match p {
+        EventPayload::Trade { price, trade_id, aggressor, .. } => {
+            assert_eq!(price, px("100.5"));
+            assert_eq!(trade_id.as_deref(), Some("42"));
+            assert_eq!(aggressor, Some(Side::Sell));
+        }
+        other => panic!("{:?}", other),
+    }
+
+    let p = decode("Trade", json!({"price": "100.5", "qty": "0.25", "is_maker": false}));
+    assert!(matches!(p, EventPayload::Trade { trade_id: None, aggressor: None, .. }));
+}
+
+#[test]
+fn bbo_sizes_are_optional() {
+    let p = decode("TickerBbo", json!({"bid": "100", "ask": "101", "bid_qty": "1.5", "ask_qty": "2"}));
+    match p {
+        EventPayload::TickerBbo { bid_qty, ask_qty, .. } => {
+            assert_eq!((bid_qty, ask_qty), (Some("1.5".parse::<Qty>().unwrap()), Some(Qty::from_int(2))));
+        }
+        other => panic!("{:?}", other),
+    }
+    let p = decode("TickerBbo", json!({"bid": "100", "ask": "101"}));
+    assert!(matches!(p, EventPayload::TickerBbo { bid_qty: None, ask_qty: None, .. }));
+}
+
+#[test]
+fn derivatives_payloads() {
+    assert!(matches!(decode("MarkPrice", json!({"price": "100.1"})), EventPayload::MarkPrice { price } if price == px("100.1")));
+    assert!(matches!(decode("IndexPrice", json!({"price": "99.9"})), EventPayload::IndexPrice { price } if price == px("99.9")));
+
+    match decode("FundingRate", json!({"rate": "-0.0001", "next_funding_ts": {"nanos": 1000, "source": "Exchange"}})) {
+        EventPayload::FundingRate { rate, next_funding_ts } => {
+            assert_eq!(rate, "-0.0001".parse::<Decimal>().unwrap());
+            assert_eq!(next_funding_ts, Some(Timestamp::new(1000, TimeSource::Exchange)));
+        }
+        other => panic!("{:?}", other),
+    }
+
+    match decode("Liquidation", json!({"side": "buy", "price": "98", "qty": "3"})) {
+        EventPayload::Liquidation { side, price, qty } => {
+            assert_eq!((side, price, qty), (Side::Buy, px("98"), Qty::from_int(3)));
+        }
+        other => panic!("{:?}", other),
+    }
+
+    match decode("OpenInterest", json!({"qty": "1200.5", "notional": "120050000"})) {
+        EventPayload::OpenInterest { qty, notional } => {
+            assert_eq!(qty, "1200.5".parse().unwrap());
+            assert_eq!(notional, Some("120050000".parse::<Notional>().unwrap()));
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn derivatives_event_ids_differ_by_type() {
+    let mark = decode_event(wire("MarkPrice", json!({"price": "1"}))).unwrap();
+    let index = decode_event(wire("IndexPrice", json!({"price": "1"}))).unwrap();
+    assert_ne!(mark.id, index.id);
+    assert_eq!(mark.event_type, EventType::MarkPrice);
+}
+
+#[test]
+fn invalid_payloads_are_rejected() {
+    let err = decode_event(wire("Liquidation", json!({"side": "long", "price": "98", "qty": "3"}))).unwrap_err();
+    assert!(matches!(err, DecodeError::Invalid("payload.Liquidation.side")), "{}", err);
+
+    let err = decode_event(wire("FundingRate", json!({"next_funding_ts": null}))).unwrap_err();
+    assert!(matches!(err, DecodeError::Invalid("payload.FundingRate")), "{}", err);
+
+    let mut w = wire("MarkPrice", json!({"price": "1"}));
+    w.event_type = "IndexPrice".to_string();
+    assert!(matches!(decode_event(w).unwrap_err(), DecodeError::PayloadKeyMismatch { expected: "IndexPrice", .. }));
+
+    let err = decode_event(wire("OrderAck", json!({"order_id": "1"}))).unwrap_err();
+    assert!(matches!(err, DecodeError::Unsupported(_)));
+}
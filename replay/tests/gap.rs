@@ -1,5 +1,6 @@
 use anyhow::Result;
 use el_core::event::{Event, EventPayload, EventType, Exchange};
+use el_core::num::{Price, Qty};
 use el_core::time::{Timestamp, TimeSource};
 use eventlog::EventLogWriter;
 use std::collections::HashMap;
@@ -26,7 +27,7 @@ fn mk_delta(i: u64) -> Event {
         schema_version: 1,
         integrity_flags: vec![],
         payload: EventPayload::BookDelta {
-            bids: vec![(50_000.0 + (i % 10) as f64, 1.0)],
+            bids: vec![(Price::from_int(50_000 + (i % 10) as i64), Qty::from_int(1))],
             asks: vec![],
         },
         meta: HashMap::new(),
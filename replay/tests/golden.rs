@@ -17,11 +17,13 @@ fn parse_expected(path: &str) -> Result<Vec<u64>> {
 }
 
 #[test]
+#[ignore = "golden_hashes.txt is empty until gen_golden is run against tests/data/golden_events_book.log under STATE_HASH_VERSION 2"]
 fn golden_replay_chain_hashes_match() -> Result<()> {
     let log_path = "tests/data/golden_events_book.log";
     let expected_path = "tests/golden_hashes.txt";
 
     let expected = parse_expected(expected_path)?;
+    anyhow::ensure!(!expected.is_empty(), "{} holds no hashes", expected_path);
     let actual = util::run_and_collect_chain_hashes(log_path, expected.len())?;
 
     anyhow::ensure!(expected == actual, "GOLDEN MISMATCH");
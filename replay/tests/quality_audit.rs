@@ -0,0 +1,91 @@
+use anyhow::Result;
+use el_core::event::{Event, EventPayload, EventType, Exchange};
+use el_core::num::{Price, Qty};
+use el_core::time::{TimeSource, Timestamp};
+use eventlog::writer::WriterOptions;
+use eventlog::{EventLogWriter, LogFormat};
+use replay::quality::audit::{audit_log, AuditOptions};
+use replay::quality::seq::Gap;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    let _ = std::fs::remove_file(eventlog::index::index_path(&p));
+    p
+}
+
+fn delta(symbol: &str, seq: u64) -> Event {
+    Event {
+        id: uuid::Uuid::new_v4(),
+        event_type: EventType::BookDelta,
+        instrument: el_core::instrument::InstrumentKey::new(Exchange::Binance, symbol),
+        exchange: Exchange::Binance,
+        symbol: symbol.to_string(),
+        ts_exchange: None,
+        ts_recv: Timestamp::new(seq as i64, TimeSource::Receive),
+        ts_proc: Timestamp::new(seq as i64, TimeSource::Process),
+        seq: Some(seq),
+        schema_version: 1,
+        integrity_flags: vec![],
+        payload: EventPayload::BookDelta { bids: vec![(Price::from_int(100), Qty::from_int(1))], asks: vec![] },
+        meta: HashMap::new(),
+    }
+}
+
+/// BTCUSDT seqs 1..=400 without 50, 51 and 300, interleaved with ETHUSDT.
+fn write(path: &Path, format: LogFormat) -> Result<()> {
+    let opts = WriterOptions { format, index_stride: Some(8), ..Default::default() };
+    let mut w = EventLogWriter::open_with(path, "md", opts)?;
+    for seq in (1..=400u64).filter(|s| ![50, 51, 300].contains(s)) {
+        w.write(&delta("BTCUSDT", seq))?;
+        if seq % 3 == 0 {
+            w.write(&delta("ETHUSDT", seq * 10))?;
+        }
+    }
+    w.append_bytes("snapshot_hash", 0, &7u64.to_le_bytes())?;
+    Ok(w.flush()?)
+}
+
+#[test]
+fn parallel_audit_finds_the_gaps_a_single_scan_does() -> Result<()> {
+    for format in [LogFormat::Binary, LogFormat::JsonLines] {
+        let path = tmp_log(&format!("quality_audit_{:?}.log", format));
+        write(&path, format)?;
+
+        let one = audit_log(&path, &Exchange::Binance, "BTCUSDT", &AuditOptions { threads: 1, ..Default::default() })?;
+        assert_eq!(one.gaps, vec![Gap { from: 50, to: 51 }, Gap { from: 300, to: 300 }]);
+        assert_eq!((one.records, one.decoded, one.matched), (397 + 131 + 1, 397 + 131, 397));
+        assert_eq!(one.missing_seqs(), 3);
+
+        for threads in [2, 7, 64] {
+            let par = audit_log(&path, &Exchange::Binance, "BTCUSDT", &AuditOptions { threads, ..Default::default() })?;
+            assert_eq!(par.gaps, one.gaps, "{:?} threads={}", format, threads);
+            assert_eq!((par.records, par.decoded, par.matched), (one.records, one.decoded, one.matched));
+        }
+
+        let head = audit_log(&path, &Exchange::Binance, "BTCUSDT", &AuditOptions { max_records: Some(70), ..Default::default() })?;
+        assert_eq!(head.records, 70);
+        assert_eq!(head.gaps, vec![Gap { from: 50, to: 51 }]);
+    }
+    Ok(())
+}
+
+#[test]
+fn regression_across_ranges_fails() -> Result<()> {
+    let path = tmp_log("quality_audit_regression.log");
+    let opts = WriterOptions { format: LogFormat::Binary, index_stride: Some(4), ..Default::default() };
+    let mut w = EventLogWriter::open_with(&path, "md", opts)?;
+    for seq in (1..=100u64).chain(90..=120) {
+        w.write(&delta("BTCUSDT", seq))?;
+    }
+    w.flush()?;
+    drop(w);
+
+    for threads in [1, 4] {
+        let err = audit_log(&path, &Exchange::Binance, "BTCUSDT", &AuditOptions { threads, ..Default::default() }).unwrap_err();
+        assert!(format!("{:#}", err).contains("regression"), "{:#}", err);
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use el_core::event::{Event, EventPayload, EventType, Exchange};
+use el_core::exec::Side;
+use el_core::instrument::InstrumentKey;
+use el_core::num::{Price, Qty};
+use el_core::time::{TimeSource, Timestamp};
+use eventlog::EventLogWriter;
+use replay::quality::audit::{audit_log, AuditOptions};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+mod util;
+
+fn tmp_log(name: &str) -> PathBuf {
+    let p = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+fn snapshot(seq: u64) -> Event {
+    Event {
+        id: uuid::Uuid::new_v4(),
+        event_type: EventType::BookSnapshot,
+        instrument: InstrumentKey::new(Exchange::Binance, "BTCUSDT"),
+        exchange: Exchange::Binance,
+        symbol: "BTCUSDT".to_string(),
+        ts_exchange: None,
+        ts_recv: Timestamp::new(seq as i64, TimeSource::Receive),
+        ts_proc: Timestamp::new(seq as i64, TimeSource::Process),
+        seq: Some(seq),
+        schema_version: el_core::event::SCHEMA_VERSION,
+        integrity_flags: vec![],
+        payload: EventPayload::BookSnapshot {
+            bids: vec![(Price::from_int(100), Qty::from_int(1))],
+            asks: vec![(Price::from_int(101), Qty::from_int(1))],
+        },
+        meta: HashMap::new(),
+    }
+}
+
+/// An `OrderSubmit` as written under schema v1: a free-form side and no
+/// order type or time in force.
+fn v1_submit(seq: u64) -> Result<Vec<u8>> {
+    let submit = Event {
+        event_type: EventType::OrderSubmit,
+        schema_version: 1,
+        payload: EventPayload::OrderSubmit {
+            order_id: "o1".to_string(),
+            client_order_id: None,
+            side: Side::Buy,
+            order_type: Default::default(),
+            time_in_force: Default::default(),
+            price: Some(Price::from_int(100)),
+            qty: Qty::from_int(1),
+            flags: Default::default(),
+        },
+        ..snapshot(seq)
+    };
+    let mut v = serde_json::to_value(&submit)?;
+    let fields = v.pointer_mut("/payload/OrderSubmit").and_then(|p| p.as_object_mut()).unwrap();
+    fields.insert("side".into(), "buy".into());
+    fields.remove("order_type");
+    fields.remove("time_in_force");
+    Ok(serde_json::to_vec(&v)?)
+}
+
+/// A valid snapshot followed by `payload` as a record of `kind`.
+fn write(name: &str, kind: &str, payload: &[u8]) -> Result<PathBuf> {
+    let path = tmp_log(name);
+    let mut w = EventLogWriter::open(&path)?;
+    w.write(&snapshot(1))?;
+    w.append_bytes(kind, 2, payload)?;
+    w.flush()?;
+    Ok(path)
+}
+
+#[test]
+fn replay_and_audit_upcast_v1_events() -> Result<()> {
+    let path = write("schema_v1_submit.log", "event", &v1_submit(2)?)?;
+
+    assert_eq!(util::run_and_collect_chain_hashes(path.to_str().unwrap(), 10)?.len(), 2);
+    let report = audit_log(&path, &Exchange::Binance, "BTCUSDT", &AuditOptions::default())?;
+    assert_eq!((report.records, report.decoded, report.matched), (2, 2, 2));
+    Ok(())
+}
+
+#[test]
+fn replay_and_audit_reject_unknown_kinds() -> Result<()> {
+    let path = write("schema_unknown_kind.log", "note", &serde_json::to_vec(&snapshot(2))?)?;
+
+    let err = util::run_and_collect_chain_hashes(path.to_str().unwrap(), 10).unwrap_err();
+    assert!(format!("{:#}", err).contains("unknown kind \"note\""), "{:#}", err);
+    let err = audit_log(&path, &Exchange::Binance, "BTCUSDT", &AuditOptions::default()).unwrap_err();
+    assert!(format!("{:#}", err).contains("unknown kind \"note\""), "{:#}", err);
+    Ok(())
+}
+
+#[test]
+fn replay_and_audit_reject_newer_versions() -> Result<()> {
+    let newer = Event { schema_version: el_core::event::SCHEMA_VERSION + 1, ..snapshot(2) };
+    let path = write("schema_newer_version.log", "event", &serde_json::to_vec(&newer)?)?;
+
+    let err = util::run_and_collect_chain_hashes(path.to_str().unwrap(), 10).unwrap_err();
+    assert!(format!("{:#}", err).contains("unsupported \"event\" version"), "{:#}", err);
+    let err = audit_log(&path, &Exchange::Binance, "BTCUSDT", &AuditOptions::default()).unwrap_err();
+    assert!(format!("{:#}", err).contains("unsupported \"event\" version"), "{:#}", err);
+    Ok(())
+}
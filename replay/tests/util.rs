@@ -1,21 +1,13 @@
 use anyhow::{Context, Result};
-use blake3::Hasher;
-use el_core::event::{Event, EventPayload, EventType};
-use eventlog::EventLogReader;
-use orderbook::OrderBook;
-
-fn chain_step(prev: u64, state: u64) -> u64 {
-    let mut h = Hasher::new();
-    h.update(b"chain:v1|");
-    h.update(&prev.to_le_bytes());
-    h.update(&state.to_le_bytes());
-    let out = h.finalize();
-    u64::from_le_bytes(out.as_bytes()[0..8].try_into().unwrap())
-}
+use el_core::event::Event;
+use eventlog::{EventLogReader, SchemaRegistry};
+use replay::state::{chain_step, ReplayState};
 
 pub fn run_and_collect_chain_hashes(path: &str, max_events: usize) -> Result<Vec<u64>> {
-    let mut r = EventLogReader::open(path).with_context(|| format!("open log: {}", path))?;
-    let mut book = OrderBook::new();
+    let mut r = EventLogReader::open(path)
+        .with_context(|| format!("open log: {}", path))?
+        .with_schemas(SchemaRegistry::core());
+    let mut state = ReplayState::new();
 
     let mut out: Vec<u64> = Vec::new();
     let mut prev_chain: u64 = 0;
@@ -33,21 +25,12 @@ pub fn run_and_collect_chain_hashes(path: &str, max_events: usize) -> Result<Vec
         let ev: Event = serde_json::from_slice(&payload_bytes)
             .with_context(|| format!("parse core::Event json (step={} env.seq={})", n + 1, env.seq))?;
 
-        match (&ev.event_type, &ev.payload) {
-            (EventType::BookSnapshot, EventPayload::BookSnapshot { bids, asks }) => {
-                book = OrderBook::new();
-                book.apply_levels(&bids, &asks);
-            }
-            (EventType::BookDelta, EventPayload::BookDelta { bids, asks }) => {
-                book.apply_levels(&bids, &asks);
-            }
-            _ => {}
-        }
+        state.apply(env.seq, &ev);
 
-        if let Err(e) = book.check_invariants() {
-            let bid = book.top_bid();
-            let ask = book.top_ask();
-            let state = book.state_hash64();
+        if let Err(e) = state.book.check_invariants() {
+            let bid = state.book.top_bid();
+            let ask = state.book.top_ask();
+            let state = state.state_hash64();
             let ch = chain_step(prev_chain, state);
             anyhow::bail!(
                 "INVARIANT FAIL step={} env.seq={} event_type={:?} bid={:?} ask={:?} prev_chain={} state_hash64={} chain_hash={} err={}",
@@ -55,8 +38,7 @@ pub fn run_and_collect_chain_hashes(path: &str, max_events: usize) -> Result<Vec
             );
         }
 
-        let state = book.state_hash64();
-        let ch = chain_step(prev_chain, state);
+        let ch = chain_step(prev_chain, state.state_hash64());
         out.push(ch);
         prev_chain = ch;
 
@@ -1,3 +1,7 @@
+pub mod pre_trade;
+
+pub use pre_trade::{PreTradeCheck, RiskError};
+
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
 }
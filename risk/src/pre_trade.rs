@@ -0,0 +1,66 @@
+use el_core::instrument::{InstrumentKey, InstrumentRegistry, SpecError};
+use el_core::num::{Notional, Price, Qty};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RiskError {
+    #[error(transparent)]
+    Spec(#[from] SpecError),
+
+    #[error("{instrument}: notional {notional} above limit {limit}")]
+    MaxNotional { instrument: String, notional: Notional, limit: Notional },
+}
+
+/// Pre-trade check: exchange filters from the instrument spec, then our own limits.
+#[derive(Debug, Clone)]
+pub struct PreTradeCheck {
+    specs: Arc<InstrumentRegistry>,
+    max_order_notional: Option<Notional>,
+}
+
+impl PreTradeCheck {
+    pub fn new(specs: Arc<InstrumentRegistry>) -> Self {
+        Self { specs, max_order_notional: None }
+    }
+
+    pub fn with_max_order_notional(mut self, limit: Notional) -> Self {
+        self.max_order_notional = Some(limit);
+        self
+    }
+
+    /// The order's notional in the quote asset, if it passes.
+    pub fn check(&self, instrument: &InstrumentKey, price: Price, qty: Qty) -> Result<Notional, RiskError> {
+        let spec = self.specs.require(instrument)?;
+        let notional = spec.check_order(price, qty)?;
+        match self.max_order_notional {
+            Some(limit) if notional > limit => {
+                Err(RiskError::MaxNotional { instrument: instrument.to_string(), notional, limit })
+            }
+            _ => Ok(notional),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use el_core::event::Exchange;
+
+    #[test]
+    fn spec_then_limit() {
+        let specs = InstrumentRegistry::from_json_str(
+            r#"{"instruments": [{"exchange": "Bybit", "symbol": "ETHUSDT", "base": "ETH", "quote": "USDT",
+                "kind": "perp", "tick_size": "0.01", "lot_step": "0.01", "min_notional": "5"}]}"#,
+        )
+        .unwrap();
+        let check = PreTradeCheck::new(Arc::new(specs)).with_max_order_notional(Notional::from_int(10_000));
+        let eth = InstrumentKey::new(Exchange::Bybit, "ETHUSDT");
+        let px = |s: &str| s.parse::<Price>().unwrap();
+        let qty = |s: &str| s.parse::<Qty>().unwrap();
+
+        assert_eq!(check.check(&eth, px("2500.5"), qty("2")), Ok(Notional::from_int(5001)));
+        assert!(matches!(check.check(&eth, px("2500.505"), qty("2")), Err(RiskError::Spec(SpecError::PriceOffTick { .. }))));
+        assert!(matches!(check.check(&eth, px("2500"), qty("5")), Err(RiskError::MaxNotional { .. })));
+    }
+}